@@ -14,7 +14,7 @@
 use crate::connection::State;
 use crate::ServerInfo;
 
-use super::{header::HeaderMap, status::StatusCode, Command, Message, Subscriber};
+use super::{header::HeaderMap, status::StatusCode, Command, Event, Events, Message, Subscriber};
 use crate::error::Error;
 use bytes::Bytes;
 use futures::future::TryFutureExt;
@@ -26,7 +26,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tracing::trace;
 
 static VERSION_RE: Lazy<Regex> =
@@ -52,6 +52,7 @@ pub struct Client {
     info: tokio::sync::watch::Receiver<ServerInfo>,
     pub(crate) state: tokio::sync::watch::Receiver<State>,
     sender: mpsc::Sender<Command>,
+    events: broadcast::Sender<Event>,
     next_subscription_id: Arc<AtomicU64>,
     subscription_capacity: usize,
     inbox_prefix: String,
@@ -63,6 +64,7 @@ impl Client {
         info: tokio::sync::watch::Receiver<ServerInfo>,
         state: tokio::sync::watch::Receiver<State>,
         sender: mpsc::Sender<Command>,
+        events: broadcast::Sender<Event>,
         capacity: usize,
         inbox_prefix: String,
         request_timeout: Option<Duration>,
@@ -71,7 +73,8 @@ impl Client {
             info,
             state,
             sender,
-            next_subscription_id: Arc::new(AtomicU64::new(0)),
+            events,
+            next_subscription_id: Arc::new(AtomicU64::new(1)),
             subscription_capacity: capacity,
             inbox_prefix,
             request_timeout,
@@ -335,27 +338,53 @@ impl Client {
         subject: String,
         request: Request,
     ) -> Result<Message, RequestError> {
-        let inbox = request.inbox.unwrap_or_else(|| self.new_inbox());
         let timeout = request.timeout.unwrap_or(self.request_timeout);
-        let mut sub = self.subscribe(inbox.clone()).await?;
         let payload: Bytes = request.payload.unwrap_or_else(Bytes::new);
-        match request.headers {
-            Some(headers) => {
-                self.publish_with_reply_and_headers(subject, inbox, headers, payload)
-                    .await?
+
+        let request = match request.inbox {
+            // A custom inbox needs a dedicated subscription.
+            Some(inbox) => {
+                let mut sub = self.subscribe(inbox.clone()).await?;
+                match request.headers {
+                    Some(headers) => {
+                        self.publish_with_reply_and_headers(subject, inbox, headers, payload)
+                            .await?
+                    }
+                    None => self.publish_with_reply(subject, inbox, payload).await?,
+                }
+                match timeout {
+                    Some(timeout) => {
+                        tokio::time::timeout(timeout, sub.next())
+                            .map_err(|err| {
+                                RequestError::with_source(RequestErrorKind::TimedOut, err)
+                            })
+                            .await?
+                    }
+                    None => sub.next().await,
+                }
             }
-            None => self.publish_with_reply(subject, inbox, payload).await?,
-        }
-        self.flush()
-            .await
-            .map_err(|err| RequestError::with_source(RequestErrorKind::Other, err))?;
-        let request = match timeout {
-            Some(timeout) => {
-                tokio::time::timeout(timeout, sub.next())
-                    .map_err(|err| RequestError::with_source(RequestErrorKind::TimedOut, err))
-                    .await?
+            // Otherwise the response is routed through the shared request inbox. Dropping the
+            // receiver releases its slot.
+            None => {
+                let (sender, receiver) = oneshot::channel();
+                self.sender
+                    .send(Command::Request {
+                        subject,
+                        payload,
+                        headers: request.headers,
+                        token: nuid::next(),
+                        sender,
+                    })
+                    .await
+                    .map_err(|err| RequestError::with_source(RequestErrorKind::Other, err))?;
+                match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, receiver)
+                        .map_err(|err| RequestError::with_source(RequestErrorKind::TimedOut, err))
+                        .await?
+                        .ok(),
+                    None => receiver.await.ok(),
+                }
             }
-            None => sub.next().await,
         };
         match request {
             Some(message) => {
@@ -486,6 +515,44 @@ impl Client {
         Ok(())
     }
 
+    /// Drains all subscriptions of the client, as in [Subscriber::drain], including the inbox
+    /// used for requests, then closes the connection. While draining, new subscriptions end
+    /// immediately and new requests fail. Resolves once the server confirmed no more messages
+    /// will be delivered and the connection was flushed and closed; subscribers still yield
+    /// their buffered messages before ending.
+    ///
+    /// Wrap it in [tokio::time::timeout] to bound graceful shutdown.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use std::time::Duration;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// tokio::time::timeout(Duration::from_secs(10), client.drain()).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn drain(&self) -> Result<(), DrainError> {
+        let (result, rx) = oneshot::channel();
+        self.sender
+            .send(Command::Drain { sid: None, result })
+            .await
+            .map_err(|err| DrainError::with_source(DrainErrorKind::SendError, err))?;
+        rx.await
+            .map_err(|err| DrainError::with_source(DrainErrorKind::DrainError, err))?
+            .map_err(|err| DrainError::with_source(DrainErrorKind::DrainError, err))?;
+
+        let (result, rx) = oneshot::channel();
+        self.sender
+            .send(Command::Close { result })
+            .await
+            .map_err(|err| DrainError::with_source(DrainErrorKind::SendError, err))?;
+        rx.await
+            .map_err(|err| DrainError::with_source(DrainErrorKind::DrainError, err))?
+            .map_err(|err| DrainError::with_source(DrainErrorKind::DrainError, err))
+    }
+
     /// Returns the current state of the connection.
     ///
     /// # Examples
@@ -500,6 +567,26 @@ impl Client {
     pub fn connection_state(&self) -> State {
         self.state.borrow().to_owned()
     }
+
+    /// Returns a [Stream][futures::Stream] of connection [Event]s, as an alternative to
+    /// [ConnectOptions::event_callback][crate::ConnectOptions::event_callback].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use futures::StreamExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let mut events = client.events();
+    /// while let Some(event) = events.next().await {
+    ///     println!("event: {}", event);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn events(&self) -> Events {
+        Events::new(self.events.subscribe())
+    }
 }
 
 /// Used for building customized requests.
@@ -667,3 +754,22 @@ impl Display for FlushErrorKind {
 }
 
 pub type FlushError = Error<FlushErrorKind>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrainErrorKind {
+    /// Sending the drain request failed client side.
+    SendError,
+    /// Drain failed, as the connection was closed.
+    DrainError,
+}
+
+impl Display for DrainErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SendError => write!(f, "failed to send drain request"),
+            Self::DrainError => write!(f, "drain failed"),
+        }
+    }
+}
+
+pub type DrainError = Error<DrainErrorKind>;
@@ -13,13 +13,15 @@
 
 //! This module provides a connection implementation for communicating with a NATS server.
 
+use std::collections::VecDeque;
 use std::fmt::Display;
+use std::io::IoSlice;
 use std::str::{self, FromStr};
 
 use tokio::io::{AsyncRead, AsyncWriteExt};
 use tokio::io::{AsyncReadExt, AsyncWrite};
 
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use tokio::io;
 
 use crate::header::{HeaderMap, HeaderName};
@@ -50,15 +52,39 @@ impl Display for State {
     }
 }
 
+/// Payloads of at least this size are written from their own buffer rather than copied.
+const SOLO_WRITE_THRESHOLD: usize = 4096;
+
+/// Buffered writes are sent to the stream once they reach this size, without waiting for a flush.
+const WRITE_BUFFER_HIGH_WATER: usize = 64 * 1024;
+
+/// Maximum number of buffers handed to a single vectored write.
+const MAX_IO_SLICES: usize = 64;
+
 /// A framed connection
 pub(crate) struct Connection {
     pub(crate) stream: Box<dyn AsyncReadWrite>,
     pub(crate) buffer: BytesMut,
+    /// Buffers waiting to be written, sent together with vectored writes.
+    write_buf: VecDeque<Bytes>,
+    write_buf_len: usize,
+    /// Small writes coalesced into a single buffer before joining `write_buf`.
+    flattened_writes: BytesMut,
 }
 
 /// Internal representation of the connection.
 /// Holds connection with NATS Server and communicates with `Client` via channels.
 impl Connection {
+    pub(crate) fn new(stream: Box<dyn AsyncReadWrite>, read_buffer_capacity: usize) -> Connection {
+        Connection {
+            stream,
+            buffer: BytesMut::with_capacity(read_buffer_capacity),
+            write_buf: VecDeque::new(),
+            write_buf_len: 0,
+            flattened_writes: BytesMut::new(),
+        }
+    }
+
     /// Attempts to read a server operation from the read buffer.
     /// Returns `None` if there is not enough data to parse an entire operation.
     pub(crate) fn try_read_op(&mut self) -> Result<Option<ServerOp>, io::Error> {
@@ -360,7 +386,7 @@ impl Connection {
                     serde_json::to_string(&connect_info)
                         .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
                 );
-                self.stream.write_all(op.as_bytes()).await?;
+                self.write(op.as_bytes());
             }
             ClientOp::Publish {
                 subject,
@@ -370,19 +396,19 @@ impl Connection {
             } => {
                 match headers.as_ref() {
                     Some(headers) if !headers.is_empty() => {
-                        self.stream.write_all(b"HPUB ").await?;
+                        self.write(b"HPUB ");
                     }
                     _ => {
-                        self.stream.write_all(b"PUB ").await?;
+                        self.write(b"PUB ");
                     }
                 }
 
-                self.stream.write_all(subject.as_bytes()).await?;
-                self.stream.write_all(b" ").await?;
+                self.write(subject.as_bytes());
+                self.write(b" ");
 
                 if let Some(respond) = respond {
-                    self.stream.write_all(respond.as_bytes()).await?;
-                    self.stream.write_all(b" ").await?;
+                    self.write(respond.as_bytes());
+                    self.write(b" ");
                 }
 
                 match headers {
@@ -390,35 +416,29 @@ impl Connection {
                         let headers = headers.to_bytes();
 
                         let mut header_len_buf = itoa::Buffer::new();
-                        self.stream
-                            .write_all(header_len_buf.format(headers.len()).as_bytes())
-                            .await?;
+                        self.write(header_len_buf.format(headers.len()).as_bytes());
 
-                        self.stream.write_all(b" ").await?;
+                        self.write(b" ");
 
                         let mut total_len_buf = itoa::Buffer::new();
-                        self.stream
-                            .write_all(
-                                total_len_buf
-                                    .format(headers.len() + payload.len())
-                                    .as_bytes(),
-                            )
-                            .await?;
-
-                        self.stream.write_all(b"\r\n").await?;
-                        self.stream.write_all(&headers).await?;
+                        self.write(
+                            total_len_buf
+                                .format(headers.len() + payload.len())
+                                .as_bytes(),
+                        );
+
+                        self.write(b"\r\n");
+                        self.write(&headers);
                     }
                     _ => {
                         let mut len_buf = itoa::Buffer::new();
-                        self.stream
-                            .write_all(len_buf.format(payload.len()).as_bytes())
-                            .await?;
-                        self.stream.write_all(b"\r\n").await?;
+                        self.write(len_buf.format(payload.len()).as_bytes());
+                        self.write(b"\r\n");
                     }
                 }
 
-                self.stream.write_all(payload).await?;
-                self.stream.write_all(b"\r\n").await?;
+                self.write_payload(payload);
+                self.write(b"\r\n");
             }
 
             ClientOp::Subscribe {
@@ -426,32 +446,89 @@ impl Connection {
                 subject,
                 queue_group,
             } => {
-                self.stream.write_all(b"SUB ").await?;
-                self.stream.write_all(subject.as_bytes()).await?;
+                self.write(b"SUB ");
+                self.write(subject.as_bytes());
                 if let Some(queue_group) = queue_group {
-                    self.stream
-                        .write_all(format!(" {queue_group}").as_bytes())
-                        .await?;
+                    self.write(format!(" {queue_group}").as_bytes());
                 }
-                self.stream
-                    .write_all(format!(" {sid}\r\n").as_bytes())
-                    .await?;
+                self.write(format!(" {sid}\r\n").as_bytes());
             }
 
             ClientOp::Unsubscribe { sid, max } => {
-                self.stream.write_all(b"UNSUB ").await?;
-                self.stream.write_all(format!("{sid}").as_bytes()).await?;
+                self.write(b"UNSUB ");
+                self.write(format!("{sid}").as_bytes());
                 if let Some(max) = max {
-                    self.stream.write_all(format!(" {max}").as_bytes()).await?;
+                    self.write(format!(" {max}").as_bytes());
                 }
-                self.stream.write_all(b"\r\n").await?;
+                self.write(b"\r\n");
             }
             ClientOp::Ping => {
-                self.stream.write_all(b"PING\r\n").await?;
-                self.stream.flush().await?;
+                self.write(b"PING\r\n");
             }
             ClientOp::Pong => {
-                self.stream.write_all(b"PONG\r\n").await?;
+                self.write(b"PONG\r\n");
+            }
+        }
+
+        if self.write_buf_len + self.flattened_writes.len() >= WRITE_BUFFER_HIGH_WATER {
+            self.write_buffered().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies `bytes` into the buffer of coalesced small writes.
+    fn write(&mut self, bytes: &[u8]) {
+        self.flattened_writes.extend_from_slice(bytes);
+    }
+
+    /// Queues a payload, large ones without copying them.
+    fn write_payload(&mut self, payload: &Bytes) {
+        if payload.len() < SOLO_WRITE_THRESHOLD {
+            self.write(payload);
+            return;
+        }
+
+        self.push_flattened();
+        self.write_buf_len += payload.len();
+        self.write_buf.push_back(payload.clone());
+    }
+
+    fn push_flattened(&mut self) {
+        if !self.flattened_writes.is_empty() {
+            let flattened = self.flattened_writes.split().freeze();
+            self.write_buf_len += flattened.len();
+            self.write_buf.push_back(flattened);
+        }
+    }
+
+    /// Writes all buffered data to the stream, passing many buffers to each vectored write.
+    async fn write_buffered(&mut self) -> Result<(), io::Error> {
+        self.push_flattened();
+
+        while !self.write_buf.is_empty() {
+            let mut slices = [IoSlice::new(&[]); MAX_IO_SLICES];
+            let count = self.write_buf.len().min(MAX_IO_SLICES);
+            for (slice, buf) in slices.iter_mut().zip(self.write_buf.iter()) {
+                *slice = IoSlice::new(buf);
+            }
+
+            let mut written = self.stream.write_vectored(&slices[..count]).await?;
+            if written == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write to the connection",
+                ));
+            }
+            self.write_buf_len -= written;
+
+            while let Some(front) = self.write_buf.front_mut() {
+                if written < front.len() {
+                    front.advance(written);
+                    break;
+                }
+                written -= front.len();
+                self.write_buf.pop_front();
             }
         }
 
@@ -460,6 +537,7 @@ impl Connection {
 
     /// Flush the write buffer, sending all pending data down the current write stream.
     pub(crate) async fn flush(&mut self) -> Result<(), io::Error> {
+        self.write_buffered().await?;
         self.stream.flush().await
     }
 }
@@ -468,16 +546,12 @@ impl Connection {
 mod read_op {
     use super::Connection;
     use crate::{HeaderMap, ServerError, ServerInfo, ServerOp, StatusCode};
-    use bytes::BytesMut;
     use tokio::io::{self, AsyncWriteExt};
 
     #[tokio::test]
     async fn ok() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server.write_all(b"+OK\r\n").await.unwrap();
         let result = connection.read_op().await.unwrap();
@@ -487,10 +561,7 @@ mod read_op {
     #[tokio::test]
     async fn ping() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server.write_all(b"PING\r\n").await.unwrap();
         let result = connection.read_op().await.unwrap();
@@ -500,10 +571,7 @@ mod read_op {
     #[tokio::test]
     async fn pong() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server.write_all(b"PONG\r\n").await.unwrap();
         let result = connection.read_op().await.unwrap();
@@ -513,10 +581,7 @@ mod read_op {
     #[tokio::test]
     async fn info() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server.write_all(b"INFO {}\r\n").await.unwrap();
         server.flush().await.unwrap();
@@ -543,10 +608,7 @@ mod read_op {
     #[tokio::test]
     async fn error() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server.write_all(b"INFO {}\r\n").await.unwrap();
         let result = connection.read_op().await.unwrap();
@@ -568,10 +630,7 @@ mod read_op {
     #[tokio::test]
     async fn message() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server
             .write_all(b"MSG FOO.BAR 9 11\r\nHello World\r\n")
@@ -718,10 +777,7 @@ mod read_op {
     #[tokio::test]
     async fn unknown() {
         let (stream, mut server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         server.write_all(b"ONE\r\n").await.unwrap();
         connection.read_op().await.unwrap_err();
@@ -773,16 +829,13 @@ mod read_op {
 mod write_op {
     use super::Connection;
     use crate::{ClientOp, ConnectInfo, HeaderMap, Protocol};
-    use bytes::BytesMut;
-    use tokio::io::{self, AsyncBufReadExt, BufReader};
+    use bytes::Bytes;
+    use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, BufReader};
 
     #[tokio::test]
     async fn publish() {
         let (stream, server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         connection
             .write_op(&ClientOp::Publish {
@@ -842,13 +895,42 @@ mod write_op {
         );
     }
 
+    #[tokio::test]
+    async fn publish_large_payload() {
+        let (stream, mut server) = io::duplex(1024);
+        let mut connection = Connection::new(Box::new(stream), 0);
+
+        let payload = Bytes::from(vec![b'x'; 10_000]);
+        for _ in 0..2 {
+            connection
+                .write_op(&ClientOp::Publish {
+                    subject: "FOO.BAR".into(),
+                    payload: payload.clone(),
+                    respond: None,
+                    headers: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut expected = Vec::new();
+        for _ in 0..2 {
+            expected.extend_from_slice(b"PUB FOO.BAR 10000\r\n");
+            expected.extend_from_slice(&payload);
+            expected.extend_from_slice(b"\r\n");
+        }
+
+        let mut received = vec![0; expected.len()];
+        let (flushed, read) = tokio::join!(connection.flush(), server.read_exact(&mut received));
+        flushed.unwrap();
+        read.unwrap();
+        assert_eq!(received, expected);
+    }
+
     #[tokio::test]
     async fn subscribe() {
         let (stream, server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         connection
             .write_op(&ClientOp::Subscribe {
@@ -883,10 +965,7 @@ mod write_op {
     #[tokio::test]
     async fn unsubscribe() {
         let (stream, server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         connection
             .write_op(&ClientOp::Unsubscribe { sid: 11, max: None })
@@ -916,10 +995,7 @@ mod write_op {
     #[tokio::test]
     async fn ping() {
         let (stream, server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         let mut reader = BufReader::new(server);
         let mut buffer = String::new();
@@ -935,10 +1011,7 @@ mod write_op {
     #[tokio::test]
     async fn pong() {
         let (stream, server) = io::duplex(128);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         let mut reader = BufReader::new(server);
         let mut buffer = String::new();
@@ -954,10 +1027,7 @@ mod write_op {
     #[tokio::test]
     async fn connect() {
         let (stream, server) = io::duplex(1024);
-        let mut connection = Connection {
-            stream: Box::new(stream),
-            buffer: BytesMut::new(),
-        };
+        let mut connection = Connection::new(Box::new(stream), 0);
 
         let mut reader = BufReader::new(server);
         let mut buffer = String::new();
@@ -33,7 +33,6 @@ use crate::LANG;
 use crate::VERSION;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::engine::Engine;
-use bytes::BytesMut;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::cmp;
@@ -41,7 +40,6 @@ use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::BufWriter;
 use tokio::io::ErrorKind;
 use tokio::net::TcpStream;
 use tokio::time::sleep;
@@ -49,6 +47,7 @@ use tokio_rustls::rustls;
 
 pub(crate) struct ConnectorOptions {
     pub(crate) tls_required: bool,
+    pub(crate) tls_first: bool,
     pub(crate) certificates: Vec<PathBuf>,
     pub(crate) client_cert: Option<PathBuf>,
     pub(crate) client_key: Option<PathBuf>,
@@ -60,6 +59,7 @@ pub(crate) struct ConnectorOptions {
     pub(crate) ignore_discovered_servers: bool,
     pub(crate) retain_servers_order: bool,
     pub(crate) read_buffer_capacity: u16,
+    pub(crate) max_reconnects: Option<usize>,
     pub(crate) reconnect_delay_callback: Box<dyn Fn(usize) -> Duration + Send + Sync + 'static>,
     pub(crate) auth_callback: Option<CallbackArg1<Vec<u8>, Result<Auth, AuthError>>>,
 }
@@ -111,6 +111,16 @@ impl Connector {
                         .send(Event::ClientError(ClientError::Other(error.to_string())))
                         .await
                         .ok();
+
+                    if let Some(max_reconnects) = self.options.max_reconnects {
+                        if self.attempts >= max_reconnects {
+                            self.events_tx.send(Event::Closed).await.ok();
+                            return Err(io::Error::new(
+                                io::ErrorKind::Other,
+                                "max reconnects reached",
+                            ));
+                        }
+                    }
                 }
             }
         }
@@ -296,10 +306,14 @@ impl Connector {
 
         tcp_stream.set_nodelay(true)?;
 
-        let mut connection = Connection {
-            stream: Box::new(BufWriter::new(tcp_stream)),
-            buffer: BytesMut::with_capacity(self.options.read_buffer_capacity.into()),
-        };
+        let mut connection = Connection::new(
+            Box::new(tcp_stream),
+            self.options.read_buffer_capacity.into(),
+        );
+
+        if self.options.tls_first {
+            connection = self.upgrade_to_tls(connection, tls_host).await?;
+        }
 
         let op = connection.read_op().await?;
         let info = match op {
@@ -318,32 +332,43 @@ impl Connector {
             }
         };
 
-        if self.options.tls_required || info.tls_required || tls_required {
-            let tls_config = Arc::new(
-                tls::config_tls(&self.options)
-                    .await
-                    .map_err(|err| ConnectError::with_source(crate::ConnectErrorKind::Tls, err))?,
-            );
-            let tls_connector = tokio_rustls::TlsConnector::try_from(tls_config)
-                .map_err(|err| {
-                    io::Error::new(
-                        ErrorKind::Other,
-                        format!("failed to create TLS connector from TLS config: {err}"),
-                    )
-                })
-                .map_err(|err| ConnectError::with_source(crate::ConnectErrorKind::Tls, err))?;
-
-            let domain = rustls::ServerName::try_from(tls_host)
-                .map_err(|err| ConnectError::with_source(crate::ConnectErrorKind::Tls, err))?;
-
-            connection = Connection {
-                stream: Box::new(tls_connector.connect(domain, connection.stream).await?),
-                buffer: BytesMut::new(),
-            };
-        };
+        if !self.options.tls_first
+            && (self.options.tls_required || info.tls_required || tls_required)
+        {
+            connection = self.upgrade_to_tls(connection, tls_host).await?;
+        }
 
         Ok((*info, connection))
     }
+
+    async fn upgrade_to_tls(
+        &self,
+        connection: Connection,
+        tls_host: &str,
+    ) -> Result<Connection, ConnectError> {
+        let tls_config = Arc::new(
+            tls::config_tls(&self.options)
+                .await
+                .map_err(|err| ConnectError::with_source(crate::ConnectErrorKind::Tls, err))?,
+        );
+        let tls_connector = tokio_rustls::TlsConnector::try_from(tls_config)
+            .map_err(|err| {
+                io::Error::new(
+                    ErrorKind::Other,
+                    format!("failed to create TLS connector from TLS config: {err}"),
+                )
+            })
+            .map_err(|err| ConnectError::with_source(crate::ConnectErrorKind::Tls, err))?;
+
+        let domain = rustls::ServerName::try_from(tls_host)
+            .map_err(|err| ConnectError::with_source(crate::ConnectErrorKind::Tls, err))?;
+
+        // The connection coalesces small writes, so they are sent in fewer TLS records.
+        Ok(Connection::new(
+            Box::new(tls_connector.connect(domain, connection.stream).await?),
+            self.options.read_buffer_capacity.into(),
+        ))
+    }
 }
 
 #[cfg(test)]
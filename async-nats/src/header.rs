@@ -432,6 +432,8 @@ standard_headers! {
     (NatsExpectedLastSequence, NATS_EXPECTED_LAST_SEQUENCE, b"Nats-Expected-Last-Sequence");
     /// The expected stream the message should be part of.
     (NatsExpectedStream, NATS_EXPECTED_STREAM, b"Nats-Expected-Stream");
+    /// The subject a stalled push consumer expects a flow control response on.
+    (NatsConsumerStalled, NATS_CONSUMER_STALLED, b"Nats-Consumer-Stalled");
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
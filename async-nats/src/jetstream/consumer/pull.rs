@@ -1039,24 +1039,6 @@ impl futures::Stream for Stream {
             return Poll::Ready(None);
         }
 
-        if !self.batch_config.idle_heartbeat.is_zero() {
-            trace!("checking idle hearbeats");
-            let timeout = self.batch_config.idle_heartbeat.saturating_mul(2);
-            match self
-                .heartbeat_timeout
-                .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)))
-                .poll_unpin(cx)
-            {
-                Poll::Ready(_) => {
-                    self.heartbeat_timeout = None;
-                    return Poll::Ready(Some(Err(MessagesError::new(
-                        MessagesErrorKind::MissingHeartbeat,
-                    ))));
-                }
-                Poll::Pending => (),
-            }
-        }
-
         loop {
             trace!("pending messages: {}", self.pending_messages);
             if (self.pending_messages <= self.batch_config.batch / 2
@@ -1185,6 +1167,25 @@ impl futures::Stream for Stream {
                 }
                 Poll::Pending => {
                     debug!("subscriber still pending");
+                    // Heartbeats are only checked once everything already buffered has been
+                    // consumed, so a slowly polling consumer does not see spurious errors.
+                    if !self.batch_config.idle_heartbeat.is_zero() {
+                        trace!("checking idle hearbeats");
+                        let timeout = self.batch_config.idle_heartbeat.saturating_mul(2);
+                        match self
+                            .heartbeat_timeout
+                            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)))
+                            .poll_unpin(cx)
+                        {
+                            Poll::Ready(_) => {
+                                self.heartbeat_timeout = None;
+                                return Poll::Ready(Some(Err(MessagesError::new(
+                                    MessagesErrorKind::MissingHeartbeat,
+                                ))));
+                            }
+                            Poll::Pending => (),
+                        }
+                    }
                     return std::task::Poll::Pending;
                 }
             }
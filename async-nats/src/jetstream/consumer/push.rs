@@ -110,6 +110,7 @@ impl Consumer<Config> {
             config: self.config.clone(),
             subscriber,
             heartbeat_sleep: None,
+            consumer_sequence: 0,
         })
     }
 }
@@ -119,28 +120,23 @@ pub struct Messages {
     subscriber: Subscriber,
     config: Config,
     heartbeat_sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+    consumer_sequence: u64,
+}
+
+impl Messages {
+    fn respond(&self, subject: String) {
+        let client = self.context.client.clone();
+        tokio::task::spawn(async move {
+            client.publish(subject, Bytes::from_static(b"")).await.ok();
+            client.flush().await.ok();
+        });
+    }
 }
 
 impl futures::Stream for Messages {
     type Item = Result<Message, MessagesError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
-        if !self.config.idle_heartbeat.is_zero() {
-            let heartbeat_sleep = self.config.idle_heartbeat.saturating_mul(2);
-            match self
-                .heartbeat_sleep
-                .get_or_insert_with(|| Box::pin(tokio::time::sleep(heartbeat_sleep)))
-                .poll_unpin(cx)
-            {
-                Poll::Ready(_) => {
-                    self.heartbeat_sleep = None;
-                    return Poll::Ready(Some(Err(MessagesError::new(
-                        MessagesErrorKind::MissingHeartbeat,
-                    ))));
-                }
-                Poll::Pending => (),
-            }
-        }
         loop {
             match self.subscriber.receiver.poll_recv(cx) {
                 Poll::Ready(maybe_message) => {
@@ -148,15 +144,46 @@ impl futures::Stream for Messages {
                     match maybe_message {
                         Some(message) => match message.status {
                             Some(StatusCode::IDLE_HEARTBEAT) => {
+                                // Flow control request.
                                 if let Some(subject) = message.reply {
-                                    // TODO store pending_publish as a future and return errors from it
-                                    let client = self.context.client.clone();
-                                    tokio::task::spawn(async move {
-                                        client
-                                            .publish(subject, Bytes::from_static(b""))
-                                            .await
-                                            .unwrap();
-                                    });
+                                    trace!("received flow control message");
+                                    self.respond(subject);
+                                    continue;
+                                }
+
+                                debug!("received idle heartbeat");
+                                if let Some(headers) = message.headers.as_ref() {
+                                    // The consumer is stalled waiting for a flow control response.
+                                    if let Some(subject) =
+                                        headers.get(crate::header::NATS_CONSUMER_STALLED)
+                                    {
+                                        self.respond(subject.as_str().to_string());
+                                    }
+
+                                    // Messages delivered to a queue group are spread across
+                                    // subscribers, so gaps can only be detected without one.
+                                    if self.config.deliver_group.is_none()
+                                        && self.consumer_sequence > 0
+                                    {
+                                        if let Some(sequence) = headers
+                                            .get(crate::header::NATS_LAST_CONSUMER)
+                                            .and_then(|sequence| sequence.as_str().parse().ok())
+                                        {
+                                            if sequence > self.consumer_sequence {
+                                                let last_sequence = self.consumer_sequence;
+                                                self.consumer_sequence = sequence;
+                                                return Poll::Ready(Some(Err(
+                                                    MessagesError::with_source(
+                                                        MessagesErrorKind::SequenceGap,
+                                                        format!(
+                                                            "last received consumer sequence {}, server reported {}",
+                                                            last_sequence, sequence
+                                                        ),
+                                                    ),
+                                                )));
+                                            }
+                                        }
+                                    }
                                 }
 
                                 continue;
@@ -165,16 +192,38 @@ impl futures::Stream for Messages {
                                 continue;
                             }
                             None => {
-                                return Poll::Ready(Some(Ok(jetstream::Message {
+                                let message = jetstream::Message {
                                     context: self.context.clone(),
                                     message,
-                                })))
+                                };
+                                if let Ok(info) = message.info() {
+                                    self.consumer_sequence = info.consumer_sequence;
+                                }
+                                return Poll::Ready(Some(Ok(message)));
                             }
                         },
                         None => return Poll::Ready(None),
                     }
                 }
-                Poll::Pending => return Poll::Pending,
+                Poll::Pending => {
+                    // Heartbeats are only checked once everything already buffered has been
+                    // consumed, so a slowly polling consumer does not see spurious errors.
+                    if !self.config.idle_heartbeat.is_zero() {
+                        let heartbeat_sleep = self.config.idle_heartbeat.saturating_mul(2);
+                        if self
+                            .heartbeat_sleep
+                            .get_or_insert_with(|| Box::pin(tokio::time::sleep(heartbeat_sleep)))
+                            .poll_unpin(cx)
+                            .is_ready()
+                        {
+                            self.heartbeat_sleep = None;
+                            return Poll::Ready(Some(Err(MessagesError::new(
+                                MessagesErrorKind::MissingHeartbeat,
+                            ))));
+                        }
+                    }
+                    return Poll::Pending;
+                }
             }
         }
     }
@@ -772,7 +821,7 @@ impl From<MessagesError> for OrderedError {
             MessagesErrorKind::PullBasedConsumer => {
                 OrderedError::new(OrderedErrorKind::PullBasedConsumer)
             }
-            MessagesErrorKind::Other => OrderedError {
+            MessagesErrorKind::SequenceGap | MessagesErrorKind::Other => OrderedError {
                 kind: OrderedErrorKind::Other,
                 source: err.source,
             },
@@ -781,10 +830,13 @@ impl From<MessagesError> for OrderedError {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum MessagesErrorKind {
     MissingHeartbeat,
     ConsumerDeleted,
     PullBasedConsumer,
+    /// Idle heartbeat reported messages that were never received.
+    SequenceGap,
     Other,
 }
 
@@ -795,6 +847,7 @@ impl std::fmt::Display for MessagesErrorKind {
             Self::ConsumerDeleted => write!(f, "consumer deleted"),
             Self::Other => write!(f, "error"),
             Self::PullBasedConsumer => write!(f, "cannot use with pull consumer"),
+            Self::SequenceGap => write!(f, "consumer sequence gap detected"),
         }
     }
 }
@@ -283,16 +283,22 @@ impl ObjectStore {
         let mut context = ring::digest::Context::new(&SHA256);
 
         loop {
-            let n = data
+            // Fill a whole chunk, as readers may return less than requested.
+            let n = (&mut *data)
+                .take((DEFAULT_CHUNK_SIZE - buffer.len()) as u64)
                 .read_buf(&mut buffer)
                 .await
                 .map_err(|err| PutError::with_source(PutErrorKind::ReadChunks, err))?;
 
-            if n == 0 {
+            if n > 0 && buffer.len() < DEFAULT_CHUNK_SIZE {
+                continue;
+            }
+            if buffer.is_empty() {
                 break;
             }
 
             let payload = buffer.split().freeze();
+            buffer.reserve(DEFAULT_CHUNK_SIZE);
             context.update(&payload);
 
             object_size += payload.len();
@@ -704,10 +710,11 @@ impl<'a> Object<'a> {
     pub(crate) fn new(subscription: Ordered<'a>, info: ObjectInfo) -> Self {
         Object {
             subscription: Some(subscription),
-            info,
             remaining_bytes: VecDeque::new(),
-            has_pending_messages: true,
+            // An empty object has no chunks to wait for.
+            has_pending_messages: info.chunks > 0,
             digest: Some(ring::digest::Context::new(&SHA256)),
+            info,
         }
     }
 
@@ -127,7 +127,7 @@ use futures::stream::Stream;
 use tracing::{debug, error};
 
 use core::fmt;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::iter;
 use std::net::{SocketAddr, ToSocketAddrs};
@@ -277,9 +277,25 @@ pub(crate) enum Command {
         sid: u64,
         max: Option<u64>,
     },
+    Request {
+        subject: String,
+        payload: Bytes,
+        headers: Option<HeaderMap>,
+        token: String,
+        sender: oneshot::Sender<Message>,
+    },
     Flush {
         result: oneshot::Sender<Result<(), io::Error>>,
     },
+    /// Drains a single subscription, or the whole client if `sid` is `None`.
+    Drain {
+        sid: Option<u64>,
+        result: oneshot::Sender<Result<(), io::Error>>,
+    },
+    /// Flushes and closes the connection, ending the connection handler.
+    Close {
+        result: oneshot::Sender<Result<(), io::Error>>,
+    },
     TryFlush,
 }
 
@@ -306,6 +322,28 @@ pub(crate) enum ClientOp {
     Connect(ConnectInfo),
 }
 
+/// Subscription id reserved for the shared request inbox.
+const MULTIPLEXER_SID: u64 = 0;
+
+/// Routes responses arriving on a single wildcard inbox subscription to pending requests.
+#[derive(Debug)]
+struct Multiplexer {
+    subject: String,
+    prefix: String,
+    senders: HashMap<String, oneshot::Sender<Message>>,
+}
+
+/// Work waiting for the `PONG` answering a specific `PING`: subscriptions being drained, which
+/// the server will not deliver any more messages to, and flushes or drains to resolve.
+#[derive(Debug, Default)]
+struct PendingPong {
+    drained: Vec<u64>,
+    results: Vec<oneshot::Sender<Result<(), io::Error>>>,
+}
+
+/// Maximum number of queued commands handled before the pending flushes are sent.
+const MAX_COMMANDS_BATCH: usize = 128;
+
 #[derive(Debug)]
 struct Subscription {
     subject: String,
@@ -320,10 +358,22 @@ pub(crate) struct ConnectionHandler {
     connection: Connection,
     connector: Connector,
     subscriptions: HashMap<u64, Subscription>,
+    multiplexer: Option<Multiplexer>,
+    inbox_prefix: String,
     pending_pings: usize,
+    /// One entry per `PING` in flight, in the order the `PONG`s will arrive.
+    pongs: VecDeque<PendingPong>,
+    flush_waiters: Vec<oneshot::Sender<Result<(), io::Error>>>,
+    draining: bool,
+    /// Set by [Command::Close], resolved once the connection was flushed and closed.
+    closing: Option<oneshot::Sender<Result<(), io::Error>>>,
     info_sender: tokio::sync::watch::Sender<ServerInfo>,
     ping_interval: Interval,
     flush_interval: Interval,
+    /// Publishes written since the last successful flush, replayed after a reconnect.
+    outbound: VecDeque<ClientOp>,
+    outbound_size: usize,
+    reconnect_buffer_size: usize,
 }
 
 impl ConnectionHandler {
@@ -333,6 +383,8 @@ impl ConnectionHandler {
         info_sender: tokio::sync::watch::Sender<ServerInfo>,
         ping_period: Duration,
         flush_period: Duration,
+        reconnect_buffer_size: usize,
+        inbox_prefix: String,
     ) -> ConnectionHandler {
         let mut ping_interval = interval(ping_period);
         ping_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
@@ -344,10 +396,19 @@ impl ConnectionHandler {
             connection,
             connector,
             subscriptions: HashMap::new(),
+            multiplexer: None,
+            inbox_prefix,
             pending_pings: 0,
+            pongs: VecDeque::new(),
+            flush_waiters: Vec::new(),
+            draining: false,
+            closing: None,
             info_sender,
             ping_interval,
             flush_interval,
+            outbound: VecDeque::new(),
+            outbound_size: 0,
+            reconnect_buffer_size,
         }
     }
 
@@ -360,6 +421,11 @@ impl ConnectionHandler {
                 _ = self.ping_interval.tick().fuse() => {
                     self.pending_pings += 1;
 
+                    // Free the slots of requests that were dropped before a response arrived.
+                    if let Some(multiplexer) = self.multiplexer.as_mut() {
+                        multiplexer.senders.retain(|_, sender| !sender.is_closed());
+                    }
+
                     if self.pending_pings > MAX_PENDING_PINGS {
                         debug!(
                             "pending pings {}, max pings {}. disconnecting",
@@ -370,6 +436,8 @@ impl ConnectionHandler {
 
                     if let Err(_err) = self.connection.write_op(&ClientOp::Ping).await {
                         self.handle_disconnect().await?;
+                    } else {
+                        self.pongs.push_back(PendingPong::default());
                     }
 
                     self.handle_flush().await?;
@@ -382,8 +450,24 @@ impl ConnectionHandler {
                 },
                 maybe_command = receiver.recv().fuse() => {
                     match maybe_command {
-                        Some(command) => if let Err(err) = self.handle_command(command).await {
-                            error!("error handling command {}", err);
+                        Some(command) => {
+                            if let Err(err) = self.handle_command(command).await {
+                                error!("error handling command {}", err);
+                            }
+                            // Handle commands that are already queued, so that concurrent
+                            // flushes share a single PING.
+                            for _ in 0..MAX_COMMANDS_BATCH {
+                                match receiver.try_recv() {
+                                    Ok(command) => if let Err(err) = self.handle_command(command).await {
+                                        error!("error handling command {}", err);
+                                    }
+                                    Err(_) => break,
+                                }
+                            }
+                            self.handle_flush_waiters().await?;
+                            if self.closing.is_some() {
+                                break;
+                            }
                         }
                         None => {
                             break;
@@ -399,11 +483,13 @@ impl ConnectionHandler {
                         Ok(None) => {
                             if let Err(err) = self.handle_disconnect().await {
                                 error!("error handling operation {}", err);
+                                return Err(err);
                             }
                         }
                         Err(op_err) => {
                             if let Err(err) = self.handle_disconnect().await {
                                 error!("error reconnecting {}. original error={}", err, op_err);
+                                return Err(err);
                             }
                         },
                     }
@@ -411,6 +497,14 @@ impl ConnectionHandler {
             }
         }
 
+        if let Some(result) = self.closing.take() {
+            let flushed = self.handle_flush().await;
+            self.connector.state_tx.send(State::Disconnected).ok();
+            self.connector.events_tx.try_send(Event::Closed).ok();
+            result.send(flushed).ok();
+            return Ok(());
+        }
+
         self.handle_flush().await?;
 
         Ok(())
@@ -427,6 +521,10 @@ impl ConnectionHandler {
             ServerOp::Pong => {
                 debug!("received PONG");
                 self.pending_pings = self.pending_pings.saturating_sub(1);
+
+                if let Some(pong) = self.pongs.pop_front() {
+                    self.complete_pong(pong);
+                }
             }
             ServerOp::Error(error) => {
                 self.connector
@@ -444,7 +542,27 @@ impl ConnectionHandler {
                 description,
                 length,
             } => {
-                if let Some(subscription) = self.subscriptions.get_mut(&sid) {
+                if sid == MULTIPLEXER_SID {
+                    if let Some(multiplexer) = self.multiplexer.as_mut() {
+                        let sender = subject
+                            .strip_prefix(&multiplexer.prefix)
+                            .and_then(|token| multiplexer.senders.remove(token));
+
+                        if let Some(sender) = sender {
+                            let message = Message {
+                                subject,
+                                reply,
+                                payload,
+                                headers,
+                                status,
+                                description,
+                                length,
+                            };
+                            // The request may have been dropped in the meantime.
+                            sender.send(message).ok();
+                        }
+                    }
+                } else if let Some(subscription) = self.subscriptions.get_mut(&sid) {
                     let message = Message {
                         subject,
                         reply,
@@ -508,6 +626,8 @@ impl ConnectionHandler {
     async fn handle_flush(&mut self) -> Result<(), io::Error> {
         self.connection.flush().await?;
         self.flush_interval.reset();
+        self.outbound.clear();
+        self.outbound_size = 0;
 
         Ok(())
     }
@@ -540,35 +660,70 @@ impl ConnectionHandler {
                 }
             }
             Command::Flush { result } => {
-                if let Err(_err) = self.handle_flush().await {
-                    if let Err(err) = self.handle_disconnect().await {
-                        result.send(Err(err)).map_err(|_| {
-                            io::Error::new(io::ErrorKind::Other, "one shot failed to be received")
-                        })?;
-                    } else if let Err(err) = self.handle_flush().await {
-                        result.send(Err(err)).map_err(|_| {
-                            io::Error::new(io::ErrorKind::Other, "one shot failed to be received")
-                        })?;
-                    } else {
-                        result.send(Ok(())).map_err(|_| {
-                            io::Error::new(io::ErrorKind::Other, "one shot failed to be received")
-                        })?;
-                    }
-                } else {
-                    result.send(Ok(())).map_err(|_| {
-                        io::Error::new(io::ErrorKind::Other, "one shot failed to be received")
-                    })?;
-                }
+                // Resolved by `handle_flush_waiters` once the server answered.
+                self.flush_waiters.push(result);
             }
             Command::TryFlush => {
                 self.handle_flush().await?;
             }
+            Command::Drain { sid, result } => {
+                let pong = match sid {
+                    Some(sid) if self.subscriptions.contains_key(&sid) => PendingPong {
+                        drained: vec![sid],
+                        results: vec![result],
+                    },
+                    Some(_) => PendingPong {
+                        results: vec![result],
+                        ..Default::default()
+                    },
+                    None => {
+                        // The request inbox is drained too: responses arriving before the
+                        // PONG still complete their requests, while new requests fail.
+                        self.draining = true;
+                        let mut sids: Vec<u64> = self.subscriptions.keys().copied().collect();
+                        if self.multiplexer.is_some() {
+                            sids.push(MULTIPLEXER_SID);
+                        }
+                        PendingPong {
+                            drained: sids,
+                            results: vec![result],
+                        }
+                    }
+                };
+
+                for sid in &pong.drained {
+                    if let Err(err) = self
+                        .connection
+                        .write_op(&ClientOp::Unsubscribe {
+                            sid: *sid,
+                            max: None,
+                        })
+                        .await
+                    {
+                        error!("Sending Unsubscribe failed with {:?}", err);
+                    }
+                }
+
+                // Messages sent by the server before it processed the unsubscribes arrive
+                // before the matching PONG, so the subscriptions are kept until then.
+                self.send_ping(pong).await?;
+            }
+            Command::Close { result } => {
+                self.closing = Some(result);
+            }
             Command::Subscribe {
                 sid,
                 subject,
                 queue_group,
                 sender,
             } => {
+                // The subscriber was dropped before the command got processed,
+                // e.g. a cancelled request. A drained client does not accept new subscriptions,
+                // dropping the sender ends the subscriber right away.
+                if sender.is_closed() || self.draining {
+                    return Ok(());
+                }
+
                 let subscription = Subscription {
                     sender,
                     delivered: 0,
@@ -591,22 +746,91 @@ impl ConnectionHandler {
                     error!("Sending Subscribe failed with {:?}", err);
                 }
             }
+            Command::Request {
+                subject,
+                payload,
+                headers,
+                token,
+                sender,
+            } => {
+                // Dropping the sender fails the request, as a drained client has no inbox.
+                if self.draining {
+                    return Ok(());
+                }
+
+                if self.multiplexer.is_none() {
+                    let prefix = format!("{}.{}.", self.inbox_prefix, nuid::next());
+                    let subject = format!("{}*", prefix);
+
+                    if let Err(err) = self
+                        .connection
+                        .write_op(&ClientOp::Subscribe {
+                            sid: MULTIPLEXER_SID,
+                            subject: subject.clone(),
+                            queue_group: None,
+                        })
+                        .await
+                    {
+                        error!("Sending Subscribe failed with {:?}", err);
+                    }
+
+                    self.multiplexer = Some(Multiplexer {
+                        subject,
+                        prefix,
+                        senders: HashMap::new(),
+                    });
+                }
+
+                if let Some(multiplexer) = self.multiplexer.as_mut() {
+                    let respond = format!("{}{}", multiplexer.prefix, token);
+                    multiplexer.senders.insert(token, sender);
+
+                    self.handle_publish(subject, payload, Some(respond), headers)
+                        .await?;
+                }
+            }
             Command::Publish {
                 subject,
                 payload,
                 respond,
                 headers,
             } => {
-                let pub_op = ClientOp::Publish {
-                    subject,
-                    payload,
-                    respond,
-                    headers,
-                };
-                while let Err(err) = self.connection.write_op(&pub_op).await {
-                    self.handle_disconnect().await?;
-                    error!("Sending Publish failed with {:?}", err);
-                }
+                self.handle_publish(subject, payload, respond, headers)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_publish(
+        &mut self,
+        subject: String,
+        payload: Bytes,
+        respond: Option<String>,
+        headers: Option<HeaderMap>,
+    ) -> Result<(), io::Error> {
+        // Keep the buffer of unflushed publishes bounded by flushing early.
+        if self.outbound_size + payload.len() > self.reconnect_buffer_size
+            && self.handle_flush().await.is_err()
+        {
+            self.handle_disconnect().await?;
+        }
+
+        self.outbound_size += payload.len();
+        self.outbound.push_back(ClientOp::Publish {
+            subject,
+            payload,
+            respond,
+            headers,
+        });
+
+        if let Some(pub_op) = self.outbound.back() {
+            let result = self.connection.write_op(pub_op).await;
+            if let Err(err) = result {
+                error!("Sending Publish failed with {:?}", err);
+                // The reconnect replays every unflushed publish, including this one.
+                self.handle_disconnect().await?;
             }
         }
 
@@ -622,9 +846,64 @@ impl ConnectionHandler {
         Ok(())
     }
 
+    async fn handle_flush_waiters(&mut self) -> Result<(), io::Error> {
+        if self.flush_waiters.is_empty() {
+            return Ok(());
+        }
+
+        let results = std::mem::take(&mut self.flush_waiters);
+        self.send_ping(PendingPong {
+            results,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Sends a `PING` and flushes, completing `pong` once the server answers.
+    async fn send_ping(&mut self, pong: PendingPong) -> Result<(), io::Error> {
+        self.pongs.push_back(pong);
+        if self.connection.write_op(&ClientOp::Ping).await.is_err()
+            || self.handle_flush().await.is_err()
+        {
+            // Reconnecting completes the pending pongs.
+            self.handle_disconnect().await?;
+        }
+
+        Ok(())
+    }
+
+    fn complete_pong(&mut self, pong: PendingPong) {
+        self.remove_drained(pong.drained);
+        for result in pong.results {
+            result.send(Ok(())).ok();
+        }
+    }
+
+    /// Removes drained subscriptions. Dropping their senders ends the subscribers once they
+    /// consumed buffered messages.
+    fn remove_drained(&mut self, sids: Vec<u64>) {
+        for sid in sids {
+            if sid == MULTIPLEXER_SID {
+                self.multiplexer = None;
+            } else {
+                self.subscriptions.remove(&sid);
+            }
+        }
+    }
+
     async fn handle_reconnect(&mut self) -> Result<(), io::Error> {
         let (info, connection) = self.connector.connect().await?;
         self.connection = connection;
+
+        // The new connection will not answer PINGs sent on the old one. Pending drains are
+        // complete, as the server has no state for their subscriptions anymore, and pending
+        // flushes complete with the flush below.
+        let mut results = Vec::new();
+        while let Some(pong) = self.pongs.pop_front() {
+            self.remove_drained(pong.drained);
+            results.extend(pong.results);
+        }
+
         self.info_sender.send(info).map_err(|err| {
             std::io::Error::new(
                 ErrorKind::Other,
@@ -642,9 +921,28 @@ impl ConnectionHandler {
                     subject: subscription.subject.to_owned(),
                     queue_group: subscription.queue_group.to_owned(),
                 })
-                .await
-                .unwrap();
+                .await?;
+        }
+
+        if let Some(multiplexer) = &self.multiplexer {
+            self.connection
+                .write_op(&ClientOp::Subscribe {
+                    sid: MULTIPLEXER_SID,
+                    subject: multiplexer.subject.to_owned(),
+                    queue_group: None,
+                })
+                .await?;
+        }
+
+        for op in &self.outbound {
+            self.connection.write_op(op).await?;
         }
+        self.handle_flush().await?;
+
+        for result in results {
+            result.send(Ok(())).ok();
+        }
+
         self.connector.events_tx.try_send(Event::Connected).ok();
 
         Ok(())
@@ -672,6 +970,8 @@ pub async fn connect_with_options<A: ToServerAddrs>(
 ) -> Result<Client, ConnectError> {
     let ping_period = options.ping_interval;
     let flush_period = options.flush_interval;
+    let reconnect_buffer_size = options.reconnect_buffer_size;
+    let inbox_prefix = options.inbox_prefix.clone();
 
     let (events_tx, mut events_rx) = mpsc::channel(128);
     let (state_tx, state_rx) = tokio::sync::watch::channel(State::Pending);
@@ -680,6 +980,7 @@ pub async fn connect_with_options<A: ToServerAddrs>(
         addrs,
         ConnectorOptions {
             tls_required: options.tls_required,
+            tls_first: options.tls_first,
             certificates: options.certificates,
             client_key: options.client_key,
             client_cert: options.client_cert,
@@ -691,6 +992,7 @@ pub async fn connect_with_options<A: ToServerAddrs>(
             ignore_discovered_servers: options.ignore_discovered_servers,
             retain_servers_order: options.retain_servers_order,
             read_buffer_capacity: options.read_buffer_capacity,
+            max_reconnects: options.max_reconnects,
             reconnect_delay_callback: options.reconnect_delay_callback,
             auth_callback: options.auth_callback,
         },
@@ -711,10 +1013,13 @@ pub async fn connect_with_options<A: ToServerAddrs>(
     let (info_sender, info_watcher) = tokio::sync::watch::channel(info);
     let (sender, receiver) = mpsc::channel(options.sender_capacity);
 
+    let (events_broadcast, _) = tokio::sync::broadcast::channel(EVENTS_CAPACITY);
+
     let client = Client::new(
         info_watcher,
         state_rx,
         sender,
+        events_broadcast.clone(),
         options.subscription_capacity,
         options.inbox_prefix,
         options.request_timeout,
@@ -722,15 +1027,24 @@ pub async fn connect_with_options<A: ToServerAddrs>(
 
     task::spawn(async move {
         while let Some(event) = events_rx.recv().await {
+            // Sending only fails if there are no `Events` streams.
+            events_broadcast.send(event.clone()).ok();
             options.event_callback.call(event).await
         }
     });
 
     task::spawn(async move {
         if connection.is_none() && options.retry_on_initial_connect {
-            let (info, connection_ok) = connector.connect().await.unwrap();
-            info_sender.send(info).ok();
-            connection = Some(connection_ok);
+            match connector.connect().await {
+                Ok((info, connection_ok)) => {
+                    info_sender.send(info).ok();
+                    connection = Some(connection_ok);
+                }
+                Err(err) => {
+                    error!("failed to connect: {}", err);
+                    return Err(err);
+                }
+            }
         }
         let connection = connection.unwrap();
         let mut connection_handler = ConnectionHandler::new(
@@ -739,6 +1053,8 @@ pub async fn connect_with_options<A: ToServerAddrs>(
             info_sender,
             ping_period,
             flush_period,
+            reconnect_buffer_size,
+            inbox_prefix,
         );
         connection_handler.process(receiver).await
     });
@@ -746,6 +1062,51 @@ pub async fn connect_with_options<A: ToServerAddrs>(
     Ok(client)
 }
 
+/// Number of events buffered for each [Events] stream before the slowest one starts lagging.
+const EVENTS_CAPACITY: usize = 128;
+
+/// A [Stream] of connection [Event]s, created by [Client::events].
+///
+/// Every stream receives every event emitted after it was created. If the stream is not
+/// polled often enough, the oldest events are skipped.
+pub struct Events {
+    inner: futures::stream::BoxStream<'static, Event>,
+}
+
+impl Events {
+    pub(crate) fn new(receiver: tokio::sync::broadcast::Receiver<Event>) -> Events {
+        let inner = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("events stream lagged, skipped {} events", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Events {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events").finish()
+    }
+}
+
+impl Stream for Events {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     Connected,
@@ -754,6 +1115,9 @@ pub enum Event {
     SlowConsumer(u64),
     ServerError(ServerError),
     ClientError(ClientError),
+    /// The connection was closed, either by [Client::drain] or after reaching
+    /// [ConnectOptions::max_reconnects].
+    Closed,
 }
 
 impl fmt::Display for Event {
@@ -765,6 +1129,7 @@ impl fmt::Display for Event {
             Event::SlowConsumer(sid) => write!(f, "slow consumers for subscription {sid}"),
             Event::ServerError(err) => write!(f, "server error: {err}"),
             Event::ClientError(err) => write!(f, "client error: {err}"),
+            Event::Closed => write!(f, "closed"),
         }
     }
 }
@@ -939,6 +1304,43 @@ impl Subscriber {
         Ok(())
     }
 
+    /// Unsubscribes from the subject, while still delivering messages that were sent by the
+    /// server before it processed the unsubscribe. Resolves once no more messages will be
+    /// received; the [Subscriber] then ends after all buffered messages were consumed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use futures::StreamExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let mut subscriber = client.subscribe("events".into()).await?;
+    ///
+    /// subscriber.drain().await?;
+    /// while let Some(message) = subscriber.next().await {
+    ///     println!("message received: {:?}", message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn drain(&mut self) -> Result<(), client::DrainError> {
+        let (result, rx) = oneshot::channel();
+        self.sender
+            .send(Command::Drain {
+                sid: Some(self.sid),
+                result,
+            })
+            .await
+            .map_err(|err| {
+                client::DrainError::with_source(client::DrainErrorKind::SendError, err)
+            })?;
+        rx.await
+            .map_err(|err| {
+                client::DrainError::with_source(client::DrainErrorKind::DrainError, err)
+            })?
+            .map_err(|err| client::DrainError::with_source(client::DrainErrorKind::DrainError, err))
+    }
+
     /// Unsubscribes from subscription after reaching given number of messages.
     /// This is the total number of messages received by this subscription in it's whole
     /// lifespan. If it already reached or surpassed the passed value, it will immediately stop.
@@ -989,16 +1391,21 @@ impl From<tokio::sync::mpsc::error::SendError<Command>> for UnsubscribeError {
 impl Drop for Subscriber {
     fn drop(&mut self) {
         self.receiver.close();
-        tokio::spawn({
-            let sender = self.sender.clone();
-            let sid = self.sid;
-            async move {
-                sender
-                    .send(Command::Unsubscribe { sid, max: None })
-                    .await
-                    .ok();
+
+        // Avoid spawning when possible, so dropping a pending request or subscription
+        // outside of a runtime (or during shutdown) does not panic.
+        let sid = self.sid;
+        if let Err(mpsc::error::TrySendError::Full(command)) = self
+            .sender
+            .try_send(Command::Unsubscribe { sid, max: None })
+        {
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                let sender = self.sender.clone();
+                handle.spawn(async move {
+                    sender.send(command).await.ok();
+                });
             }
-        });
+        }
     }
 }
 
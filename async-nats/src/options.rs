@@ -51,6 +51,7 @@ pub struct ConnectOptions {
     pub(crate) connection_timeout: Duration,
     pub(crate) auth: Auth,
     pub(crate) tls_required: bool,
+    pub(crate) tls_first: bool,
     pub(crate) certificates: Vec<PathBuf>,
     pub(crate) client_cert: Option<PathBuf>,
     pub(crate) client_key: Option<PathBuf>,
@@ -80,6 +81,7 @@ impl fmt::Debug for ConnectOptions {
             .entry(&"max_reconnects", &self.max_reconnects)
             .entry(&"connection_timeout", &self.connection_timeout)
             .entry(&"tls_required", &self.tls_required)
+            .entry(&"tls_first", &self.tls_first)
             .entry(&"certificates", &self.certificates)
             .entry(&"client_cert", &self.client_cert)
             .entry(&"client_key", &self.client_key)
@@ -101,9 +103,10 @@ impl Default for ConnectOptions {
             no_echo: false,
             retry_on_failed_connect: false,
             reconnect_buffer_size: 8 * 1024 * 1024,
-            max_reconnects: Some(60),
+            max_reconnects: None,
             connection_timeout: Duration::from_secs(5),
             tls_required: false,
+            tls_first: false,
             certificates: Vec::new(),
             client_cert: None,
             client_key: None,
@@ -568,6 +571,27 @@ impl ConnectOptions {
         self
     }
 
+    /// Performs the TLS handshake immediately after the TCP connection is established, before
+    /// the server sends its `INFO`. The server has to be configured with `handshake_first`.
+    /// Implies [ConnectOptions::require_tls].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::ConnectError> {
+    /// let nc = async_nats::ConnectOptions::new()
+    ///     .tls_first()
+    ///     .connect("tls://demo.nats.io")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tls_first(mut self) -> ConnectOptions {
+        self.tls_first = true;
+        self.tls_required = true;
+        self
+    }
+
     /// Sets the interval for flushing. NATS connection will send buffered data to the NATS Server
     /// whenever buffer limit is reached, but it is also necessary to flush once in a while if
     /// client is sending rarely and small messages. Flush interval allows to modify that interval.
@@ -833,6 +857,45 @@ impl ConnectOptions {
         self
     }
 
+    /// Sets the maximum number of consecutive connection attempts before the client gives up
+    /// and closes, emitting [crate::Event::Closed]. By default the client keeps trying to
+    /// reconnect forever.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// async_nats::ConnectOptions::new()
+    ///     .max_reconnects(10)
+    ///     .connect("demo.nats.io")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn max_reconnects<T: Into<Option<usize>>>(mut self, max_reconnects: T) -> ConnectOptions {
+        self.max_reconnects = max_reconnects.into();
+        self
+    }
+
+    /// Sets the maximum number of payload bytes of publishes kept in memory until they are
+    /// flushed. If the connection is lost, buffered publishes are resent after reconnecting.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// async_nats::ConnectOptions::new()
+    ///     .reconnect_buffer_size(1024 * 1024)
+    ///     .connect("demo.nats.io")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reconnect_buffer_size(mut self, reconnect_buffer_size: usize) -> ConnectOptions {
+        self.reconnect_buffer_size = reconnect_buffer_size;
+        self
+    }
+
     pub fn retry_on_initial_connect(mut self) -> ConnectOptions {
         self.retry_on_initial_connect = true;
         self
@@ -13,16 +13,18 @@
 
 use std::{
     collections::HashMap,
+    future::Future,
     sync::{Arc, Mutex},
     task::Poll,
     time::Instant,
 };
 
+use bytes::Bytes;
 use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, trace};
 
-use crate::{Client, Subscriber};
+use crate::{Client, Message, Subscriber};
 
 use super::{error, Endpoints, Request, ShutdownReceiverFuture};
 
@@ -43,25 +45,29 @@ impl Stream for Endpoint {
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
         trace!("polling for next request");
-        match self.shutdown_future.as_mut() {
-            Some(shutdown) => match shutdown.as_mut().poll(cx) {
+        if self.shutdown_future.is_none() {
+            if let Some(mut receiver) = self.shutdown.take() {
+                self.shutdown_future = Some(Box::pin(async move { receiver.recv().await }));
+            }
+        }
+        if let Some(shutdown) = self.shutdown_future.as_mut() {
+            match shutdown.as_mut().poll(cx) {
                 Poll::Ready(_result) => {
                     debug!("got stop broadcast");
+                    self.shutdown_future = None;
+                    // Drain, so requests that already reached the endpoint are still handled.
+                    let (result, _) = tokio::sync::oneshot::channel();
                     self.requests
                         .sender
-                        .try_send(crate::Command::Unsubscribe {
-                            sid: self.requests.sid,
-                            max: None,
+                        .try_send(crate::Command::Drain {
+                            sid: Some(self.requests.sid),
+                            result,
                         })
                         .ok();
                 }
                 Poll::Pending => {
                     trace!("stop broadcast still pending");
                 }
-            },
-            None => {
-                let mut receiver = self.shutdown.take().unwrap();
-                self.shutdown_future = Some(Box::pin(async move { receiver.recv().await }));
             }
         }
         trace!("checking for new messages");
@@ -93,6 +99,45 @@ impl Stream for Endpoint {
 }
 
 impl Endpoint {
+    /// Handles requests with an async `handler` until the [Endpoint] or its
+    /// [Service][super::Service] is stopped, responding with the handler's result.
+    /// Requests are handled concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// # let client = async_nats::connect("demo.nats.io").await?;
+    /// let service = client
+    ///     .service_builder()
+    ///     .start("generator", "1.0.0")
+    ///     .await?;
+    ///
+    /// let endpoint = service.endpoint("echo").await?;
+    /// endpoint
+    ///     .serve(|message| async move { Ok(message.payload) })
+    ///     .await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn serve<F, Fut>(self, handler: F)
+    where
+        F: Fn(Message) -> Fut,
+        Fut: Future<Output = Result<Bytes, error::Error>>,
+    {
+        self.for_each_concurrent(None, |request| {
+            let response = handler(request.message.clone());
+            async move {
+                if let Err(err) = request.respond(response.await).await {
+                    debug!("failed to respond to request: {}", err);
+                }
+            }
+        })
+        .await
+    }
+
     /// Stops the [Endpoint] and unsubscribes from the subject.
     pub async fn stop(&mut self) -> Result<(), std::io::Error> {
         self.requests
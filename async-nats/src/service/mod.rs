@@ -697,7 +697,10 @@ impl Request {
         let stats = stats.endpoints.get_mut(self.endpoint.as_str()).unwrap();
         stats.requests += 1;
         stats.processing_time += elapsed;
-        stats.average_processing_time = stats.processing_time.checked_div(2).unwrap();
+        stats.average_processing_time = stats
+            .processing_time
+            .checked_div(stats.requests as u32)
+            .unwrap_or_default();
         result
     }
 }
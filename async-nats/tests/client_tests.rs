@@ -226,6 +226,81 @@ mod client {
         assert_eq!(err.kind(), RequestErrorKind::TimedOut)
     }
 
+    #[tokio::test]
+    async fn request_cancelled() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut sub = client.subscribe("service".into()).await.unwrap();
+        client.flush().await.unwrap();
+
+        // Drop a number of pending requests before they receive a response.
+        for _ in 0..100 {
+            tokio::time::timeout(
+                Duration::from_millis(1),
+                client.request("service".into(), "payload".into()),
+            )
+            .await
+            .ok();
+        }
+
+        tokio::spawn({
+            let client = client.clone();
+            async move {
+                while let Some(message) = sub.next().await {
+                    if let Some(reply) = message.reply {
+                        client.publish(reply, "reply".into()).await.unwrap();
+                    }
+                }
+            }
+        });
+
+        let response = client
+            .request("service".into(), "payload".into())
+            .await
+            .unwrap();
+        assert_eq!(response.payload, Bytes::from("reply"));
+    }
+
+    #[tokio::test]
+    async fn request_concurrent() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut sub = client.subscribe("service".into()).await.unwrap();
+        client.flush().await.unwrap();
+
+        tokio::spawn({
+            let client = client.clone();
+            async move {
+                while let Some(message) = sub.next().await {
+                    if let Some(reply) = message.reply {
+                        client.publish(reply, message.payload).await.unwrap();
+                    }
+                }
+            }
+        });
+
+        let requests = (0..2000).map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let response = client
+                    .request("service".into(), i.to_string().into())
+                    .await
+                    .unwrap();
+                assert_eq!(response.payload, i.to_string());
+            })
+        });
+
+        tokio::time::timeout(
+            Duration::from_secs(30),
+            futures::future::try_join_all(requests),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn request_no_responders() {
         let server = nats_server::run_basic_server();
@@ -485,6 +560,135 @@ mod client {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn publish_during_reconnect() {
+        let server = nats_server::run_basic_server();
+        let port = server.client_port().to_string();
+
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let mut subscriber = client.subscribe("test".to_string()).await.unwrap();
+        client.flush().await.unwrap();
+
+        drop(server);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let publisher = tokio::spawn({
+            let client = client.clone();
+            async move {
+                for i in 0..10 {
+                    client
+                        .publish("test".to_string(), i.to_string().into())
+                        .await
+                        .unwrap();
+                }
+                client.flush().await.unwrap();
+            }
+        });
+
+        let _server = nats_server::run_server_with_port("", Some(port.as_str()));
+
+        tokio::time::timeout(Duration::from_secs(15), publisher)
+            .await
+            .unwrap()
+            .unwrap();
+
+        for i in 0..10 {
+            let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(message.payload, i.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn connection_events() {
+        let server = nats_server::run_basic_server();
+        let port = server.client_port().to_string();
+
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let mut events = client.events();
+
+        drop(server);
+        let _server = nats_server::run_server_with_port("", Some(port.as_str()));
+
+        let event = tokio::time::timeout(Duration::from_secs(15), events.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event, Event::Disconnected);
+
+        let event = tokio::time::timeout(
+            Duration::from_secs(15),
+            events
+                .by_ref()
+                .filter(|event| futures::future::ready(*event == Event::Connected))
+                .next(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(event, Some(Event::Connected));
+    }
+
+    #[tokio::test]
+    async fn concurrent_flush() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut subscriber = client.subscribe("test".into()).await.unwrap();
+
+        let flushes = (0..100).map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client.publish("test".into(), "data".into()).await.unwrap();
+                client.flush().await.unwrap();
+            })
+        });
+        futures::future::try_join_all(flushes).await.unwrap();
+
+        // A resolved flush means the server processed everything sent before it.
+        for _ in 0..100 {
+            tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn drain() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut subscriber = client.subscribe("test".into()).await.unwrap();
+        let mut other = client.subscribe("other".into()).await.unwrap();
+        client.flush().await.unwrap();
+
+        for _ in 0..100 {
+            client.publish("test".into(), "data".into()).await.unwrap();
+        }
+        subscriber.drain().await.unwrap();
+
+        // All messages published before draining are delivered, then the subscriber ends.
+        tokio::time::timeout(Duration::from_secs(5), async {
+            assert_eq!(subscriber.by_ref().count().await, 100);
+        })
+        .await
+        .unwrap();
+
+        // Draining the client closes the connection without waiting for subscribers, which
+        // still yield their buffered messages and then end.
+        client.publish("other".into(), "data".into()).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), client.drain())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.by_ref().count().await, 1);
+
+        assert!(client.subscribe("late".into()).await.is_err());
+        assert!(client.request("test".into(), "data".into()).await.is_err());
+    }
+
     #[tokio::test]
     #[cfg_attr(target_os = "windows", ignore)]
     async fn lame_duck_callback() {
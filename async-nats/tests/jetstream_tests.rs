@@ -1810,6 +1810,57 @@ mod jetstream {
         }
     }
 
+    #[tokio::test]
+    async fn pull_stream_slow_consumer_with_heartbeat() {
+        let server = nats_server::run_server("tests/configs/jetstream.conf");
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let context = async_nats::jetstream::new(client);
+
+        context
+            .create_stream(stream::Config {
+                name: "events".to_string(),
+                subjects: vec!["events".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let stream = context.get_stream("events").await.unwrap();
+        stream
+            .create_consumer(consumer::pull::Config {
+                durable_name: Some("pull".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let consumer: PullConsumer = stream.get_consumer("pull").await.unwrap();
+
+        for i in 0..10 {
+            context
+                .publish("events".to_string(), format!("message: {i}").into())
+                .await
+                .unwrap()
+                .await
+                .unwrap();
+        }
+
+        let mut messages = consumer
+            .stream()
+            .max_messages_per_batch(10)
+            .heartbeat(Duration::from_millis(100))
+            .messages()
+            .await
+            .unwrap()
+            .take(10);
+
+        // Processing slower than the heartbeat interval should not surface missed heartbeats
+        // while messages are still buffered.
+        while let Some(message) = messages.next().await {
+            message.unwrap().ack().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+        }
+    }
+
     #[cfg(feature = "server_2_10")]
     #[tokio::test]
     async fn update_consumer() {
@@ -444,6 +444,48 @@ mod kv {
         }
     }
 
+    #[tokio::test]
+    async fn watch_cancellation() {
+        let server = nats_server::run_server("tests/configs/jetstream.conf");
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let context = async_nats::jetstream::new(client);
+
+        let kv = context
+            .create_key_value(async_nats::jetstream::kv::Config {
+                bucket: "cancel".to_string(),
+                history: 15,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // The watch outlives the bucket handle it was created from.
+        let mut watch = {
+            let kv = kv.clone();
+            kv.watch("foo").await.unwrap()
+        };
+
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let handle = tokio::task::spawn(async move {
+            let mut count = 0;
+            loop {
+                tokio::select! {
+                    entry = watch.next() => {
+                        entry.unwrap().unwrap();
+                        count += 1;
+                    }
+                    _ = &mut shutdown_rx => break count,
+                }
+            }
+        });
+
+        kv.put("foo", "bar".into()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown_tx.send(()).unwrap();
+        assert_eq!(handle.await.unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn watch_with_history() {
         let server = nats_server::run_server("tests/configs/jetstream.conf");
@@ -69,6 +69,60 @@ mod object_store {
         assert_eq!(result, bytes);
     }
 
+    #[tokio::test]
+    async fn put_streaming_reader() {
+        let server = nats_server::run_server("tests/configs/jetstream.conf");
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let jetstream = async_nats::jetstream::new(client);
+
+        let bucket = jetstream
+            .create_object_store(async_nats::jetstream::object_store::Config {
+                bucket: "bucket".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // A pipe hands out data in small pieces, which still have to end up in full chunks.
+        let (mut writer, mut reader) = tokio::io::duplex(1024);
+        let bytes = vec![7; 1024 * 1024];
+        tokio::spawn({
+            let bytes = bytes.clone();
+            async move {
+                tokio::io::AsyncWriteExt::write_all(&mut writer, &bytes)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let info = bucket.put("FOO", &mut reader).await.unwrap();
+        assert_eq!(info.size, bytes.len());
+        assert_eq!(info.chunks, 8);
+
+        let mut result = Vec::new();
+        bucket
+            .get("FOO")
+            .await
+            .unwrap()
+            .read_to_end(&mut result)
+            .await
+            .unwrap();
+        assert_eq!(result, bytes);
+
+        // Empty objects have no chunks and should be read without waiting for any.
+        bucket.put("EMPTY", &mut tokio::io::empty()).await.unwrap();
+        let mut result = Vec::new();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            bucket.get("EMPTY").await.unwrap().read_to_end(&mut result),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(result.is_empty());
+    }
+
     #[tokio::test]
     async fn watch() {
         let server = nats_server::run_server("tests/configs/jetstream.conf");
@@ -168,6 +168,39 @@ mod service {
         responses.next().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn serve() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let service = client
+            .service_builder()
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+
+        let endpoint = service.endpoint("echo").await.unwrap();
+        tokio::spawn(endpoint.serve(|message| async move {
+            if message.payload.is_empty() {
+                return Err(async_nats::service::error::Error {
+                    status: "empty payload".to_string(),
+                    code: 400,
+                });
+            }
+            Ok(message.payload)
+        }));
+
+        let response = client.request("echo".into(), "data".into()).await.unwrap();
+        assert_eq!(response.payload, "data");
+
+        let response = client.request("echo".into(), "".into()).await.unwrap();
+        assert!(response.headers.is_some());
+
+        let stats = service.stats().await;
+        let stats = stats.values().next().unwrap();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.errors, 1);
+    }
+
     #[tokio::test]
     async fn groups() {
         let server = nats_server::run_basic_server();
@@ -44,6 +44,27 @@ mod client {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn tls_first() {
+        let server = nats_server::run_server("tests/configs/tls_first.conf");
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+        let client = async_nats::ConnectOptions::new()
+            .add_root_certificates(path.join("tests/configs/certs/rootCA.pem"))
+            .add_client_certificate(
+                path.join("tests/configs/certs/client-cert.pem"),
+                path.join("tests/configs/certs/client-key.pem"),
+            )
+            .tls_first()
+            .connect(server.client_url())
+            .await
+            .unwrap();
+
+        let mut subscriber = client.subscribe("foo".into()).await.unwrap();
+        client.publish("foo".into(), "data".into()).await.unwrap();
+        assert!(subscriber.next().await.is_some());
+    }
+
     #[tokio::test]
     async fn ip_basic_tls() {
         let server = nats_server::run_server("tests/configs/ip-tls.conf");
@@ -25,6 +25,8 @@ use regex::Regex;
 
 pub struct Server {
     inner: Inner,
+    // Config generated by a `ServerBuilder`, removed on drop.
+    generated_cfg: Option<PathBuf>,
 }
 
 struct Inner {
@@ -39,6 +41,7 @@ struct Inner {
 lazy_static! {
     static ref SD_RE: Regex = Regex::new(r#".+\sStore Directory:\s+"([^"]+)""#).unwrap();
     static ref CLIENT_RE: Regex = Regex::new(r#".+\sclient connections on\s+(\S+)"#).unwrap();
+    static ref BINARY: PathBuf = locate_binary();
 }
 
 impl Drop for Server {
@@ -54,6 +57,9 @@ impl Drop for Server {
             // Remove Logfile.
             fs::remove_file(self.inner.logfile.as_os_str()).ok();
         }
+        if let Some(cfg) = self.generated_cfg.take() {
+            fs::remove_file(cfg).ok();
+        }
     }
 }
 
@@ -140,7 +146,7 @@ impl Server {
 }
 
 pub fn set_lame_duck_mode(s: &Server) {
-    let mut cmd = Command::new("nats-server");
+    let mut cmd = Command::new(binary());
     cmd.arg("--signal")
         .arg(format!("ldm={}", s.client_pid()))
         .spawn()
@@ -236,6 +242,7 @@ impl Cluster {
 pub fn run_server_with_port(cfg: &str, port: Option<&str>) -> Server {
     Server {
         inner: do_run(cfg, port, None),
+        generated_cfg: None,
     }
 }
 
@@ -247,7 +254,7 @@ fn do_run(cfg: &str, port: Option<&str>, id: Option<String>) -> Inner {
 
     // Always use dynamic ports so tests can run in parallel.
     // Create env for a storage directory for jetstream.
-    let mut cmd = Command::new("nats-server");
+    let mut cmd = Command::new(binary());
     cmd.arg("--store_dir")
         .arg(store_dir.as_path().to_str().unwrap())
         .arg("-p");
@@ -290,7 +297,7 @@ fn run_cluster_node_with_port(
 
     // Always use dynamic ports so tests can run in parallel.
     // Create env for a storage directory for jetstream.
-    let mut cmd = Command::new("nats-server");
+    let mut cmd = Command::new(binary());
     cmd.arg("--store_dir")
         .arg(store_dir.as_path().to_str().unwrap())
         .arg("-p");
@@ -332,6 +339,7 @@ fn run_cluster_node_with_port(
             logfile,
             pidfile,
         },
+        generated_cfg: None,
     }
 }
 
@@ -340,6 +348,166 @@ pub fn run_basic_server() -> Server {
     run_server("")
 }
 
+/// Builds a config for a local NATS server listening on a random port.
+///
+/// # Example
+/// ```no_run
+/// let server = nats_server::ServerBuilder::new()
+///     .jetstream(true)
+///     .user_pass("derek", "s3cr3t")
+///     .run();
+/// let url = server.client_url_with("derek", "s3cr3t");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ServerBuilder {
+    jetstream: bool,
+    tls: Option<(PathBuf, PathBuf)>,
+    user_pass: Option<(String, String)>,
+    token: Option<String>,
+}
+
+impl ServerBuilder {
+    pub fn new() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    /// Enables JetStream.
+    pub fn jetstream(mut self, enabled: bool) -> ServerBuilder {
+        self.jetstream = enabled;
+        self
+    }
+
+    /// Requires TLS using the given certificate and key files.
+    pub fn tls(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> ServerBuilder {
+        self.tls = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// Requires clients to authenticate with a user and password.
+    pub fn user_pass(mut self, user: &str, pass: &str) -> ServerBuilder {
+        self.user_pass = Some((user.to_string(), pass.to_string()));
+        self
+    }
+
+    /// Requires clients to authenticate with a token.
+    pub fn token(mut self, token: &str) -> ServerBuilder {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Returns the generated config.
+    pub fn config(&self) -> String {
+        let mut cfg = String::new();
+        if self.jetstream {
+            cfg.push_str("jetstream {}\n");
+        }
+        if let Some((cert, key)) = &self.tls {
+            cfg.push_str(&format!(
+                "tls {{\n  cert_file: {:?}\n  key_file: {:?}\n}}\n",
+                cert.display().to_string(),
+                key.display().to_string()
+            ));
+        }
+        if let Some((user, pass)) = &self.user_pass {
+            cfg.push_str(&format!(
+                "authorization {{\n  user: {user:?}\n  password: {pass:?}\n}}\n"
+            ));
+        } else if let Some(token) = &self.token {
+            cfg.push_str(&format!("authorization {{\n  token: {token:?}\n}}\n"));
+        }
+        cfg
+    }
+
+    /// Starts the server, which gets stopped and cleaned up on drop.
+    pub fn run(self) -> Server {
+        let cfg = env::temp_dir().join(format!("nats-server-{}.conf", nuid::next()));
+        fs::write(&cfg, self.config()).expect("could not write server config");
+        Server {
+            inner: do_run(cfg.to_str().unwrap(), None, None),
+            generated_cfg: Some(cfg),
+        }
+    }
+}
+
+/// Returns the `nats-server` binary used to run servers.
+///
+/// `NATS_SERVER_BIN` takes precedence, followed by `nats-server` in `PATH`. Otherwise, if
+/// `NATS_SERVER_VERSION` is set (e.g. `v2.10.1`), that release is downloaded once into the
+/// temporary directory with `curl` and `unzip`.
+pub fn binary() -> PathBuf {
+    BINARY.clone()
+}
+
+fn locate_binary() -> PathBuf {
+    let name = format!("nats-server{}", env::consts::EXE_SUFFIX);
+    if let Some(path) = env::var_os("NATS_SERVER_BIN") {
+        return PathBuf::from(path);
+    }
+    if let Some(path) = env::var_os("PATH")
+        .iter()
+        .flat_map(env::split_paths)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+    {
+        return path;
+    }
+    if let Ok(version) = env::var("NATS_SERVER_VERSION") {
+        if let Some(path) = download_binary(&version, &name) {
+            return path;
+        }
+    }
+    // Let spawning report the missing binary.
+    PathBuf::from(name)
+}
+
+fn download_binary(version: &str, name: &str) -> Option<PathBuf> {
+    let os = match env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    let arch = match env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "386",
+        arch => arch,
+    };
+    let release = format!("nats-server-{version}-{os}-{arch}");
+    let dir = env::temp_dir().join("nats-server-releases");
+    let path = dir.join(&release).join(name);
+    if path.is_file() {
+        return Some(path);
+    }
+
+    fs::create_dir_all(&dir).ok()?;
+    let archive = dir.join(format!("{release}.zip"));
+    let url =
+        format!("https://github.com/nats-io/nats-server/releases/download/{version}/{release}.zip");
+    let downloaded = Command::new("curl")
+        .arg("-sSfL")
+        .arg("-o")
+        .arg(&archive)
+        .arg(url)
+        .status()
+        .ok()?
+        .success();
+    let unpacked = downloaded
+        && Command::new("unzip")
+            .arg("-oq")
+            .arg(&archive)
+            .arg("-d")
+            .arg(&dir)
+            .status()
+            .ok()?
+            .success();
+    fs::remove_file(&archive).ok();
+
+    if unpacked && path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -378,6 +546,24 @@ mod tests {
         jetstream.delete_stream("replicated").await.unwrap();
     }
 
+    #[tokio::test]
+    async fn builder() {
+        let server = crate::ServerBuilder::new()
+            .jetstream(true)
+            .user_pass("derek", "s3cr3t")
+            .run();
+
+        let client = async_nats::ConnectOptions::with_user_and_password(
+            "derek".to_string(),
+            "s3cr3t".to_string(),
+        )
+        .connect(server.client_url())
+        .await
+        .unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+        jetstream.query_account().await.unwrap();
+    }
+
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
     async fn cluster_without_js() {
@@ -403,6 +403,10 @@ pub struct Message {
     /// using `JetStream`.
     #[doc(hidden)]
     pub double_acked: Arc<AtomicBool>,
+
+    /// When the message was received, tracked if [`crate::Options::metrics`] is set.
+    #[doc(hidden)]
+    pub received: Option<std::time::Instant>,
 }
 
 impl From<crate::Message> for Message {
@@ -414,6 +418,7 @@ impl From<crate::Message> for Message {
             headers: sync.headers,
             client: sync.client,
             double_acked: sync.double_acked,
+            received: sync.received,
         }
     }
 }
@@ -469,6 +474,7 @@ impl Default for Message {
             headers: None,
             client: None,
             double_acked: Arc::new(AtomicBool::new(false)),
+            received: None,
         }
     }
 }
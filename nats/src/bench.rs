@@ -0,0 +1,252 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Standardized throughput and latency measurements of a connection.
+//!
+//! A [`Bench`] runs publish, publish/subscribe and request/reply tests against an existing
+//! connection and returns a [`BenchResult`] for each, for self-diagnostics in operational
+//! tooling. The tests use a subject of their own, so they can run against servers in use.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use nats::bench::Bench;
+//!
+//! let nc = nats::connect("demo.nats.io")?;
+//! let bench = Bench::new(&nc).messages(10_000).payload_size(128);
+//!
+//! let result = bench.publish_subscribe()?;
+//! println!("{:.0} msgs/sec", result.messages_per_second());
+//!
+//! let result = bench.request()?;
+//! if let Some(latency) = result.latency {
+//!     println!("p99 request latency: {:?}", latency.p99);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::Connection;
+
+/// Runs benchmarks against a connection.
+#[derive(Debug, Clone)]
+pub struct Bench<'a> {
+    connection: &'a Connection,
+    subject: Option<String>,
+    messages: usize,
+    payload_size: usize,
+    timeout: Duration,
+}
+
+impl<'a> Bench<'a> {
+    /// Creates a benchmark of 100,000 messages of 128 bytes, or 1,000 requests.
+    pub fn new(connection: &'a Connection) -> Bench<'a> {
+        Bench {
+            connection,
+            subject: None,
+            messages: 100_000,
+            payload_size: 128,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Sets the subject of the tests, a unique subject by default.
+    pub fn subject(mut self, subject: impl Into<String>) -> Bench<'a> {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets the number of messages, or requests, of each test.
+    pub fn messages(mut self, messages: usize) -> Bench<'a> {
+        self.messages = messages;
+        self
+    }
+
+    /// Sets the size of the payloads.
+    pub fn payload_size(mut self, payload_size: usize) -> Bench<'a> {
+        self.payload_size = payload_size;
+        self
+    }
+
+    /// Sets how long a test may take to receive all messages, or a single request its
+    /// response, before failing with `TimedOut`.
+    pub fn timeout(mut self, timeout: Duration) -> Bench<'a> {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Measures the throughput of publishing, until all messages are flushed to the server.
+    pub fn publish(&self) -> io::Result<BenchResult> {
+        let subject = self.resolved_subject();
+        let payload = vec![0; self.payload_size];
+
+        let start = Instant::now();
+        for _ in 0..self.messages {
+            self.connection.publish(&subject, &payload)?;
+        }
+        self.connection.flush_timeout(self.timeout)?;
+
+        Ok(self.result(start.elapsed(), None))
+    }
+
+    /// Measures the throughput of publishing messages until they are all received by a
+    /// subscription of the same connection.
+    pub fn publish_subscribe(&self) -> io::Result<BenchResult> {
+        let subject = self.resolved_subject();
+        let payload = vec![0; self.payload_size];
+        let subscription = self.connection.subscribe(&subject)?;
+        self.connection.flush_timeout(self.timeout)?;
+
+        let messages = self.messages;
+        let timeout = self.timeout;
+        let receiver = {
+            let subscription = subscription.clone();
+            thread::Builder::new()
+                .name(self.connection.0.client.thread_name("bench_subscriber"))
+                .spawn(move || -> io::Result<()> {
+                    for _ in 0..messages {
+                        subscription.next_timeout(timeout)?;
+                    }
+                    Ok(())
+                })?
+        };
+
+        let start = Instant::now();
+        let published = (0..self.messages)
+            .try_for_each(|_| self.connection.publish(&subject, &payload))
+            .and_then(|_| self.connection.flush_timeout(self.timeout));
+        if let Err(err) = published {
+            subscription.unsubscribe().ok();
+            return Err(err);
+        }
+        let received = receiver
+            .join()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "benchmark subscriber panicked"))?;
+        let duration = start.elapsed();
+
+        subscription.unsubscribe()?;
+        received?;
+        Ok(self.result(duration, None))
+    }
+
+    /// Measures the throughput and latency of requests, sent one after another to a
+    /// responder on the same connection.
+    pub fn request(&self) -> io::Result<BenchResult> {
+        let subject = self.resolved_subject();
+        let payload = vec![0; self.payload_size];
+        let responder = self
+            .connection
+            .subscribe(&subject)?
+            .with_handler(|message| message.respond(&message.data));
+        self.connection.flush_timeout(self.timeout)?;
+
+        let mut latencies = Vec::with_capacity(self.messages);
+        let start = Instant::now();
+        for _ in 0..self.messages {
+            let sent = Instant::now();
+            if let Err(err) = self
+                .connection
+                .request_timeout(&subject, &payload, self.timeout)
+            {
+                responder.unsubscribe().ok();
+                return Err(err);
+            }
+            latencies.push(sent.elapsed());
+        }
+        let duration = start.elapsed();
+
+        responder.unsubscribe()?;
+        Ok(self.result(duration, Latency::from_samples(latencies)))
+    }
+
+    fn resolved_subject(&self) -> String {
+        self.subject
+            .clone()
+            .unwrap_or_else(|| format!("_BENCH.{}", crate::nuid::next()))
+    }
+
+    fn result(&self, duration: Duration, latency: Option<Latency>) -> BenchResult {
+        BenchResult {
+            messages: self.messages,
+            bytes: self.messages * self.payload_size,
+            duration,
+            latency,
+        }
+    }
+}
+
+/// The result of a benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    /// Number of messages, or requests, of the test.
+    pub messages: usize,
+    /// Number of payload bytes of the test.
+    pub bytes: usize,
+    /// How long the test took.
+    pub duration: Duration,
+    /// Latency of requests, for request tests.
+    pub latency: Option<Latency>,
+}
+
+impl BenchResult {
+    /// Returns the number of messages, or requests, per second.
+    pub fn messages_per_second(&self) -> f64 {
+        self.messages as f64 / self.duration.as_secs_f64()
+    }
+
+    /// Returns the number of payload bytes per second.
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bytes as f64 / self.duration.as_secs_f64()
+    }
+}
+
+/// Distribution of request latencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Latency {
+    /// Lowest latency.
+    pub min: Duration,
+    /// Mean latency.
+    pub mean: Duration,
+    /// Median latency.
+    pub p50: Duration,
+    /// 90th percentile latency.
+    pub p90: Duration,
+    /// 99th percentile latency.
+    pub p99: Duration,
+    /// Highest latency.
+    pub max: Duration,
+}
+
+impl Latency {
+    fn from_samples(mut samples: Vec<Duration>) -> Option<Latency> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+        let total: Duration = samples.iter().sum();
+        Some(Latency {
+            min: samples[0],
+            mean: total / samples.len() as u32,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples[samples.len() - 1],
+        })
+    }
+}
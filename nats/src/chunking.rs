@@ -0,0 +1,357 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Publishing of payloads larger than the maximum payload of the server.
+//!
+//! [`Connection::publish_chunked`](crate::Connection::publish_chunked) splits payloads that
+//! exceed [`Connection::max_payload`](crate::Connection::max_payload) into chunks, numbered
+//! with the [`NATS_CHUNK_SEQUENCE`] header and sharing a [`NATS_CHUNK_ID`], and sends
+//! smaller payloads as plain messages. A [`ChunkedSubscription`] reassembles the chunks,
+//! verifying the SHA-256 digest of the whole payload, and yields plain messages unchanged.
+//!
+//! Payloads are only reassembled if all of their chunks arrive within the reassembly
+//! timeout, so this suits payloads that occasionally exceed the limit of the server. The
+//! JetStream object store is better suited to routinely large payloads.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! let nc = nats::connect("demo.nats.io")?;
+//! let sub = nc.subscribe_chunked("reports")?;
+//! nc.publish_chunked("reports", vec![0; 10 * 1024 * 1024])?;
+//!
+//! if let Some(report) = sub.next() {
+//!     println!("received a report of {} bytes", report?.data.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use base64::URL_SAFE;
+use parking_lot::Mutex;
+use ring::digest::{digest, SHA256};
+
+pub use crate::header::{NATS_CHUNK_COUNT, NATS_CHUNK_DIGEST, NATS_CHUNK_ID, NATS_CHUNK_SEQUENCE};
+use crate::{Connection, HeaderMap, Message, Subscription};
+
+/// How long a [`ChunkedSubscription`] waits for the remaining chunks of a payload by
+/// default.
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Room left in each chunk for the chunk headers and any propagated trace context.
+const HEADERS_RESERVE: usize = 512;
+
+/// Publishes a payload, split into chunks if it exceeds the maximum payload of the server.
+pub(crate) fn publish(
+    connection: &Connection,
+    subject: &str,
+    reply: Option<&str>,
+    headers: Option<&HeaderMap>,
+    msg: &[u8],
+) -> io::Result<()> {
+    let max_payload = connection.max_payload();
+    let headers_len = headers.map_or(0, |headers| headers.to_bytes().len());
+    if msg.len() + headers_len <= max_payload {
+        return connection.publish_with_reply_or_headers(subject, reply, headers, msg);
+    }
+
+    let chunk_size = max_payload
+        .checked_sub(headers_len + HEADERS_RESERVE)
+        .filter(|size| *size > 0)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the headers leave no room for chunks within the maximum payload",
+            )
+        })?;
+
+    let id = crate::nuid::next();
+    let count = (msg.len() + chunk_size - 1) / chunk_size;
+    let checksum = checksum(msg);
+    for (sequence, chunk) in msg.chunks(chunk_size).enumerate() {
+        let mut chunk_headers = headers.cloned().unwrap_or_default();
+        chunk_headers.insert(NATS_CHUNK_ID, id.as_str());
+        chunk_headers.insert(NATS_CHUNK_SEQUENCE, sequence.to_string());
+        chunk_headers.insert(NATS_CHUNK_COUNT, count.to_string());
+        chunk_headers.insert(NATS_CHUNK_DIGEST, checksum.as_str());
+        connection.publish_with_reply_or_headers(subject, reply, Some(&chunk_headers), chunk)?;
+    }
+    Ok(())
+}
+
+/// The chunks of a payload received so far.
+#[derive(Debug)]
+struct Partial {
+    /// The first chunk received, whose subject, reply and headers the reassembled message
+    /// takes.
+    message: Message,
+    chunks: BTreeMap<usize, Vec<u8>>,
+    count: usize,
+    checksum: String,
+    expires: Instant,
+}
+
+/// A subscription reassembling payloads published with
+/// [`Connection::publish_chunked`](crate::Connection::publish_chunked).
+///
+/// Messages without chunk headers are yielded unchanged. Payloads whose chunks do not all
+/// arrive within the reassembly timeout, and payloads failing the digest check, are yielded
+/// as errors without terminating the subscription.
+#[derive(Debug)]
+pub struct ChunkedSubscription {
+    subscription: Subscription,
+    timeout: Duration,
+    pending: Mutex<HashMap<String, Partial>>,
+}
+
+impl ChunkedSubscription {
+    /// Wraps an existing subscription.
+    pub fn new(subscription: Subscription) -> ChunkedSubscription {
+        ChunkedSubscription {
+            subscription,
+            timeout: DEFAULT_REASSEMBLY_TIMEOUT,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets how long to wait for the remaining chunks of a payload after receiving its
+    /// first chunk, [`DEFAULT_REASSEMBLY_TIMEOUT`] by default.
+    pub fn with_reassembly_timeout(mut self, timeout: Duration) -> ChunkedSubscription {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the underlying subscription.
+    pub fn subscription(&self) -> &Subscription {
+        &self.subscription
+    }
+
+    /// Get the next message or reassembled payload, or `None` if the subscription has been
+    /// unsubscribed or the connection closed.
+    pub fn next(&self) -> Option<io::Result<Message>> {
+        self.receive(None)
+    }
+
+    /// Get the next message or reassembled payload, or a timeout error if none is
+    /// available for timeout.
+    pub fn next_timeout(&self, timeout: Duration) -> io::Result<Message> {
+        self.receive(Some(Instant::now() + timeout))
+            .unwrap_or_else(|| {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "next_timeout: unsubscribed",
+                ))
+            })
+    }
+
+    /// Returns a blocking iterator over messages and reassembled payloads.
+    pub fn iter(&self) -> ChunkedIter<'_> {
+        ChunkedIter { subscription: self }
+    }
+
+    /// Unsubscribe a subscription immediately without draining, discarding partially
+    /// received payloads.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        self.subscription.unsubscribe()
+    }
+
+    /// Waits for the next message or reassembled payload until the deadline, if any,
+    /// reporting incomplete payloads as they expire.
+    fn receive(&self, deadline: Option<Instant>) -> Option<io::Result<Message>> {
+        loop {
+            if let Some(err) = self.expire() {
+                return Some(Err(err));
+            }
+
+            let expires = self
+                .pending
+                .lock()
+                .values()
+                .map(|partial| partial.expires)
+                .min();
+            let wait_until = match (expires, deadline) {
+                (Some(expires), Some(deadline)) => Some(expires.min(deadline)),
+                (expires, deadline) => expires.or(deadline),
+            };
+
+            let message = match wait_until {
+                None => self.subscription.next()?,
+                Some(wait_until) => {
+                    let timeout = wait_until.saturating_duration_since(Instant::now());
+                    match self.subscription.next_timeout(timeout) {
+                        Ok(message) => message,
+                        Err(err) if err.kind() == io::ErrorKind::TimedOut => {
+                            if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                                return Some(Err(err));
+                            }
+                            continue;
+                        }
+                        Err(_) => return None,
+                    }
+                }
+            };
+
+            if let Some(result) = self.reassemble(message) {
+                return Some(result);
+            }
+        }
+    }
+
+    /// Removes the first payload whose reassembly timed out, returning an error for it.
+    fn expire(&self) -> Option<io::Error> {
+        let mut pending = self.pending.lock();
+        let now = Instant::now();
+        let id = pending
+            .iter()
+            .find(|(_, partial)| partial.expires <= now)
+            .map(|(id, _)| id.clone())?;
+        let partial = pending.remove(&id)?;
+
+        Some(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "timed out reassembling payload {} on {}, received {} of {} chunks",
+                id,
+                partial.message.subject,
+                partial.chunks.len(),
+                partial.count
+            ),
+        ))
+    }
+
+    /// Adds a chunk to its payload, returning the payload once complete, or returns the
+    /// message itself if it is not a chunk.
+    fn reassemble(&self, mut message: Message) -> Option<io::Result<Message>> {
+        let headers = match message.headers.as_ref() {
+            Some(headers) if headers.contains_key(NATS_CHUNK_ID) => headers,
+            _ => return Some(Ok(message)),
+        };
+
+        let (id, sequence, count, expected_checksum) = match parse_headers(headers) {
+            Ok(parsed) => parsed,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let data = mem::take(&mut message.data);
+        let mut pending = self.pending.lock();
+        let partial = pending.entry(id.clone()).or_insert_with(|| Partial {
+            message,
+            chunks: BTreeMap::new(),
+            count,
+            checksum: expected_checksum,
+            expires: Instant::now() + self.timeout,
+        });
+        if partial.count != count {
+            pending.remove(&id);
+            return Some(Err(invalid_chunk(format!(
+                "chunks of payload {} disagree on the chunk count",
+                id
+            ))));
+        }
+
+        partial.chunks.insert(sequence, data);
+        if partial.chunks.len() < partial.count {
+            return None;
+        }
+
+        let partial = pending.remove(&id)?;
+        drop(pending);
+
+        let data: Vec<u8> = partial.chunks.into_values().flatten().collect();
+        let checksum = checksum(&data);
+        if checksum != partial.checksum {
+            return Some(Err(invalid_chunk(format!(
+                "digest mismatch reassembling payload {}, expected {}, got {}",
+                id, partial.checksum, checksum
+            ))));
+        }
+
+        let mut message = partial.message;
+        message.headers = message.headers.map(|headers| {
+            headers
+                .iter()
+                .filter(|(name, _)| !is_chunk_header(name))
+                .flat_map(|(name, values)| values.iter().map(move |value| (name, value)))
+                .collect::<HeaderMap>()
+        });
+        message.data = data;
+        Some(Ok(message))
+    }
+}
+
+/// Returns the digest header value of a payload.
+fn checksum(payload: &[u8]) -> String {
+    format!(
+        "SHA-256={}",
+        base64::encode_config(digest(&SHA256, payload), URL_SAFE)
+    )
+}
+
+fn is_chunk_header(name: &str) -> bool {
+    [
+        NATS_CHUNK_ID,
+        NATS_CHUNK_SEQUENCE,
+        NATS_CHUNK_COUNT,
+        NATS_CHUNK_DIGEST,
+    ]
+    .contains(&name)
+}
+
+/// Parses the id, sequence, count and digest of a chunk.
+fn parse_headers(headers: &HeaderMap) -> io::Result<(String, usize, usize, String)> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .ok_or_else(|| invalid_chunk(format!("chunk is missing the {} header", name)))
+    };
+    let number = |name: &str| {
+        header(name)?
+            .parse::<usize>()
+            .map_err(|_| invalid_chunk(format!("chunk has an invalid {} header", name)))
+    };
+
+    let id = header(NATS_CHUNK_ID)?.clone();
+    let sequence = number(NATS_CHUNK_SEQUENCE)?;
+    let count = number(NATS_CHUNK_COUNT)?;
+    let checksum = header(NATS_CHUNK_DIGEST)?.clone();
+    if sequence >= count {
+        return Err(invalid_chunk(format!(
+            "chunk {} of payload {} is out of range, it has {} chunks",
+            sequence, id, count
+        )));
+    }
+    Ok((id, sequence, count, checksum))
+}
+
+fn invalid_chunk(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A blocking iterator over the messages and reassembled payloads of a
+/// [`ChunkedSubscription`].
+pub struct ChunkedIter<'a> {
+    subscription: &'a ChunkedSubscription,
+}
+
+impl<'a> Iterator for ChunkedIter<'a> {
+    type Item = io::Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.subscription.next()
+    }
+}
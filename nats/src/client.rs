@@ -16,6 +16,7 @@ use std::fmt;
 use std::io::prelude::*;
 use std::io::{self, BufReader, BufWriter, Error, ErrorKind};
 use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use std::thread::{self, JoinHandle};
@@ -23,11 +24,19 @@ use std::time::{Duration, Instant};
 
 use crossbeam_channel as channel;
 use crossbeam_channel::RecvTimeoutError;
+use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
 
-use crate::connector::{Connector, NatsStream, ServerAddress};
+use crate::compression;
+use crate::connector::{self, Connector, NatsStream, ServerAddress, TlsConfig};
+use crate::dispatch::WorkerPool;
+use crate::error::ServerError;
 use crate::message::Message;
+use crate::metrics::{Counters, Statistics};
 use crate::proto::{self, ClientOp, ServerOp};
+use crate::rate_limit::RateLimiter;
+use crate::request_mux::RequestMux;
+use crate::tap;
 use crate::{header::HeaderMap, inject_delay, inject_io_failure, Options, ServerInfo};
 
 const BUF_CAPACITY: usize = 32 * 1024;
@@ -47,6 +56,12 @@ pub(crate) struct State {
 struct MetaState {
     /// Set of subjects that are currently muted.
     mutes: HashSet<u64>,
+
+    /// Listeners notified after each reconnect, by id.
+    reconnect_listeners: HashMap<u64, ReconnectListener>,
+
+    /// Id of the next reconnect listener.
+    next_listener_id: u64,
 }
 
 struct WriteState {
@@ -86,16 +101,61 @@ pub(crate) struct ReadState {
 /// A predicate used to preprocess messages for a subscription as they arrive over the wire.
 pub(crate) type Preprocessor = Box<dyn Fn(u64, &Message) -> bool + Send + Sync>;
 
+/// A handler run by the read loop for each message instead of queueing it.
+pub(crate) type MessageHandler = Arc<dyn Fn(Message) + Send + Sync>;
+
+/// A listener run on the client thread after each reconnect, which must not block.
+pub(crate) type ReconnectListener = Arc<dyn Fn() + Send + Sync>;
+
 /// A registered subscription.
 pub(crate) struct Subscription {
     subject: String,
     queue_group: Option<String>,
     messages: channel::Sender<Message>,
     preprocess: Preprocessor,
+    handler: Option<MessageHandler>,
     pub(crate) pending_messages_limit: Option<usize>,
+    pending_bytes_limit: Option<usize>,
+    /// Payload sizes of the messages queued since the byte limit was set, oldest first.
+    pending_sizes: VecDeque<usize>,
+    pending_bytes: usize,
     pub(crate) dropped_messages: usize,
 }
 
+impl Subscription {
+    /// Sets the limits of messages and payload bytes waiting in the queue.
+    pub(crate) fn set_pending_limits(&mut self, messages: Option<usize>, bytes: Option<usize>) {
+        self.pending_messages_limit = messages;
+        self.pending_bytes_limit = bytes;
+        if bytes.is_none() {
+            self.pending_sizes.clear();
+            self.pending_bytes = 0;
+        }
+    }
+
+    /// Accounts for a message about to be queued, returning false if queueing it would
+    /// exceed the pending limits.
+    fn reserve(&mut self, len: usize) -> bool {
+        if let Some(limit) = self.pending_messages_limit {
+            if limit <= self.messages.len() {
+                return false;
+            }
+        }
+        if let Some(limit) = self.pending_bytes_limit {
+            // The queue is FIFO, so the messages taken from it are the oldest ones.
+            while self.pending_sizes.len() > self.messages.len() {
+                self.pending_bytes -= self.pending_sizes.pop_front().unwrap_or_default();
+            }
+            if self.pending_bytes + len > limit {
+                return false;
+            }
+            self.pending_sizes.push_back(len);
+            self.pending_bytes += len;
+        }
+        true
+    }
+}
+
 /// A NATS client.
 #[derive(Clone)]
 pub struct Client {
@@ -111,6 +171,27 @@ pub struct Client {
     /// The options that this `Client` was created using.
     pub(crate) options: Arc<Options>,
 
+    /// Cumulative statistics of the connection.
+    stats: Arc<Counters>,
+
+    /// Number of reconnects since the client was created, unlike the statistics never reset.
+    reconnects: Arc<AtomicU64>,
+
+    /// TLS config used when connecting, shared with the connector.
+    tls_config: TlsConfig,
+
+    /// Publish rate limits, if configured.
+    rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// Worker pool shared by the subscriptions of the connection, if configured.
+    pub(crate) dispatch_pool: Option<WorkerPool>,
+
+    /// Multiplexer of requests with deadlines, created on first use.
+    request_mux: Arc<OnceCell<Arc<RequestMux>>>,
+
+    /// The most recent error of the connection, reported or not.
+    last_error: Arc<Mutex<Option<String>>>,
+
     /// handler of client thread.
     pub(crate) client_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
 
@@ -128,11 +209,19 @@ impl Client {
         let (run_sender, run_receiver) = channel::bounded(1);
         let (pong_sender, pong_receiver) = channel::bounded::<()>(1);
 
+        let options = Arc::new(options);
+
+        // Connector for creating the initial connection and reconnecting when
+        // it is broken.
+        let connector = Connector::new(urls, options.clone())?;
+
         // The client state.
         let client = Client {
             state: Arc::new(State {
                 meta: Mutex::new(MetaState {
                     mutes: HashSet::new(),
+                    reconnect_listeners: HashMap::new(),
+                    next_listener_id: 1,
                 }),
                 write: Mutex::new(WriteState {
                     writer: None,
@@ -149,41 +238,49 @@ impl Client {
             }),
             server_info: Arc::new(Mutex::new(ServerInfo::default())),
             shutdown: Arc::new(Mutex::new(false)),
-            options: Arc::new(options),
+            options: options.clone(),
+            stats: Arc::new(Counters::default()),
+            reconnects: Arc::new(AtomicU64::new(0)),
+            tls_config: connector.tls_config(),
+            rate_limiter: RateLimiter::new(&options).map(Arc::new),
+            dispatch_pool: options
+                .dispatch_workers
+                .map(|workers| {
+                    WorkerPool::new(format!("{}_dispatch", options.thread_name_prefix), workers)
+                })
+                .transpose()?,
+            request_mux: Arc::new(OnceCell::new()),
+            last_error: Arc::new(Mutex::new(None)),
             client_thread: Arc::new(Mutex::new(None)),
             flush_thread: Arc::new(Mutex::new(None)),
         };
 
-        let options = client.options.clone();
-
-        // Connector for creating the initial connection and reconnecting when
-        // it is broken.
-        let connector = Connector::new(urls, options.clone())?;
-
         // Spawn the client thread responsible for:
         // - Maintaining a connection to the server and reconnecting when it is
         //   broken.
         // - Reading messages from the server and processing them.
         // - Forwarding MSG operations to subscribers.
-        let handle = thread::spawn({
-            let client = client.clone();
-            move || {
-                let res = client.run(connector);
-                run_sender.send(res).ok();
-
-                // One final flush before shutting down.
-                // This way we make sure buffered published messages reach the
-                // server.
-                {
-                    let mut write = client.state.write.lock();
-                    if let Some(writer) = write.writer.as_mut() {
-                        writer.flush().ok();
+        let handle = thread::Builder::new()
+            .name(client.thread_name("client"))
+            .spawn({
+                let client = client.clone();
+                move || {
+                    let res = client.run(connector);
+                    run_sender.send(res).ok();
+
+                    // One final flush before shutting down.
+                    // This way we make sure buffered published messages reach the
+                    // server.
+                    {
+                        let mut write = client.state.write.lock();
+                        if let Some(writer) = write.writer.as_mut() {
+                            writer.flush().ok();
+                        }
                     }
-                }
 
-                options.close_callback.call();
-            }
-        });
+                    options.close_callback.call();
+                }
+            })?;
 
         *client.client_thread.lock() = Some(handle);
 
@@ -196,90 +293,92 @@ impl Client {
         }
 
         // Spawn a thread that periodically flushes buffered messages.
-        let handle = thread::spawn({
-            let client = client.clone();
-            move || {
-                // Track last flush/write time.
-                const MIN_FLUSH_BETWEEN: Duration = Duration::from_millis(5);
-
-                // Handle recv timeouts and check if we should send a PING.
-                // TODO(dlc) - Make configurable.
-                const PING_INTERVAL: Duration = Duration::from_secs(2 * 60);
-                const MAX_PINGS_OUT: u8 = 2;
-
-                let mut last = Instant::now().checked_sub(MIN_FLUSH_BETWEEN).unwrap();
-
-                // Wait until at least one message is buffered.
-                loop {
-                    // if client is shutting down, stop periodic flushes.
-                    if client.check_shutdown().is_err() {
-                        break;
-                    }
-                    match flush_wanted.recv_timeout(PING_INTERVAL) {
-                        Ok(_) => {
-                            let since = last.elapsed();
-                            if since < MIN_FLUSH_BETWEEN {
-                                thread::sleep(MIN_FLUSH_BETWEEN - since);
-                            }
-
-                            // Flush the writer.
-                            let mut write = client.state.write.lock();
-                            let mut read = client.state.read.lock();
-
-                            if let Some(writer) = write.writer.as_mut() {
-                                let res = writer.flush();
-                                last = Instant::now();
-                                // If flushing fails, disconnect.
-                                if res.is_err() {
-                                    writer.get_ref().shutdown();
-                                    write.writer = None;
-                                    read.pongs.clear();
+        let handle = thread::Builder::new()
+            .name(client.thread_name("flusher"))
+            .spawn({
+                let client = client.clone();
+                move || {
+                    // Track last flush/write time.
+                    const MIN_FLUSH_BETWEEN: Duration = Duration::from_millis(5);
+
+                    // Handle recv timeouts and check if we should send a PING.
+                    // TODO(dlc) - Make configurable.
+                    const PING_INTERVAL: Duration = Duration::from_secs(2 * 60);
+                    const MAX_PINGS_OUT: u8 = 2;
+
+                    let mut last = Instant::now().checked_sub(MIN_FLUSH_BETWEEN).unwrap();
+
+                    // Wait until at least one message is buffered.
+                    loop {
+                        // if client is shutting down, stop periodic flushes.
+                        if client.check_shutdown().is_err() {
+                            break;
+                        }
+                        match flush_wanted.recv_timeout(PING_INTERVAL) {
+                            Ok(_) => {
+                                let since = last.elapsed();
+                                if since < MIN_FLUSH_BETWEEN {
+                                    thread::sleep(MIN_FLUSH_BETWEEN - since);
                                 }
-                            }
 
-                            // NB see locking protocol for state.write and state.read
-                            drop(read);
-                            drop(write);
-                        }
-                        Err(RecvTimeoutError::Timeout) => {
-                            let mut write = client.state.write.lock();
-                            let mut read = client.state.read.lock();
+                                // Flush the writer.
+                                let mut write = client.state.write.lock();
+                                let mut read = client.state.read.lock();
 
-                            if read.pings_out >= MAX_PINGS_OUT {
                                 if let Some(writer) = write.writer.as_mut() {
-                                    writer.get_ref().shutdown();
-                                }
-                                write.writer = None;
-                                read.pongs.clear();
-                            } else if read.last_active.elapsed() > PING_INTERVAL {
-                                read.pings_out += 1;
-                                read.pongs.push_back(write.flush_kicker.clone());
-                                // Send out a PING here.
-                                if let Some(mut writer) = write.writer.as_mut() {
-                                    // Ok to ignore errors here.
-                                    proto::encode(&mut writer, ClientOp::Ping).ok();
                                     let res = writer.flush();
+                                    last = Instant::now();
+                                    // If flushing fails, disconnect.
                                     if res.is_err() {
-                                        // NB see locking protocol for state.write and state.read
                                         writer.get_ref().shutdown();
                                         write.writer = None;
                                         read.pongs.clear();
                                     }
                                 }
+
+                                // NB see locking protocol for state.write and state.read
+                                drop(read);
+                                drop(write);
                             }
+                            Err(RecvTimeoutError::Timeout) => {
+                                let mut write = client.state.write.lock();
+                                let mut read = client.state.read.lock();
 
-                            // NB see locking protocol for state.write and state.read
-                            drop(read);
-                            drop(write);
-                        }
-                        _ => {
-                            // Any other err break and exit.
-                            break;
+                                if read.pings_out >= MAX_PINGS_OUT {
+                                    if let Some(writer) = write.writer.as_mut() {
+                                        writer.get_ref().shutdown();
+                                    }
+                                    write.writer = None;
+                                    read.pongs.clear();
+                                } else if read.last_active.elapsed() > PING_INTERVAL {
+                                    read.pings_out += 1;
+                                    read.pongs.push_back(write.flush_kicker.clone());
+                                    // Send out a PING here.
+                                    if let Some(mut writer) = write.writer.as_mut() {
+                                        // Ok to ignore errors here.
+                                        client.encode(&mut writer, ClientOp::Ping).ok();
+                                        let res = writer.flush();
+                                        if res.is_err() {
+                                            // NB see locking protocol for state.write and state.read
+                                            writer.get_ref().shutdown();
+                                            write.writer = None;
+                                            read.pongs.clear();
+                                        }
+                                    }
+                                }
+
+                                // NB see locking protocol for state.write and state.read
+                                drop(read);
+                                drop(write);
+                            }
+                            _ => {
+                                // Any other err break and exit.
+                                break;
+                            }
                         }
                     }
                 }
-            }
-        });
+            })?;
 
         *client.flush_thread.lock() = Some(handle);
         Ok(client)
@@ -291,7 +390,11 @@ impl Client {
     }
 
     /// Makes a round trip to the server to ensure buffered messages reach it.
+    ///
+    /// The timeout is a deadline for the whole round trip, so a connection that silently
+    /// stopped responding fails with `TimedOut` instead of blocking.
     pub(crate) fn flush(&self, timeout: Duration) -> io::Result<()> {
+        let deadline = Instant::now() + timeout;
         let pong = {
             // Inject random delays when testing.
             inject_delay();
@@ -311,7 +414,7 @@ impl Client {
                     // rather than the timeout because right now the timeout
                     // applies to each write syscall individually.
                     writer.get_ref().set_write_timeout(Some(timeout))?;
-                    proto::encode(&mut writer, ClientOp::Ping)?;
+                    self.encode(&mut writer, ClientOp::Ping)?;
                     writer.flush()?;
                     writer.get_ref().set_write_timeout(None)?;
                 }
@@ -329,12 +432,38 @@ impl Client {
         };
 
         // Wait until the PONG operation is received.
-        match pong.recv() {
+        match pong.recv_deadline(deadline) {
             Ok(()) => Ok(()),
-            Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "flush failed")),
+            Err(RecvTimeoutError::Timeout) => Err(crate::Error::TimedOut.into()),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(Error::new(ErrorKind::ConnectionReset, "flush failed"))
+            }
         }
     }
 
+    /// Makes a round trip to the server, dropping the connection so the client reconnects
+    /// if the PONG does not arrive before the timeout.
+    pub(crate) fn ping(&self, timeout: Duration) -> io::Result<()> {
+        let res = self.flush(timeout);
+
+        if matches!(&res, Err(err) if err.kind() == ErrorKind::TimedOut) {
+            let mut write = self.state.write.lock();
+            let mut read = self.state.read.lock();
+
+            if let Some(writer) = write.writer.as_mut() {
+                writer.get_ref().shutdown();
+            }
+            write.writer = None;
+            read.pongs.clear();
+
+            // NB see locking protocol for state.write and state.read
+            drop(read);
+            drop(write);
+        }
+
+        res
+    }
+
     /// Closes the client.
     pub(crate) fn close(&self) {
         // Inject random delays when testing.
@@ -347,13 +476,13 @@ impl Client {
 
         // Initiate shutdown process.
         if self.shutdown() {
-            // Clear all subscriptions.
+            // Clear all subscriptions, dropping them after releasing the locks.
             let old_subscriptions = mem::take(&mut read.subscriptions);
-            for (sid, _) in old_subscriptions {
+            for sid in old_subscriptions.keys().copied() {
                 // Send an UNSUB message and ignore errors.
                 if let Some(writer) = write.writer.as_mut() {
                     let max_msgs = None;
-                    proto::encode(writer, ClientOp::Unsub { sid, max_msgs }).ok();
+                    self.encode(writer, ClientOp::Unsub { sid, max_msgs }).ok();
                     write.flush_kicker.try_send(()).ok();
                 }
             }
@@ -364,7 +493,7 @@ impl Client {
                 // TODO: for some reason sometimes Push Consumer Subscription cause
                 // `close()` to hang. Sending ping unblocks read_line. Not worth investigating further
                 // this edge case as async client will not have this issue.
-                proto::encode(&mut writer, ClientOp::Ping).ok();
+                self.encode(&mut writer, ClientOp::Ping).ok();
                 writer.flush().ok();
             }
 
@@ -373,6 +502,7 @@ impl Client {
             // NB see locking protocol for state.write and state.read
             drop(read);
             drop(write);
+            drop(old_subscriptions);
 
             // wait for the threads.
             self.client_thread.lock().take().map(JoinHandle::join);
@@ -389,9 +519,14 @@ impl Client {
         !old
     }
 
+    /// Returns true if the client is connected to a server, and not reconnecting or closed.
+    pub(crate) fn is_connected(&self) -> bool {
+        self.state.write.lock().writer.is_some()
+    }
+
     fn check_shutdown(&self) -> io::Result<()> {
         if *self.shutdown.lock() {
-            Err(Error::new(ErrorKind::NotConnected, "the client is closed"))
+            Err(crate::Error::Closed.into())
         } else {
             Ok(())
         }
@@ -432,7 +567,7 @@ impl Client {
                 queue_group,
                 sid,
             };
-            proto::encode(writer, op).ok();
+            self.encode(writer, op).ok();
             write.flush_kicker.try_send(()).ok();
         }
 
@@ -445,7 +580,11 @@ impl Client {
                 queue_group: queue_group.map(ToString::to_string),
                 messages: sender,
                 preprocess: message_processor,
-                pending_messages_limit: None,
+                handler: None,
+                pending_messages_limit: self.options.subscription_capacity,
+                pending_bytes_limit: None,
+                pending_sizes: VecDeque::new(),
+                pending_bytes: 0,
                 dropped_messages: 0,
             },
         );
@@ -463,6 +602,32 @@ impl Client {
         Ok(meta.mutes.insert(sid))
     }
 
+    /// Returns the name of a thread spawned for the connection, with the configured prefix.
+    pub(crate) fn thread_name(&self, name: impl fmt::Display) -> String {
+        format!("{}_{}", self.options.thread_name_prefix, name)
+    }
+
+    /// Returns the multiplexer of requests with deadlines, subscribing to its reply
+    /// subjects on first use.
+    pub(crate) fn request_mux(&self) -> io::Result<&Arc<RequestMux>> {
+        self.request_mux
+            .get_or_try_init(|| RequestMux::new(self, format!("_INBOX.{}", self.next_id())))
+    }
+
+    /// Registers a listener to run after each reconnect, returning its id.
+    pub(crate) fn add_reconnect_listener(&self, listener: ReconnectListener) -> u64 {
+        let mut meta = self.state.meta.lock();
+        let id = meta.next_listener_id;
+        meta.next_listener_id += 1;
+        meta.reconnect_listeners.insert(id, listener);
+        id
+    }
+
+    /// Removes a reconnect listener.
+    pub(crate) fn remove_reconnect_listener(&self, id: u64) {
+        self.state.meta.lock().reconnect_listeners.remove(&id);
+    }
+
     /// Resubscribes an existing subscription by unsubscribing from the old subject and subscribing
     /// to the new subject returning a new sid while retaining the existing channel receiver.
     pub(crate) fn resubscribe(&self, old_sid: u64, new_subject: &str) -> io::Result<u64> {
@@ -486,7 +651,7 @@ impl Client {
 
         // Send an UNSUB and SUB messages.
         if let Some(writer) = write.writer.as_mut() {
-            proto::encode(
+            self.encode(
                 writer,
                 ClientOp::Unsub {
                     sid: old_sid,
@@ -499,7 +664,7 @@ impl Client {
         read.subscriptions.insert(new_sid, subscription);
 
         if let Some(writer) = write.writer.as_mut() {
-            proto::encode(
+            self.encode(
                 writer,
                 ClientOp::Sub {
                     sid: new_sid,
@@ -525,8 +690,11 @@ impl Client {
         let mut write = self.state.write.lock();
         let mut read = self.state.read.lock();
 
-        // Remove the subscription from the map.
-        if read.subscriptions.remove(&sid).is_none() {
+        // Remove the subscription from the map. It is dropped after releasing the locks,
+        // since its handler may own the last reference to the subscription, which
+        // unsubscribes on drop.
+        let removed = read.subscriptions.remove(&sid);
+        if removed.is_none() {
             // already unsubscribed
 
             // NB see locking protocol for state.write and state.read
@@ -539,17 +707,36 @@ impl Client {
         // Send an UNSUB message.
         if let Some(writer) = write.writer.as_mut() {
             let max_msgs = None;
-            proto::encode(writer, ClientOp::Unsub { sid, max_msgs })?;
+            self.encode(writer, ClientOp::Unsub { sid, max_msgs })?;
             write.flush_kicker.try_send(()).ok();
         }
 
         // NB see locking protocol for state.write and state.read
         drop(read);
         drop(write);
+        drop(removed);
 
         Ok(())
     }
 
+    /// Replaces the message channel of a subscription with a handler run by the read loop.
+    /// Messages still `queued` are handed to the handler first, before the read loop can see
+    /// it, so newer messages never overtake them.
+    pub(crate) fn set_handler(
+        &self,
+        sid: u64,
+        handler: MessageHandler,
+        queued: &channel::Receiver<Message>,
+    ) {
+        let mut read = self.state.read.lock();
+        if let Some(subscription) = read.subscriptions.get_mut(&sid) {
+            for m in queued.try_iter() {
+                handler(m);
+            }
+            subscription.handler = Some(handler);
+        }
+    }
+
     /// Publishes a message with optional reply subject and headers.
     pub fn publish(
         &self,
@@ -557,21 +744,55 @@ impl Client {
         reply_to: Option<&str>,
         headers: Option<&HeaderMap>,
         msg: &[u8],
+    ) -> io::Result<()> {
+        self.do_publish(subject, reply_to, headers, msg, true)
+    }
+
+    /// Publishes a response to a request, which is not rate limited.
+    pub(crate) fn publish_response(
+        &self,
+        subject: &str,
+        reply_to: Option<&str>,
+        headers: Option<&HeaderMap>,
+        msg: &[u8],
+    ) -> io::Result<()> {
+        self.do_publish(subject, reply_to, headers, msg, false)
+    }
+
+    /// Exempts subjects starting with `prefix` from the rate limits.
+    pub(crate) fn exempt_from_rate_limits(&self, prefix: &str) {
+        if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+            rate_limiter.exempt(prefix);
+        }
+    }
+
+    fn do_publish(
+        &self,
+        subject: &str,
+        reply_to: Option<&str>,
+        headers: Option<&HeaderMap>,
+        msg: &[u8],
+        rate_limited: bool,
     ) -> io::Result<()> {
         // Inject random delays when testing.
         inject_delay();
 
-        let server_info = self.server_info.lock();
-        if headers.is_some() && !server_info.headers {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "the server does not support headers",
-            ));
+        // Check if the client is closed, before possibly waiting for the rate limits.
+        self.check_shutdown()?;
+
+        self.check_headers_supported(headers)?;
+
+        if rate_limited {
+            if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+                rate_limiter.acquire(subject)?;
+            }
         }
-        drop(server_info);
 
-        // Check if the client is closed.
-        self.check_shutdown()?;
+        let compressed = self.compress(headers, msg)?;
+        let (headers, msg) = match compressed.as_ref() {
+            Some((headers, msg)) => (Some(headers), msg.as_slice()),
+            None => (headers, msg),
+        };
 
         let op = match headers {
             Some(headers) if !headers.is_empty() => ClientOp::Hpub {
@@ -594,15 +815,20 @@ impl Client {
         match write.writer.as_mut() {
             None => {
                 // If reconnecting, write into the buffer.
-                proto::encode(&mut write.buffer, op)?;
+                self.encode(&mut write.buffer, op)?;
                 write.buffer.flush()?;
+                self.stats.message_sent(msg.len());
+                if let Some(metrics) = self.options.metrics.as_ref() {
+                    metrics.message_sent(subject, msg.len());
+                    metrics.pending_bytes(write.buffer.written);
+                }
                 Ok(())
             }
             Some(mut writer) => {
                 assert_eq!(written, 0);
 
                 // If connected, write into the writer.
-                let res = proto::encode(&mut writer, op);
+                let res = self.encode(&mut writer, op);
 
                 // If writing fails, disconnect.
                 if res.is_err() {
@@ -615,11 +841,45 @@ impl Client {
 
                 write.flush_kicker.try_send(()).ok();
 
+                if res.is_ok() {
+                    self.stats.message_sent(msg.len());
+                    if let Some(metrics) = self.options.metrics.as_ref() {
+                        metrics.message_sent(subject, msg.len());
+                    }
+                }
+
                 res
             }
         }
     }
 
+    /// Returns an error if there are headers to publish but the server does not support them.
+    fn check_headers_supported(&self, headers: Option<&HeaderMap>) -> io::Result<()> {
+        let headers = headers.map_or(false, |headers| !headers.is_empty());
+        if headers && !self.server_info.lock().headers {
+            return Err(crate::Error::UnsupportedFeature("headers").into());
+        }
+        Ok(())
+    }
+
+    /// Compresses the payload if it reaches the compression threshold and the server
+    /// supports headers, returning the headers marking its encoding and the compressed
+    /// payload.
+    fn compress(
+        &self,
+        headers: Option<&HeaderMap>,
+        msg: &[u8],
+    ) -> io::Result<Option<(HeaderMap, Vec<u8>)>> {
+        match self.options.payload_compression {
+            Some((compression, threshold))
+                if msg.len() >= threshold && self.server_info.lock().headers =>
+            {
+                compression::compress_payload(compression, headers, msg)
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Attempts to publish a message without blocking.
     ///
     /// This only works when the write buffer has enough space to encode the
@@ -636,6 +896,25 @@ impl Client {
             return Some(Err(e));
         }
 
+        if let Err(e) = self.check_headers_supported(headers) {
+            return Some(Err(e));
+        }
+
+        if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+            if let Err(e) = rate_limiter.try_acquire(subject)? {
+                return Some(Err(e));
+            }
+        }
+
+        let compressed = match self.compress(headers, msg) {
+            Ok(compressed) => compressed,
+            Err(e) => return Some(Err(e)),
+        };
+        let (headers, msg) = match compressed.as_ref() {
+            Some((headers, msg)) => (Some(headers), msg.as_slice()),
+            None => (headers, msg),
+        };
+
         // Estimate how many bytes the message will consume when written into
         // the stream. We must make a conservative guess: it's okay to
         // overestimate but not to underestimate.
@@ -647,19 +926,18 @@ impl Client {
                 .sum::<usize>();
         }
 
-        let op = if let Some(headers) = headers {
-            ClientOp::Hpub {
+        let op = match headers {
+            Some(headers) if !headers.is_empty() => ClientOp::Hpub {
                 subject,
                 reply_to,
                 payload: msg,
                 headers,
-            }
-        } else {
-            ClientOp::Pub {
+            },
+            _ => ClientOp::Pub {
                 subject,
                 reply_to,
                 payload: msg,
-            }
+            },
         };
 
         let mut write = self.state.write.try_lock()?;
@@ -667,7 +945,16 @@ impl Client {
         match write.writer.as_mut() {
             None => {
                 // If reconnecting, write into the buffer.
-                let res = proto::encode(&mut write.buffer, op).and_then(|_| write.buffer.flush());
+                let res = self
+                    .encode(&mut write.buffer, op)
+                    .and_then(|_| write.buffer.flush());
+                if res.is_ok() {
+                    self.stats.message_sent(msg.len());
+                    if let Some(metrics) = self.options.metrics.as_ref() {
+                        metrics.message_sent(subject, msg.len());
+                        metrics.pending_bytes(write.buffer.written);
+                    }
+                }
                 Some(res)
             }
             Some(mut writer) => {
@@ -679,7 +966,7 @@ impl Client {
 
                 // If connected, write into the writer. This is not going to
                 // block because there's enough space in the buffer.
-                let res = proto::encode(&mut writer, op);
+                let res = self.encode(&mut writer, op);
                 write.flush_kicker.try_send(()).ok();
 
                 // If writing fails, disconnect.
@@ -689,12 +976,86 @@ impl Client {
                     // NB see locking protocol for state.write and state.read
                     let mut read = self.state.read.lock();
                     read.pongs.clear();
+                } else {
+                    self.stats.message_sent(msg.len());
+                    if let Some(metrics) = self.options.metrics.as_ref() {
+                        metrics.message_sent(subject, msg.len());
+                    }
                 }
                 Some(res)
             }
         }
     }
 
+    /// Returns the next unique identifier from the configured generator.
+    pub(crate) fn next_id(&self) -> String {
+        match self.options.id_generator.as_ref() {
+            Some(generator) => generator.next_id(),
+            None => crate::nuid::next(),
+        }
+    }
+
+    /// Returns the cumulative statistics of the connection.
+    pub(crate) fn stats(&self) -> Statistics {
+        let write = self.state.write.lock();
+        let pending_bytes = match write.writer.as_ref() {
+            Some(writer) => writer.buffer().len(),
+            None => write.buffer.written,
+        };
+        self.stats.snapshot(pending_bytes)
+    }
+
+    /// Resets the cumulative statistics of the connection.
+    pub(crate) fn stats_reset(&self) {
+        self.stats.reset();
+    }
+
+    /// Returns the number of reconnects since the client was created.
+    pub(crate) fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Returns the most recent error of the connection.
+    pub(crate) fn last_error(&self) -> Option<String> {
+        self.last_error.lock().clone()
+    }
+
+    /// Records an error as the most recent one.
+    fn record_error(&self, err: &io::Error) {
+        *self.last_error.lock() = Some(err.to_string());
+    }
+
+    /// Records an error and passes it to the error callback.
+    fn report_error(&self, connector: &Connector, err: io::Error) {
+        self.record_error(&err);
+        connector.get_options().error_callback.call(self, err);
+    }
+
+    /// Reloads the TLS certificates and keys used by future connections.
+    pub(crate) fn reload_tls(&self) -> io::Result<()> {
+        connector::reload_tls(&self.options, &self.tls_config)
+    }
+
+    /// Tracks when a message was received and reports redelivered `JetStream` messages to
+    /// the metrics hooks.
+    fn record_delivery(&self, message: &mut Message) {
+        if let Some(metrics) = self.options.metrics.as_ref() {
+            message.received = Some(Instant::now());
+            #[cfg(feature = "jetstream")]
+            if let Some(info) = message.jetstream_message_info() {
+                if info.delivered > 1 {
+                    metrics.message_redelivered(info.stream, info.consumer, info.delivered);
+                }
+            }
+        }
+    }
+
+    /// Encodes an operation, passing it to the protocol tap.
+    fn encode(&self, writer: impl Write, op: ClientOp<'_>) -> io::Result<()> {
+        tap::outbound(&self.options, &op);
+        proto::encode(writer, op)
+    }
+
     /// Runs the loop that connects and reconnects the client.
     fn run(&self, mut connector: Connector) -> io::Result<()> {
         let mut first_connect = true;
@@ -704,7 +1065,10 @@ impl Client {
             let use_backoff = self.options.retry_on_failed_connect || !first_connect;
 
             // Make a connection to the server.
-            let (server_info, stream) = connector.connect(use_backoff)?;
+            let (server_info, stream) = connector.connect(use_backoff).map_err(|err| {
+                self.record_error(&err);
+                err
+            })?;
             self.process_info(&server_info, &connector);
 
             let reader = BufReader::with_capacity(BUF_CAPACITY, stream.clone());
@@ -715,13 +1079,31 @@ impl Client {
                 // Connected! Now dispatch MSG operations.
                 if !first_connect {
                     connector.get_options().reconnect_callback.call();
+                    let listeners: Vec<ReconnectListener> = self
+                        .state
+                        .meta
+                        .lock()
+                        .reconnect_listeners
+                        .values()
+                        .cloned()
+                        .collect();
+                    for listener in listeners {
+                        listener();
+                    }
+                    self.stats.reconnected();
+                    self.reconnects.fetch_add(1, Ordering::Relaxed);
+                    if let Some(metrics) = self.options.metrics.as_ref() {
+                        metrics.reconnected();
+                    }
                 }
-                if self.dispatch(reader, &mut connector).is_ok() {
+                match self.dispatch(reader, &mut connector) {
                     // If the client stopped gracefully, return.
-                    return Ok(());
-                } else {
-                    connector.get_options().disconnect_callback.call();
-                    self.state.write.lock().writer = None;
+                    Ok(()) => return Ok(()),
+                    Err(err) => {
+                        self.record_error(&err);
+                        connector.get_options().disconnect_callback.call();
+                        self.state.write.lock().writer = None;
+                    }
                 }
             }
 
@@ -747,6 +1129,8 @@ impl Client {
         server_info: ServerInfo,
         mut writer: BufWriter<NatsStream>,
     ) -> io::Result<()> {
+        span!("reconnect", server = %server_info.server_id);
+
         // Inject random delays when testing.
         inject_delay();
 
@@ -765,7 +1149,7 @@ impl Client {
         // Restart subscriptions that existed before the last reconnect.
         for (sid, subscription) in &read.subscriptions {
             // Send a SUB operation to the server.
-            proto::encode(
+            self.encode(
                 &mut writer,
                 ClientOp::Sub {
                     subject: subscription.subject.as_str(),
@@ -784,6 +1168,9 @@ impl Client {
         // Write buffered PUB operations into the new writer.
         writer.write_all(buffered)?;
         writer.flush()?;
+        if let Some(metrics) = self.options.metrics.as_ref() {
+            metrics.pending_bytes(0);
+        }
 
         // All good, continue with this connection.
         *self.server_info.lock() = server_info;
@@ -806,6 +1193,9 @@ impl Client {
         if server_info.lame_duck_mode {
             connector.get_options().lame_duck_callback.call();
         }
+        if let Some(callback) = connector.get_options().server_info_callback.as_ref() {
+            callback(server_info);
+        }
     }
 
     /// Updates our last activity from the server.
@@ -818,6 +1208,8 @@ impl Client {
     fn dispatch(&self, mut reader: impl BufRead, connector: &mut Connector) -> io::Result<()> {
         // Handle operations received from the server.
         while let Some(op) = proto::decode(&mut reader)? {
+            tap::inbound(&self.options, &op);
+
             // Inject random delays when testing.
             inject_delay();
 
@@ -843,7 +1235,7 @@ impl Client {
                     let read = self.state.read.lock();
 
                     if let Some(w) = write.writer.as_mut() {
-                        proto::encode(w, ClientOp::Pong)?;
+                        self.encode(w, ClientOp::Pong)?;
                         write.flush_kicker.try_send(()).ok();
                     }
 
@@ -886,18 +1278,25 @@ impl Client {
                         continue;
                     }
 
+                    self.stats.message_received(payload.len());
+                    if let Some(metrics) = self.options.metrics.as_ref() {
+                        metrics.message_received(&subject, payload.len());
+                    }
+
                     let mut read = self.state.read.lock();
 
                     // Send the message to matching subscription.
-                    if let Some(subscription) = read.subscriptions.get(&sid) {
-                        let msg = Message {
+                    if let Some(subscription) = read.subscriptions.get_mut(&sid) {
+                        let mut msg = Message {
                             subject,
                             reply: reply_to,
                             data: payload,
                             headers: None,
                             client: Some(self.clone()),
                             double_acked: Default::default(),
+                            received: None,
                         };
+                        self.record_delivery(&mut msg);
 
                         // Preprocess and drop the message from the buffer if it the predicate
                         // returns true
@@ -906,24 +1305,27 @@ impl Client {
                             continue;
                         }
 
-                        //check if subscription has set limits for slow consumers
-                        if let Some(pending_messages_limit) = subscription.pending_messages_limit {
-                            if pending_messages_limit <= subscription.messages.len() {
-                                connector.get_options().error_callback.call(
-                                    self,
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!(
-                                            "slow consumer detected for subscription on subject {}. dropping messages",
-                                            subscription.subject
-                                        ),
-                                    ),
-                                );
-                                read.subscriptions
-                                    .entry(sid)
-                                    .and_modify(|sub| sub.dropped_messages += 1);
-                                continue;
+                        // Run the handler of the subscription without holding the lock, so
+                        // it can use the client.
+                        if let Some(handler) = subscription.handler.clone() {
+                            drop(read);
+                            handler(msg);
+                            continue;
+                        }
+
+                        // Drop the message if the subscription is over its pending limits.
+                        if !subscription.reserve(msg.data.len()) {
+                            subscription.dropped_messages += 1;
+                            if let Some(metrics) = self.options.metrics.as_ref() {
+                                metrics.slow_consumer(&subscription.subject);
                             }
+                            let subject = subscription.subject.clone();
+                            drop(read);
+                            self.report_error(
+                                connector,
+                                crate::Error::SlowConsumer { subject }.into(),
+                            );
+                            continue;
                         }
 
                         // Send a message or drop it if the channel is
@@ -934,27 +1336,38 @@ impl Client {
 
                 ServerOp::Hmsg {
                     subject,
-                    headers,
+                    mut headers,
                     sid,
                     reply_to,
-                    payload,
+                    mut payload,
                 } => {
                     // Ignore muted subscriptions
                     if self.state.meta.lock().mutes.get(&sid).is_some() {
                         continue;
                     }
 
-                    let read = self.state.read.lock();
+                    self.stats.message_received(payload.len());
+                    if let Some(metrics) = self.options.metrics.as_ref() {
+                        metrics.message_received(&subject, payload.len());
+                    }
+
+                    if self.options.decompress_payloads {
+                        payload = compression::decompress_payload(&mut headers, payload);
+                    }
+
+                    let mut read = self.state.read.lock();
                     // Send the message to matching subscription.
-                    if let Some(subscription) = read.subscriptions.get(&sid) {
-                        let msg = Message {
+                    if let Some(subscription) = read.subscriptions.get_mut(&sid) {
+                        let mut msg = Message {
                             subject,
                             reply: reply_to,
                             data: payload,
                             headers: Some(headers),
                             client: Some(self.clone()),
                             double_acked: Default::default(),
+                            received: None,
                         };
+                        self.record_delivery(&mut msg);
 
                         // Preprocess and drop the message from the buffer if it the predicate
                         // returns true
@@ -963,21 +1376,27 @@ impl Client {
                             continue;
                         }
 
-                        //check if subscription has set limits for slow consumers
-                        if let Some(pending_messages_limit) = subscription.pending_messages_limit {
-                            if pending_messages_limit <= subscription.messages.len() {
-                                connector.get_options().error_callback.call(
-                                    self,
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!(
-                                            "slow consumer detected for subscription on subject {}. dropping messages",
-                                            subscription.subject
-                                        ),
-                                    ),
-                                );
-                                continue;
+                        // Run the handler of the subscription without holding the lock, so
+                        // it can use the client.
+                        if let Some(handler) = subscription.handler.clone() {
+                            drop(read);
+                            handler(msg);
+                            continue;
+                        }
+
+                        // Drop the message if the subscription is over its pending limits.
+                        if !subscription.reserve(msg.data.len()) {
+                            subscription.dropped_messages += 1;
+                            if let Some(metrics) = self.options.metrics.as_ref() {
+                                metrics.slow_consumer(&subscription.subject);
                             }
+                            let subject = subscription.subject.clone();
+                            drop(read);
+                            self.report_error(
+                                connector,
+                                crate::Error::SlowConsumer { subject }.into(),
+                            );
+                            continue;
                         }
                         // Send a message or drop it if the channel is
                         // disconnected or full.
@@ -985,15 +1404,19 @@ impl Client {
                     }
                 }
 
+                // Acknowledgements in verbose mode are discarded, not matched to operations.
+                ServerOp::Ok => {}
+
                 ServerOp::Err(msg) => {
-                    connector
-                        .get_options()
-                        .error_callback
-                        .call(self, Error::new(ErrorKind::Other, msg));
+                    let err = ServerError::parse(&msg);
+                    if let Some(callback) = connector.get_options().server_error_callback.as_ref() {
+                        callback(err.clone());
+                    }
+                    self.report_error(connector, crate::Error::Server(err).into());
                 }
 
                 ServerOp::Unknown(line) => {
-                    log::warn!("unknown op: {}", line);
+                    crate::logging::warn!("unknown op: {}", line);
                 }
             }
         }
@@ -0,0 +1,191 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The [CloudEvents NATS protocol binding](https://github.com/cloudevents/spec/blob/main/cloudevents/bindings/nats-protocol-binding.md)
+//! in binary content mode.
+//!
+//! Attributes travel as `ce-` prefixed headers, `datacontenttype` as the `Content-Type`
+//! header, and the data as the payload, so events interoperate with the CloudEvents SDKs of
+//! other languages. Events are published with [`crate::Connection::publish_cloud_event`] and
+//! received with [`crate::Message::cloud_event`].
+
+use std::collections::BTreeMap;
+use std::io;
+
+use time::format_description::well_known::Rfc3339;
+
+use crate::header::{HeaderMap, CONTENT_TYPE};
+
+type DateTime = time::OffsetDateTime;
+
+/// Prefix of the headers carrying the attributes of an event.
+pub const PREFIX: &str = "ce-";
+
+/// The version of the CloudEvents specification implemented.
+pub const SPEC_VERSION: &str = "1.0";
+
+/// A CloudEvent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudEvent {
+    /// Identifies the event, unique within the scope of its source.
+    pub id: String,
+    /// Identifies the context in which the event happened.
+    pub source: String,
+    /// The version of the specification the event uses.
+    pub spec_version: String,
+    /// The type of the event, the `type` attribute.
+    pub event_type: String,
+    /// The content type of the data.
+    pub data_content_type: Option<String>,
+    /// The schema the data adheres to.
+    pub data_schema: Option<String>,
+    /// The subject of the event in the context of its source.
+    pub subject: Option<String>,
+    /// When the event happened.
+    pub time: Option<DateTime>,
+    /// Extension attributes, by name.
+    pub extensions: BTreeMap<String, String>,
+    /// The data of the event.
+    pub data: Vec<u8>,
+}
+
+impl CloudEvent {
+    /// Creates an event without data or optional attributes.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::cloudevents::CloudEvent;
+    ///
+    /// let mut event = CloudEvent::new("1", "/orders", "com.example.order.created");
+    /// event.data_content_type = Some("application/json".to_string());
+    /// event.data = br#"{"id":1}"#.to_vec();
+    /// ```
+    pub fn new(
+        id: impl Into<String>,
+        source: impl Into<String>,
+        event_type: impl Into<String>,
+    ) -> CloudEvent {
+        CloudEvent {
+            id: id.into(),
+            source: source.into(),
+            spec_version: SPEC_VERSION.to_string(),
+            event_type: event_type.into(),
+            data_content_type: None,
+            data_schema: None,
+            subject: None,
+            time: None,
+            extensions: BTreeMap::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Returns the headers carrying the attributes of the event.
+    pub fn to_headers(&self) -> io::Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert(attribute("id"), self.id.as_str());
+        headers.insert(attribute("source"), self.source.as_str());
+        headers.insert(attribute("specversion"), self.spec_version.as_str());
+        headers.insert(attribute("type"), self.event_type.as_str());
+        if let Some(data_content_type) = &self.data_content_type {
+            headers.insert(CONTENT_TYPE, data_content_type.as_str());
+        }
+        if let Some(data_schema) = &self.data_schema {
+            headers.insert(attribute("dataschema"), data_schema.as_str());
+        }
+        if let Some(subject) = &self.subject {
+            headers.insert(attribute("subject"), subject.as_str());
+        }
+        if let Some(time) = &self.time {
+            let time = time
+                .format(&Rfc3339)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+            headers.insert(attribute("time"), time);
+        }
+        for (name, value) in &self.extensions {
+            headers.insert(attribute(name), value.as_str());
+        }
+        Ok(headers)
+    }
+
+    /// Parses an event from the headers and payload of a message in binary content mode.
+    ///
+    /// Header names are matched case insensitively. Fails with
+    /// `io::ErrorKind::InvalidData` if a required attribute is missing, the spec version is
+    /// not supported, or the time is malformed.
+    pub fn from_parts(headers: &HeaderMap, data: &[u8]) -> io::Result<CloudEvent> {
+        let mut id = None;
+        let mut source = None;
+        let mut spec_version = None;
+        let mut event_type = None;
+        let mut event = CloudEvent::new("", "", "");
+
+        for (name, values) in headers.iter() {
+            let value = match values.iter().next() {
+                Some(value) => value.clone(),
+                None => continue,
+            };
+            let name = name.to_lowercase();
+            if name == CONTENT_TYPE.to_lowercase() {
+                event.data_content_type = Some(value);
+                continue;
+            }
+            let attribute = match name.strip_prefix(PREFIX) {
+                Some(attribute) => attribute,
+                None => continue,
+            };
+            match attribute {
+                "id" => id = Some(value),
+                "source" => source = Some(value),
+                "specversion" => spec_version = Some(value),
+                "type" => event_type = Some(value),
+                "dataschema" => event.data_schema = Some(value),
+                "subject" => event.subject = Some(value),
+                "time" => {
+                    let time = DateTime::parse(&value, &Rfc3339)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    event.time = Some(time);
+                }
+                extension => {
+                    event.extensions.insert(extension.to_string(), value);
+                }
+            }
+        }
+
+        event.spec_version = spec_version.ok_or_else(|| missing("specversion"))?;
+        if event.spec_version != SPEC_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported CloudEvents spec version {}",
+                    event.spec_version
+                ),
+            ));
+        }
+        event.id = id.ok_or_else(|| missing("id"))?;
+        event.source = source.ok_or_else(|| missing("source"))?;
+        event.event_type = event_type.ok_or_else(|| missing("type"))?;
+        event.data = data.to_vec();
+        Ok(event)
+    }
+}
+
+fn attribute(name: &str) -> String {
+    format!("{}{}", PREFIX, name)
+}
+
+fn missing(attribute: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("missing CloudEvents attribute {}", attribute),
+    )
+}
@@ -0,0 +1,172 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compression of payloads.
+//!
+//! With [`Options::compress_payloads`](crate::Options::compress_payloads), payloads of at
+//! least the given size are compressed before publishing, and marked with their encoding in
+//! the [`CONTENT_ENCODING`] header. Payloads that do not shrink are published unchanged.
+//!
+//! Messages with a known encoding are decompressed on receipt, and the header removed,
+//! unless disabled with [`Options::decompress_payloads`](crate::Options::decompress_payloads).
+//! Other messages, including those from peers that do not compress, are delivered unchanged.
+//! Compressing and decompressing requires the `compression` feature.
+//!
+//! # Example
+//! ```no_run
+//! # #[cfg(feature = "compression")]
+//! # fn main() -> std::io::Result<()> {
+//! use nats::compression::Compression;
+//!
+//! let nc = nats::Options::new()
+//!     .compress_payloads(Compression::Zstd, 4096)
+//!     .connect("demo.nats.io")?;
+//! nc.publish("logs", "text heavy log line ".repeat(1000))?;
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "compression"))]
+//! # fn main() {}
+//! ```
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+pub use crate::header::CONTENT_ENCODING;
+use crate::HeaderMap;
+
+/// Algorithms available to compress payloads.
+///
+/// Compressing and decompressing requires the `compression` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Gzip compression.
+    Gzip,
+    /// Zstandard compression.
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the name of the algorithm, as recorded in headers.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Returns the algorithm with the given name, if known.
+    pub(crate) fn from_name(name: &str) -> Option<Compression> {
+        match name {
+            "gzip" => Some(Compression::Gzip),
+            "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    pub(crate) fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        use std::io::Write;
+
+        match self {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Compression::Zstd => zstd::stream::encode_all(data, 0),
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    pub(crate) fn decompress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        use std::io::Read;
+
+        match self {
+            Compression::Gzip => {
+                let mut decoded = Vec::new();
+                flate2::read::GzDecoder::new(data).read_to_end(&mut decoded)?;
+                Ok(decoded)
+            }
+            Compression::Zstd => zstd::stream::decode_all(data),
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    pub(crate) fn compress(self, _data: &[u8]) -> io::Result<Vec<u8>> {
+        Err(compression_unsupported())
+    }
+
+    #[cfg(not(feature = "compression"))]
+    pub(crate) fn decompress(self, _data: &[u8]) -> io::Result<Vec<u8>> {
+        Err(compression_unsupported())
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn compression_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "compression requires the `compression` feature",
+    )
+}
+
+/// Compresses a payload, returning the headers marking its encoding and the compressed
+/// payload, or `None` if the payload is already encoded or does not shrink.
+pub(crate) fn compress_payload(
+    compression: Compression,
+    headers: Option<&HeaderMap>,
+    payload: &[u8],
+) -> io::Result<Option<(HeaderMap, Vec<u8>)>> {
+    if headers.map_or(false, |headers| headers.contains_key(CONTENT_ENCODING)) {
+        return Ok(None);
+    }
+
+    let compressed = compression.compress(payload)?;
+    if compressed.len() >= payload.len() {
+        return Ok(None);
+    }
+
+    let mut headers = headers.cloned().unwrap_or_default();
+    headers.insert(CONTENT_ENCODING, compression.name());
+    Ok(Some((headers, compressed)))
+}
+
+/// Decompresses a payload with a known encoding, removing the header marking it. Returns
+/// the payload unchanged if it has no known encoding or fails to decompress.
+pub(crate) fn decompress_payload(headers: &mut HeaderMap, payload: Vec<u8>) -> Vec<u8> {
+    let compression = match headers
+        .get(CONTENT_ENCODING)
+        .and_then(|name| Compression::from_name(name))
+    {
+        Some(compression) => compression,
+        None => return payload,
+    };
+
+    match compression.decompress(&payload) {
+        Ok(decompressed) => {
+            headers.remove(CONTENT_ENCODING);
+            decompressed
+        }
+        Err(err) => {
+            crate::logging::error!(
+                "failed to decompress {} payload: {}",
+                compression.name(),
+                err
+            );
+            payload
+        }
+    }
+}
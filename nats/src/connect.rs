@@ -14,6 +14,8 @@
 use crate::SecureString;
 
 /// Info to construct a CONNECT message.
+const REDACTED: &str = "[REDACTED]";
+
 #[derive(Clone, Debug)]
 #[doc(hidden)]
 #[allow(clippy::module_name_repetitions)]
@@ -84,6 +86,22 @@ pub enum Protocol {
 }
 
 impl ConnectInfo {
+    /// Returns a copy with the credentials replaced, for logging and captures.
+    pub(crate) fn redacted(&self) -> ConnectInfo {
+        let mut connect_info = self.clone();
+        for secret in [
+            &mut connect_info.user_jwt,
+            &mut connect_info.signature,
+            &mut connect_info.pass,
+            &mut connect_info.auth_token,
+        ] {
+            if secret.is_some() {
+                *secret = Some(REDACTED.to_string().into());
+            }
+        }
+        connect_info
+    }
+
     pub(crate) fn dump(&self) -> Option<String> {
         let mut obj = json::object! {
             verbose: self.verbose,
@@ -25,7 +25,10 @@ use std::time::Duration;
 use url::{Host, Url};
 
 use crate::auth_utils;
+use crate::pinning::PinnedVerifier;
 use crate::proto::{self, ClientOp, ServerOp};
+use crate::record::Recorder;
+use crate::rustls::client::{ServerCertVerifier, WebPkiVerifier};
 use crate::rustls::{ClientConfig, ClientConnection};
 use crate::secure_wipe::SecureString;
 use crate::{connect::ConnectInfo, inject_io_failure, AuthStyle, Options, ServerInfo};
@@ -42,8 +45,58 @@ pub(crate) struct Connector {
     /// Configured options for establishing connections.
     options: Arc<Options>,
 
-    /// TLS config.
-    tls_config: Arc<ClientConfig>,
+    /// TLS config, shared with the client so it can be reloaded.
+    tls_config: TlsConfig,
+
+    /// Records the traffic of all connections, if enabled.
+    recorder: Option<Arc<Recorder>>,
+
+    /// Settings of individual servers, see [`Options::server_options`].
+    servers: Vec<ServerSettings>,
+}
+
+/// Authentication and TLS settings used when connecting to a single server.
+struct ServerSettings {
+    host: String,
+    port: u16,
+    options: Arc<Options>,
+    /// TLS config, if the options have TLS settings.
+    tls_config: Option<Arc<ClientConfig>>,
+}
+
+impl ServerSettings {
+    fn new(server: &str, options: Arc<Options>) -> io::Result<ServerSettings> {
+        let address: ServerAddress = server.parse()?;
+        let has_tls_settings = options.tls_client_config.is_some()
+            || !options.certificates.is_empty()
+            || options.client_cert.is_some()
+            || !options.certificate_pins.is_empty()
+            || !options.public_key_pins.is_empty()
+            || options.certificate_verifier.is_some();
+        let tls_config = if has_tls_settings {
+            Some(Arc::new(configure_tls(&options)?))
+        } else {
+            None
+        };
+
+        Ok(ServerSettings {
+            host: address.host().to_string(),
+            port: address.port(),
+            options,
+            tls_config,
+        })
+    }
+}
+
+/// The TLS config used for new connections, replaced by [`reload_tls`].
+pub(crate) type TlsConfig = Arc<Mutex<Arc<ClientConfig>>>;
+
+/// Reloads the configured certificates and keys into the TLS config. On failure the
+/// previous config stays in use.
+pub(crate) fn reload_tls(options: &Arc<Options>, tls_config: &TlsConfig) -> io::Result<()> {
+    let config = configure_tls(options)?;
+    *tls_config.lock() = Arc::new(config);
+    Ok(())
 }
 
 fn configure_tls(options: &Arc<Options>) -> Result<ClientConfig, Error> {
@@ -90,28 +143,47 @@ fn configure_tls(options: &Arc<Options>) -> Result<ClientConfig, Error> {
             root_store.add_server_trust_anchors(trust_anchors);
         }
 
+        let mut verifier = options.certificate_verifier.clone();
+        if !options.certificate_pins.is_empty() || !options.public_key_pins.is_empty() {
+            let inner = verifier.unwrap_or_else(|| {
+                Arc::new(WebPkiVerifier::new(root_store.clone(), None))
+                    as Arc<dyn ServerCertVerifier>
+            });
+            verifier = Some(Arc::new(PinnedVerifier::new(
+                inner,
+                &options.certificate_pins,
+                &options.public_key_pins,
+            )?));
+        }
+
         let builder = rustls::ClientConfig::builder()
             .with_safe_defaults()
             .with_root_certificates(root_store);
 
-        if let Some(cert) = &options.client_cert {
+        let mut config = if let Some(cert) = &options.client_cert {
             if let Some(key) = &options.client_key {
                 let cert = auth_utils::load_certs(cert)?;
                 let key = auth_utils::load_key(key)?;
 
-                return builder.with_client_auth_cert(cert, key).map_err(|_| {
+                builder.with_client_auth_cert(cert, key).map_err(|_| {
                     io::Error::new(ErrorKind::Other, "could not add certificate or key")
-                });
+                })?
             } else {
                 return Err(io::Error::new(
                     ErrorKind::Other,
                     "found certificate, but no key",
                 ));
             }
+        } else {
+            // if there are no client certs provided, connect with just TLS.
+            builder.with_no_client_auth()
+        };
+
+        if let Some(verifier) = verifier {
+            config.dangerous().set_certificate_verifier(verifier);
         }
 
-        // if there are no client certs provided, connect with just TLS.
-        Ok(builder.with_no_client_auth())
+        Ok(config)
     }
 }
 
@@ -119,16 +191,33 @@ impl Connector {
     /// Creates a new connector with the URLs and options.
     pub(crate) fn new(urls: Vec<ServerAddress>, options: Arc<Options>) -> io::Result<Connector> {
         let tls_config = configure_tls(&options)?;
+        let recorder = match options.record_path.as_ref() {
+            Some(path) => Some(Arc::new(Recorder::create(path)?)),
+            None => None,
+        };
+
+        let servers = options
+            .server_options
+            .iter()
+            .map(|(server, options)| ServerSettings::new(server, options.clone()))
+            .collect::<io::Result<_>>()?;
 
         let connector = Connector {
             attempts: urls.into_iter().map(|url| (url, 0)).collect(),
             options,
-            tls_config: Arc::new(tls_config),
+            tls_config: Arc::new(Mutex::new(Arc::new(tls_config))),
+            recorder,
+            servers,
         };
 
         Ok(connector)
     }
 
+    /// Returns the shared TLS config of the connector.
+    pub(crate) fn tls_config(&self) -> TlsConfig {
+        self.tls_config.clone()
+    }
+
     /// Adds an URL to the list of servers.
     pub(crate) fn add_server(&mut self, url: ServerAddress) {
         self.attempts.insert(url, 0);
@@ -138,6 +227,13 @@ impl Connector {
         self.options.clone()
     }
 
+    /// Returns the settings of a server, if it has any.
+    fn server_settings(&self, server: &ServerAddress) -> Option<&ServerSettings> {
+        self.servers
+            .iter()
+            .find(|settings| settings.host == server.host() && settings.port == server.port())
+    }
+
     /// Get the list of servers with enough reconnection attempts left
     fn get_servers(&mut self) -> io::Result<Vec<ServerAddress>> {
         let servers: Vec<_> = self
@@ -184,6 +280,7 @@ impl Connector {
                 let reconnects = self.attempts.get_mut(server).unwrap();
                 let sleep_duration = self.options.reconnect_delay_callback.call(*reconnects);
                 *reconnects += 1;
+                let attempt = *reconnects;
 
                 let lookup_res = server.socket_addrs();
 
@@ -199,12 +296,22 @@ impl Connector {
                 fastrand::shuffle(&mut addrs);
 
                 for addr in addrs {
+                    span!(
+                        "connect",
+                        server = %server.host(),
+                        addr = %addr,
+                        attempt
+                    );
+
                     // Sleep for some time if this is not the first connection
                     // attempt for this server.
                     thread::sleep(sleep_duration);
 
                     // Try connecting to this address.
                     let res = self.connect_addr(addr, server);
+                    if let Err(err) = &res {
+                        crate::logging::debug!("failed to connect to {}: {}", addr, err);
+                    }
 
                     // Check if connecting worked out.
                     let (server_info, stream) = match res {
@@ -252,7 +359,15 @@ impl Connector {
             stream.read_exact(byte)?;
             line.push(byte[0]);
         }
-        let server_info = match proto::decode(&line[..])? {
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder.connected();
+            recorder.inbound(&line);
+        }
+        let op = proto::decode(&line[..])?;
+        if let Some(op) = &op {
+            crate::tap::inbound(&self.options, op);
+        }
+        let server_info = match op {
             Some(ServerOp::Info(server_info)) => server_info,
             Some(op) => {
                 return Err(Error::new(
@@ -269,8 +384,12 @@ impl Connector {
         // - Has `self.options.tls_required(true)` been set?
         // - Was the server address prefixed with `tls://`?
         // - Does the INFO line contain `tls_required: true`?
-        let tls_required =
-            self.options.tls_required || server.tls_required() || server_info.tls_required;
+        // - Do the options of this server require TLS?
+        let settings = self.server_settings(server);
+        let tls_required = self.options.tls_required
+            || server.tls_required()
+            || server_info.tls_required
+            || settings.map_or(false, |settings| settings.options.tls_required);
 
         // Upgrade to TLS if required.
         let session = if tls_required {
@@ -286,8 +405,12 @@ impl Connector {
                     )
                 })?;
 
+            let tls_config = match settings.and_then(|settings| settings.tls_config.clone()) {
+                Some(tls_config) => tls_config,
+                None => self.tls_config.lock().clone(),
+            };
             Some(
-                ClientConnection::new(self.tls_config.clone(), server_name)
+                ClientConnection::new(tls_config, server_name)
                     .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
             )
         } else {
@@ -299,8 +422,8 @@ impl Connector {
         let mut connect_info = ConnectInfo {
             tls_required,
             name: self.options.name.clone().map(SecureString::from),
-            pedantic: false,
-            verbose: false,
+            pedantic: self.options.pedantic,
+            verbose: self.options.verbose,
             lang: crate::LANG.to_string(),
             version: crate::VERSION.to_string(),
             protocol: crate::connect::Protocol::Dynamic,
@@ -311,15 +434,23 @@ impl Connector {
             nkey: None,
             signature: None,
             echo: !self.options.no_echo,
-            headers: true,
-            no_responders: true,
+            // No responders are reported through headers, so both depend on the server
+            // supporting headers.
+            headers: server_info.headers,
+            no_responders: server_info.headers,
         };
 
+        // Authentication in the URL takes precedence over the options of the server, which
+        // take precedence over the options of the client.
         let server_auth = server.auth();
-        let auth = if let AuthStyle::NoAuth = server_auth {
-            &self.options.auth
-        } else {
-            &server_auth
+        let auth = match (&server_auth, settings) {
+            (AuthStyle::NoAuth, Some(settings))
+                if !matches!(settings.options.auth, AuthStyle::NoAuth) =>
+            {
+                &settings.options.auth
+            }
+            (AuthStyle::NoAuth, _) => &self.options.auth,
+            _ => &server_auth,
         };
 
         // Fill in the info that authenticates the client.
@@ -347,23 +478,59 @@ impl Connector {
         }
 
         // Send CONNECT and PING messages.
-        proto::encode(&mut stream, ClientOp::Connect(&connect_info))?;
-        proto::encode(&mut stream, ClientOp::Ping)?;
+        for op in [ClientOp::Connect(&connect_info), ClientOp::Ping] {
+            crate::tap::outbound(&self.options, &op);
+            proto::encode(&mut stream, op)?;
+        }
         stream.flush()?;
 
+        // The handshake is recorded by hand to keep credentials out of captures.
+        if let Some(recorder) = self.recorder.as_ref() {
+            let mut handshake = Vec::new();
+            proto::encode(&mut handshake, ClientOp::Connect(&connect_info.redacted()))?;
+            proto::encode(&mut handshake, ClientOp::Ping)?;
+            recorder.outbound(&handshake);
+        }
+
         let mut reader = BufReader::new(stream.clone());
 
         // Wait for a PONG.
         loop {
-            match proto::decode(&mut reader)? {
+            let op = proto::decode(&mut reader)?;
+            if let Some(op) = &op {
+                crate::tap::inbound(&self.options, op);
+            }
+            match op {
                 // If we get PONG, the server is happy and we're done
                 // connecting.
-                Some(ServerOp::Pong) => break,
+                Some(ServerOp::Pong) => {
+                    if let Some(recorder) = self.recorder.as_ref() {
+                        recorder.inbound(b"PONG\r\n");
+                    }
+                    break;
+                }
+
+                // In verbose mode the CONNECT is acknowledged before the PONG.
+                Some(ServerOp::Ok) => {
+                    if let Some(recorder) = self.recorder.as_ref() {
+                        recorder.inbound(b"+OK\r\n");
+                    }
+                }
 
                 // Respond to a PING with a PONG.
                 Some(ServerOp::Ping) => {
+                    crate::tap::outbound(&self.options, &ClientOp::Pong);
                     proto::encode(&mut stream, ClientOp::Pong)?;
                     stream.flush()?;
+                    if let Some(recorder) = self.recorder.as_ref() {
+                        recorder.inbound(b"PING\r\n");
+                        recorder.outbound(b"PONG\r\n");
+                    }
+                }
+
+                // The server rejected the connection, e.g. because of invalid credentials.
+                Some(ServerOp::Err(msg)) => {
+                    return Err(crate::Error::from_server(msg).into());
                 }
 
                 // No other operations should arrive at this time.
@@ -384,6 +551,9 @@ impl Connector {
             }
         }
 
+        // Record everything after the handshake as it goes over the wire.
+        stream.recorder = self.recorder.clone();
+
         Ok((server_info, stream))
     }
 }
@@ -394,6 +564,7 @@ impl Connector {
 #[derive(Clone)]
 pub(crate) struct NatsStream {
     flavor: Arc<Flavor>,
+    recorder: Option<Arc<Recorder>>,
 }
 
 enum Flavor {
@@ -417,7 +588,10 @@ impl NatsStream {
             }
         };
         let flavor = Arc::new(flavor);
-        Ok(NatsStream { flavor })
+        Ok(NatsStream {
+            flavor,
+            recorder: None,
+        })
     }
 
     pub(crate) fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
@@ -445,13 +619,17 @@ impl Read for NatsStream {
 
 impl Read for &NatsStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match &*self.flavor {
+        let n = match &*self.flavor {
             Flavor::Tcp(tcp) => (tcp.deref()).read(buf),
             Flavor::Tls(tls) => tls_op(tls, |session, eof| match session.reader().read(buf) {
                 Ok(0) if !eof => Err(io::ErrorKind::WouldBlock.into()),
                 res => res,
             }),
+        }?;
+        if let (Some(recorder), true) = (self.recorder.as_ref(), n > 0) {
+            recorder.inbound(&buf[..n]);
         }
+        Ok(n)
     }
 }
 
@@ -467,10 +645,14 @@ impl Write for NatsStream {
 
 impl Write for &NatsStream {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        match &*self.flavor {
+        let n = match &*self.flavor {
             Flavor::Tcp(tcp) => (tcp.deref()).write(buf),
             Flavor::Tls(tls) => tls_op(tls, |session, _| session.writer().write(buf)),
+        }?;
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder.outbound(&buf[..n]);
         }
+        Ok(n)
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -0,0 +1,98 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Choice of the threads running subscription handlers.
+//!
+//! [`Subscription::with_handler`](crate::Subscription::with_handler) runs each handler on a
+//! thread of its own. [`Subscription::with_handler_on`](crate::Subscription::with_handler_on)
+//! lets a subscription run its handler on the thread reading from the server instead,
+//! avoiding the hand-off for handlers that return quickly, or on a [`WorkerPool`] shared by
+//! many subscriptions.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! use nats::dispatch::{Dispatch, WorkerPool};
+//!
+//! let pool = WorkerPool::new("orders", 4)?;
+//! nc.subscribe("orders.created")?
+//!     .with_handler_on(Dispatch::Pool(pool.clone()), |msg| msg.respond("created"));
+//! nc.subscribe("orders.deleted")?
+//!     .with_handler_on(Dispatch::Pool(pool), |msg| msg.respond("deleted"));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::thread;
+
+use crossbeam_channel as channel;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Where the handler of a subscription runs.
+#[derive(Debug, Clone)]
+pub enum Dispatch {
+    /// On the thread reading from the server. This has the lowest latency, but the next
+    /// message of any subscription of the connection is only read once the handler returns,
+    /// so it only suits handlers that never block.
+    Inline,
+    /// On a thread dedicated to the subscription, like [`Subscription::with_handler`].
+    ///
+    /// [`Subscription::with_handler`]: crate::Subscription::with_handler
+    Dedicated,
+    /// On the threads of a worker pool, which may run messages of a subscription
+    /// concurrently and out of order.
+    Pool(WorkerPool),
+}
+
+/// A named pool of threads running subscription handlers.
+///
+/// The threads are named `{name}_{index}`, and exit once the pool and all handlers using it
+/// are dropped.
+#[derive(Debug, Clone)]
+pub struct WorkerPool {
+    name: String,
+    jobs: channel::Sender<Job>,
+}
+
+impl WorkerPool {
+    /// Spawns a pool of `threads` threads, at least one.
+    pub fn new(name: impl Into<String>, threads: usize) -> io::Result<WorkerPool> {
+        let name = name.into();
+        let (jobs, receiver) = channel::unbounded::<Job>();
+        for i in 0..threads.max(1) {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("{name}_{i}"))
+                .spawn(move || {
+                    for job in receiver {
+                        job();
+                    }
+                })?;
+        }
+        Ok(WorkerPool { name, jobs })
+    }
+
+    /// Returns the name of the pool.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Queues a job to run on one of the threads.
+    pub(crate) fn execute(&self, job: impl FnOnce() + Send + 'static) {
+        // The threads only exit once all senders are dropped.
+        self.jobs.send(Box::new(job)).ok();
+    }
+}
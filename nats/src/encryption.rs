@@ -0,0 +1,328 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client-side authenticated encryption of payloads.
+//!
+//! An [`Encryption`] encrypts payloads with AES-256-GCM or ChaCha20-Poly1305 under keys
+//! looked up by id in a [`KeyProvider`]. Keys can be rotated by changing the current key of
+//! the provider while keeping previous keys available for decryption.
+//!
+//! Messages published with
+//! [`Connection::publish_encrypted`](crate::Connection::publish_encrypted) record the key id
+//! and algorithm in the [`NATS_ENCRYPTION_KEY`] and [`NATS_ENCRYPTION`] headers, and are
+//! decrypted with [`Encryption::open`], or by typed subscriptions set up with
+//! [`TypedSubscription::with_encryption`](crate::typed::TypedSubscription::with_encryption).
+//! Values without headers, such as those of key-value buckets, are encrypted with
+//! [`Encryption::encrypt`], which prefixes the key id and algorithm to the payload.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use nats::encryption::{Encryption, StaticKeys};
+//!
+//! let encryption = Encryption::new(StaticKeys::new("2023-10", [7; 32]));
+//!
+//! let nc = nats::connect("demo.nats.io")?;
+//! let sub = nc.subscribe("secrets")?;
+//! nc.publish_encrypted("secrets", &encryption, "hunter2")?;
+//!
+//! if let Some(message) = sub.next() {
+//!     let message = encryption.open(message)?;
+//!     assert_eq!(message.data, b"hunter2");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::sync::Arc;
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+pub use crate::header::{NATS_ENCRYPTION, NATS_ENCRYPTION_KEY};
+use crate::secure_wipe::SecureVec;
+use crate::{HeaderMap, Message};
+
+/// The version of the envelope produced by [`Encryption::encrypt`].
+const ENVELOPE_VERSION: u8 = 1;
+
+/// Provides the 256-bit keys used to encrypt and decrypt payloads.
+pub trait KeyProvider: Send + Sync {
+    /// Returns the id of the key that encrypts new payloads.
+    fn current_key_id(&self) -> io::Result<String>;
+
+    /// Returns the key with the given id, failing if it is unknown.
+    fn key(&self, key_id: &str) -> io::Result<Vec<u8>>;
+}
+
+/// A fixed set of keys held in memory, which are scrambled on drop.
+#[derive(Clone)]
+pub struct StaticKeys {
+    current: String,
+    keys: HashMap<String, SecureVec>,
+}
+
+impl StaticKeys {
+    /// Creates a key set encrypting with the given key.
+    pub fn new(key_id: impl Into<String>, key: [u8; 32]) -> StaticKeys {
+        let current = key_id.into();
+        let mut keys = HashMap::new();
+        keys.insert(current.clone(), SecureVec::from(key.to_vec()));
+        StaticKeys { current, keys }
+    }
+
+    /// Adds a key that is only used to decrypt payloads, such as a rotated out key.
+    pub fn with_key(mut self, key_id: impl Into<String>, key: [u8; 32]) -> StaticKeys {
+        self.keys
+            .insert(key_id.into(), SecureVec::from(key.to_vec()));
+        self
+    }
+}
+
+impl fmt::Debug for StaticKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticKeys")
+            .field("current", &self.current)
+            .field("keys", &self.keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl KeyProvider for StaticKeys {
+    fn current_key_id(&self) -> io::Result<String> {
+        Ok(self.current.clone())
+    }
+
+    fn key(&self, key_id: &str) -> io::Result<Vec<u8>> {
+        self.keys
+            .get(key_id)
+            .map(|key| key.to_vec())
+            .ok_or_else(|| {
+                io::Error::new(ErrorKind::NotFound, format!("unknown key id {}", key_id))
+            })
+    }
+}
+
+/// Authenticated encryption algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// AES-256 in Galois/Counter Mode.
+    Aes256Gcm,
+    /// ChaCha20 with the Poly1305 authenticator.
+    #[default]
+    ChaCha20Poly1305,
+}
+
+impl Algorithm {
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Aes256Gcm => "AES-256-GCM",
+            Algorithm::ChaCha20Poly1305 => "ChaCha20-Poly1305",
+        }
+    }
+
+    fn from_name(name: &str) -> io::Result<Algorithm> {
+        match name {
+            "AES-256-GCM" => Ok(Algorithm::Aes256Gcm),
+            "ChaCha20-Poly1305" => Ok(Algorithm::ChaCha20Poly1305),
+            _ => Err(invalid_data(format!(
+                "unknown encryption algorithm {}",
+                name
+            ))),
+        }
+    }
+
+    fn id(self) -> u8 {
+        match self {
+            Algorithm::Aes256Gcm => 1,
+            Algorithm::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> io::Result<Algorithm> {
+        match id {
+            1 => Ok(Algorithm::Aes256Gcm),
+            2 => Ok(Algorithm::ChaCha20Poly1305),
+            _ => Err(invalid_data(format!("unknown encryption algorithm {}", id))),
+        }
+    }
+
+    fn key(self, key: &[u8]) -> io::Result<LessSafeKey> {
+        let algorithm = match self {
+            Algorithm::Aes256Gcm => &AES_256_GCM,
+            Algorithm::ChaCha20Poly1305 => &CHACHA20_POLY1305,
+        };
+        UnboundKey::new(algorithm, key)
+            .map(LessSafeKey::new)
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "encryption keys are 32 bytes"))
+    }
+}
+
+/// Encrypts and decrypts payloads with the keys of a [`KeyProvider`].
+///
+/// Clones share the key provider.
+#[derive(Clone)]
+pub struct Encryption {
+    provider: Arc<dyn KeyProvider>,
+    algorithm: Algorithm,
+    random: SystemRandom,
+}
+
+impl fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encryption")
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+impl Encryption {
+    /// Creates an encryption with the keys of the provider, using ChaCha20-Poly1305.
+    pub fn new(provider: impl KeyProvider + 'static) -> Encryption {
+        Encryption {
+            provider: Arc::new(provider),
+            algorithm: Algorithm::default(),
+            random: SystemRandom::new(),
+        }
+    }
+
+    /// Sets the algorithm encrypting new payloads. Payloads are decrypted with the
+    /// algorithm recorded with them.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Encryption {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Encrypts a payload with the current key, recording the key id and algorithm in the
+    /// headers.
+    pub fn seal(&self, payload: &[u8], headers: &mut HeaderMap) -> io::Result<Vec<u8>> {
+        let key_id = self.provider.current_key_id()?;
+        let sealed = self.seal_with(self.algorithm, &key_id, payload, Vec::new())?;
+        headers.insert(NATS_ENCRYPTION_KEY, key_id);
+        headers.insert(NATS_ENCRYPTION, self.algorithm.name());
+        Ok(sealed)
+    }
+
+    /// Decrypts the payload of a message sealed with [`Encryption::seal`], removing the
+    /// encryption headers. Fails with `InvalidData` if the message is not encrypted or
+    /// fails authentication.
+    pub fn open(&self, mut message: Message) -> io::Result<Message> {
+        let headers = message
+            .headers
+            .as_mut()
+            .ok_or_else(|| invalid_data("message is not encrypted".to_string()))?;
+        let key_id = headers
+            .get(NATS_ENCRYPTION_KEY)
+            .cloned()
+            .ok_or_else(|| invalid_data("message is not encrypted".to_string()))?;
+        let algorithm = match headers.get(NATS_ENCRYPTION) {
+            Some(name) => Algorithm::from_name(name)?,
+            None => Algorithm::default(),
+        };
+
+        message.data = self.open_with(algorithm, &key_id, &message.data)?;
+        headers.remove(NATS_ENCRYPTION_KEY);
+        headers.remove(NATS_ENCRYPTION);
+        Ok(message)
+    }
+
+    /// Encrypts a payload with the current key into an envelope recording the key id and
+    /// algorithm, for values that have no headers.
+    pub fn encrypt(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let key_id = self.provider.current_key_id()?;
+        let key_id_len = u8::try_from(key_id.len()).map_err(|_| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "key ids are at most 255 bytes long",
+            )
+        })?;
+
+        let mut envelope = vec![ENVELOPE_VERSION, self.algorithm.id(), key_id_len];
+        envelope.extend_from_slice(key_id.as_bytes());
+        self.seal_with(self.algorithm, &key_id, payload, envelope)
+    }
+
+    /// Decrypts an envelope produced by [`Encryption::encrypt`].
+    pub fn decrypt(&self, envelope: &[u8]) -> io::Result<Vec<u8>> {
+        let malformed = || invalid_data("malformed encrypted payload".to_string());
+        match envelope.first() {
+            Some(&ENVELOPE_VERSION) => {}
+            Some(_) => {
+                return Err(invalid_data(
+                    "unknown encrypted payload version".to_string(),
+                ))
+            }
+            None => return Err(malformed()),
+        }
+
+        let algorithm = Algorithm::from_id(*envelope.get(1).ok_or_else(malformed)?)?;
+        let key_id_len = *envelope.get(2).ok_or_else(malformed)? as usize;
+        let key_id = envelope.get(3..3 + key_id_len).ok_or_else(malformed)?;
+        let key_id = std::str::from_utf8(key_id).map_err(|_| malformed())?;
+        self.open_with(algorithm, key_id, &envelope[3 + key_id_len..])
+    }
+
+    /// Appends the nonce and the encrypted payload, authenticating the key id, to `out`.
+    fn seal_with(
+        &self,
+        algorithm: Algorithm,
+        key_id: &str,
+        payload: &[u8],
+        mut out: Vec<u8>,
+    ) -> io::Result<Vec<u8>> {
+        let key = algorithm.key(&self.provider.key(key_id)?)?;
+
+        let mut nonce = [0; NONCE_LEN];
+        self.random
+            .fill(&mut nonce)
+            .map_err(|_| io::Error::new(ErrorKind::Other, "failed to generate a nonce"))?;
+
+        let mut sealed = payload.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(key_id.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| io::Error::new(ErrorKind::Other, "failed to encrypt payload"))?;
+
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// Decrypts a nonce followed by an encrypted payload.
+    fn open_with(&self, algorithm: Algorithm, key_id: &str, sealed: &[u8]) -> io::Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid_data("malformed encrypted payload".to_string()));
+        }
+        let key = algorithm.key(&self.provider.key(key_id)?)?;
+        let nonce = Nonce::try_assume_unique_for_key(&sealed[..NONCE_LEN])
+            .map_err(|_| invalid_data("malformed encrypted payload".to_string()))?;
+
+        let mut payload = sealed[NONCE_LEN..].to_vec();
+        let len = key
+            .open_in_place(nonce, Aad::from(key_id.as_bytes()), &mut payload)
+            .map_err(|_| invalid_data("failed to decrypt payload".to_string()))?
+            .len();
+        payload.truncate(len);
+        Ok(payload)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
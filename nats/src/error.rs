@@ -0,0 +1,312 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured errors of the client.
+//!
+//! Fallible APIs return [`std::io::Result`]. Errors originating in the client carry an
+//! [`Error`] inside the [`std::io::Error`], which can be recovered with [`Error::from_io`] or
+//! the [`From<std::io::Error>`] conversion instead of matching on error messages.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! let nc = nats::connect("demo.nats.io")?;
+//! match nc.request("service", "data") {
+//!     Ok(response) => println!("{}", response),
+//!     Err(err) => match nats::Error::from(err) {
+//!         nats::Error::NoResponders => println!("the service is not running"),
+//!         err => return Err(err.into()),
+//!     },
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{error, fmt, io};
+
+#[cfg(feature = "jetstream")]
+use crate::jetstream;
+
+/// An error returned by the client.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The server sent an unexpected or malformed protocol message, or an `-ERR`.
+    Protocol(String),
+    /// The server rejected the credentials while connecting.
+    Auth(String),
+    /// The operation did not complete in time.
+    TimedOut,
+    /// A request was sent to a subject nobody is listening on.
+    NoResponders,
+    /// The JetStream API returned an error.
+    #[cfg(feature = "jetstream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jetstream")))]
+    JetStream(jetstream::Error),
+    /// Messages of a subscription were dropped because its pending limits were reached.
+    SlowConsumer {
+        /// Subject of the subscription.
+        subject: String,
+    },
+    /// The connection is closed.
+    Closed,
+    /// Headers could not be parsed.
+    InvalidHeader(String),
+    /// A subject does not follow the token rules.
+    InvalidSubject(String),
+    /// The server does not support a protocol feature, like headers.
+    UnsupportedFeature(&'static str),
+    /// A message was not published because it is over the publish rate limit.
+    RateLimited,
+    /// A request was not forwarded because it already went through the subject, or through
+    /// too many hops.
+    ForwardingLoop {
+        /// Subject the request was forwarded to.
+        subject: String,
+        /// Number of times the request was already forwarded.
+        hops: u32,
+    },
+    /// The server sent an `-ERR` on an established connection.
+    Server(ServerError),
+    /// An I/O error without a more specific kind.
+    Io(io::Error),
+}
+
+impl Error {
+    /// Returns the [`Error`] carried by an [`io::Error`] returned from the client, if any.
+    pub fn from_io(err: &io::Error) -> Option<&Error> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<Error>())
+    }
+
+    /// Classifies an `-ERR` sent by the server while connecting.
+    pub(crate) fn from_server(message: String) -> Error {
+        match ServerError::parse(&message) {
+            ServerError::AuthorizationViolation | ServerError::AuthenticationExpired => {
+                Error::Auth(message)
+            }
+            ServerError::Other(_) => Error::Protocol(message),
+            err => Error::Server(err),
+        }
+    }
+
+    fn io_kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Protocol(_) => io::ErrorKind::InvalidData,
+            Error::Auth(_) => io::ErrorKind::PermissionDenied,
+            Error::TimedOut => io::ErrorKind::TimedOut,
+            Error::NoResponders => io::ErrorKind::NotFound,
+            #[cfg(feature = "jetstream")]
+            Error::JetStream(_) => io::ErrorKind::Other,
+            Error::SlowConsumer { .. } => io::ErrorKind::Other,
+            Error::Closed => io::ErrorKind::NotConnected,
+            Error::InvalidHeader(_) | Error::InvalidSubject(_) => io::ErrorKind::InvalidInput,
+            Error::UnsupportedFeature(_) => io::ErrorKind::Unsupported,
+            Error::RateLimited => io::ErrorKind::WouldBlock,
+            Error::ForwardingLoop { .. } => io::ErrorKind::InvalidInput,
+            Error::Server(ServerError::PermissionsViolation { .. }) => {
+                io::ErrorKind::PermissionDenied
+            }
+            Error::Server(_) => io::ErrorKind::Other,
+            Error::Io(err) => err.kind(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Protocol(message) => write!(f, "protocol error: {message}"),
+            Error::Auth(message) => write!(f, "authorization error: {message}"),
+            Error::TimedOut => write!(f, "timed out"),
+            Error::NoResponders => write!(f, "no responders"),
+            #[cfg(feature = "jetstream")]
+            Error::JetStream(err) => write!(f, "jetstream error: {err}"),
+            Error::SlowConsumer { subject } => write!(
+                f,
+                "slow consumer detected for subscription on subject {subject}. dropping messages"
+            ),
+            Error::Closed => write!(f, "the client is closed"),
+            Error::InvalidHeader(message) | Error::InvalidSubject(message) => {
+                write!(f, "{message}")
+            }
+            Error::UnsupportedFeature(feature) => {
+                write!(f, "the server does not support {feature}")
+            }
+            Error::RateLimited => write!(f, "publish rate limit exceeded"),
+            Error::ForwardingLoop { subject, hops } => write!(
+                f,
+                "forwarding loop detected on subject {subject} after {hops} hops"
+            ),
+            Error::Server(err) => write!(f, "{err}"),
+            Error::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "jetstream")]
+            Error::JetStream(err) => Some(err),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        if err.get_ref().map_or(false, |inner| inner.is::<Error>()) {
+            return *err.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+        #[cfg(feature = "jetstream")]
+        if err
+            .get_ref()
+            .map_or(false, |inner| inner.is::<jetstream::Error>())
+        {
+            return Error::JetStream(
+                *err.into_inner()
+                    .unwrap()
+                    .downcast::<jetstream::Error>()
+                    .unwrap(),
+            );
+        }
+        if err.kind() == io::ErrorKind::TimedOut {
+            Error::TimedOut
+        } else {
+            Error::Io(err)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::new(err.io_kind(), err),
+        }
+    }
+}
+
+#[cfg(feature = "jetstream")]
+impl From<jetstream::Error> for Error {
+    fn from(err: jetstream::Error) -> Error {
+        Error::JetStream(err)
+    }
+}
+
+/// The operation a permissions violation was reported for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionOperation {
+    /// Publishing to the subject.
+    Publish,
+    /// Subscribing to the subject.
+    Subscribe,
+}
+
+/// An `-ERR` sent by the server, parsed into the errors clients usually react to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServerError {
+    /// The client is not allowed to publish or subscribe to a subject.
+    PermissionsViolation {
+        /// The denied operation.
+        operation: PermissionOperation,
+        /// The denied subject.
+        subject: String,
+    },
+    /// A published message exceeded the maximum payload of the server.
+    MaxPayloadExceeded,
+    /// The server did not receive a PONG in time and closed the connection.
+    StaleConnection,
+    /// The credentials were rejected.
+    AuthorizationViolation,
+    /// The credentials of the user or account expired or were revoked.
+    AuthenticationExpired,
+    /// Any other error message.
+    Other(String),
+}
+
+impl ServerError {
+    /// Parses the message of an `-ERR` sent by the server. The [`fmt::Display`]
+    /// implementation formats the error the way the server does.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::error::{PermissionOperation, ServerError};
+    ///
+    /// assert_eq!(
+    ///     ServerError::parse(r#"Permissions Violation for Publish to "orders""#),
+    ///     ServerError::PermissionsViolation {
+    ///         operation: PermissionOperation::Publish,
+    ///         subject: "orders".to_string(),
+    ///     }
+    /// );
+    /// ```
+    pub fn parse(message: &str) -> ServerError {
+        let lowercase = message.to_lowercase();
+        if lowercase.starts_with("permissions violation") {
+            let operation = if lowercase.contains(" for subscription ") {
+                PermissionOperation::Subscribe
+            } else {
+                PermissionOperation::Publish
+            };
+            let subject = message
+                .split(" to ")
+                .nth(1)
+                .unwrap_or_default()
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .trim_matches('"')
+                .to_string();
+            ServerError::PermissionsViolation { operation, subject }
+        } else if lowercase.starts_with("maximum payload") {
+            ServerError::MaxPayloadExceeded
+        } else if lowercase.starts_with("stale connection") {
+            ServerError::StaleConnection
+        } else if lowercase.starts_with("authorization violation") {
+            ServerError::AuthorizationViolation
+        } else if lowercase.contains("authentication expired")
+            || lowercase.contains("authentication revoked")
+        {
+            ServerError::AuthenticationExpired
+        } else {
+            ServerError::Other(message.to_string())
+        }
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::PermissionsViolation {
+                operation: PermissionOperation::Publish,
+                subject,
+            } => write!(f, r#"Permissions Violation for Publish to "{subject}""#),
+            ServerError::PermissionsViolation {
+                operation: PermissionOperation::Subscribe,
+                subject,
+            } => write!(
+                f,
+                r#"Permissions Violation for Subscription to "{subject}""#
+            ),
+            ServerError::MaxPayloadExceeded => write!(f, "Maximum Payload Violation"),
+            ServerError::StaleConnection => write!(f, "Stale Connection"),
+            ServerError::AuthorizationViolation => write!(f, "Authorization Violation"),
+            ServerError::AuthenticationExpired => write!(f, "User Authentication Expired"),
+            ServerError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
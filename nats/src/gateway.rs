@@ -0,0 +1,172 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Translation between [`http`] requests and responses and NATS request/reply.
+//!
+//! The method, the path with its query and the status travel in `Nats-Http-*` headers, HTTP
+//! headers are copied into NATS headers, and bodies become payloads. An HTTP-to-NATS gateway
+//! forwards requests with [`crate::Connection::request_http`], and services reply to them with
+//! [`crate::Message::respond_http`].
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! use std::time::Duration;
+//!
+//! // A service answering HTTP requests sent over NATS.
+//! nc.subscribe("api.>")?.with_handler(|message| {
+//!     let request = nats::gateway::decode_request(&message)?;
+//!     let response = http::Response::builder()
+//!         .status(200)
+//!         .body(format!("{} {}", request.method(), request.uri()).into_bytes())
+//!         .unwrap();
+//!     message.respond_http(&response)
+//! });
+//!
+//! // The gateway side, translating an incoming HTTP request.
+//! let request = http::Request::get("/orders/1").body(Vec::new()).unwrap();
+//! let subject = nats::gateway::subject_for_path("api", request.uri().path())?;
+//! let response = nc.request_http(&subject, &request, Some(Duration::from_secs(5)))?;
+//! assert_eq!(response.status(), 200);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+
+use crate::header::HeaderMap;
+use crate::Message;
+
+/// Header carrying the method of a request.
+pub const HTTP_METHOD: &str = "Nats-Http-Method";
+
+/// Header carrying the path and query of a request.
+pub const HTTP_PATH: &str = "Nats-Http-Path";
+
+/// Header carrying the status code of a response.
+pub const HTTP_STATUS: &str = "Nats-Http-Status";
+
+/// Maps a request path onto a subject below `prefix`, one token per path segment.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if a segment cannot be a subject token.
+///
+/// # Example
+/// ```
+/// let subject = nats::gateway::subject_for_path("api", "/orders/1").unwrap();
+/// assert_eq!(subject, "api.orders.1");
+/// ```
+pub fn subject_for_path(prefix: &str, path: &str) -> io::Result<String> {
+    let mut subject = prefix.to_string();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        if segment
+            .chars()
+            .any(|c| matches!(c, '.' | '*' | '>') || c.is_whitespace())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("path segment {:?} is not a valid subject token", segment),
+            ));
+        }
+        if !subject.is_empty() {
+            subject.push('.');
+        }
+        subject.push_str(segment);
+    }
+    Ok(subject)
+}
+
+/// Returns the headers carrying the method, path and headers of a request.
+pub fn encode_request<B>(request: &http::Request<B>) -> io::Result<HeaderMap> {
+    let mut headers = encode_headers(request.headers())?;
+    headers.insert(HTTP_METHOD, request.method().as_str());
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path| path.as_str());
+    headers.insert(HTTP_PATH, path);
+    Ok(headers)
+}
+
+/// Rebuilds the request carried by a message, with its payload as the body.
+///
+/// A message without a method is treated as a `POST` to `/`.
+pub fn decode_request(message: &Message) -> io::Result<http::Request<Vec<u8>>> {
+    let mut builder = http::Request::builder();
+    if let Some(headers) = message.headers.as_ref() {
+        if let Some(method) = headers.get(HTTP_METHOD) {
+            builder = builder.method(method.as_str());
+        } else {
+            builder = builder.method(http::Method::POST);
+        }
+        if let Some(path) = headers.get(HTTP_PATH) {
+            builder = builder.uri(path.as_str());
+        }
+        for (name, value) in decode_headers(headers) {
+            builder = builder.header(name, value);
+        }
+    } else {
+        builder = builder.method(http::Method::POST);
+    }
+    builder.body(message.data.clone()).map_err(invalid_data)
+}
+
+/// Returns the headers carrying the status and headers of a response.
+pub fn encode_response<B>(response: &http::Response<B>) -> io::Result<HeaderMap> {
+    let mut headers = encode_headers(response.headers())?;
+    headers.insert(HTTP_STATUS, response.status().as_str());
+    Ok(headers)
+}
+
+/// Rebuilds the response carried by a message, with its payload as the body.
+///
+/// A message without a status, for example from a responder unaware of HTTP, is treated
+/// as `200 OK`.
+pub fn decode_response(message: &Message) -> io::Result<http::Response<Vec<u8>>> {
+    let mut builder = http::Response::builder();
+    if let Some(headers) = message.headers.as_ref() {
+        if let Some(status) = headers.get(HTTP_STATUS) {
+            builder = builder.status(status.as_str());
+        }
+        for (name, value) in decode_headers(headers) {
+            builder = builder.header(name, value);
+        }
+    }
+    builder.body(message.data.clone()).map_err(invalid_data)
+}
+
+fn encode_headers(from: &http::HeaderMap) -> io::Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in from {
+        let value = value
+            .to_str()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        headers.append(name.as_str(), value);
+    }
+    Ok(headers)
+}
+
+fn decode_headers(headers: &HeaderMap) -> impl Iterator<Item = (&str, &str)> {
+    headers
+        .iter()
+        .filter(|(name, _)| !name.starts_with("Nats-Http-"))
+        .flat_map(|(name, values)| {
+            values
+                .iter()
+                .map(move |value| (name.as_str(), value.as_str()))
+        })
+}
+
+fn invalid_data(err: http::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
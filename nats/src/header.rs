@@ -21,7 +21,8 @@ use std::{
 use std::collections::hash_set;
 use std::iter::Iterator;
 
-use log::trace;
+use crate::logging::trace;
+use serde::{Deserialize, Serialize};
 
 const HEADER_LINE: &str = "NATS/1.0";
 const HEADER_LINE_LEN: usize = HEADER_LINE.len();
@@ -53,8 +54,66 @@ pub const NATS_LAST_CONSUMER: &str = "Nats-Last-Consumer";
 /// Nats-Consumer-Stalled
 pub const NATS_CONSUMER_STALLED: &str = "Nats-Consumer-Stalled";
 
+/// Nats-Stream
+pub const NATS_STREAM: &str = "Nats-Stream";
+
+/// Nats-Sequence
+pub const NATS_SEQUENCE: &str = "Nats-Sequence";
+
+/// Nats-Time-Stamp
+pub const NATS_TIME_STAMP: &str = "Nats-Time-Stamp";
+
+/// Nats-Subject
+pub const NATS_SUBJECT: &str = "Nats-Subject";
+
+/// Nats-TTL
+pub const NATS_TTL: &str = "Nats-TTL";
+
+/// Nats-Marker-Reason
+pub const NATS_MARKER_REASON: &str = "Nats-Marker-Reason";
+
+/// Nats-Msg-Size
+pub const NATS_MSG_SIZE: &str = "Nats-Msg-Size";
+
+/// Content-Type
+pub const CONTENT_TYPE: &str = "Content-Type";
+
+/// Content-Encoding
+pub const CONTENT_ENCODING: &str = "Content-Encoding";
+
+/// Nats-Schema
+pub const NATS_SCHEMA: &str = "Nats-Schema";
+
+/// Nats-Chunk-Id
+pub const NATS_CHUNK_ID: &str = "Nats-Chunk-Id";
+
+/// Nats-Chunk-Sequence
+pub const NATS_CHUNK_SEQUENCE: &str = "Nats-Chunk-Sequence";
+
+/// Nats-Chunk-Count
+pub const NATS_CHUNK_COUNT: &str = "Nats-Chunk-Count";
+
+/// Nats-Chunk-Digest
+pub const NATS_CHUNK_DIGEST: &str = "Nats-Chunk-Digest";
+
+/// Nats-Encryption
+pub const NATS_ENCRYPTION: &str = "Nats-Encryption";
+
+/// Nats-Encryption-Key
+pub const NATS_ENCRYPTION_KEY: &str = "Nats-Encryption-Key";
+
+/// Nats-Hop-Count
+pub const NATS_HOP_COUNT: &str = "Nats-Hop-Count";
+
+/// Nats-Hop
+pub const NATS_HOP: &str = "Nats-Hop";
+
+/// Nats-Original-Subject
+pub const NATS_ORIGINAL_SUBJECT: &str = "Nats-Original-Subject";
+
 /// A multi-map from header name to a set of values for that header
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct HeaderMap {
     /// A multi-map from header name to a set of values for that header
     inner: HashMap<String, HashSet<String>>,
@@ -140,10 +199,7 @@ impl<'a> FromIterator<&'a (&'a str, &'a str)> for HeaderMap {
 
 fn parse_error<T, E: AsRef<str>>(e: E) -> std::io::Result<T> {
     trace!("header parse error: {}", e.as_ref());
-    Err(std::io::Error::new(
-        std::io::ErrorKind::InvalidInput,
-        e.as_ref(),
-    ))
+    Err(crate::Error::InvalidHeader(e.as_ref().to_string()).into())
 }
 
 fn is_continuation(c: char) -> bool {
@@ -209,8 +265,15 @@ impl TryFrom<&[u8]> for HeaderMap {
 
                 let mut s = String::from(v.trim());
                 while let Some(v) = lines.next_if(|s| s.starts_with(is_continuation)) {
-                    s.push(' ');
-                    s.push_str(v.trim());
+                    // Blank continuation lines would otherwise leave stray whitespace
+                    // that does not survive serialization.
+                    let v = v.trim();
+                    if !v.is_empty() {
+                        if !s.is_empty() {
+                            s.push(' ');
+                        }
+                        s.push_str(v);
+                    }
                 }
 
                 entry.insert(s);
@@ -277,6 +340,23 @@ impl HeaderMap {
     pub fn contains_key(&self, key: &str) -> bool {
         self.inner.contains_key(key)
     }
+
+    /// Removes a key from the map, returning its values if the key was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use nats::HeaderMap;
+    /// # use nats::header::STATUS;
+    /// let mut map = HeaderMap::new();
+    /// map.insert(STATUS, "200");
+    ///
+    /// assert!(map.remove(STATUS).is_some());
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<HashSet<String>> {
+        self.inner.remove(key)
+    }
 }
 
 impl HeaderMap {
@@ -535,6 +615,10 @@ mod try_from {
     fn malformed_line() {
         let error = HeaderMap::try_from("NATS/1.0 200\r\n\nX-Test-A a\r\n".as_bytes()).unwrap_err();
         assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(matches!(
+            crate::Error::from_io(&error),
+            Some(crate::Error::InvalidHeader(_))
+        ));
     }
 
     #[test]
@@ -602,3 +686,55 @@ mod try_from {
         );
     }
 }
+
+#[cfg(test)]
+mod properties {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn header_map() -> impl Strategy<Value = HeaderMap> {
+        let name = "[A-Za-z0-9][A-Za-z0-9_-]{0,15}";
+        let value = "([!-~]([ \t]*[!-~])*)?";
+        prop::collection::vec((name, value), 0..8).prop_map(|entries| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in entries {
+                headers.append(name.as_str(), value);
+            }
+            headers
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(headers in header_map()) {
+            let parsed = HeaderMap::try_from(headers.to_bytes().as_slice()).unwrap();
+            prop_assert_eq!(parsed, headers);
+        }
+
+        #[test]
+        fn parsed_headers_round_trip(data in prop::collection::vec(any::<u8>(), 0..256)) {
+            let mut input = b"NATS/1.0".to_vec();
+            input.extend_from_slice(&data);
+            if let Ok(headers) = HeaderMap::try_from(input.as_slice()) {
+                let parsed = HeaderMap::try_from(headers.to_bytes().as_slice()).unwrap();
+                prop_assert_eq!(parsed, headers);
+            }
+        }
+
+        #[test]
+        fn continuation_lines(
+            first in "[!-~]{1,8}",
+            rest in prop::collection::vec(("[ \t]{1,3}", "[!-~]{1,8}"), 1..4),
+        ) {
+            let mut input = format!("NATS/1.0\r\nX-Test: {}\r\n", first);
+            let mut expected = first.clone();
+            for (indent, value) in &rest {
+                input.push_str(&format!("{}{}\r\n", indent, value));
+                expected.push(' ');
+                expected.push_str(value);
+            }
+            let headers = HeaderMap::try_from(input.as_bytes()).unwrap();
+            prop_assert_eq!(headers.get("X-Test"), Some(&expected));
+        }
+    }
+}
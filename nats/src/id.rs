@@ -0,0 +1,72 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use parking_lot::Mutex;
+
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const ID_LEN: usize = 22;
+
+/// Generates the unique identifiers used for inboxes, service instances, locks and
+/// object uploads, set with [`crate::Options::id_generator`].
+///
+/// By default identifiers are random NUIDs. Replacing the generator makes them
+/// reproducible, e.g. for golden tests and recorded protocol fixtures.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// let nc = nats::Options::new()
+///     .id_generator(nats::SeededIdGenerator::new(42))
+///     .connect("demo.nats.io")?;
+/// let inbox = nc.new_inbox();
+/// # Ok(())
+/// # }
+/// ```
+pub trait IdGenerator: Send + Sync + 'static {
+    /// Returns the next identifier. Identifiers must be valid subject tokens.
+    fn next_id(&self) -> String;
+}
+
+/// Generates random NUIDs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NuidGenerator;
+
+impl IdGenerator for NuidGenerator {
+    fn next_id(&self) -> String {
+        crate::nuid::next()
+    }
+}
+
+/// Generates NUID-like identifiers from a seed, producing the same sequence on every run.
+#[derive(Debug)]
+pub struct SeededIdGenerator {
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl SeededIdGenerator {
+    /// Creates a generator producing the sequence of `seed`.
+    pub fn new(seed: u64) -> SeededIdGenerator {
+        SeededIdGenerator {
+            rng: Mutex::new(fastrand::Rng::with_seed(seed)),
+        }
+    }
+}
+
+impl IdGenerator for SeededIdGenerator {
+    fn next_id(&self) -> String {
+        let rng = self.rng.lock();
+        (0..ID_LEN)
+            .map(|_| ALPHABET[rng.usize(..ALPHABET.len())] as char)
+            .collect()
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::jetstream::AckKind;
+use crate::{Connection, Message};
+
+/// Number of processed messages after which a [`BatchAck`] acknowledges by default.
+pub const DEFAULT_MAX_PENDING: usize = 100;
+
+/// How long a [`BatchAck`] waits at most to acknowledge processed messages by default.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+struct State {
+    /// Consumer sequences of messages received but not yet processed.
+    in_flight: BTreeSet<u64>,
+
+    /// Reply subjects of processed messages not yet acknowledged, by consumer sequence.
+    processed: BTreeMap<u64, String>,
+
+    max_pending: usize,
+    interval: Duration,
+    last_ack: Instant,
+}
+
+#[derive(Debug)]
+struct Inner {
+    connection: Connection,
+    state: Mutex<State>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Err(err) = self.ack(&mut self.state.lock()) {
+            crate::logging::error!("failed to acknowledge processed messages: {}", err);
+        }
+    }
+}
+
+impl Inner {
+    /// Acknowledges the highest processed message not preceded by one still in flight,
+    /// which with `AckPolicy::All` also acknowledges every message before it.
+    fn ack(&self, state: &mut State) -> io::Result<()> {
+        let ackable = match state.in_flight.iter().next() {
+            Some(first_in_flight) => state.processed.range(..first_in_flight).next_back(),
+            None => state.processed.iter().next_back(),
+        };
+        let (sequence, reply) = match ackable {
+            Some((sequence, reply)) => (*sequence, reply),
+            None => return Ok(()),
+        };
+
+        self.connection.publish(reply, AckKind::Ack)?;
+        state.processed = state.processed.split_off(&(sequence + 1));
+        state.last_ack = Instant::now();
+        Ok(())
+    }
+}
+
+/// Acknowledges the messages of a consumer with `AckPolicy::All` in batches, for handlers
+/// processing messages concurrently.
+///
+/// Messages are tracked when received and marked done once processed. Once enough messages
+/// are processed, or the interval passed, the highest processed message not preceded by one
+/// still being processed is acknowledged, acknowledging all of them at once. Processed
+/// messages left are acknowledged when the last clone is dropped.
+///
+/// Created by [`PushSubscription::batch_ack`](crate::jetstream::PushSubscription::batch_ack)
+/// and [`PullSubscription::batch_ack`](crate::jetstream::PullSubscription::batch_ack).
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// # let nc = nats::connect("demo.nats.io")?;
+/// # let js = nats::jetstream::new(nc);
+/// use nats::jetstream::SubscribeOptions;
+///
+/// let subscription =
+///     js.subscribe_with_options("events", &SubscribeOptions::new().ack_all())?;
+/// let acks = subscription.batch_ack()?;
+/// for message in subscription.iter() {
+///     acks.track(&message)?;
+///     let acks = acks.clone();
+///     std::thread::spawn(move || {
+///         println!("processing {}", message);
+///         acks.done(&message).ok();
+///     });
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BatchAck(Arc<Inner>);
+
+impl BatchAck {
+    /// Creates a batch acknowledger, acknowledging in the background once the interval
+    /// passes.
+    pub(crate) fn new(connection: Connection) -> BatchAck {
+        let batch_ack = BatchAck(Arc::new(Inner {
+            connection,
+            state: Mutex::new(State {
+                in_flight: BTreeSet::new(),
+                processed: BTreeMap::new(),
+                max_pending: DEFAULT_MAX_PENDING,
+                interval: DEFAULT_INTERVAL,
+                last_ack: Instant::now(),
+            }),
+        }));
+
+        let inner = Arc::downgrade(&batch_ack.0);
+        thread::Builder::new()
+            .name(
+                batch_ack
+                    .0
+                    .connection
+                    .0
+                    .client
+                    .thread_name("jetstream_batch_ack"),
+            )
+            .spawn(move || run_interval(inner))
+            .expect("threads should be spawnable");
+
+        batch_ack
+    }
+
+    /// Sets the number of processed messages after which they are acknowledged,
+    /// [`DEFAULT_MAX_PENDING`] by default.
+    pub fn max_pending(self, max_pending: usize) -> BatchAck {
+        self.0.state.lock().max_pending = max_pending.max(1);
+        self
+    }
+
+    /// Sets how long processed messages wait at most to be acknowledged,
+    /// [`DEFAULT_INTERVAL`] by default.
+    pub fn interval(self, interval: Duration) -> BatchAck {
+        self.0.state.lock().interval = interval;
+        self
+    }
+
+    /// Tracks a received message, which must be done before handing it to a handler so
+    /// that no later message is acknowledged while it is processed.
+    pub fn track(&self, message: &Message) -> io::Result<()> {
+        let sequence = consumer_sequence(message)?;
+        self.0.state.lock().in_flight.insert(sequence);
+        Ok(())
+    }
+
+    /// Marks a tracked message as processed, acknowledging processed messages if there
+    /// are enough of them or the interval passed.
+    pub fn done(&self, message: &Message) -> io::Result<()> {
+        let sequence = consumer_sequence(message)?;
+        let reply = message.reply.clone().ok_or_else(not_jetstream)?;
+
+        let mut state = self.0.state.lock();
+        state.in_flight.remove(&sequence);
+        state.processed.insert(sequence, reply);
+        if state.processed.len() >= state.max_pending || state.last_ack.elapsed() >= state.interval
+        {
+            self.0.ack(&mut state)?;
+        }
+        Ok(())
+    }
+
+    /// Stops tracking a message without acknowledging it, for instance after
+    /// negatively acknowledging it, so later messages can be acknowledged.
+    pub fn forget(&self, message: &Message) -> io::Result<()> {
+        let sequence = consumer_sequence(message)?;
+        self.0.state.lock().in_flight.remove(&sequence);
+        Ok(())
+    }
+
+    /// Acknowledges processed messages now.
+    pub fn flush(&self) -> io::Result<()> {
+        self.0.ack(&mut self.0.state.lock())
+    }
+}
+
+/// Acknowledges processed messages once the interval passes, until the acknowledger is
+/// dropped.
+fn run_interval(inner: Weak<Inner>) {
+    loop {
+        let wait = match inner.upgrade() {
+            Some(inner) => {
+                let mut state = inner.state.lock();
+                let elapsed = state.last_ack.elapsed();
+                if elapsed >= state.interval {
+                    if let Err(err) = inner.ack(&mut state) {
+                        crate::logging::error!("failed to acknowledge processed messages: {}", err);
+                    }
+                    // Wait a full interval after an attempt, even if nothing was acknowledged.
+                    state.last_ack = Instant::now();
+                    state.interval
+                } else {
+                    state.interval - elapsed
+                }
+            }
+            None => return,
+        };
+        thread::sleep(wait);
+    }
+}
+
+fn consumer_sequence(message: &Message) -> io::Result<u64> {
+    message
+        .jetstream_message_info()
+        .map(|info| info.consumer_seq)
+        .ok_or_else(not_jetstream)
+}
+
+fn not_jetstream() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "not a JetStream message")
+}
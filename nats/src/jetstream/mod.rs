@@ -102,7 +102,7 @@ use std::{
     error, fmt,
     fmt::Debug,
     io::{self, ErrorKind},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use parking_lot::Mutex;
@@ -115,6 +115,14 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 
 const ORDERED_IDLE_HEARTBEAT: Duration = Duration::from_nanos(5_000_000_000);
 
+/// How often, and how many times, a push subscription resuming after a reconnect checks
+/// for its consumer while JetStream is not yet available.
+const RESUME_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const RESUME_ATTEMPTS: usize = 20;
+
+/// Batch acknowledgements
+pub mod batch_ack;
+
 /// Pull subscriptions
 pub mod pull_subscription;
 
@@ -124,6 +132,7 @@ pub mod push_subscription;
 mod types;
 
 // We use a fully qualified crate path so these are documented as re-exports.
+pub use crate::jetstream::batch_ack::BatchAck;
 pub use crate::jetstream::pull_subscription::PullSubscription;
 pub use crate::jetstream::push_subscription::PushSubscription;
 
@@ -553,6 +562,39 @@ where
     }
 }
 
+/// Parses the response to a publish, failing if it is an API error.
+fn parse_publish_ack(response: &Message) -> io::Result<PublishAck> {
+    let res: ApiResponse<PublishAck> = serde_json::de::from_slice(&response.data)?;
+    match res {
+        ApiResponse::Ok(pub_ack) => Ok(pub_ack),
+        ApiResponse::Err { error, .. } => {
+            crate::logging::debug!(
+                "failed to parse API response: {:?}",
+                std::str::from_utf8(&response.data)
+            );
+
+            Err(io::Error::new(ErrorKind::Other, error))
+        }
+    }
+}
+
+impl PublishSummary {
+    /// Records the response to the publish of the message at the index.
+    fn record(&mut self, index: usize, response: io::Result<Message>) {
+        match response.and_then(|response| parse_publish_ack(&response)) {
+            Ok(ack) if ack.duplicate => self.duplicates += 1,
+            Ok(_) => self.published += 1,
+            Err(err) => self.failures.push((index, err)),
+        }
+    }
+}
+
+/// Reports the lag of a consumer until dropped, see [`JetStream::report_consumer_lag`].
+#[derive(Debug)]
+pub struct ConsumerLagReporter {
+    _stop: crossbeam_channel::Sender<()>,
+}
+
 /// A context for performing `JetStream` operations.
 #[derive(Clone, Debug)]
 pub struct JetStream {
@@ -563,6 +605,14 @@ pub struct JetStream {
 impl JetStream {
     /// Create a new `JetStream` context.
     pub fn new(connection: Connection, options: JetStreamOptions) -> Self {
+        // API requests are never rate limited, whatever their prefix.
+        if !options.api_prefix.trim_end_matches('.').is_empty() {
+            connection
+                .0
+                .client
+                .exempt_from_rate_limits(&options.api_prefix);
+        }
+
         Self {
             connection,
             options,
@@ -653,18 +703,98 @@ impl JetStream {
             msg,
         )?;
 
-        let res: ApiResponse<PublishAck> = serde_json::de::from_slice(&res_msg.data)?;
-        match res {
-            ApiResponse::Ok(pub_ack) => Ok(pub_ack),
-            ApiResponse::Err { error, .. } => {
-                log::debug!(
-                    "failed to parse API response: {:?}",
-                    std::str::from_utf8(&res_msg.data)
-                );
+        parse_publish_ack(&res_msg)
+    }
 
-                Err(io::Error::new(ErrorKind::Other, error))
+    /// Publishes the messages of an iterator, each on the subject returned by `subject_fn`,
+    /// keeping up to 256 messages in flight instead of waiting for each acknowledgement,
+    /// for bulk backfills from files or databases.
+    ///
+    /// Messages that fail to be published are reported in the summary instead of stopping
+    /// the others from being published.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # let js = nats::jetstream::new(nc);
+    /// let lines = vec!["eu,order 1", "us,order 2", "eu,order 3"];
+    /// let summary = js.publish_iter(
+    ///     |line: &&str| format!("orders.{}", line.split(',').next().unwrap()),
+    ///     lines,
+    /// );
+    /// println!("published {} messages", summary.published);
+    /// for (index, err) in &summary.failures {
+    ///     eprintln!("failed to publish line {}: {}", index, err);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_iter<I, F, S>(&self, subject_fn: F, iter: I) -> PublishSummary
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        F: FnMut(&I::Item) -> S,
+        S: AsRef<str>,
+    {
+        self.publish_iter_with_options(subject_fn, iter, &PublishIterOptions::default())
+    }
+
+    /// Publishes the messages of an iterator with the given options, like
+    /// [`JetStream::publish_iter`].
+    pub fn publish_iter_with_options<I, F, S>(
+        &self,
+        mut subject_fn: F,
+        iter: I,
+        options: &PublishIterOptions,
+    ) -> PublishSummary
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        F: FnMut(&I::Item) -> S,
+        S: AsRef<str>,
+    {
+        let mut summary = PublishSummary::default();
+        let max_in_flight = options.max_in_flight.max(1);
+        let (sender, acks) = crossbeam_channel::unbounded();
+        let mut in_flight = 0;
+
+        for (index, message) in iter.into_iter().enumerate() {
+            // Every request completes with its acknowledgement or once its deadline passes.
+            while in_flight >= max_in_flight {
+                if let Ok((index, result)) = acks.recv() {
+                    summary.record(index, result);
+                }
+                in_flight -= 1;
             }
+
+            let headers = options.id_prefix.as_ref().map(|prefix| {
+                let mut headers = HeaderMap::new();
+                headers.insert(header::NATS_MSG_ID, format!("{}{}", prefix, index));
+                headers
+            });
+            let sender = sender.clone();
+            let sent = self.connection.request_with_headers_and_callback(
+                subject_fn(&message).as_ref(),
+                headers.as_ref(),
+                message,
+                Instant::now() + options.timeout,
+                move |result| {
+                    sender.send((index, result)).ok();
+                },
+            );
+            match sent {
+                Ok(()) => in_flight += 1,
+                Err(err) => summary.failures.push((index, err)),
+            }
+        }
+        drop(sender);
+
+        for (index, result) in acks.iter().take(in_flight) {
+            summary.record(index, result);
         }
+        summary.failures.sort_by_key(|(index, _)| *index);
+        summary
     }
 
     /// Create an ephemeral push consumer subscription.
@@ -904,6 +1034,45 @@ impl JetStream {
             }
         };
 
+        let sequence_gap_callback =
+            maybe_options.and_then(|options| options.sequence_gap_callback.clone());
+        let wants_reset_on_sequence_gap =
+            maybe_options.map_or(false, |options| options.reset_on_sequence_gap);
+
+        // Checks specific to sequence gap detection.
+        if sequence_gap_callback.is_some() || wants_reset_on_sequence_gap {
+            // Members of a queue group each receive a share of the sequences.
+            if maybe_queue.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "queue subscription doesn't support sequence gap detection",
+                ));
+            }
+        }
+
+        if let Some(options) = maybe_options.filter(|options| options.reset_on_sequence_gap) {
+            // Only ephemeral consumers can be recreated from another sequence.
+            if options.durable_name.is_some() || options.consumer_name.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "reset on sequence gap requires an ephemeral consumer",
+                ));
+            }
+        }
+
+        if let Some(options) = maybe_options.filter(|options| options.resume_on_reconnect) {
+            // Only ephemeral consumers can be recreated from another sequence.
+            if maybe_queue.is_some()
+                || options.durable_name.is_some()
+                || options.consumer_name.is_some()
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "resume on reconnect requires an ephemeral consumer",
+                ));
+            }
+        }
+
         // Checks specific to ordered consumers.
         if let Some(options) = maybe_options.filter(|options| options.ordered) {
             // Check for queue subscription.
@@ -1251,63 +1420,104 @@ impl JetStream {
         // the subscription can be redirected as needed.
         let shared_sid = Arc::new(AtomicU64::new(0));
 
-        // Create our message preprocessor
-        let preprocessor = {
-            // Sequences for gap detection
-            let sequence_pair = Arc::new(Mutex::new(SequencePair {
-                consumer_seq: 0,
-                stream_seq: 0,
-            }));
-
-            let handle_sequence_mismatch = {
-                // Context used to send replies and make requests.
-                let context = self.clone();
+        // Sequences for gap detection, continuing from the messages an existing consumer
+        // already delivered.
+        let sequence_pair = Arc::new(Mutex::new(
+            maybe_consumer_info
+                .as_ref()
+                .map_or(SequencePair::default(), |consumer_info| {
+                    consumer_info.delivered
+                }),
+        ));
+
+        // Ordered consumers always reset on gaps, other consumers only detect them on request.
+        let reset_on_sequence_gap = is_ordered || wants_reset_on_sequence_gap;
+        let detect_sequence_gaps = reset_on_sequence_gap || sequence_gap_callback.is_some();
+
+        // Resuming after a reconnect needs the last stream sequence received.
+        let resume_on_reconnect =
+            maybe_options.map_or(false, |options| options.resume_on_reconnect);
+        let track_sequences = detect_sequence_gaps || resume_on_reconnect;
+
+        // The name of the consumer, shared so that it can be updated when the consumer is
+        // recreated.
+        let shared_consumer = Arc::new(Mutex::new(String::new()));
+
+        let handle_sequence_mismatch = {
+            // Context used to send replies and make requests.
+            let context = self.clone();
+            let consumer_config = consumer_config.clone();
+            let sequence_pair = sequence_pair.clone();
+            let stream_name = stream_name.clone();
+            let shared_sid = shared_sid.clone();
+            let shared_consumer = shared_consumer.clone();
+
+            move |sid: u64, start_seq: u64| {
+                let stream_name = stream_name.clone();
+                let context = context.clone();
                 let consumer_config = consumer_config.clone();
                 let sequence_pair = sequence_pair.clone();
-                let stream_name = stream_name.clone();
                 let shared_sid = shared_sid.clone();
+                let shared_consumer = shared_consumer.clone();
+
+                // Immediately mute the subscription so that messages no longer get delivered to it.
+                //
+                // If we are already muted then the triggering slipped through and we can
+                // return early as a resubscription will already be on the way.
+                if !context.connection.0.client.mute(sid).unwrap() {
+                    return true;
+                }
 
-                move |sid: u64, start_seq: u64| {
-                    let stream_name = stream_name.clone();
-                    let context = context.clone();
-                    let consumer_config = consumer_config.clone();
-                    let sequence_pair = sequence_pair.clone();
-                    let shared_sid = shared_sid.clone();
-
-                    // Immediately mute the subscription so that messages no longer get delivered to it.
-                    //
-                    // If we are already muted then the triggering slipped through and we can
-                    // return early as a resubscription will already be on the way.
-                    if !context.connection.0.client.mute(sid).unwrap() {
-                        return true;
+                thread::spawn(move || {
+                    // The new consumer starts over, reset before its first message can arrive.
+                    sequence_pair.lock().consumer_seq = 0;
+
+                    let new_deliver_subject = context.connection.new_inbox();
+                    let result = context
+                        .connection
+                        .0
+                        .client
+                        .resubscribe(sid, &new_deliver_subject);
+
+                    if let Ok(new_sid) = result {
+                        shared_sid.store(new_sid, Ordering::Relaxed);
+
+                        let mut consumer_config = consumer_config.clone();
+                        consumer_config.deliver_subject = Some(new_deliver_subject);
+                        consumer_config.deliver_policy = DeliverPolicy::ByStartSeq;
+                        consumer_config.opt_start_seq = Some(start_seq);
+                        if let Ok(consumer_info) =
+                            context.add_consumer(stream_name, consumer_config)
+                        {
+                            *shared_consumer.lock() = consumer_info.name;
+                        }
                     }
+                });
 
-                    thread::spawn(move || {
-                        let new_deliver_subject = context.connection.new_inbox();
-                        let result = context
-                            .connection
-                            .0
-                            .client
-                            .resubscribe(sid, &new_deliver_subject);
-
-                        if let Ok(new_sid) = result {
-                            shared_sid.store(new_sid, Ordering::Relaxed);
-
-                            let mut consumer_config = consumer_config.clone();
-                            consumer_config.deliver_subject = Some(new_deliver_subject);
-                            consumer_config.deliver_policy = DeliverPolicy::ByStartSeq;
-                            consumer_config.opt_start_seq = Some(start_seq);
-                            context.add_consumer(stream_name, consumer_config).ok();
-                        }
+                true
+            }
+        };
 
-                        let mut sequence_info = sequence_pair.lock();
-                        sequence_info.consumer_seq = 0;
-                    });
+        // Create our message preprocessor
+        let preprocessor = {
+            let sequence_pair = sequence_pair.clone();
 
-                    true
+            let report_sequence_gap = {
+                let stream_name = stream_name.clone();
+
+                move |expected: u64, received: u64, last_stream_seq: u64| {
+                    if let Some(callback) = sequence_gap_callback.as_ref() {
+                        (callback.0)(&SequenceGap {
+                            stream: stream_name.clone(),
+                            expected,
+                            received,
+                            last_stream_seq,
+                        });
+                    }
                 }
             };
 
+            let handle_sequence_mismatch = handle_sequence_mismatch.clone();
             let context = self.clone();
 
             move |sid: u64, message: &Message| {
@@ -1331,41 +1541,67 @@ impl JetStream {
                         );
                     }
 
-                    // if it is not an ordered consumer, don't handle sequence mismatch.
-                    if is_ordered {
+                    // if we are not detecting gaps, don't handle sequence mismatch.
+                    if !detect_sequence_gaps {
                         return false;
                     }
 
                     let maybe_consumer_seq = message
                         .headers
                         .as_ref()
-                        .and_then(|headers| headers.get(header::NATS_LAST_CONSUMER));
+                        .and_then(|headers| headers.get(header::NATS_LAST_CONSUMER))
+                        .and_then(|consumer_seq| consumer_seq.parse::<u64>().ok());
 
                     if let Some(consumer_seq) = maybe_consumer_seq {
-                        let consumer_seq = consumer_seq.parse::<u64>().unwrap();
-                        let sequence_info = sequence_pair.lock();
-                        if consumer_seq != sequence_info.consumer_seq {
-                            return handle_sequence_mismatch(sid, sequence_info.stream_seq + 1);
+                        let mut sequence_info = sequence_pair.lock();
+                        let last = *sequence_info;
+                        if consumer_seq != last.consumer_seq && !reset_on_sequence_gap {
+                            // Only report the missing messages once.
+                            sequence_info.consumer_seq = consumer_seq;
+                        }
+                        drop(sequence_info);
+
+                        if consumer_seq != last.consumer_seq {
+                            report_sequence_gap(
+                                last.consumer_seq + 1,
+                                consumer_seq,
+                                last.stream_seq,
+                            );
+                            if reset_on_sequence_gap {
+                                return handle_sequence_mismatch(sid, last.stream_seq + 1);
+                            }
                         }
                     }
 
                     return false;
                 }
 
-                // if it is not an ordered consumer, don't handle sequence mismatch.
-                if !is_ordered {
+                // if we are not tracking sequences, don't handle sequence mismatch.
+                if !track_sequences {
                     return false;
                 }
 
                 // Track messages for sequence mismatches.
                 if let Some(message_info) = message.jetstream_message_info() {
                     let mut sequence_info = sequence_pair.lock();
-                    if message_info.consumer_seq != sequence_info.consumer_seq + 1 {
-                        return handle_sequence_mismatch(sid, sequence_info.stream_seq + 1);
+                    let last = *sequence_info;
+                    let in_order = message_info.consumer_seq == last.consumer_seq + 1;
+                    if in_order || !reset_on_sequence_gap {
+                        sequence_info.stream_seq = message_info.stream_seq;
+                        sequence_info.consumer_seq = message_info.consumer_seq;
                     }
+                    drop(sequence_info);
 
-                    sequence_info.stream_seq = message_info.stream_seq;
-                    sequence_info.consumer_seq = message_info.consumer_seq;
+                    if !in_order && detect_sequence_gaps {
+                        report_sequence_gap(
+                            last.consumer_seq + 1,
+                            message_info.consumer_seq,
+                            last.stream_seq,
+                        );
+                        if reset_on_sequence_gap {
+                            return handle_sequence_mismatch(sid, last.stream_seq + 1);
+                        }
+                    }
                 }
 
                 false
@@ -1449,11 +1685,79 @@ impl JetStream {
         };
 
         shared_sid.store(sid, Ordering::Relaxed);
+        *shared_consumer.lock() = consumer_info.name.clone();
+
+        {
+            let mut sequence_info = sequence_pair.lock();
+            if consumer_ownership == ConsumerOwnership::No {
+                // Consumers bound after failing to create one continue from their delivered
+                // messages.
+                if sequence_info.consumer_seq < consumer_info.delivered.consumer_seq {
+                    *sequence_info = consumer_info.delivered;
+                }
+            } else if sequence_info.stream_seq < consumer_info.delivered.stream_seq {
+                // New consumers report the stream sequence they start after, so that
+                // recreating them before any message arrived starts at the same place.
+                sequence_info.stream_seq = consumer_info.delivered.stream_seq;
+            }
+        }
+
+        // Recreate the consumer if it did not survive a reconnect.
+        let reconnect_listener = if resume_on_reconnect {
+            let context = self.clone();
+            let stream_name = stream_name.clone();
+            let shared_sid = shared_sid.clone();
+            let shared_consumer = shared_consumer.clone();
+            let sequence_pair = sequence_pair.clone();
+
+            Some(
+                self.connection
+                    .0
+                    .client
+                    .add_reconnect_listener(Arc::new(move || {
+                        let context = context.clone();
+                        let stream_name = stream_name.clone();
+                        let shared_sid = shared_sid.clone();
+                        let shared_consumer = shared_consumer.clone();
+                        let sequence_pair = sequence_pair.clone();
+                        let handle_sequence_mismatch = handle_sequence_mismatch.clone();
+
+                        // Requests can't be made from the client thread running listeners.
+                        thread::spawn(move || {
+                            for _ in 0..RESUME_ATTEMPTS {
+                                let consumer = shared_consumer.lock().clone();
+                                let err = match context.consumer_info(&stream_name, consumer) {
+                                    Ok(_) => return,
+                                    Err(err) => err,
+                                };
+
+                                match api_error_code(err) {
+                                    Some(ErrorCode::ConsumerNotFound) => {
+                                        let start_seq = sequence_pair.lock().stream_seq + 1;
+                                        handle_sequence_mismatch(
+                                            shared_sid.load(Ordering::Relaxed),
+                                            start_seq,
+                                        );
+                                        return;
+                                    }
+                                    Some(_) => return,
+                                    // JetStream may not be available yet.
+                                    None => thread::sleep(RESUME_RETRY_INTERVAL),
+                                }
+                            }
+                        });
+                    })),
+            )
+        } else {
+            None
+        };
 
         Ok(PushSubscription::new(
             shared_sid,
             consumer_info,
+            shared_consumer,
             consumer_ownership,
+            reconnect_listener,
             receiver,
             self.clone(),
         ))
@@ -1656,6 +1960,84 @@ impl JetStream {
         Ok(message)
     }
 
+    /// Get the last message from a stream by subject using the direct get API.
+    ///
+    /// Direct gets can be answered by any replica of a stream with `allow_direct` set, or by a
+    /// mirror with `mirror_direct` set, instead of only the stream leader.
+    pub fn direct_get_last_message<S: AsRef<str>>(
+        &self,
+        stream_name: S,
+        stream_subject: &str,
+    ) -> io::Result<StreamMessage> {
+        let stream_name: &str = stream_name.as_ref();
+        if stream_name.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "the stream name must not be empty",
+            ));
+        }
+
+        let subject = format!("{}DIRECT.GET.{}", self.api_prefix(), stream_name);
+        let request = serde_json::ser::to_vec(&StreamMessageGetRequest {
+            seq: None,
+            last_by_subject: Some(stream_subject.to_string()),
+        })?;
+
+        let message =
+            self.connection
+                .request_timeout(&subject, &request, Duration::from_secs(5))?;
+
+        let headers = message.headers.ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                "direct get response missing headers",
+            )
+        })?;
+
+        if let Some(status) = headers.get(header::STATUS) {
+            let code = status.parse::<usize>().unwrap_or_default();
+            let err_code = if code == 404 {
+                ErrorCode::NoMessageFound
+            } else {
+                ErrorCode::BadRequest
+            };
+
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                Error {
+                    code,
+                    err_code,
+                    description: headers.get(header::DESCRIPTION).cloned(),
+                },
+            ));
+        }
+
+        let sequence = headers
+            .get(header::NATS_SEQUENCE)
+            .and_then(|sequence| sequence.parse::<u64>().ok())
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "missing sequence header"))?;
+
+        let time = headers
+            .get(header::NATS_TIME_STAMP)
+            .and_then(|time| {
+                DateTime::parse(time, &time::format_description::well_known::Rfc3339).ok()
+            })
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "missing time stamp header"))?;
+
+        let subject = headers
+            .get(header::NATS_SUBJECT)
+            .cloned()
+            .unwrap_or_else(|| stream_subject.to_string());
+
+        Ok(StreamMessage {
+            subject,
+            sequence,
+            headers: Some(headers),
+            data: message.data,
+            time,
+        })
+    }
+
     /// Delete message in a `JetStream` stream.
     pub fn delete_message<S: AsRef<str>>(
         &self,
@@ -1781,6 +2163,101 @@ impl JetStream {
         self.js_request(&subject, b"")
     }
 
+    /// Returns how far a consumer is behind its stream, from the consumer and stream
+    /// information.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # let js = nats::jetstream::new(nc);
+    /// let lag = js.consumer_lag("orders", "billing")?;
+    /// println!("billing is {} messages behind", lag.lag());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn consumer_lag<S, C>(&self, stream: S, consumer: C) -> io::Result<ConsumerLag>
+    where
+        S: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let info = self.consumer_info(stream.as_ref(), consumer)?;
+        let stream = self.stream_info(stream)?;
+        Ok(ConsumerLag::new(&info, &stream.state))
+    }
+
+    /// Measures the lag of a consumer every interval, reporting it to
+    /// [`Metrics::consumer_lag`](crate::Metrics::consumer_lag) until the returned reporter is
+    /// dropped.
+    ///
+    /// Fails with `InvalidInput` if the connection has no metrics hooks, set with
+    /// [`crate::Options::metrics`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # struct Exporter;
+    /// # impl nats::Metrics for Exporter {}
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .metrics(Exporter)
+    ///     .connect("demo.nats.io")?;
+    /// let js = nats::jetstream::new(nc);
+    /// let _reporter = js.report_consumer_lag("orders", "billing", Duration::from_secs(15))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn report_consumer_lag(
+        &self,
+        stream: &str,
+        consumer: &str,
+        interval: Duration,
+    ) -> io::Result<ConsumerLagReporter> {
+        let metrics = self
+            .connection
+            .0
+            .client
+            .options
+            .metrics
+            .clone()
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "the connection has no metrics hooks",
+                )
+            })?;
+
+        let (stop, stopped) = crossbeam_channel::bounded::<()>(0);
+        let (js, stream, consumer) = (self.clone(), stream.to_string(), consumer.to_string());
+        thread::Builder::new()
+            .name(
+                self.connection
+                    .0
+                    .client
+                    .thread_name("jetstream_consumer_lag"),
+            )
+            .spawn(move || {
+                // Runs until the reporter, holding the only sender, is dropped.
+                while let Err(crossbeam_channel::RecvTimeoutError::Timeout) =
+                    stopped.recv_timeout(interval)
+                {
+                    match js.consumer_lag(&stream, &consumer) {
+                        Ok(lag) => metrics.consumer_lag(&lag),
+                        Err(err) => crate::logging::error!(
+                            "failed to measure the lag of consumer {} on stream {}: {}",
+                            consumer,
+                            stream,
+                            err
+                        ),
+                    }
+                }
+            })
+            .expect("threads should be spawnable");
+
+        Ok(ConsumerLagReporter { _stop: stop })
+    }
+
     /// Query `JetStream` account information.
     pub fn account_info(&self) -> io::Result<AccountInfo> {
         self.js_request(&format!("{}INFO", self.api_prefix()), b"")
@@ -1790,6 +2267,8 @@ impl JetStream {
     where
         Res: DeserializeOwned,
     {
+        span!("jetstream_api", subject = %subject);
+
         let res_msg = self
             .connection
             .request_timeout(subject, req, Duration::from_secs(5))?;
@@ -1797,7 +2276,7 @@ impl JetStream {
         match res {
             ApiResponse::Ok(stream_info) => Ok(stream_info),
             ApiResponse::Err { error, .. } => {
-                log::error!(
+                crate::logging::error!(
                     "failed to parse API response: {:?}",
                     std::str::from_utf8(&res_msg.data)
                 );
@@ -1816,3 +2295,20 @@ impl JetStream {
 pub fn new(nc: Connection) -> JetStream {
     JetStream::new(nc, JetStreamOptions::default())
 }
+
+/// Returns the code of an error returned by the JetStream API, if it is one.
+fn api_error_code(err: io::Error) -> Option<ErrorCode> {
+    err.into_inner()
+        .and_then(|inner| inner.downcast::<Error>().ok())
+        .map(|err| err.error_code())
+}
+
+/// Returns true if a publish failed because the last sequence of the stream or subject was
+/// not the expected one.
+pub(crate) fn is_wrong_last_sequence(err: &io::Error) -> bool {
+    err.get_ref()
+        .and_then(|inner_err| inner_err.downcast_ref::<Error>())
+        .map_or(false, |error| {
+            error.error_code() == ErrorCode::StreamWrongLastSequence
+        })
+}
@@ -12,10 +12,12 @@
 // limitations under the License.
 
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-use crate::jetstream::{ConsumerInfo, ConsumerOwnership, JetStream};
+use crate::jetstream::{AckKind, BatchAck, ConsumerInfo, ConsumerOwnership, JetStream};
 use crate::Message;
 
 use super::{AckPolicy, BatchOptions};
@@ -57,6 +59,12 @@ impl Drop for Inner {
     }
 }
 
+/// How long the pulls of [`PullSubscription::consume`] wait for messages before expiring.
+const CONSUME_EXPIRES: Duration = Duration::from_secs(1);
+
+/// How often [`PullSubscription::consume`] checks whether it was stopped while waiting.
+const CONSUME_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// A `PullSubscription` pulls messages from Server triggered by client actions
 /// Pull Subscription does nothing on itself. It has to explicitly request messages
 /// using one of available
@@ -192,6 +200,38 @@ impl PullSubscription {
         })
     }
 
+    /// Fetches a batch of messages like [`PullSubscription::fetch`], each wrapped in a
+    /// [`ManagedMessage`] negatively acknowledged when dropped unless it was acknowledged, so
+    /// messages left behind by an early return or a panic are redelivered right away instead
+    /// of once their ack wait passes.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// use nats::jetstream::pull_subscription::DropAckPolicy;
+    ///
+    /// let consumer = context.pull_subscribe("jobs")?;
+    /// for mut message in consumer.fetch_managed(10)?.on_drop(DropAckPolicy::Term) {
+    ///     let job = std::str::from_utf8(&message.data)
+    ///         .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    ///     println!("processing job: {}", job);
+    ///     message.ack()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_managed<I: Into<BatchOptions>>(
+        &self,
+        batch: I,
+    ) -> io::Result<ManagedBatchIter<'_>> {
+        Ok(ManagedBatchIter {
+            batch: self.fetch(batch)?,
+            on_drop: DropAckPolicy::Nak,
+        })
+    }
+
     /// High level method that fetches given set of messages, processes them in user-provider
     /// closure and acks them automatically according to `Consumer` `AckPolicy`.
     ///
@@ -436,6 +476,248 @@ impl PullSubscription {
         Iter { subscription: self }
     }
 
+    /// Returns an endless iterator over the messages of the consumer, which pulls the next
+    /// batch once all messages of the previous one were received. A batch that expires
+    /// or finds no messages is pulled again, so prefer `expires` over `no_wait` to avoid
+    /// pulling in a busy loop while the consumer is empty.
+    ///
+    /// The rate messages are consumed at can be limited with [`Messages::rate_limit`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use nats::jetstream::BatchOptions;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// use nats::jetstream::pull_subscription::PullRateLimit;
+    ///
+    /// let consumer = context.pull_subscribe("events")?;
+    /// let batch = BatchOptions {
+    ///     batch: 100,
+    ///     expires: Some(5_000_000_000),
+    ///     no_wait: false,
+    /// };
+    /// for message in consumer.messages(batch).rate_limit(PullRateLimit::Messages(500)) {
+    ///     let message = message?;
+    ///     println!("received message: {:?}", message);
+    ///     message.ack()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn messages<I: Into<BatchOptions>>(&self, batch: I) -> Messages<'_> {
+        Messages {
+            subscription: self,
+            batch: batch.into(),
+            rate_limit: None,
+            remaining: 0,
+            last_request: None,
+            consumed: 0,
+        }
+    }
+
+    /// Handles the messages of the consumer on a pool of worker threads until stopped,
+    /// pulling batches as workers become available, so at most `max_concurrent` messages
+    /// are handled at once and as many are buffered.
+    ///
+    /// With [`ConsumeAckPolicy::Auto`], messages are acknowledged when the handler returns
+    /// `Ok`, and negatively acknowledged for redelivery when it returns an error, unless
+    /// the consumer does not require acknowledgements. Messages still buffered when
+    /// stopped are redelivered once their ack wait expires.
+    ///
+    /// Like [`PushSubscription::with_handler`](crate::jetstream::PushSubscription::with_handler),
+    /// dropping the returned [`ConsumeHandler`] does not stop consuming.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// use nats::jetstream::pull_subscription::ConsumeOptions;
+    ///
+    /// let consumer = context.pull_subscribe("jobs")?;
+    /// let handler = consumer.consume(
+    ///     |message| {
+    ///         println!("processing job: {}", message);
+    ///         Ok(())
+    ///     },
+    ///     ConsumeOptions {
+    ///         max_concurrent: 8,
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    /// # std::thread::sleep(std::time::Duration::from_secs(10));
+    /// handler.stop()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn consume<F>(&self, handler: F, options: ConsumeOptions) -> io::Result<ConsumeHandler>
+    where
+        F: Fn(&Message) -> io::Result<()> + Send + Sync + 'static,
+    {
+        if options.max_concurrent == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "max_concurrent must be at least 1",
+            ));
+        }
+
+        let handler = Arc::new(handler);
+        let stop = Arc::new(AtomicBool::new(false));
+        let name = self.0.context.connection.0.client.thread_name(format_args!(
+            "jetstream_pull_consumer_{}_{}",
+            self.0.info.stream_name, self.0.info.name
+        ));
+
+        // Messages are handed to workers one at a time, so pulling waits for a free worker.
+        let (jobs, pending_jobs) = channel::bounded::<Message>(0);
+        let workers = (0..options.max_concurrent)
+            .map(|worker| {
+                let pending_jobs = pending_jobs.clone();
+                let handler = handler.clone();
+                let ack = options.ack_policy == ConsumeAckPolicy::Auto
+                    && self.0.consumer_ack_policy != AckPolicy::None;
+
+                thread::Builder::new()
+                    .name(format!("{}_worker_{}", name, worker))
+                    .spawn(move || {
+                        for message in pending_jobs.iter() {
+                            let result = handler(&message);
+                            if let Err(err) = result.as_ref() {
+                                crate::logging::error!("Error in callback! {:?}", err);
+                            }
+                            if ack {
+                                let kind = if result.is_ok() {
+                                    AckKind::Ack
+                                } else {
+                                    AckKind::Nak
+                                };
+                                if let Err(err) = message.ack_kind(kind) {
+                                    crate::logging::error!(
+                                        "failed to acknowledge message: {}",
+                                        err
+                                    );
+                                }
+                            }
+                        }
+                    })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let driver = {
+            let subscription = self.clone();
+            let stop = stop.clone();
+            let batch = BatchOptions {
+                batch: options.max_concurrent,
+                expires: Some(CONSUME_EXPIRES.as_nanos() as usize),
+                no_wait: false,
+            };
+
+            thread::Builder::new().name(name).spawn(move || {
+                let result = subscription.drive_consume(batch, &jobs, &stop);
+
+                // Let the workers finish their messages.
+                drop(jobs);
+                for worker in workers {
+                    worker.join().ok();
+                }
+                result
+            })?
+        };
+
+        Ok(ConsumeHandler { stop, driver })
+    }
+
+    /// Pulls batches and hands their messages to the workers of [`PullSubscription::consume`]
+    /// until stopped.
+    fn drive_consume(
+        &self,
+        batch: BatchOptions,
+        jobs: &channel::Sender<Message>,
+        stop: &AtomicBool,
+    ) -> io::Result<()> {
+        // Messages of the last pull request that are yet to be received.
+        let mut remaining = 0;
+        let mut last_request = Instant::now();
+
+        while !stop.load(Ordering::Acquire) {
+            if remaining == 0 {
+                self.request_batch(batch)?;
+                remaining = batch.batch;
+                last_request = Instant::now();
+            }
+
+            match self.0.messages.recv_timeout(CONSUME_POLL_INTERVAL) {
+                Ok(message) if message.is_no_messages() || message.is_request_timeout() => {
+                    // The rest of the batch is not coming, pull again.
+                    remaining = 0;
+                }
+                Ok(message) => {
+                    remaining -= 1;
+                    if jobs.send(message).is_err() {
+                        break;
+                    }
+                }
+                Err(channel::RecvTimeoutError::Timeout) => {
+                    // Pull again if the server never reported the request expiring.
+                    if last_request.elapsed() > CONSUME_EXPIRES * 2 {
+                        remaining = 0;
+                    }
+                }
+                Err(channel::RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "consume: unsubscribed",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a [`BatchAck`] acknowledging the messages of this subscription in batches,
+    /// for consumers with `AckPolicy::All` whose messages are processed concurrently.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// use nats::jetstream::{AckPolicy, ConsumerConfig, PullSubscribeOptions};
+    ///
+    /// context.add_consumer(
+    ///     "batch_ack",
+    ///     ConsumerConfig {
+    ///         durable_name: Some("worker".to_string()),
+    ///         ack_policy: AckPolicy::All,
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    /// let subscription = context.pull_subscribe_with_options(
+    ///     "batch_ack",
+    ///     &PullSubscribeOptions::new().durable_name("worker".to_string()),
+    /// )?;
+    /// let acks = subscription.batch_ack()?;
+    /// for message in subscription.fetch(10)? {
+    ///     acks.track(&message)?;
+    ///     acks.done(&message)?;
+    /// }
+    /// acks.flush()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch_ack(&self) -> io::Result<BatchAck> {
+        if self.0.consumer_ack_policy != AckPolicy::All {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "batch acknowledgements require a consumer with AckPolicy::All",
+            ));
+        }
+
+        Ok(BatchAck::new(self.0.context.connection.clone()))
+    }
+
     /// utility to stop iterators if `no messages` or `request timeout` is encountered.
     fn preprocess(&self, message: Option<Message>) -> Option<Message> {
         if let Some(message) = message {
@@ -451,6 +733,63 @@ impl PullSubscription {
     }
 }
 
+/// When [`PullSubscription::consume`] acknowledges messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsumeAckPolicy {
+    /// Acknowledges messages whose handler returned `Ok`, and negatively acknowledges
+    /// messages whose handler returned an error so they are redelivered.
+    Auto,
+    /// Leaves acknowledging messages to the handler.
+    Manual,
+}
+
+impl Default for ConsumeAckPolicy {
+    fn default() -> ConsumeAckPolicy {
+        ConsumeAckPolicy::Auto
+    }
+}
+
+/// Options of [`PullSubscription::consume`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsumeOptions {
+    /// Number of worker threads, and so the maximum number of messages handled at once.
+    pub max_concurrent: usize,
+    /// When messages are acknowledged.
+    pub ack_policy: ConsumeAckPolicy,
+}
+
+impl Default for ConsumeOptions {
+    fn default() -> ConsumeOptions {
+        ConsumeOptions {
+            max_concurrent: 1,
+            ack_policy: ConsumeAckPolicy::Auto,
+        }
+    }
+}
+
+/// A `ConsumeHandler` may be used to stop the workers of [`PullSubscription::consume`].
+pub struct ConsumeHandler {
+    stop: Arc<AtomicBool>,
+    driver: JoinHandle<io::Result<()>>,
+}
+
+impl ConsumeHandler {
+    /// Stops pulling messages and waits for the workers to finish the messages they are
+    /// handling. Returns the error that stopped consuming early, if any.
+    pub fn stop(self) -> io::Result<()> {
+        self.stop.store(true, Ordering::Release);
+        self.driver
+            .join()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "consume: pulling thread panicked"))?
+    }
+
+    /// Returns true if consuming stopped early because of an error, which
+    /// [`ConsumeHandler::stop`] returns.
+    pub fn is_finished(&self) -> bool {
+        self.driver.is_finished()
+    }
+}
+
 /// Iterator that will endlessly wait for messages, unless `no messages` or `request timeout` is encountered.
 pub struct Iter<'a> {
     subscription: &'a PullSubscription,
@@ -462,6 +801,83 @@ impl<'a> Iterator for Iter<'a> {
     }
 }
 
+/// A limit on the rate a [`Messages`] iterator consumes messages at, enforced between pull
+/// requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullRateLimit {
+    /// Messages per second.
+    Messages(u64),
+    /// Payload bytes per second.
+    Bytes(u64),
+}
+
+/// Endless iterator pulling batches of messages, see [`PullSubscription::messages`].
+pub struct Messages<'a> {
+    subscription: &'a PullSubscription,
+    batch: BatchOptions,
+    rate_limit: Option<PullRateLimit>,
+    /// Messages of the last pull request that are yet to be received.
+    remaining: usize,
+    last_request: Option<Instant>,
+    /// Messages or bytes consumed since the last pull request, depending on the rate limit.
+    consumed: u64,
+}
+
+impl<'a> Messages<'a> {
+    /// Limits the rate messages are consumed at. Before pulling the next batch, the
+    /// iterator waits until the messages consumed since the previous pull are within the
+    /// limit, so bursts are at most one batch.
+    pub fn rate_limit(mut self, limit: PullRateLimit) -> Messages<'a> {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Waits until pulling the next batch keeps the consumption within the rate limit.
+    fn throttle(&self) {
+        if let (Some(limit), Some(last_request)) = (self.rate_limit, self.last_request) {
+            let per_second = match limit {
+                PullRateLimit::Messages(rate) | PullRateLimit::Bytes(rate) => rate.max(1),
+            };
+            let allowed = Duration::from_secs_f64(self.consumed as f64 / per_second as f64);
+            if let Some(wait) = allowed.checked_sub(last_request.elapsed()) {
+                std::thread::sleep(wait);
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = io::Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.remaining == 0 {
+                self.throttle();
+                if let Err(err) = self.subscription.request_batch(self.batch) {
+                    return Some(Err(err));
+                }
+                self.last_request = Some(Instant::now());
+                self.remaining = self.batch.batch;
+                self.consumed = 0;
+            }
+
+            let message = self.subscription.0.messages.recv().ok()?;
+            if message.is_no_messages() || message.is_request_timeout() {
+                // The rest of the batch is not coming, pull again.
+                self.remaining = 0;
+                continue;
+            }
+
+            self.remaining -= 1;
+            self.consumed += match self.rate_limit {
+                Some(PullRateLimit::Bytes(_)) => message.data.len() as u64,
+                _ => 1,
+            };
+            return Some(Ok(message));
+        }
+    }
+}
+
 /// Iterator that retrieves messages unless `no messages` or `request timeout` is encountered, or
 /// timeout is reached.
 pub struct TimeoutIter<'a> {
@@ -495,6 +911,103 @@ impl<'a> Iterator for BatchIter<'a> {
     }
 }
 
+/// How a [`ManagedMessage`] dropped without being acknowledged is acknowledged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropAckPolicy {
+    /// Negatively acknowledges the message, so it is redelivered.
+    Nak,
+    /// Terminates the message, so it is not redelivered.
+    Term,
+}
+
+impl Default for DropAckPolicy {
+    fn default() -> DropAckPolicy {
+        DropAckPolicy::Nak
+    }
+}
+
+/// Iterator over a batch of [`ManagedMessage`]s, see [`PullSubscription::fetch_managed`].
+pub struct ManagedBatchIter<'a> {
+    batch: BatchIter<'a>,
+    on_drop: DropAckPolicy,
+}
+
+impl<'a> ManagedBatchIter<'a> {
+    /// Sets how messages dropped without being acknowledged are acknowledged,
+    /// [`DropAckPolicy::Nak`] by default.
+    pub fn on_drop(mut self, policy: DropAckPolicy) -> ManagedBatchIter<'a> {
+        self.on_drop = policy;
+        self
+    }
+}
+
+impl<'a> Iterator for ManagedBatchIter<'a> {
+    type Item = ManagedMessage;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.batch.next().map(|message| ManagedMessage {
+            message,
+            on_drop: self.on_drop,
+            settled: false,
+        })
+    }
+}
+
+/// A message acknowledged according to its [`DropAckPolicy`] when dropped, unless it was
+/// acknowledged or released with [`ManagedMessage::into_inner`].
+#[derive(Debug)]
+pub struct ManagedMessage {
+    message: Message,
+    on_drop: DropAckPolicy,
+    settled: bool,
+}
+
+impl ManagedMessage {
+    /// Acknowledges the message.
+    pub fn ack(&mut self) -> io::Result<()> {
+        self.ack_kind(AckKind::Ack)
+    }
+
+    /// Acknowledges the message with the given kind. Progress acknowledgements extend the
+    /// ack wait, leaving the message to be acknowledged.
+    pub fn ack_kind(&mut self, ack_kind: AckKind) -> io::Result<()> {
+        self.message.ack_kind(ack_kind)?;
+        if !matches!(ack_kind, AckKind::Progress) {
+            self.settled = true;
+        }
+        Ok(())
+    }
+
+    /// Returns the message, leaving acknowledging it to the caller.
+    pub fn into_inner(mut self) -> Message {
+        self.settled = true;
+        std::mem::take(&mut self.message)
+    }
+}
+
+impl std::ops::Deref for ManagedMessage {
+    type Target = Message;
+
+    fn deref(&self) -> &Message {
+        &self.message
+    }
+}
+
+impl Drop for ManagedMessage {
+    fn drop(&mut self) {
+        if self.settled {
+            return;
+        }
+        let ack_kind = match self.on_drop {
+            DropAckPolicy::Nak => AckKind::Nak,
+            DropAckPolicy::Term => AckKind::Term,
+        };
+        if let Err(err) = self.message.ack_kind(ack_kind) {
+            crate::logging::error!("failed to acknowledge dropped message: {}", err);
+        }
+    }
+}
+
 /// Iterator for handling batches of messages. Works like `TryIter` except stopping after
 /// reading number of messages defined in `batch_size`.
 pub struct TryBatchIter<'a> {
@@ -18,9 +18,11 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use crossbeam_channel as channel;
+use parking_lot::Mutex;
 
-use crate::jetstream::{AckPolicy, ConsumerInfo, ConsumerOwnership, JetStream};
+use crate::jetstream::{AckPolicy, BatchAck, ConsumerInfo, ConsumerOwnership, JetStream};
 use crate::message::Message;
+use crate::shutdown::Drain;
 use crate::DEFAULT_FLUSH_TIMEOUT;
 
 #[derive(Debug)]
@@ -34,8 +36,9 @@ pub(crate) struct Inner {
     /// Name of the stream associated with the subscription.
     pub(crate) stream: String,
 
-    /// Name of the consumer associated with the subscription.
-    pub(crate) consumer: String,
+    /// Name of the consumer associated with the subscription, which changes when the
+    /// consumer is recreated.
+    pub(crate) consumer: Arc<Mutex<String>>,
 
     /// Ack policy used in while processing messages.
     pub(crate) consumer_ack_policy: AckPolicy,
@@ -45,12 +48,23 @@ pub(crate) struct Inner {
     /// Indicates if we own the consumer and are responsible for deleting it or not.
     pub(crate) consumer_ownership: ConsumerOwnership,
 
+    /// Id of the listener recreating the consumer after a reconnect, if any.
+    pub(crate) reconnect_listener: Option<u64>,
+
     /// Client associated with subscription.
     pub(crate) context: JetStream,
 }
 
 impl Drop for Inner {
     fn drop(&mut self) {
+        if let Some(id) = self.reconnect_listener {
+            self.context
+                .connection
+                .0
+                .client
+                .remove_reconnect_listener(id);
+        }
+
         self.context
             .connection
             .0
@@ -61,7 +75,7 @@ impl Drop for Inner {
         // Delete the consumer, if we own it.
         if self.consumer_ownership == ConsumerOwnership::Yes {
             self.context
-                .delete_consumer(&self.stream, &self.consumer)
+                .delete_consumer(&self.stream, self.consumer.lock().clone())
                 .ok();
         }
     }
@@ -77,17 +91,20 @@ impl PushSubscription {
     pub(crate) fn new(
         sid: Arc<AtomicU64>,
         consumer_info: ConsumerInfo,
+        consumer: Arc<Mutex<String>>,
         consumer_ownership: ConsumerOwnership,
+        reconnect_listener: Option<u64>,
         messages: channel::Receiver<Message>,
         context: JetStream,
     ) -> PushSubscription {
         PushSubscription(Arc::new(Inner {
             sid,
             stream: consumer_info.stream_name,
-            consumer: consumer_info.name,
+            consumer,
             consumer_ack_policy: consumer_info.config.ack_policy,
             num_pending: consumer_info.num_pending,
             consumer_ownership,
+            reconnect_listener,
             messages,
             context,
         }))
@@ -337,15 +354,16 @@ impl PushSubscription {
         // dropped it will not unsubscribe from the server.
         let sub = self.clone();
         thread::Builder::new()
-            .name(format!(
-                "nats_jetstream_push_subscriber_{}_{}",
-                self.0.stream, self.0.consumer,
-            ))
+            .name(self.0.context.connection.0.client.thread_name(format_args!(
+                "jetstream_push_subscriber_{}_{}",
+                self.0.stream,
+                self.0.consumer.lock(),
+            )))
             .spawn(move || {
                 for m in sub.iter() {
                     if let Err(e) = handler(m) {
                         // TODO(dlc) - Capture for last error?
-                        log::error!("Error in callback! {:?}", e);
+                        crate::logging::error!("Error in callback! {:?}", e);
                     }
                 }
             })
@@ -389,19 +407,20 @@ impl PushSubscription {
         // dropped it will not unsubscribe from the server.
         let sub = self.clone();
         thread::Builder::new()
-            .name(format!(
-                "nats_push_subscriber_{}_{}",
-                self.0.consumer, self.0.stream
-            ))
+            .name(self.0.context.connection.0.client.thread_name(format_args!(
+                "push_subscriber_{}_{}",
+                self.0.consumer.lock(),
+                self.0.stream
+            )))
             .spawn(move || {
                 for message in sub.iter() {
                     if let Err(err) = handler(&message) {
-                        log::error!("Error in callback! {:?}", err);
+                        crate::logging::error!("Error in callback! {:?}", err);
                     }
 
                     if consumer_ack_policy != AckPolicy::None {
                         if let Err(err) = message.ack() {
-                            log::error!("Error in callback! {:?}", err);
+                            crate::logging::error!("Error in callback! {:?}", err);
                         }
                     }
                 }
@@ -503,7 +522,39 @@ impl PushSubscription {
     pub fn consumer_info(&self) -> io::Result<ConsumerInfo> {
         self.0
             .context
-            .consumer_info(&self.0.stream, &self.0.consumer)
+            .consumer_info(&self.0.stream, self.0.consumer.lock().clone())
+    }
+
+    /// Returns a [`BatchAck`] acknowledging the messages of this subscription in batches,
+    /// for consumers with `AckPolicy::All` whose messages are processed concurrently.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// use nats::jetstream::SubscribeOptions;
+    ///
+    /// let subscription =
+    ///     context.subscribe_with_options("batch_ack", &SubscribeOptions::new().ack_all())?;
+    /// let acks = subscription.batch_ack()?;
+    /// for message in subscription.iter() {
+    ///     acks.track(&message)?;
+    ///     acks.done(&message)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch_ack(&self) -> io::Result<BatchAck> {
+        if self.0.consumer_ack_policy != AckPolicy::All {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "batch acknowledgements require a consumer with AckPolicy::All",
+            ));
+        }
+
+        Ok(BatchAck::new(self.0.context.connection.clone()))
     }
 
     /// Unsubscribe a subscription immediately without draining.
@@ -539,7 +590,7 @@ impl PushSubscription {
         if self.0.consumer_ownership == ConsumerOwnership::Yes {
             self.0
                 .context
-                .delete_consumer(&self.0.stream, &self.0.consumer)
+                .delete_consumer(&self.0.stream, self.0.consumer.lock().clone())
                 .ok();
         }
 
@@ -624,7 +675,7 @@ impl PushSubscription {
         if self.0.consumer_ownership == ConsumerOwnership::Yes {
             self.0
                 .context
-                .delete_consumer(&self.0.stream, &self.0.consumer)
+                .delete_consumer(&self.0.stream, self.0.consumer.lock().clone())
                 .ok();
         }
 
@@ -675,6 +726,26 @@ impl Handler {
     }
 }
 
+impl Drain for PushSubscription {
+    fn drain(&self) -> io::Result<()> {
+        PushSubscription::drain(self)
+    }
+
+    fn is_drained(&self) -> bool {
+        self.0.messages.is_empty()
+    }
+}
+
+impl Drain for Handler {
+    fn drain(&self) -> io::Result<()> {
+        self.subscription.drain()
+    }
+
+    fn is_drained(&self) -> bool {
+        self.subscription.0.messages.is_empty()
+    }
+}
+
 /// A non-blocking iterator over messages from a `PushSubscription`
 pub struct TryIter<'a> {
     subscription: &'a PushSubscription,
@@ -11,7 +11,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use crate::header::HeaderMap;
 use serde::{Deserialize, Serialize};
@@ -22,6 +24,50 @@ use time::serde::rfc3339;
 /// A UTC time
 pub type DateTime = time::OffsetDateTime;
 
+/// A representation of the times reported by the server, which are parsed as [`DateTime`].
+///
+/// Implemented for [`DateTime`], [`SystemTime`], and `chrono::DateTime<chrono::Utc>` with
+/// the `chrono` feature, so the time accessors of the info types return whichever the
+/// application uses.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// # let nc = nats::connect("demo.nats.io")?;
+/// # let js = nats::jetstream::new(nc);
+/// use std::time::SystemTime;
+///
+/// let info = js.stream_info("events")?;
+/// let created: SystemTime = info.created_at();
+/// let idle = SystemTime::now().duration_since(info.state.last_at::<SystemTime>());
+/// # Ok(())
+/// # }
+/// ```
+pub trait FromDateTime {
+    /// Converts a time reported by the server.
+    fn from_date_time(time: DateTime) -> Self;
+}
+
+impl FromDateTime for DateTime {
+    fn from_date_time(time: DateTime) -> DateTime {
+        time
+    }
+}
+
+impl FromDateTime for SystemTime {
+    fn from_date_time(time: DateTime) -> SystemTime {
+        time.into()
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl FromDateTime for chrono::DateTime<chrono::Utc> {
+    fn from_date_time(time: DateTime) -> chrono::DateTime<chrono::Utc> {
+        SystemTime::from(time).into()
+    }
+}
+
 #[derive(Serialize)]
 pub(crate) struct StreamMessageGetRequest {
     #[serde(default, skip_serializing_if = "is_default")]
@@ -359,6 +405,73 @@ pub struct StreamConfig {
     /// Indicates if purges will be denied or not.
     #[serde(default, skip_serializing_if = "is_default")]
     pub deny_purge: bool,
+    /// Maintains a 1:1 mirror of another stream with name matching this property.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mirror: Option<Source>,
+    /// Sources configured for this stream.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub sources: Option<Vec<Source>>,
+    /// Allow direct gets of messages from any replica of the stream.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub allow_direct: bool,
+    /// Allow direct gets of the mirrored stream to be served by this mirror.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mirror_direct: bool,
+    /// Allow messages to carry a per-message TTL in the `Nats-TTL` header.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub allow_msg_ttl: bool,
+    /// When set, a delete marker with this TTL is placed when a subject is removed by limits.
+    #[serde(default, with = "serde_nanos", skip_serializing_if = "is_default")]
+    pub subject_delete_marker_ttl: Duration,
+}
+
+/// A stream which is mirrored or sourced by another stream.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Source {
+    /// Name of the origin stream.
+    pub name: String,
+    /// Optional sequence number from which to start.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub opt_start_seq: Option<u64>,
+    /// Optional time from which to start.
+    #[serde(default, skip_serializing_if = "is_default", with = "rfc3339::option")]
+    pub opt_start_time: Option<DateTime>,
+    /// Optional subject filter for the source stream.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub filter_subject: Option<String>,
+    /// Location of the origin stream when it lives in another account or domain.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub external: Option<External>,
+}
+
+impl Source {
+    /// Creates a source for the stream with the given name in the local domain.
+    pub fn new<S: ToString>(name: S) -> Source {
+        Source {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the origin stream from the given `JetStream` domain.
+    pub fn domain<S: AsRef<str>>(mut self, domain: S) -> Source {
+        self.external = Some(External {
+            api_prefix: format!("$JS.{}.API", domain.as_ref()),
+            deliver_prefix: None,
+        });
+        self
+    }
+}
+
+/// Describes where to find a stream that lives outside the local account or domain.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct External {
+    /// Subject prefix used to reach the remote `JetStream` API.
+    #[serde(rename = "api")]
+    pub api_prefix: String,
+    /// Subject prefix on which messages are delivered from the remote stream.
+    #[serde(default, rename = "deliver", skip_serializing_if = "is_default")]
+    pub deliver_prefix: Option<String>,
 }
 
 fn is_default<T: Default + Eq>(t: &T) -> bool {
@@ -395,6 +508,13 @@ pub struct StreamInfo {
     pub cluster: ClusterInfo,
 }
 
+impl StreamInfo {
+    /// The time that this stream was created
+    pub fn created_at<T: FromDateTime>(&self) -> T {
+        T::from_date_time(self.created)
+    }
+}
+
 /// Information about a received message
 #[derive(Debug, Clone)]
 pub struct JetStreamMessageInfo<'a> {
@@ -441,6 +561,18 @@ pub struct StreamState {
     pub consumer_count: usize,
 }
 
+impl StreamState {
+    /// The time associated with the oldest message still present in this stream
+    pub fn first_at<T: FromDateTime>(&self) -> T {
+        T::from_date_time(self.first_ts)
+    }
+
+    /// The time that the last message was received by this stream
+    pub fn last_at<T: FromDateTime>(&self) -> T {
+        T::from_date_time(self.last_ts)
+    }
+}
+
 /// `DeliverPolicy` determines how the consumer should select the first message to deliver.
 #[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -682,6 +814,50 @@ pub struct ConsumerInfo {
     pub push_bound: bool,
 }
 
+impl ConsumerInfo {
+    /// The time the consumer was created
+    pub fn created_at<T: FromDateTime>(&self) -> T {
+        T::from_date_time(self.created)
+    }
+}
+
+/// How far a consumer is behind its stream, returned by
+/// [`JetStream::consumer_lag`](crate::jetstream::JetStream::consumer_lag).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConsumerLag {
+    /// The stream being consumed
+    pub stream: String,
+    /// The consumer's name
+    pub consumer: String,
+    /// The number of messages matching the consumer not yet delivered
+    pub num_pending: u64,
+    /// The number of messages delivered but not yet acknowledged
+    pub num_ack_pending: u64,
+    /// The last sequence number assigned to a message in the stream
+    pub stream_last_seq: u64,
+    /// The stream sequence below which every message was acknowledged
+    pub ack_floor_stream_seq: u64,
+}
+
+impl ConsumerLag {
+    pub(crate) fn new(info: &ConsumerInfo, state: &StreamState) -> ConsumerLag {
+        ConsumerLag {
+            stream: info.stream_name.clone(),
+            consumer: info.name.clone(),
+            num_pending: info.num_pending,
+            num_ack_pending: info.num_ack_pending as u64,
+            stream_last_seq: state.last_seq,
+            ack_floor_stream_seq: info.ack_floor.stream_seq,
+        }
+    }
+
+    /// The number of messages not yet processed by the consumer, either not delivered or
+    /// not acknowledged.
+    pub fn lag(&self) -> u64 {
+        self.num_pending + self.num_ack_pending
+    }
+}
+
 /// Information about the stream's, consumer's associated `JetStream` cluster
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct ClusterInfo {
@@ -720,6 +896,17 @@ pub struct SequencePair {
     pub consumer_seq: u64,
     /// The aggregate for all stream consumers
     pub stream_seq: u64,
+    /// The last time a message was delivered or acknowledged, reported by servers 2.10
+    /// and later
+    #[serde(default, skip_serializing_if = "is_default", with = "rfc3339::option")]
+    pub last_active: Option<DateTime>,
+}
+
+impl SequencePair {
+    /// The last time a message was delivered or acknowledged, if reported by the server
+    pub fn last_active_at<T: FromDateTime>(&self) -> Option<T> {
+        self.last_active.map(T::from_date_time)
+    }
 }
 
 /// Used for next Pull Request for Pull Consumer
@@ -816,6 +1003,13 @@ pub struct SubscribeOptions {
     pub(crate) flow_control: Option<bool>,
     pub(crate) rate_limit: Option<u64>,
     pub(crate) headers_only: Option<bool>,
+
+    // For sequence gap detection:
+    pub(crate) sequence_gap_callback: Option<SequenceGapCallback>,
+    pub(crate) reset_on_sequence_gap: bool,
+
+    // For resuming after a reconnect:
+    pub(crate) resume_on_reconnect: bool,
 }
 
 impl SubscribeOptions {
@@ -988,6 +1182,58 @@ impl SubscribeOptions {
         self.idle_heartbeat = Some(interval);
         self
     }
+
+    /// Calls `callback` when the consumer sequence of a delivered message does not follow
+    /// the previous one, or an idle heartbeat reports messages that were never received,
+    /// for instance after the consumer was recreated. Not supported by queue subscriptions,
+    /// whose members each receive a share of the sequences.
+    pub fn on_sequence_gap<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&SequenceGap) + Send + Sync + 'static,
+    {
+        self.sequence_gap_callback = Some(SequenceGapCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Recreates the consumer from the stream sequence following the last message received
+    /// in order when a sequence gap is detected, like ordered subscriptions do. Only
+    /// supported by subscriptions creating an ephemeral consumer.
+    pub fn reset_on_sequence_gap(mut self) -> Self {
+        self.reset_on_sequence_gap = true;
+        self
+    }
+
+    /// Recreates the consumer after a reconnect if the server no longer knows it, for
+    /// instance after a restart, starting from the stream sequence following the last
+    /// message received. Messages received but not yet acknowledged are not redelivered.
+    /// Only supported by subscriptions creating an ephemeral consumer.
+    pub fn resume_on_reconnect(mut self) -> Self {
+        self.resume_on_reconnect = true;
+        self
+    }
+}
+
+/// A gap in the consumer sequences delivered to a push subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceGap {
+    /// Name of the stream of the consumer.
+    pub stream: String,
+    /// The consumer sequence expected next.
+    pub expected: u64,
+    /// The consumer sequence of the delivered message, or the last consumer sequence
+    /// reported by an idle heartbeat.
+    pub received: u64,
+    /// The stream sequence of the last message received in order.
+    pub last_stream_seq: u64,
+}
+
+#[derive(Clone)]
+pub(crate) struct SequenceGapCallback(pub(crate) Arc<dyn Fn(&SequenceGap) + Send + Sync>);
+
+impl fmt::Debug for SequenceGapCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map().entry(&"callback", &"set").finish()
+    }
 }
 
 /// Options for publishing
@@ -1007,6 +1253,41 @@ pub struct PublishOptions {
     pub expected_last_subject_sequence: Option<u64>,
 }
 
+/// Options for publishing the messages of an iterator with
+/// [`JetStream::publish_iter_with_options`](crate::jetstream::JetStream::publish_iter_with_options).
+#[derive(Debug, Clone)]
+pub struct PublishIterOptions {
+    /// Maximum number of messages published but not yet acknowledged.
+    pub max_in_flight: usize,
+    /// Duration to wait for the acknowledgement of each message before timing out.
+    pub timeout: Duration,
+    /// Prefix of the message ids, followed by the index of each message, so publishing the
+    /// same messages again, for instance when resuming an interrupted backfill, is
+    /// deduplicated by the stream.
+    pub id_prefix: Option<String>,
+}
+
+impl Default for PublishIterOptions {
+    fn default() -> PublishIterOptions {
+        PublishIterOptions {
+            max_in_flight: 256,
+            timeout: Duration::from_secs(5),
+            id_prefix: None,
+        }
+    }
+}
+
+/// The outcome of publishing the messages of an iterator.
+#[derive(Debug, Default)]
+pub struct PublishSummary {
+    /// Number of messages stored by the stream.
+    pub published: usize,
+    /// Number of messages acknowledged as duplicates, and not stored again.
+    pub duplicates: usize,
+    /// Index and error of each message that failed to be published, in order.
+    pub failures: Vec<(usize, io::Error)>,
+}
+
 /// contains info about the `JetStream` usage from the current account.
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct AccountInfo {
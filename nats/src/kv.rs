@@ -14,17 +14,30 @@
 //! Support for Key Value Store.
 //! This feature is experimental and the API may change.
 
+use std::collections::HashMap;
+use std::fmt;
 use std::io;
-use std::time::Duration;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use parking_lot::{Mutex, RwLock};
+
+use crate::client::Client;
+use crate::encryption::Encryption;
 use crate::header::{self, HeaderMap};
 use crate::jetstream::{
-    DateTime, DiscardPolicy, Error, ErrorCode, JetStream, PushSubscription, StorageType,
-    StreamConfig, StreamInfo, StreamMessage, SubscribeOptions,
+    is_wrong_last_sequence, DateTime, DiscardPolicy, Error, ErrorCode, JetStream, PushSubscription,
+    Source, StorageType, StreamConfig, StreamInfo, StreamMessage, SubscribeOptions,
 };
 use crate::message::Message;
+use crate::typed::{self, Json};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
 /// Configuration values for key value stores.
 #[derive(Debug, Default)]
@@ -45,15 +58,27 @@ pub struct Config {
     pub storage: StorageType,
     /// How many replicas to keep for each entry in a cluster.
     pub num_replicas: usize,
+    /// Mirror another bucket, possibly in another domain. The bucket name is used as the
+    /// source name and does not need the `KV_` prefix.
+    pub mirror: Option<Source>,
+    /// Source entries from other buckets into this bucket.
+    pub sources: Option<Vec<Source>>,
+    /// Enables per-key TTLs and places delete markers with this TTL when entries expire,
+    /// requires at least server version 2.11.0.
+    pub limit_markers: Option<Duration>,
 }
 
 const MAX_HISTORY: i64 = 64;
 const ALL_KEYS: &str = ">";
 
+const KV_STREAM_PREFIX: &str = "KV_";
+
 const KV_OPERATION: &str = "KV-Operation";
 const KV_OPERATION_DELETE: &str = "DEL";
 const KV_OPERATION_PURGE: &str = "PURGE";
 
+const MARKER_REASON_REMOVE: &str = "Remove";
+
 const NATS_ROLLUP: &str = "Nats-Rollup";
 const ROLLUP_SUBJECT: &str = "sub";
 
@@ -78,6 +103,14 @@ fn kv_operation_from_maybe_headers(maybe_headers: Option<&HeaderMap>) -> Operati
                 _ => Operation::Put,
             };
         }
+
+        // Markers placed by the server when limits remove a subject.
+        if let Some(reason) = headers.get(header::NATS_MARKER_REASON) {
+            return match reason.as_str() {
+                MARKER_REASON_REMOVE => Operation::Delete,
+                _ => Operation::Purge,
+            };
+        }
     }
 
     Operation::Put
@@ -92,6 +125,15 @@ lazy_static! {
     static ref VALID_KEY_RE: Regex = Regex::new(r#"\A[-/_=\.a-zA-Z0-9]+\z"#).unwrap();
 }
 
+// Source and mirror configuration refers to buckets, which are backed by streams with a prefix.
+fn bucket_source(source: &Source) -> Source {
+    let mut source = source.clone();
+    if !source.name.starts_with(KV_STREAM_PREFIX) {
+        source.name = format!("{}{}", KV_STREAM_PREFIX, source.name);
+    }
+    source
+}
+
 fn is_valid_bucket_name(bucket_name: &str) -> bool {
     VALID_BUCKET_RE.is_match(bucket_name)
 }
@@ -153,16 +195,54 @@ impl JetStream {
             ));
         }
 
-        Ok(Store {
-            name: bucket.to_string(),
-            stream_name,
-            prefix: format!("$KV.{bucket}."),
-            context: self.clone(),
-            domain_prefix: self
-                .options
-                .has_domain
-                .then(|| self.options.api_prefix.clone()),
-        })
+        Ok(self.bind_store(bucket, stream_info))
+    }
+
+    // Builds a store handle, resolving the origin bucket when the backing stream is a mirror.
+    fn bind_store(&self, bucket: &str, stream_info: StreamInfo) -> Store {
+        let direct = stream_info.config.allow_direct || stream_info.config.mirror_direct;
+        let allow_msg_ttl = stream_info.config.allow_msg_ttl;
+        let domain_prefix = self
+            .options
+            .has_domain
+            .then(|| self.options.api_prefix.clone());
+
+        match stream_info.config.mirror {
+            Some(mirror) => {
+                let origin = mirror
+                    .name
+                    .strip_prefix(KV_STREAM_PREFIX)
+                    .unwrap_or(&mirror.name)
+                    .to_string();
+
+                let prefix = format!("$KV.{origin}.");
+                let put_prefix = match mirror.external {
+                    Some(external) => format!("{}.{}", external.api_prefix, prefix),
+                    None => prefix.clone(),
+                };
+
+                Store {
+                    name: bucket.to_string(),
+                    stream_name: stream_info.config.name,
+                    prefix,
+                    put_prefix: Some(put_prefix),
+                    context: self.clone(),
+                    domain_prefix,
+                    direct,
+                    allow_msg_ttl,
+                }
+            }
+            None => Store {
+                name: bucket.to_string(),
+                stream_name: stream_info.config.name,
+                prefix: format!("$KV.{bucket}."),
+                put_prefix: None,
+                context: self.clone(),
+                domain_prefix,
+                direct,
+                allow_msg_ttl,
+            },
+        }
     }
 
     /// Create a new key-value store bucket.
@@ -226,10 +306,42 @@ impl JetStream {
             config.num_replicas
         };
 
+        let allow_direct = self.connection.is_server_compatible_version(2, 9, 0);
+
+        let subject_delete_marker_ttl = match config.limit_markers {
+            Some(ttl) => {
+                if !self.connection.is_server_compatible_version(2, 11, 0) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "limit markers require at least server version 2.11.0",
+                    ));
+                }
+                ttl
+            }
+            None => Duration::default(),
+        };
+
+        // A mirror receives its subjects from the origin bucket and serves direct gets for it.
+        let (subjects, mirror, mirror_direct) = match config.mirror.as_ref() {
+            Some(mirror) => (Vec::new(), Some(bucket_source(mirror)), allow_direct),
+            None => (vec![format!("$KV.{}.>", config.bucket)], None, false),
+        };
+
+        let sources = config
+            .sources
+            .as_ref()
+            .map(|sources| sources.iter().map(bucket_source).collect());
+
         let stream_info = self.add_stream(&StreamConfig {
             name: format!("KV_{}", config.bucket),
             description: Some(config.description.to_string()),
-            subjects: vec![format!("$KV.{}.>", config.bucket)],
+            subjects,
+            mirror,
+            sources,
+            allow_direct,
+            mirror_direct,
+            allow_msg_ttl: config.limit_markers.is_some(),
+            subject_delete_marker_ttl,
             max_msgs_per_subject: history,
             max_bytes: config.max_bytes,
             max_age: config.max_age,
@@ -242,16 +354,7 @@ impl JetStream {
             ..Default::default()
         })?;
 
-        Ok(Store {
-            name: config.bucket.to_string(),
-            stream_name: stream_info.config.name,
-            prefix: format!("$KV.{}.", config.bucket),
-            context: self.clone(),
-            domain_prefix: self
-                .options
-                .has_domain
-                .then(|| self.options.api_prefix.clone()),
-        })
+        Ok(self.bind_store(&config.bucket, stream_info))
     }
 
     /// Delete the specified key value store bucket.
@@ -320,6 +423,12 @@ pub struct Store {
     prefix: String,
     context: JetStream,
     domain_prefix: Option<String>,
+    // Prefix used for writes when the bucket is a mirror of another bucket.
+    put_prefix: Option<String>,
+    // Whether reads can use the direct get API.
+    direct: bool,
+    // Whether the stream expires messages with a TTL, used for the leases of locks.
+    allow_msg_ttl: bool,
 }
 
 impl Store {
@@ -366,7 +475,14 @@ impl Store {
         subject.push_str(&self.prefix);
         subject.push_str(key);
 
-        match self.context.get_last_message(&self.stream_name, &subject) {
+        let result = if self.direct {
+            self.context
+                .direct_get_last_message(&self.stream_name, &subject)
+        } else {
+            self.context.get_last_message(&self.stream_name, &subject)
+        };
+
+        match result {
             Ok(message) => {
                 let operation = kv_operation_from_stream_message(&message);
                 let entry = Entry {
@@ -457,18 +573,68 @@ impl Store {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
         }
 
-        let mut subject = String::new();
-        if let Some(api_prefix) = self.domain_prefix.as_ref() {
-            subject.push_str(api_prefix);
-        }
-        subject.push_str(&self.prefix);
-        subject.push_str(key);
+        let subject = self.put_subject(key);
 
         let publish_ack = self.context.publish(&subject, value)?;
 
         Ok(publish_ack.sequence)
     }
 
+    /// Places the new value for the key into the bucket, the entry is removed by the server
+    /// once the `ttl` has elapsed.
+    ///
+    /// The bucket must have been created with `limit_markers` set and the TTL has a
+    /// granularity of one second.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use nats::kv::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # let bucket = context.create_key_value(&Config {
+    /// #  bucket: "put_with_ttl".to_string(),
+    /// #  limit_markers: Some(Duration::from_secs(60)),
+    /// #  ..Default::default()
+    /// # })?;
+    /// #
+    /// bucket.put_with_ttl("session", b"token", Duration::from_secs(30))?;
+    /// # context.delete_key_value("put_with_ttl")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn put_with_ttl(
+        &self,
+        key: &str,
+        value: impl AsRef<[u8]>,
+        ttl: Duration,
+    ) -> io::Result<u64> {
+        if !is_valid_key(key) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
+        }
+
+        if ttl.as_secs() < 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ttl must be at least one second",
+            ));
+        }
+
+        let subject = self.put_subject(key);
+
+        let mut headers = HeaderMap::default();
+        headers.insert(header::NATS_TTL, format!("{}s", ttl.as_secs()));
+
+        let message = Message::new(&subject, None, value, Some(headers));
+        let publish_ack = self.context.publish_message(&message)?;
+
+        Ok(publish_ack.sequence)
+    }
+
     /// Creates the key/value pair if it does not exist or is marked for deletion.
     ///
     /// # Examples
@@ -534,12 +700,7 @@ impl Store {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
         }
 
-        let mut subject = String::new();
-        if let Some(api_prefix) = self.domain_prefix.as_ref() {
-            subject.push_str(api_prefix);
-        }
-        subject.push_str(&self.prefix);
-        subject.push_str(key);
+        let subject = self.put_subject(key);
 
         let mut headers = HeaderMap::default();
         headers.insert(
@@ -581,12 +742,7 @@ impl Store {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
         }
 
-        let mut subject = String::new();
-        if let Some(api_prefix) = self.domain_prefix.as_ref() {
-            subject.push_str(api_prefix);
-        }
-        subject.push_str(&self.prefix);
-        subject.push_str(key);
+        let subject = self.put_subject(key);
 
         let mut headers = HeaderMap::default();
         headers.insert(KV_OPERATION, KV_OPERATION_DELETE.to_string());
@@ -624,9 +780,7 @@ impl Store {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
         }
 
-        let mut subject = String::new();
-        subject.push_str(&self.prefix);
-        subject.push_str(key);
+        let subject = self.put_subject(key);
 
         let mut headers = HeaderMap::default();
         headers.insert(KV_OPERATION, KV_OPERATION_PURGE.to_string());
@@ -766,6 +920,338 @@ impl Store {
     pub fn bucket(&self) -> &String {
         &self.name
     }
+
+    // Subject that writes for the key are published to.
+    fn put_subject(&self, key: &str) -> String {
+        let mut subject = String::new();
+        match self.put_prefix.as_ref() {
+            Some(put_prefix) => subject.push_str(put_prefix),
+            None => {
+                if let Some(api_prefix) = self.domain_prefix.as_ref() {
+                    subject.push_str(api_prefix);
+                }
+                subject.push_str(&self.prefix);
+            }
+        }
+        subject.push_str(key);
+        subject
+    }
+
+    /// Attempts to acquire the lock stored under `key` for the given lease.
+    ///
+    /// Returns `None` when the lock is held by someone else and their lease has not yet
+    /// expired. When the bucket was created with `limit_markers`, the lease is also enforced
+    /// by the server as a per-key TTL, so abandoned locks are removed and observed as released
+    /// by [`Store::watch_lock`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use nats::kv::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # let bucket = context.create_key_value(&Config {
+    /// #  bucket: "locks".to_string(),
+    /// #  ..Default::default()
+    /// # })?;
+    /// #
+    /// if let Some(mut lock) = bucket.acquire("leader", Duration::from_secs(10))? {
+    ///     lock.renew()?;
+    ///     lock.release()?;
+    /// }
+    /// # context.delete_key_value("locks")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn acquire(&self, key: &str, lease: Duration) -> io::Result<Option<Lock>> {
+        let owner = self.context.connection.0.client.next_id();
+        let revision = match self.entry(key)? {
+            Some(entry) if entry.operation == Operation::Put => {
+                if !LockValue::decode(&entry.value)?.is_expired(entry.created) {
+                    return Ok(None);
+                }
+                entry.revision
+            }
+            Some(entry) => entry.revision,
+            None => 0,
+        };
+
+        let mut lock = Lock {
+            store: self.clone(),
+            key: key.to_string(),
+            owner,
+            lease,
+            revision,
+            ttl: self.allow_msg_ttl,
+        };
+
+        match lock.publish(None) {
+            Ok(()) => Ok(Some(lock)),
+            Err(err) if is_wrong_last_sequence(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns an iterator over changes in ownership of the lock stored under `key`.
+    pub fn watch_lock(&self, key: &str) -> io::Result<LockWatch> {
+        Ok(LockWatch {
+            watch: self.watch(key)?,
+            owner: None,
+        })
+    }
+
+    /// Returns a read cache of the bucket, holding the latest value of every key in memory
+    /// and kept up to date by a watcher, see [`CachedKeyValue`].
+    ///
+    /// Blocks until the current values are loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// let config = context.key_value("config")?.cached()?;
+    /// config.on_staleness_change(|stale| {
+    ///     if stale {
+    ///         eprintln!("serving configuration from a stale cache");
+    ///     }
+    /// });
+    ///
+    /// let limit = config.get("rate_limit");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cached(&self) -> io::Result<CachedKeyValue> {
+        CachedKeyValue::new(self.clone())
+    }
+
+    /// Returns a view of this bucket which encodes and decodes values as `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use nats::kv::Config;
+    /// # use serde::{Deserialize, Serialize};
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Profile {
+    ///     name: String,
+    /// }
+    ///
+    /// let bucket = context.create_key_value(&Config {
+    ///     bucket: "profiles".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let profiles = bucket.typed::<Profile>();
+    /// profiles.put("derek", &Profile { name: "Derek".to_string() })?;
+    /// let profile = profiles.get("derek")?;
+    /// #
+    /// # context.delete_key_value("profiles")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn typed<T>(&self) -> TypedKeyValue<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        TypedKeyValue::new(self.clone())
+    }
+
+    /// Returns a view of the bucket whose values are encoded with the [`typed::Codec`] `C`.
+    pub fn typed_with_codec<T, C>(&self) -> TypedKeyValue<T, C>
+    where
+        C: typed::Encode<T> + typed::Decode<T>,
+    {
+        TypedKeyValue::new(self.clone())
+    }
+}
+
+/// A key-value bucket whose values are encoded with a [`typed::Codec`], JSON by default.
+///
+/// Encoding and decoding failures are reported as `io::ErrorKind::InvalidData` with the
+/// error of the codec as the inner error.
+#[derive(Debug)]
+pub struct TypedKeyValue<T, C = Json> {
+    store: Store,
+    encryption: Option<Encryption>,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T, C> Clone for TypedKeyValue<T, C> {
+    fn clone(&self) -> Self {
+        TypedKeyValue {
+            store: self.store.clone(),
+            encryption: self.encryption.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, C> TypedKeyValue<T, C>
+where
+    C: typed::Encode<T> + typed::Decode<T>,
+{
+    /// Wraps an existing bucket.
+    pub fn new(store: Store) -> TypedKeyValue<T, C> {
+        TypedKeyValue {
+            store,
+            encryption: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Encrypts values before placing them into the bucket, and decrypts them on retrieval.
+    /// Values that are not encrypted fail to decode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> TypedKeyValue<T, C> {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Returns the underlying untyped bucket.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Returns the latest entry for the key, if any.
+    pub fn entry(&self, key: &str) -> io::Result<Option<TypedEntry<T>>> {
+        self.store
+            .entry(key)?
+            .map(|entry| TypedEntry::from_entry::<C>(entry, self.encryption.as_ref()))
+            .transpose()
+    }
+
+    /// Returns the latest value for the key, if any.
+    pub fn get(&self, key: &str) -> io::Result<Option<T>> {
+        match self.store.get(key)? {
+            Some(value) => decode::<T, C>(&value, self.encryption.as_ref()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Places the new value for the key into the bucket.
+    pub fn put(&self, key: &str, value: &T) -> io::Result<u64> {
+        self.store.put(key, self.encode(value)?)
+    }
+
+    /// Creates the key/value pair if it does not exist or is marked for deletion.
+    pub fn create(&self, key: &str, value: &T) -> io::Result<u64> {
+        self.store.create(key, self.encode(value)?)
+    }
+
+    /// Updates the value if the latest revision matches.
+    pub fn update(&self, key: &str, value: &T, revision: u64) -> io::Result<u64> {
+        self.store.update(key, self.encode(value)?, revision)
+    }
+
+    /// Marks an entry as deleted by placing a delete marker but leaves the revision history intact.
+    pub fn delete(&self, key: &str) -> io::Result<()> {
+        self.store.delete(key)
+    }
+
+    /// Remove any entries associated with the key and all historical revisions.
+    pub fn purge(&self, key: &str) -> io::Result<()> {
+        self.store.purge(key)
+    }
+
+    /// Returns an iterator which iterates over each entry for specific key pattern as they happen.
+    pub fn watch<K: AsRef<str>>(&self, key: K) -> io::Result<TypedWatch<T, C>> {
+        Ok(TypedWatch {
+            watch: self.store.watch(key)?,
+            encryption: self.encryption.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns an iterator which iterates over each entry as they happen.
+    pub fn watch_all(&self) -> io::Result<TypedWatch<T, C>> {
+        self.watch(ALL_KEYS)
+    }
+
+    fn encode(&self, value: &T) -> io::Result<Vec<u8>> {
+        let value = C::encode(value)?;
+        match self.encryption.as_ref() {
+            Some(encryption) => encryption.encrypt(&value),
+            None => Ok(value),
+        }
+    }
+}
+
+/// Decodes a value, decrypting it first if the bucket is encrypted.
+fn decode<T, C: typed::Decode<T>>(value: &[u8], encryption: Option<&Encryption>) -> io::Result<T> {
+    match encryption {
+        Some(encryption) => C::decode(&encryption.decrypt(value)?),
+        None => C::decode(value),
+    }
+}
+
+/// An entry in a typed key-value bucket.
+#[derive(Debug, Clone)]
+pub struct TypedEntry<T> {
+    /// Name of the bucket the entry is in.
+    pub bucket: String,
+    /// The key that was retrieved.
+    pub key: String,
+    /// The decoded value, `None` for delete and purge markers.
+    pub value: Option<T>,
+    /// A unique sequence for this value.
+    pub revision: u64,
+    /// Distance from the latest value.
+    pub delta: u64,
+    /// The time the data was put in the bucket.
+    pub created: DateTime,
+    /// The kind of operation that caused this entry.
+    pub operation: Operation,
+}
+
+impl<T> TypedEntry<T> {
+    fn from_entry<C: typed::Decode<T>>(
+        entry: Entry,
+        encryption: Option<&Encryption>,
+    ) -> io::Result<TypedEntry<T>> {
+        let value = match entry.operation {
+            Operation::Put => Some(decode::<T, C>(&entry.value, encryption)?),
+            _ => None,
+        };
+
+        Ok(TypedEntry {
+            bucket: entry.bucket,
+            key: entry.key,
+            value,
+            revision: entry.revision,
+            delta: entry.delta,
+            created: entry.created,
+            operation: entry.operation,
+        })
+    }
+}
+
+/// An iterator used to watch changes in a typed bucket.
+///
+/// Values that fail to decode are yielded as errors without terminating the iterator.
+pub struct TypedWatch<T, C = Json> {
+    watch: Watch,
+    encryption: Option<Encryption>,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T, C: typed::Decode<T>> Iterator for TypedWatch<T, C> {
+    type Item = io::Result<TypedEntry<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let encryption = self.encryption.as_ref();
+        self.watch
+            .next()
+            .map(|entry| TypedEntry::from_entry::<C>(entry, encryption))
+    }
 }
 
 /// An iterator used to iterate through the keys of a bucket.
@@ -864,6 +1350,171 @@ impl Iterator for History {
     }
 }
 
+/// How long creating a [`CachedKeyValue`] waits at most for the current values.
+const CACHE_LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the watcher of a [`CachedKeyValue`] checks the connection while idle.
+const CACHE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+type StalenessCallback = Box<dyn Fn(bool) + Send + Sync>;
+
+struct Cache {
+    store: Store,
+    entries: RwLock<HashMap<String, Entry>>,
+    revision: AtomicU64,
+    stale: AtomicBool,
+    on_staleness_change: Mutex<Option<StalenessCallback>>,
+}
+
+impl Cache {
+    fn apply(&self, entry: Entry) {
+        self.revision.fetch_max(entry.revision, Ordering::AcqRel);
+        let mut entries = self.entries.write();
+        match entry.operation {
+            Operation::Put => {
+                entries.insert(entry.key.clone(), entry);
+            }
+            Operation::Delete | Operation::Purge => {
+                entries.remove(&entry.key);
+            }
+        }
+    }
+
+    fn set_stale(&self, stale: bool) {
+        if self.stale.swap(stale, Ordering::AcqRel) != stale {
+            if let Some(callback) = self.on_staleness_change.lock().as_ref() {
+                callback(stale);
+            }
+        }
+    }
+}
+
+/// A read cache of a key-value bucket, created by [`Store::cached`].
+///
+/// The latest value of every key is held in memory, so reads don't make a request to the
+/// server. A watcher applies changes as they happen, in the order of their revisions.
+/// The cache is stale while the connection is lost, as changes may be missed until it is
+/// reestablished, and for good once the watcher stops, for instance if the bucket is
+/// deleted. Reads keep returning the last values seen meanwhile.
+///
+/// Clones share the same cache, whose watcher stops once every clone is dropped.
+#[derive(Clone)]
+pub struct CachedKeyValue(Arc<Cache>);
+
+impl fmt::Debug for CachedKeyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedKeyValue")
+            .field("bucket", &self.0.store.name)
+            .field("revision", &self.revision())
+            .field("stale", &self.is_stale())
+            .finish()
+    }
+}
+
+impl CachedKeyValue {
+    fn new(store: Store) -> io::Result<CachedKeyValue> {
+        let watch = store.watch_all()?;
+        let cache = Arc::new(Cache {
+            store,
+            entries: RwLock::new(HashMap::new()),
+            revision: AtomicU64::new(0),
+            stale: AtomicBool::new(false),
+            on_staleness_change: Mutex::new(None),
+        });
+
+        // Load the current values, delivered first and ending with no more pending.
+        let info = watch.subscription.consumer_info()?;
+        let mut loaded = info.num_pending == 0 && info.delivered.consumer_seq == 0;
+        let deadline = Instant::now() + CACHE_LOAD_TIMEOUT;
+        while !loaded {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let message = watch.subscription.next_timeout(timeout)?;
+            if let Some(entry) = watch.entry(&message) {
+                loaded = entry.delta == 0;
+                cache.apply(entry);
+            }
+        }
+
+        let client = cache.store.context.connection.0.client.clone();
+        let weak = Arc::downgrade(&cache);
+        thread::Builder::new()
+            .name(client.thread_name(format_args!("kv_cache_{}", cache.store.name)))
+            .spawn(move || run_cache(weak, watch, client))
+            .expect("threads should be spawnable");
+
+        Ok(CachedKeyValue(cache))
+    }
+
+    /// Returns the underlying bucket, for writes.
+    pub fn store(&self) -> &Store {
+        &self.0.store
+    }
+
+    /// Returns the latest value of a key, if it has one.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.0
+            .entries
+            .read()
+            .get(key)
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Returns the latest entry of a key, if it has a value.
+    pub fn entry(&self, key: &str) -> Option<Entry> {
+        self.0.entries.read().get(key).cloned()
+    }
+
+    /// Returns the keys with a value.
+    pub fn keys(&self) -> Vec<String> {
+        self.0.entries.read().keys().cloned().collect()
+    }
+
+    /// Returns the revision of the latest change applied to the cache, 0 if there was none.
+    pub fn revision(&self) -> u64 {
+        self.0.revision.load(Ordering::Acquire)
+    }
+
+    /// Returns true if the cache may be missing changes.
+    pub fn is_stale(&self) -> bool {
+        self.0.stale.load(Ordering::Acquire)
+    }
+
+    /// Sets a callback called with `true` when the cache becomes stale, and with `false`
+    /// when it is up to date again. The callback runs on the thread of the watcher.
+    pub fn on_staleness_change<F>(&self, callback: F)
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        *self.0.on_staleness_change.lock() = Some(Box::new(callback));
+    }
+}
+
+/// Applies the changes of a bucket to a cache until it is dropped or the watcher stops.
+fn run_cache(cache: Weak<Cache>, watch: Watch, client: Client) {
+    loop {
+        let result = watch.subscription.next_timeout(CACHE_CHECK_INTERVAL);
+        let cache = match cache.upgrade() {
+            Some(cache) => cache,
+            None => return,
+        };
+        match result {
+            Ok(message) => {
+                if let Some(entry) = watch.entry(&message) {
+                    cache.apply(entry);
+                }
+                cache.set_stale(false);
+            }
+            Err(err) if err.kind() == io::ErrorKind::TimedOut => {
+                cache.set_stale(!client.is_connected());
+            }
+            Err(_) => {
+                cache.set_stale(true);
+                return;
+            }
+        }
+    }
+}
+
 /// An iterator used to watch changes in a bucket.
 pub struct Watch {
     bucket: String,
@@ -876,30 +1527,179 @@ impl Iterator for Watch {
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.subscription.next() {
-            Some(message) => {
-                if let Some(info) = message.jetstream_message_info() {
-                    let operation = kv_operation_from_maybe_headers(message.headers.as_ref());
+            Some(message) => self.entry(&message),
+            None => None,
+        }
+    }
+}
 
-                    let key = message
-                        .subject
-                        .strip_prefix(&self.prefix)
-                        .map(|s| s.to_string())
-                        .unwrap();
+impl Watch {
+    fn entry(&self, message: &Message) -> Option<Entry> {
+        let info = message.jetstream_message_info()?;
+        let operation = kv_operation_from_maybe_headers(message.headers.as_ref());
+
+        let key = message
+            .subject
+            .strip_prefix(&self.prefix)
+            .map(|s| s.to_string())
+            .unwrap();
+
+        Some(Entry {
+            bucket: self.bucket.clone(),
+            key,
+            value: message.data.clone(),
+            revision: info.stream_seq,
+            created: info.published,
+            delta: info.pending,
+            operation,
+        })
+    }
+}
 
-                    Some(Entry {
-                        bucket: self.bucket.clone(),
-                        key,
-                        value: message.data.clone(),
-                        revision: info.stream_seq,
-                        created: info.published,
-                        delta: info.pending,
-                        operation,
-                    })
-                } else {
-                    None
+// The value stored under a lock key.
+#[derive(Debug, Serialize, Deserialize)]
+struct LockValue {
+    owner: String,
+    #[serde(with = "serde_nanos")]
+    lease: Duration,
+}
+
+impl LockValue {
+    fn decode(value: &[u8]) -> io::Result<LockValue> {
+        <Json as typed::Decode<LockValue>>::decode(value)
+    }
+
+    fn is_expired(&self, created: DateTime) -> bool {
+        created + self.lease < DateTime::now_utc()
+    }
+}
+
+/// A lock held in a key-value bucket, acquired with [`Store::acquire`].
+///
+/// The lock is not released when dropped, it has to be released explicitly or
+/// left to expire once its lease elapses.
+#[derive(Debug)]
+pub struct Lock {
+    store: Store,
+    key: String,
+    owner: String,
+    lease: Duration,
+    revision: u64,
+    ttl: bool,
+}
+
+impl Lock {
+    /// The key the lock is stored under.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The unique identifier of this lock holder.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// The revision of the entry backing the lock.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Extends the lease, failing if the lock has been taken over in the meantime.
+    pub fn renew(&mut self) -> io::Result<()> {
+        self.publish(None).map_err(|err| {
+            if is_wrong_last_sequence(&err) {
+                io::Error::new(io::ErrorKind::Other, "lock is no longer held")
+            } else {
+                err
+            }
+        })
+    }
+
+    /// Releases the lock, unless it has been taken over in the meantime.
+    pub fn release(mut self) -> io::Result<()> {
+        match self.publish(Some(KV_OPERATION_DELETE)) {
+            Ok(()) => Ok(()),
+            Err(err) if is_wrong_last_sequence(&err) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn publish(&mut self, operation: Option<&str>) -> io::Result<()> {
+        if !is_valid_key(&self.key) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
+        }
+
+        let mut headers = HeaderMap::default();
+        headers.insert(
+            header::NATS_EXPECTED_LAST_SUBJECT_SEQUENCE,
+            self.revision.to_string(),
+        );
+
+        let value = match operation {
+            Some(operation) => {
+                headers.insert(KV_OPERATION, operation.to_string());
+                Vec::new()
+            }
+            None => {
+                if self.ttl {
+                    let secs = self.lease.as_secs() + u64::from(self.lease.subsec_nanos() > 0);
+                    headers.insert(header::NATS_TTL, format!("{}s", secs.max(1)));
                 }
+                <Json as typed::Encode<LockValue>>::encode(&LockValue {
+                    owner: self.owner.clone(),
+                    lease: self.lease,
+                })?
             }
-            None => None,
+        };
+
+        let subject = self.store.put_subject(&self.key);
+        let message = Message::new(&subject, None, value, Some(headers));
+        let publish_ack = self.store.context.publish_message(&message)?;
+        self.revision = publish_ack.sequence;
+
+        Ok(())
+    }
+}
+
+/// A change in ownership of a lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockEvent {
+    /// The lock was acquired by the given owner.
+    Acquired(String),
+    /// The lock was released or removed.
+    Released,
+}
+
+/// An iterator over changes in ownership of a lock, created by [`Store::watch_lock`].
+pub struct LockWatch {
+    watch: Watch,
+    owner: Option<String>,
+}
+
+impl Iterator for LockWatch {
+    type Item = LockEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.watch.next()?;
+            let owner = match entry.operation {
+                Operation::Put => match LockValue::decode(&entry.value) {
+                    Ok(value) => Some(value.owner),
+                    Err(_) => continue,
+                },
+                _ => None,
+            };
+
+            // Renewals by the current owner are not a change in ownership.
+            if owner == self.owner {
+                continue;
+            }
+
+            self.owner = owner.clone();
+            return Some(match owner {
+                Some(owner) => LockEvent::Acquired(owner),
+                None => LockEvent::Released,
+            });
         }
     }
 }
@@ -190,6 +190,27 @@
 // As this is a deprecated client, we don't want warnings from new lints to make CI red.
 #![allow(clippy::all)]
 #![allow(warnings)]
+// Logging goes through `tracing` when the `tracing` feature is enabled, and through `log`
+// otherwise.
+#[cfg(not(feature = "tracing"))]
+use log as logging;
+#[cfg(feature = "tracing")]
+use tracing as logging;
+
+// Enters a span for the rest of the enclosing block. Spans are only recorded with the
+// `tracing` feature enabled.
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($name:expr $(, $($fields:tt)*)?) => {
+        let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($tokens:tt)*) => {};
+}
+
 /// Async-enabled NATS client.
 pub mod asynk;
 
@@ -197,27 +218,105 @@ mod auth_utils;
 mod client;
 mod connect;
 mod connector;
+pub mod error;
+mod id;
 mod message;
+mod metrics;
 mod options;
+mod pinning;
 mod proto;
+mod record;
+mod request_mux;
 mod secure_wipe;
+mod subject;
 mod subscription;
+mod tap;
+mod timer_wheel;
 
 /// Header constants and types.
 pub mod header;
 
 /// `JetStream` stream management and consumers.
+#[cfg(feature = "jetstream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jetstream")))]
 pub mod jetstream;
 
-#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+#[cfg(feature = "kv")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "kv", feature = "unstable"))))]
 pub mod kv;
 
-#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+#[cfg(feature = "object_store")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "object_store", feature = "unstable"))))]
 pub mod object_store;
 
+pub mod nuid;
+
+pub mod partition;
+
+pub mod pool;
+
+pub mod rate_limit;
+
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+pub mod rpc;
+
+pub mod router;
+
+pub mod service;
+
+pub mod shutdown;
+
+pub mod cloudevents;
+
+pub mod chunking;
+
+pub mod compression;
+
+pub mod encryption;
+
+pub mod bench;
+
+pub mod dispatch;
+
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub mod gateway;
+
+pub mod trace_context;
+
+pub mod typed;
+
+#[cfg(feature = "test_utils")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test_utils")))]
+pub mod test;
+
 #[cfg(feature = "fault_injection")]
 mod fault_injection;
 
+/// Entry points for the targets in `fuzz/`, built by `cargo fuzz`.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzzing {
+    use std::convert::TryFrom;
+
+    use crate::header::HeaderMap;
+
+    /// Decodes server operations until the input is exhausted or invalid.
+    pub fn decode(mut data: &[u8]) {
+        while let Ok(Some(_)) = crate::proto::decode(&mut data) {}
+    }
+
+    /// Parses headers and checks that they survive a round trip.
+    pub fn headers(data: &[u8]) {
+        if let Ok(headers) = HeaderMap::try_from(data) {
+            let reparsed = HeaderMap::try_from(headers.to_bytes().as_slice())
+                .expect("serialized headers should parse");
+            assert_eq!(headers, reparsed);
+        }
+    }
+}
+
 #[cfg(feature = "fault_injection")]
 use fault_injection::{inject_delay, inject_io_failure};
 
@@ -245,7 +344,7 @@ pub type Headers = HeaderMap;
 pub use header::HeaderMap;
 
 use std::{
-    io::{self, Error, ErrorKind},
+    io::{self, ErrorKind},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -254,10 +353,17 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 pub use connector::{IntoServerList, ServerAddress};
+pub use error::Error;
+pub use id::{IdGenerator, NuidGenerator, SeededIdGenerator};
+#[cfg(feature = "jetstream")]
 pub use jetstream::JetStreamOptions;
 pub use message::Message;
+pub use metrics::{Metrics, RequestOutcome, Statistics};
 pub use options::Options;
+pub use request_mux::PendingRequest;
+pub use subject::Subject;
 pub use subscription::{Handler, Subscription};
+pub use tap::{Direction, ProtocolEvent, TAP_PAYLOAD_PREVIEW};
 
 /// A re-export of the `rustls` crate used in this crate,
 /// for use in cases where manual client configurations
@@ -267,6 +373,11 @@ pub use rustls;
 #[doc(hidden)]
 pub use connect::ConnectInfo;
 
+#[doc(hidden)]
+pub use subject::{
+    check_template as __check_subject_template, render_template as __render_subject_template,
+};
+
 use client::Client;
 use options::AuthStyle;
 use secure_wipe::{SecureString, SecureVec};
@@ -275,6 +386,21 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 const LANG: &str = "rust";
 const DEFAULT_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// How often an idempotent request checks for reconnects while waiting for its response.
+const REISSUE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Classifies the result of a request for the request complete callback.
+fn request_outcome(result: &io::Result<Message>) -> RequestOutcome {
+    match result {
+        Ok(_) => RequestOutcome::Success,
+        Err(err) => match Error::from_io(err) {
+            Some(Error::NoResponders) => RequestOutcome::NoResponders,
+            Some(Error::TimedOut) => RequestOutcome::TimedOut,
+            _ => RequestOutcome::Failed,
+        },
+    }
+}
+
 lazy_static! {
     static ref VERSION_RE: Regex = Regex::new(r#"\Av?([0-9]+)\.?([0-9]+)?\.?([0-9]+)?"#).unwrap();
 }
@@ -437,8 +563,8 @@ impl Connection {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn subscribe(&self, subject: &str) -> io::Result<Subscription> {
-        self.do_subscribe(subject, None)
+    pub fn subscribe(&self, subject: impl Into<Subject>) -> io::Result<Subscription> {
+        self.do_subscribe(subject.into(), None)
     }
 
     /// Create a queue subscription for the given NATS connection.
@@ -451,8 +577,12 @@ impl Connection {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn queue_subscribe(&self, subject: &str, queue: &str) -> io::Result<Subscription> {
-        self.do_subscribe(subject, Some(queue))
+    pub fn queue_subscribe(
+        &self,
+        subject: impl Into<Subject>,
+        queue: &str,
+    ) -> io::Result<Subscription> {
+        self.do_subscribe(subject.into(), Some(queue))
     }
 
     /// Publish a message on the given subject.
@@ -465,7 +595,7 @@ impl Connection {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn publish(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<()> {
+    pub fn publish(&self, subject: impl AsRef<str>, msg: impl AsRef<[u8]>) -> io::Result<()> {
         self.publish_with_reply_or_headers(subject, None, None, msg)
     }
 
@@ -484,13 +614,11 @@ impl Connection {
     /// ```
     pub fn publish_request(
         &self,
-        subject: &str,
+        subject: impl AsRef<str>,
         reply: &str,
         msg: impl AsRef<[u8]>,
     ) -> io::Result<()> {
-        self.0
-            .client
-            .publish(subject, Some(reply), None, msg.as_ref())
+        self.publish_with_reply_or_headers(subject, Some(reply), None, msg)
     }
 
     /// Create a new globally unique inbox which can be used for replies.
@@ -505,7 +633,7 @@ impl Connection {
     /// # }
     /// ```
     pub fn new_inbox(&self) -> String {
-        format!("_INBOX.{}", nuid::next())
+        format!("_INBOX.{}", self.0.client.next_id())
     }
 
     /// Publish a message on the given subject as a request and receive the
@@ -520,7 +648,11 @@ impl Connection {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn request(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<Message> {
+    pub fn request(
+        &self,
+        subject: impl Into<Subject>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<Message> {
         self.request_with_headers_or_timeout(subject, None, None, msg)
     }
 
@@ -539,7 +671,7 @@ impl Connection {
     /// ```
     pub fn request_timeout(
         &self,
-        subject: &str,
+        subject: impl Into<Subject>,
         msg: impl AsRef<[u8]>,
         timeout: Duration,
     ) -> io::Result<Message> {
@@ -567,7 +699,7 @@ impl Connection {
     /// ```
     pub fn request_with_headers(
         &self,
-        subject: &str,
+        subject: impl Into<Subject>,
         msg: impl AsRef<[u8]>,
         headers: &HeaderMap,
     ) -> io::Result<Message> {
@@ -596,18 +728,126 @@ impl Connection {
     /// ```
     pub fn request_with_headers_or_timeout(
         &self,
-        subject: &str,
+        subject: impl Into<Subject>,
         maybe_headers: Option<&HeaderMap>,
         maybe_timeout: Option<Duration>,
         msg: impl AsRef<[u8]>,
+    ) -> io::Result<Message> {
+        self.request_and_report(
+            subject.into(),
+            maybe_headers,
+            maybe_timeout,
+            msg.as_ref(),
+            false,
+        )
+    }
+
+    /// Publish a message on the given subject as a request and receive the response, like
+    /// [`Connection::request_with_headers_or_timeout`], but publish the request again each
+    /// time the client reconnects while waiting for the response.
+    ///
+    /// A request in flight when the connection drops is lost along with its response, so
+    /// a plain request waits for its timeout, or forever without one, even if the server
+    /// is back after a brief restart. Only use this for idempotent requests, since the
+    /// responders may receive the request more than once.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let resp = nc.request_idempotent(
+    ///     "inventory.get",
+    ///     None,
+    ///     Some(std::time::Duration::from_secs(10)),
+    ///     "book",
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_idempotent(
+        &self,
+        subject: impl Into<Subject>,
+        maybe_headers: Option<&HeaderMap>,
+        maybe_timeout: Option<Duration>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<Message> {
+        self.request_and_report(
+            subject.into(),
+            maybe_headers,
+            maybe_timeout,
+            msg.as_ref(),
+            true,
+        )
+    }
+
+    fn request_and_report(
+        &self,
+        subject: Subject,
+        maybe_headers: Option<&HeaderMap>,
+        maybe_timeout: Option<Duration>,
+        msg: &[u8],
+        reissue: bool,
+    ) -> io::Result<Message> {
+        span!("request", subject = %subject);
+
+        let start = Instant::now();
+        let result = self.do_request(&subject, maybe_headers, maybe_timeout, msg, reissue);
+
+        if let Some(callback) = self.0.client.options.request_complete_callback.as_ref() {
+            callback(subject.as_str(), start.elapsed(), request_outcome(&result));
+        }
+
+        result
+    }
+
+    fn do_request(
+        &self,
+        subject: &Subject,
+        maybe_headers: Option<&HeaderMap>,
+        maybe_timeout: Option<Duration>,
+        msg: &[u8],
+        reissue: bool,
     ) -> io::Result<Message> {
         // Publish a request.
         let reply = self.new_inbox();
         let sub = self.subscribe(&reply)?;
-        self.publish_with_reply_or_headers(subject, Some(reply.as_str()), maybe_headers, msg)?;
+        let mut reconnects = self.0.client.reconnects();
+        self.publish_with_reply_or_headers(
+            subject.clone(),
+            Some(reply.as_str()),
+            maybe_headers,
+            msg,
+        )?;
 
         // Wait for the response
-        let result = if let Some(timeout) = maybe_timeout {
+        let result = if reissue {
+            // The subscription survives reconnects but the request may not, so check for
+            // reconnects while waiting and publish the request again after each one.
+            let deadline = maybe_timeout.map(|timeout| Instant::now() + timeout);
+            loop {
+                let wait = deadline.map_or(REISSUE_POLL_INTERVAL, |deadline| {
+                    REISSUE_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))
+                });
+                match sub.next_timeout(wait) {
+                    Err(err) if err.kind() == ErrorKind::TimedOut => {
+                        if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                            break Err(err);
+                        }
+                        let current = self.0.client.reconnects();
+                        if current != reconnects {
+                            reconnects = current;
+                            self.publish_with_reply_or_headers(
+                                subject.clone(),
+                                Some(reply.as_str()),
+                                maybe_headers,
+                                msg,
+                            )?;
+                        }
+                    }
+                    result => break result,
+                }
+            }
+        } else if let Some(timeout) = maybe_timeout {
             sub.next_timeout(timeout)
         } else if let Some(msg) = sub.next() {
             Ok(msg)
@@ -618,13 +858,29 @@ impl Connection {
         // Check for no responder status.
         if let Ok(msg) = result.as_ref() {
             if msg.is_no_responders() {
-                return Err(Error::new(ErrorKind::NotFound, "no responders"));
+                return Err(Error::NoResponders.into());
             }
         }
 
         result
     }
 
+    /// Forward an HTTP request as a NATS request and translate the reply into an HTTP
+    /// response. See [`gateway`] for how requests and responses are mapped.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn request_http<B: AsRef<[u8]>>(
+        &self,
+        subject: &str,
+        request: &http::Request<B>,
+        timeout: Option<Duration>,
+    ) -> io::Result<http::Response<Vec<u8>>> {
+        let headers = gateway::encode_request(request)?;
+        let message =
+            self.request_with_headers_or_timeout(subject, Some(&headers), timeout, request.body())?;
+        gateway::decode_response(&message)
+    }
+
     /// Publish a message on the given subject as a request and allow multiple
     /// responses.
     ///
@@ -637,7 +893,11 @@ impl Connection {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn request_multi(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<Subscription> {
+    pub fn request_multi(
+        &self,
+        subject: impl Into<Subject>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<Subscription> {
         // Publish a request.
         let reply = self.new_inbox();
         let sub = self.subscribe(&reply)?;
@@ -647,6 +907,127 @@ impl Connection {
         Ok(sub)
     }
 
+    /// Returns the worker pool spawned with the connection, if configured with
+    /// [`Options::dispatch_workers`], for subscriptions to share with
+    /// [`Subscription::with_handler_on`].
+    pub fn dispatch_pool(&self) -> Option<dispatch::WorkerPool> {
+        self.0.client.dispatch_pool.clone()
+    }
+
+    /// Publish a request without waiting for its response, which is delivered to the
+    /// returned [`PendingRequest`], or fails with `TimedOut` once the deadline passes.
+    ///
+    /// All requests sent this way share one response subscription and one timer, so
+    /// tens of thousands of them can be pending at once without a thread or timer each.
+    /// Deadlines are enforced with a resolution of 10 milliseconds.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # nc.subscribe("foo")?.with_handler(move |m| { m.respond("ans=42")?; Ok(()) });
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(2);
+    /// let pending = (0..1000)
+    ///     .map(|i| nc.send_request("foo", i.to_string(), deadline))
+    ///     .collect::<std::io::Result<Vec<_>>>()?;
+    /// for request in pending {
+    ///     println!("response: {}", request.wait()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_request(
+        &self,
+        subject: impl Into<Subject>,
+        msg: impl AsRef<[u8]>,
+        deadline: Instant,
+    ) -> io::Result<PendingRequest> {
+        let (sender, response) = crossbeam_channel::bounded(1);
+        self.request_with_callback(subject, msg, deadline, move |result| {
+            sender.send(result).ok();
+        })?;
+        Ok(PendingRequest { response })
+    }
+
+    /// Publish a request and call `callback` with its response, or a `TimedOut` error
+    /// once the deadline passes, like [`Connection::send_request`].
+    ///
+    /// The callback runs on the thread reading from the server or the thread expiring
+    /// deadlines, so it must not block.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// nc.request_with_callback(
+    ///     "foo",
+    ///     "Help me?",
+    ///     Instant::now() + Duration::from_secs(2),
+    ///     |result| match result {
+    ///         Ok(response) => println!("response: {}", response),
+    ///         Err(err) => println!("request failed: {}", err),
+    ///     },
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_with_callback<F>(
+        &self,
+        subject: impl Into<Subject>,
+        msg: impl AsRef<[u8]>,
+        deadline: Instant,
+        callback: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(io::Result<Message>) + Send + 'static,
+    {
+        self.request_with_headers_and_callback(subject, None, msg, deadline, callback)
+    }
+
+    /// Publish a request with headers and call `callback` with its response, like
+    /// [`Connection::request_with_callback`].
+    pub(crate) fn request_with_headers_and_callback<F>(
+        &self,
+        subject: impl Into<Subject>,
+        headers: Option<&HeaderMap>,
+        msg: impl AsRef<[u8]>,
+        deadline: Instant,
+        callback: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(io::Result<Message>) + Send + 'static,
+    {
+        let subject = subject.into();
+        let mux = self.0.client.request_mux()?;
+
+        let start = Instant::now();
+        let report = self.0.client.options.request_complete_callback.clone();
+        let reported_subject = subject.clone();
+        let reply = mux.register(
+            deadline,
+            Box::new(move |result| {
+                if let Some(report) = report {
+                    report(
+                        reported_subject.as_str(),
+                        start.elapsed(),
+                        request_outcome(&result),
+                    );
+                }
+                callback(result);
+            }),
+        );
+
+        if let Err(err) = self.publish_with_reply_or_headers(subject, Some(&reply), headers, msg) {
+            mux.cancel(&reply);
+            return Err(err);
+        }
+        Ok(())
+    }
+
     /// Flush a NATS connection by sending a `PING` protocol and waiting for the
     /// responding `PONG`. Will fail with `TimedOut` if the server does not
     /// respond with in 10 seconds. Will fail with `NotConnected` if the
@@ -684,6 +1065,30 @@ impl Connection {
         self.0.client.flush(duration)
     }
 
+    /// Verify the connection is alive by sending a `PING` and waiting for the responding
+    /// `PONG`. If the server takes longer than the timeout to respond, the connection is
+    /// considered stale and dropped, so the client reconnects as it would after the
+    /// connection was lost, and this fails with `TimedOut`.
+    ///
+    /// Stale connections are otherwise only detected by the periodic pings, which can take
+    /// minutes, so applications that are idle for long periods can use this before sending
+    /// critical traffic.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// if nc.ping(Duration::from_secs(1)).is_ok() {
+    ///     nc.publish("orders", "critical")?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ping(&self, timeout: Duration) -> io::Result<()> {
+        self.0.client.ping(timeout)
+    }
+
     /// Close a NATS connection. All clones of
     /// this `Connection` will also be closed,
     /// as the backing IO threads are shared.
@@ -719,8 +1124,29 @@ impl Connection {
     /// # }
     /// ```
     pub fn rtt(&self) -> io::Result<Duration> {
+        self.rtt_timeout(DEFAULT_FLUSH_TIMEOUT)
+    }
+
+    /// Calculates the round trip time between this client and the server by sending a
+    /// `PING` and waiting for the `PONG`. Fails with `TimedOut` if the server takes
+    /// longer than this duration to respond, which tells a healthy connection apart from
+    /// a TCP session that silently stopped working.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// match nc.rtt_timeout(Duration::from_secs(1)) {
+    ///     Ok(rtt) => println!("healthy, rtt: {:?}", rtt),
+    ///     Err(err) => println!("unhealthy: {}", err),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rtt_timeout(&self, duration: Duration) -> io::Result<Duration> {
         let start = Instant::now();
-        self.flush()?;
+        self.flush_timeout(duration)?;
         Ok(start.elapsed())
     }
 
@@ -753,6 +1179,77 @@ impl Connection {
         true
     }
 
+    /// Returns the cumulative statistics of the connection: messages and payload bytes
+    /// sent and received, reconnects, and the bytes currently waiting to be written.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let stats = nc.stats();
+    /// println!("published {} messages", stats.out_messages);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> Statistics {
+        self.0.client.stats()
+    }
+
+    /// Resets the cumulative statistics returned by [`Connection::stats`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nc.stats_reset();
+    /// assert_eq!(nc.stats().out_messages, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats_reset(&self) {
+        self.0.client.stats_reset();
+    }
+
+    /// Returns the most recent error of the connection, like an `-ERR` sent by the server,
+    /// a slow consumer or a lost connection, whether or not an error callback handled it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// if let Some(err) = nc.last_error() {
+    ///     println!("connection unhealthy: {}", err);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_error(&self) -> Option<String> {
+        self.0.client.last_error()
+    }
+
+    /// Reloads the root certificates, client certificate and key from the files they were
+    /// configured with, so rotated certificates are used from the next reconnect on without
+    /// restarting the process. The current connection is not affected.
+    ///
+    /// If loading fails, the error is returned and the previous certificates stay in use.
+    /// Connections configured with [`Options::tls_client_config`] keep using that config.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .client_cert("client-cert.pem", "client-key.pem")
+    ///     .connect("tls://demo.nats.io")?;
+    ///
+    /// // After the files were rotated on disk.
+    /// nc.reload_tls()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reload_tls(&self) -> io::Result<()> {
+        self.0.client.reload_tls()
+    }
+
     /// Returns the client IP as known by the server.
     /// Supported as of server version 2.1.6.
     /// # Example
@@ -767,7 +1264,7 @@ impl Connection {
         let info = self.0.client.server_info();
 
         match info.client_ip.as_str() {
-            "" => Err(Error::new(
+            "" => Err(io::Error::new(
                 ErrorKind::Other,
                 &*format!(
                     "client_ip was not provided by the server. It is \
@@ -778,7 +1275,7 @@ impl Connection {
             )),
             ip => match ip.parse() {
                 Ok(addr) => Ok(addr),
-                Err(_) => Err(Error::new(
+                Err(_) => Err(io::Error::new(
                     ErrorKind::InvalidData,
                     &*format!(
                         "client_ip provided by the server cannot be parsed. \
@@ -865,14 +1362,342 @@ impl Connection {
     /// ```
     pub fn publish_with_reply_or_headers(
         &self,
-        subject: &str,
+        subject: impl AsRef<str>,
         reply: Option<&str>,
         headers: Option<&HeaderMap>,
         msg: impl AsRef<[u8]>,
     ) -> io::Result<()> {
+        let subject = subject.as_ref();
+        subject::validate_publish(subject)?;
+
+        // Propagate the current trace context, unless the caller already did.
+        if let Some(provider) = self.0.client.options.trace_context_provider.as_ref() {
+            let propagated = headers.map_or(false, |headers| {
+                headers.contains_key(trace_context::TRACEPARENT)
+            });
+            if !propagated && self.0.client.server_info.lock().headers {
+                if let Some(context) = provider() {
+                    let mut headers = headers.cloned().unwrap_or_default();
+                    context.inject(&mut headers);
+                    return self
+                        .0
+                        .client
+                        .publish(subject, reply, Some(&headers), msg.as_ref());
+                }
+            }
+        }
+
         self.0.client.publish(subject, reply, headers, msg.as_ref())
     }
 
+    /// Publish a CloudEvent in binary content mode, with its attributes as `ce-` headers
+    /// and its data as the payload.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::cloudevents::CloudEvent;
+    ///
+    /// let mut event = CloudEvent::new("1", "/orders", "com.example.order.created");
+    /// event.data = b"order 1".to_vec();
+    /// nc.publish_cloud_event("orders", &event)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_cloud_event(
+        &self,
+        subject: impl AsRef<str>,
+        event: &cloudevents::CloudEvent,
+    ) -> io::Result<()> {
+        let headers = event.to_headers()?;
+        self.publish_with_reply_or_headers(subject, None, Some(&headers), &event.data)
+    }
+
+    /// Publish a value serialized as JSON, stamped with a `Content-Type` of
+    /// `application/json` if the server supports headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// #[derive(serde::Serialize)]
+    /// struct Order {
+    ///     id: u64,
+    /// }
+    ///
+    /// nc.publish_json("orders", &Order { id: 1 })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_json<T: serde::Serialize + ?Sized>(
+        &self,
+        subject: impl AsRef<str>,
+        value: &T,
+    ) -> io::Result<()> {
+        self.publish_with_codec::<typed::Json, T>(subject, value)
+    }
+
+    /// Publish a value encoded with the [`typed::Codec`] `C`, stamped with its
+    /// `Content-Type` if the server supports headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::typed::Json;
+    ///
+    /// nc.publish_with_codec::<Json, _>("numbers", &vec![1, 2, 3])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_with_codec<C: typed::Encode<T>, T: ?Sized>(
+        &self,
+        subject: impl AsRef<str>,
+        value: &T,
+    ) -> io::Result<()> {
+        let payload = C::encode(value)?;
+        if self.0.client.server_info.lock().headers {
+            let headers = typed::encode_headers::<T, C>();
+            self.publish_with_reply_or_headers(subject, None, Some(&headers), payload)
+        } else {
+            self.publish(subject, payload)
+        }
+    }
+
+    /// Publish an already encoded payload stamped with a `Content-Type`, so typed
+    /// subscriptions pick the codec registered for it, see
+    /// [`typed::TypedSubscription::with_decoder`]. Fails if the server does not support
+    /// headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nc.publish_with_content_type("numbers", "application/json", "[1, 2, 3]")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_with_content_type(
+        &self,
+        subject: impl AsRef<str>,
+        content_type: &str,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, content_type);
+        self.publish_with_reply_or_headers(subject, None, Some(&headers), msg)
+    }
+
+    /// Republish a received message on another subject with its payload and headers, for
+    /// audit taps and bridges. The reply subject is not kept, so the new subscribers can't
+    /// respond to the original requester.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let tap = nc.clone();
+    /// let _handler = nc
+    ///     .subscribe("orders.>")?
+    ///     .with_handler(move |message| tap.forward(&message, "audit.orders"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn forward(&self, message: &Message, subject: impl AsRef<str>) -> io::Result<()> {
+        self.publish_with_reply_or_headers(subject, None, message.headers.as_ref(), &message.data)
+    }
+
+    /// Republish a received message on another subject like [`Connection::forward`],
+    /// recording the subject it was received on in [`header::NATS_ORIGINAL_SUBJECT`] unless
+    /// it was already forwarded with its original subject. Fails if the server does not
+    /// support headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let tap = nc.clone();
+    /// let _handler = nc.subscribe("orders.>")?.with_handler(move |message| {
+    ///     tap.forward_with_original_subject(&message, "audit.orders")
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn forward_with_original_subject(
+        &self,
+        message: &Message,
+        subject: impl AsRef<str>,
+    ) -> io::Result<()> {
+        let mut headers = message.headers.clone().unwrap_or_default();
+        if !headers.contains_key(header::NATS_ORIGINAL_SUBJECT) {
+            headers.insert(header::NATS_ORIGINAL_SUBJECT, message.subject.as_str());
+        }
+        self.publish_with_reply_or_headers(subject, None, Some(&headers), &message.data)
+    }
+
+    /// Publish a payload encrypted with the current key of the [`encryption::Encryption`],
+    /// recording the key id and algorithm in headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::encryption::{Encryption, StaticKeys};
+    ///
+    /// let encryption = Encryption::new(StaticKeys::new("2023-10", [7; 32]));
+    /// nc.publish_encrypted("secrets", &encryption, "hunter2")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_encrypted(
+        &self,
+        subject: impl AsRef<str>,
+        encryption: &encryption::Encryption,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        let mut headers = HeaderMap::new();
+        let payload = encryption.seal(msg.as_ref(), &mut headers)?;
+        self.publish_with_reply_or_headers(subject, None, Some(&headers), payload)
+    }
+
+    /// Publish a value encoded with the [`typed::Codec`] `C` and encrypted with the current
+    /// key of the [`encryption::Encryption`], for a typed subscription set up with
+    /// [`typed::TypedSubscription::with_encryption`] to decrypt.
+    pub fn publish_encrypted_with_codec<C: typed::Encode<T>, T: ?Sized>(
+        &self,
+        subject: impl AsRef<str>,
+        value: &T,
+        encryption: &encryption::Encryption,
+    ) -> io::Result<()> {
+        let mut headers = typed::encode_headers::<T, C>();
+        let payload = encryption.seal(&C::encode(value)?, &mut headers)?;
+        self.publish_with_reply_or_headers(subject, None, Some(&headers), payload)
+    }
+
+    /// Create a subscription yielding payloads decoded from JSON along with their messages.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let numbers = nc.subscribe_json::<Vec<u32>>("numbers")?;
+    /// if let Some(Ok((numbers, message))) = numbers.next() {
+    ///     println!("received {:?} on {}", numbers, message.subject);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe_json<T: serde::de::DeserializeOwned>(
+        &self,
+        subject: &str,
+    ) -> io::Result<typed::TypedSubscription<T>> {
+        self.subscribe_with_codec(subject)
+    }
+
+    /// Create a subscription yielding payloads decoded with the [`typed::Codec`] `C`
+    /// along with their messages.
+    pub fn subscribe_with_codec<T, C: typed::Decode<T>>(
+        &self,
+        subject: &str,
+    ) -> io::Result<typed::TypedSubscription<T, C>> {
+        Ok(typed::TypedSubscription::new(self.subscribe(subject)?))
+    }
+
+    /// Publish a protobuf message, stamped with a `Content-Type` of `application/protobuf`
+    /// and its full name in the `Nats-Schema` header if the server supports headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// struct Order {
+    ///     #[prost(uint64, tag = "1")]
+    ///     id: u64,
+    /// }
+    ///
+    /// impl prost::Name for Order {
+    ///     const NAME: &'static str = "Order";
+    ///     const PACKAGE: &'static str = "shop";
+    /// }
+    ///
+    /// nc.publish_protobuf("orders", &Order { id: 1 })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "prost")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+    pub fn publish_protobuf<T: prost::Message + prost::Name>(
+        &self,
+        subject: impl AsRef<str>,
+        value: &T,
+    ) -> io::Result<()> {
+        self.publish_with_codec::<typed::Protobuf, T>(subject, value)
+    }
+
+    /// Create a subscription yielding decoded protobuf messages along with their messages.
+    #[cfg(feature = "prost")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+    pub fn subscribe_protobuf<T: prost::Message + prost::Name + Default>(
+        &self,
+        subject: &str,
+    ) -> io::Result<typed::TypedSubscription<T, typed::Protobuf>> {
+        self.subscribe_with_codec(subject)
+    }
+
+    /// Publish a payload, split into chunks for a [`chunking::ChunkedSubscription`] to
+    /// reassemble if it exceeds [`Connection::max_payload`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nc.publish_chunked("reports", vec![0; 10 * 1024 * 1024])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_chunked(
+        &self,
+        subject: impl AsRef<str>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        chunking::publish(self, subject.as_ref(), None, None, msg.as_ref())
+    }
+
+    /// Publish a payload with an optional reply subject and headers, split into chunks
+    /// carrying the reply subject and headers if it exceeds [`Connection::max_payload`].
+    pub fn publish_chunked_with_reply_or_headers(
+        &self,
+        subject: impl AsRef<str>,
+        reply: Option<&str>,
+        headers: Option<&HeaderMap>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        chunking::publish(self, subject.as_ref(), reply, headers, msg.as_ref())
+    }
+
+    /// Create a subscription reassembling payloads published with
+    /// [`Connection::publish_chunked`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let sub = nc.subscribe_chunked("reports")?;
+    /// for report in sub.iter() {
+    ///     println!("received a report of {} bytes", report?.data.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe_chunked(
+        &self,
+        subject: impl Into<Subject>,
+    ) -> io::Result<chunking::ChunkedSubscription> {
+        Ok(chunking::ChunkedSubscription::new(self.subscribe(subject)?))
+    }
+
     /// Returns the maximum payload size the most recently
     /// connected server will accept.
     ///
@@ -887,8 +1712,9 @@ impl Connection {
         self.0.client.server_info.lock().max_payload
     }
 
-    fn do_subscribe(&self, subject: &str, queue: Option<&str>) -> io::Result<Subscription> {
-        let (sid, receiver) = self.0.client.subscribe(subject, queue)?;
+    fn do_subscribe(&self, subject: Subject, queue: Option<&str>) -> io::Result<Subscription> {
+        subject.validate()?;
+        let (sid, receiver) = self.0.client.subscribe(&subject, queue)?;
         Ok(Subscription::new(
             sid,
             subject.to_string(),
@@ -17,6 +17,7 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Instant,
 };
 
 use crate::{
@@ -28,6 +29,9 @@ use time::OffsetDateTime;
 
 pub(crate) const MESSAGE_NOT_BOUND: &str = "message not bound to a connection";
 
+/// Number of times a request can be forwarded with [`Message::forward_request`].
+const MAX_HOPS: u32 = 16;
+
 /// A message received on a subject.
 #[derive(Clone)]
 pub struct Message {
@@ -52,6 +56,10 @@ pub struct Message {
     /// using `JetStream`.
     #[doc(hidden)]
     pub double_acked: Arc<AtomicBool>,
+
+    /// When the message was received, tracked if [`crate::Options::metrics`] is set.
+    #[doc(hidden)]
+    pub received: Option<Instant>,
 }
 
 impl From<crate::asynk::Message> for Message {
@@ -63,6 +71,7 @@ impl From<crate::asynk::Message> for Message {
             headers: asynk.headers,
             client: asynk.client,
             double_acked: asynk.double_acked,
+            received: asynk.received,
         }
     }
 }
@@ -96,10 +105,111 @@ impl Message {
             .client
             .as_ref()
             .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, MESSAGE_NOT_BOUND))?;
-        client.publish(reply.as_str(), None, None, msg.as_ref())?;
+        client.publish_response(reply.as_str(), None, None, msg.as_ref())?;
         Ok(())
     }
 
+    /// Forwards a request to the next stage of a pipeline, which can respond to the original
+    /// requester since the reply subject is kept.
+    ///
+    /// The headers of the request are kept too, with the number of hops incremented in
+    /// [`header::NATS_HOP_COUNT`] and the subject of this stage added to [`header::NATS_HOP`].
+    /// Forwarding to a subject the request already went through, or more than 16 times, fails
+    /// with [`crate::Error::ForwardingLoop`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let _validate = nc.subscribe("orders.validate")?.with_handler(|request| {
+    ///     request.forward_request("orders.price", &request.data)
+    /// });
+    /// let _price = nc.subscribe("orders.price")?.with_handler(|request| {
+    ///     println!("priced after {} hops", request.hop_count());
+    ///     request.respond("42")
+    /// });
+    ///
+    /// let response = nc.request("orders.validate", "order")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn forward_request(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<()> {
+        let reply = self.reply.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "No reply subject to reply to")
+        })?;
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, MESSAGE_NOT_BOUND))?;
+        crate::subject::validate_publish(subject)?;
+
+        let hops = self.hop_count();
+        let visited = subject == self.subject
+            || self.headers.as_ref().map_or(false, |headers| {
+                headers
+                    .get_all(header::NATS_HOP)
+                    .iter()
+                    .any(|hop| hop == subject)
+            });
+        if visited || hops >= MAX_HOPS {
+            return Err(crate::Error::ForwardingLoop {
+                subject: subject.to_string(),
+                hops,
+            }
+            .into());
+        }
+
+        let mut headers = self.headers.clone().unwrap_or_default();
+        headers.insert(header::NATS_HOP_COUNT, (hops + 1).to_string());
+        headers.append(header::NATS_HOP, self.subject.as_str());
+        client.publish(subject, Some(reply), Some(&headers), msg.as_ref())
+    }
+
+    /// Returns how many times the message was forwarded with [`Message::forward_request`].
+    pub fn hop_count(&self) -> u32 {
+        self.headers
+            .as_ref()
+            .and_then(|headers| headers.get(header::NATS_HOP_COUNT))
+            .and_then(|hops| hops.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Respond to a request forwarded with [`crate::Connection::request_http`] with an HTTP
+    /// response.
+    #[cfg(feature = "http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+    pub fn respond_http<B: AsRef<[u8]>>(&self, response: &http::Response<B>) -> io::Result<()> {
+        let reply = self.reply.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "No reply subject to reply to")
+        })?;
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, MESSAGE_NOT_BOUND))?;
+        let headers = crate::gateway::encode_response(response)?;
+        client.publish_response(
+            reply.as_str(),
+            None,
+            Some(&headers),
+            response.body().as_ref(),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the W3C trace context propagated in the headers of the message, if any.
+    pub fn trace_context(&self) -> Option<crate::trace_context::TraceContext> {
+        crate::trace_context::TraceContext::extract(self.headers.as_ref()?)
+    }
+
+    /// Parses the message as a CloudEvent in binary content mode.
+    pub fn cloud_event(&self) -> io::Result<crate::cloudevents::CloudEvent> {
+        let headers = self
+            .headers
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message has no headers"))?;
+        crate::cloudevents::CloudEvent::from_parts(headers, &self.data)
+    }
+
     /// Determine if the message is a no responders response from the server.
     pub fn is_no_responders(&self) -> bool {
         if !self.data.is_empty() {
@@ -177,7 +287,10 @@ impl Message {
 
         false
     }
+}
 
+#[cfg(feature = "jetstream")]
+impl Message {
     /// Acknowledge a `JetStream` message with a default acknowledgment.
     /// See `AckKind` documentation for details of what other types of
     /// acks are available. If you need to send a non-default ack, use
@@ -190,7 +303,9 @@ impl Message {
         if self.double_acked.load(Ordering::Acquire) {
             return Ok(());
         }
-        self.respond(b"")
+        self.respond(b"")?;
+        self.record_ack(crate::jetstream::AckKind::Ack);
+        Ok(())
     }
 
     /// Acknowledge a `JetStream` message. See `AckKind` documentation for
@@ -199,7 +314,9 @@ impl Message {
     ///
     /// Does not check whether this message has already been double-acked.
     pub fn ack_kind(&self, ack_kind: crate::jetstream::AckKind) -> io::Result<()> {
-        self.respond(ack_kind)
+        self.respond(ack_kind)?;
+        self.record_ack(ack_kind);
+        Ok(())
     }
 
     /// Acknowledge a `JetStream` message and wait for acknowledgment from the server
@@ -229,9 +346,11 @@ impl Message {
         loop {
             retries += 1;
             if retries == 2 {
-                log::warn!("double_ack is retrying until the server connection is reestablished");
+                crate::logging::warn!(
+                    "double_ack is retrying until the server connection is reestablished"
+                );
             }
-            let ack_reply = format!("_INBOX.{}", nuid::next());
+            let ack_reply = format!("_INBOX.{}", client.next_id());
             let sub_ret = client.subscribe(&ack_reply, None);
             if sub_ret.is_err() {
                 std::thread::sleep(std::time::Duration::from_millis(100));
@@ -241,7 +360,8 @@ impl Message {
             let sub =
                 crate::Subscription::new(sid, ack_reply.to_string(), receiver, client.clone());
 
-            let pub_ret = client.publish(original_reply, Some(&ack_reply), None, ack_kind.as_ref());
+            let pub_ret =
+                client.publish_response(original_reply, Some(&ack_reply), None, ack_kind.as_ref());
             if pub_ret.is_err() {
                 std::thread::sleep(std::time::Duration::from_millis(100));
                 continue;
@@ -251,11 +371,29 @@ impl Message {
                 .is_ok()
             {
                 self.double_acked.store(true, Ordering::Release);
+                self.record_ack(ack_kind);
                 return Ok(());
             }
         }
     }
 
+    /// Reports the acknowledgment of a `JetStream` message to the metrics hooks.
+    fn record_ack(&self, ack_kind: crate::jetstream::AckKind) {
+        if let crate::jetstream::AckKind::Progress = ack_kind {
+            return;
+        }
+        let (received, client) = match (self.received, self.client.as_ref()) {
+            (Some(received), Some(client)) => (received, client),
+            _ => return,
+        };
+        if let (Some(metrics), Some(info)) = (
+            client.options.metrics.as_ref(),
+            self.jetstream_message_info(),
+        ) {
+            metrics.message_acked(info.stream, info.consumer, ack_kind, received.elapsed());
+        }
+    }
+
     /// Returns the `JetStream` message ID
     /// if this is a `JetStream` message.
     /// Returns `None` if this is not
@@ -295,7 +433,7 @@ impl Message {
                 match str::parse(try_parse!(str)) {
                     Ok(parsed) => parsed,
                     Err(e) => {
-                        log::error!(
+                        crate::logging::error!(
                             "failed to parse jetstream reply \
                             subject: {}, error: {:?}. Is your \
                             nats-server up to date?",
@@ -316,7 +454,7 @@ impl Message {
                     }
                     next
                 } else {
-                    log::error!(
+                    crate::logging::error!(
                         "unexpectedly few tokens while parsing \
                         jetstream reply subject: {}. Is your \
                         nats-server up to date?",
@@ -395,6 +533,7 @@ impl Default for Message {
             headers: None,
             client: None,
             double_acked: Arc::new(AtomicBool::new(false)),
+            received: None,
         }
     }
 }
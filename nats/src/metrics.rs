@@ -0,0 +1,161 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "jetstream")]
+use crate::jetstream::{AckKind, ConsumerLag};
+
+/// Hooks invoked by the client for instrumentation, set with [`crate::Options::metrics`].
+///
+/// All methods have empty default implementations, so exporters only implement the events
+/// they are interested in. The hooks are called on the hot path of the client and should
+/// return quickly, for example by updating atomic counters.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+///
+/// #[derive(Default)]
+/// struct Counters {
+///     bytes_out: AtomicU64,
+/// }
+///
+/// struct Exporter(Arc<Counters>);
+///
+/// impl nats::Metrics for Exporter {
+///     fn message_sent(&self, _subject: &str, bytes: usize) {
+///         self.0.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+///     }
+/// }
+///
+/// # fn main() -> std::io::Result<()> {
+/// let counters = Arc::new(Counters::default());
+/// let nc = nats::Options::new()
+///     .metrics(Exporter(counters.clone()))
+///     .connect("demo.nats.io")?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Metrics: Send + Sync + 'static {
+    /// A message with a payload of `bytes` was published on `subject`.
+    fn message_sent(&self, _subject: &str, _bytes: usize) {}
+
+    /// A message with a payload of `bytes` was received on `subject`.
+    fn message_received(&self, _subject: &str, _bytes: usize) {}
+
+    /// The client reconnected to a server.
+    fn reconnected(&self) {}
+
+    /// A message for a subscription on `subject` was dropped because the subscription
+    /// reached its pending limits.
+    fn slow_consumer(&self, _subject: &str) {}
+
+    /// The number of bytes buffered while disconnected changed to `bytes`.
+    fn pending_bytes(&self, _bytes: usize) {}
+
+    /// A `JetStream` message of `consumer` on `stream` was acknowledged with `kind`,
+    /// `latency` after it was delivered to the client. Progress acknowledgments are not
+    /// reported.
+    #[cfg(feature = "jetstream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jetstream")))]
+    fn message_acked(&self, _stream: &str, _consumer: &str, _kind: AckKind, _latency: Duration) {}
+
+    /// A `JetStream` message of `consumer` on `stream` was delivered again, for the
+    /// `delivered`th time. Repeatedly redelivered messages usually can't be processed.
+    fn message_redelivered(&self, _stream: &str, _consumer: &str, _delivered: i64) {}
+
+    /// The lag of a consumer was measured by a reporter started with
+    /// [`JetStream::report_consumer_lag`](crate::jetstream::JetStream::report_consumer_lag).
+    #[cfg(feature = "jetstream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jetstream")))]
+    fn consumer_lag(&self, _lag: &ConsumerLag) {}
+}
+
+/// Outcome of a request, passed to the callback set with
+/// [`crate::Options::on_request_complete`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    /// A response was received.
+    Success,
+    /// Nobody was listening on the subject.
+    NoResponders,
+    /// No response was received in time.
+    TimedOut,
+    /// The request failed for another reason, like a lost connection.
+    Failed,
+}
+
+/// Cumulative statistics of a connection, returned by [`crate::Connection::stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Statistics {
+    /// Number of messages received.
+    pub in_messages: u64,
+    /// Number of messages published.
+    pub out_messages: u64,
+    /// Number of payload bytes received.
+    pub in_bytes: u64,
+    /// Number of payload bytes published.
+    pub out_bytes: u64,
+    /// Number of times the client reconnected to a server.
+    pub reconnects: u64,
+    /// Number of bytes currently waiting to be written to the server.
+    pub pending_bytes: u64,
+}
+
+/// Counters backing [`Statistics`], updated by the client.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    in_messages: AtomicU64,
+    out_messages: AtomicU64,
+    in_bytes: AtomicU64,
+    out_bytes: AtomicU64,
+    reconnects: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn message_sent(&self, bytes: usize) {
+        self.out_messages.fetch_add(1, Ordering::Relaxed);
+        self.out_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_received(&self, bytes: usize) {
+        self.in_messages.fetch_add(1, Ordering::Relaxed);
+        self.in_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn reconnected(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, pending_bytes: usize) -> Statistics {
+        Statistics {
+            in_messages: self.in_messages.load(Ordering::Relaxed),
+            out_messages: self.out_messages.load(Ordering::Relaxed),
+            in_bytes: self.in_bytes.load(Ordering::Relaxed),
+            out_bytes: self.out_bytes.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            pending_bytes: pending_bytes as u64,
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.in_messages.store(0, Ordering::Relaxed);
+        self.out_messages.store(0, Ordering::Relaxed);
+        self.in_bytes.store(0, Ordering::Relaxed);
+        self.out_bytes.store(0, Ordering::Relaxed);
+        self.reconnects.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of NUIDs, the identifiers NATS uses for inboxes.
+//!
+//! A NUID is 22 alphanumeric characters, made of a random prefix and a sequence which is
+//! incremented by a random step. They are collision resistant and cheap to generate, which
+//! makes them a good fit for message ids used for deduplication.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! let mut headers = nats::HeaderMap::new();
+//! headers.insert(nats::header::NATS_MSG_ID, nats::nuid::next());
+//! nc.publish_with_reply_or_headers("orders", None, Some(&headers), "order")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+/// Length of a NUID.
+pub const LEN: usize = 22;
+
+/// Returns the next NUID of a global, thread-safe generator.
+pub fn next() -> String {
+    ::nuid::next()
+}
+
+/// A NUID generator, avoiding the lock of the global generator when owned by one thread.
+pub struct Nuid(::nuid::NUID);
+
+impl fmt::Debug for Nuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Nuid").finish_non_exhaustive()
+    }
+}
+
+impl Nuid {
+    /// Creates a generator with a random prefix and sequence.
+    pub fn new() -> Nuid {
+        Nuid(::nuid::NUID::new())
+    }
+
+    /// Returns the next NUID.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> String {
+        self.0.next()
+    }
+}
+
+impl Default for Nuid {
+    fn default() -> Nuid {
+        Nuid::new()
+    }
+}
@@ -14,10 +14,10 @@
 //! Support for Object Store.
 //! This feature is experimental and the API may change.
 
-use crate::header::HeaderMap;
+use crate::header::{self, HeaderMap};
 use crate::jetstream::{
-    DateTime, DiscardPolicy, JetStream, PushSubscription, StorageType, StreamConfig,
-    SubscribeOptions,
+    is_wrong_last_sequence, DateTime, DiscardPolicy, Error, ErrorCode, JetStream, PublishOptions,
+    PushSubscription, StorageType, StreamConfig, SubscribeOptions,
 };
 use crate::Message;
 use base64::URL_SAFE;
@@ -25,7 +25,11 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use ring::digest::SHA256;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::cmp;
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
 use std::io::{self, ErrorKind};
 use std::time::Duration;
 use time::serde::rfc3339;
@@ -56,6 +60,35 @@ fn encode_object_name(object_name: &str) -> String {
     base64::encode_config(object_name, URL_SAFE)
 }
 
+// Reads until the buffer is full or the reader is exhausted, so that every chunk but the
+// last one has the same size.
+fn read_chunk(data: &mut impl io::Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match data.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(filled)
+}
+
+// State of an upload recorded before its chunks are stored, used to resume it.
+#[derive(Debug, Serialize, Deserialize)]
+struct Upload {
+    name: String,
+    description: Option<String>,
+    link: Option<ObjectLink>,
+    headers: Option<HeaderMap>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compression: Option<Compression>,
+    nuid: String,
+    chunk_size: usize,
+}
+
 /// Configuration values for object store buckets.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -110,11 +143,12 @@ impl JetStream {
         let stream_name = format!("OBJ_{bucket_name}");
         let chunk_subject = format!("$O.{bucket_name}.C.>");
         let meta_subject = format!("$O.{bucket_name}.M.>");
+        let upload_subject = format!("$O.{bucket_name}.U.>");
 
         self.add_stream(&StreamConfig {
             name: stream_name,
             description: config.description.clone(),
-            subjects: vec![chunk_subject, meta_subject],
+            subjects: vec![chunk_subject, meta_subject, upload_subject],
             max_age: config.max_age,
             storage: config.storage,
             num_replicas: config.num_replicas,
@@ -221,6 +255,18 @@ pub struct ObjectInfo {
     /// Set to true if the object has been deleted.
     #[serde(default)]
     pub deleted: bool,
+    /// Headers associated with the object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HeaderMap>,
+    /// Algorithm the chunks of the object are compressed with, recorded under the
+    /// `nats.compression` key of the object information. Objects without it are stored
+    /// uncompressed.
+    #[serde(
+        default,
+        rename = "nats.compression",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub compression: Option<Compression>,
 }
 
 /// Meta information about an object.
@@ -232,8 +278,21 @@ pub struct ObjectMeta {
     pub description: Option<String>,
     /// Link this object points to, if any.
     pub link: Option<ObjectLink>,
+    /// Maximum size of the chunks the object is split into, defaults to 128KiB.
+    #[serde(skip)]
+    pub chunk_size: Option<usize>,
+    /// Headers associated with the object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HeaderMap>,
+    /// Compress each chunk with the given algorithm, recorded in [`ObjectInfo::compression`].
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[serde(skip)]
+    pub compression: Option<Compression>,
 }
 
+pub use crate::compression::Compression;
+
 impl From<&str> for ObjectMeta {
     fn from(s: &str) -> ObjectMeta {
         ObjectMeta {
@@ -252,6 +311,43 @@ pub struct ObjectLink {
     pub bucket: Option<String>,
 }
 
+/// Error returned when the data read for an object does not match its recorded digest.
+///
+/// It is returned as the inner error of an `io::Error` with kind `InvalidData`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityError {
+    expected: String,
+    computed: String,
+}
+
+impl IntegrityError {
+    /// The digest recorded in the object information.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// The digest computed from the data that was read.
+    pub fn computed(&self) -> &str {
+        &self.computed
+    }
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "object digest mismatch, expected {} but computed {}",
+            self.expected, self.computed
+        )
+    }
+}
+
+impl error::Error for IntegrityError {}
+
+fn encode_digest(digest: ring::digest::Digest) -> String {
+    format!("SHA-256={}", base64::encode_config(digest, URL_SAFE))
+}
+
 /// A blob store capable of storing large objects efficiently in streams.
 pub struct ObjectStore {
     name: String,
@@ -266,12 +362,14 @@ pub struct Object {
     remaining_bytes: Vec<u8>,
     has_pending_messages: bool,
     digest: Option<ring::digest::Context>,
+    compression: Option<Compression>,
 }
 
 impl Object {
     pub(crate) fn new(subscription: PushSubscription, info: ObjectInfo) -> Self {
         Object {
             subscription,
+            compression: info.compression,
             info,
             remaining_bytes: Vec::new(),
             has_pending_messages: true,
@@ -301,21 +399,31 @@ impl io::Read for Object {
         if self.has_pending_messages {
             let maybe_message = self.subscription.next();
             if let Some(message) = maybe_message {
-                let len = cmp::min(buffer.len(), message.data.len());
-                buffer[..len].copy_from_slice(&message.data[..len]);
+                let data = match self.compression {
+                    Some(compression) => Cow::Owned(compression.decompress(&message.data)?),
+                    None => Cow::Borrowed(message.data.as_slice()),
+                };
+
+                let len = cmp::min(buffer.len(), data.len());
+                buffer[..len].copy_from_slice(&data[..len]);
                 if let Some(context) = &mut self.digest {
-                    context.update(&message.data);
+                    context.update(&data);
                 }
-                self.remaining_bytes.extend_from_slice(&message.data[len..]);
+                self.remaining_bytes.extend_from_slice(&data[len..]);
 
                 if let Some(message_info) = message.jetstream_message_info() {
                     if message_info.pending == 0 {
                         let digest = self.digest.take().map(|context| context.finish());
                         if let Some(digest) = digest {
-                            if format!("SHA-256={}", base64::encode_config(digest, URL_SAFE))
-                                != self.info.digest
-                            {
-                                return Err(io::Error::new(ErrorKind::InvalidData, "wrong digest"));
+                            let computed = encode_digest(digest);
+                            if computed != self.info.digest {
+                                return Err(io::Error::new(
+                                    ErrorKind::InvalidData,
+                                    IntegrityError {
+                                        expected: self.info.digest.clone(),
+                                        computed,
+                                    },
+                                ));
                             }
                         } else {
                             return Err(io::Error::new(
@@ -334,6 +442,42 @@ impl io::Read for Object {
     }
 }
 
+/// A byte range of an object, returned by `get_range`.
+pub struct ObjectRange {
+    context: JetStream,
+    stream_name: String,
+    // Stream sequence of each chunk overlapping the range, with the bytes to skip and take.
+    segments: VecDeque<(u64, usize, usize)>,
+    remaining_bytes: Vec<u8>,
+}
+
+impl io::Read for ObjectRange {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.remaining_bytes.is_empty() {
+            let (sequence, skip, take) = match self.segments.pop_front() {
+                Some(segment) => segment,
+                None => return Ok(0),
+            };
+
+            let message = self.context.get_message(&self.stream_name, sequence)?;
+            if message.data.len() < skip + take {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "object chunk is shorter than expected",
+                ));
+            }
+
+            self.remaining_bytes = message.data[skip..skip + take].to_vec();
+        }
+
+        let len = cmp::min(buffer.len(), self.remaining_bytes.len());
+        buffer[..len].copy_from_slice(&self.remaining_bytes[..len]);
+        self.remaining_bytes.drain(..len);
+
+        Ok(len)
+    }
+}
+
 impl ObjectStore {
     /// Instantiates a new object store
     pub(crate) fn new(name: String, context: JetStream) -> Self {
@@ -385,6 +529,29 @@ impl ObjectStore {
     }
 
     /// Seals the object store from further modifications.
+    ///
+    /// Sealing can not be undone, any further `put` or `delete` fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "seal".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// bucket.seal()?;
+    /// assert!(bucket.is_sealed()?);
+    ///
+    /// # context.delete_object_store("seal")?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn seal(&self) -> io::Result<()> {
         let stream_name = format!("OBJ_{}", self.name);
         let stream_info = self.context.stream_info(stream_name)?;
@@ -397,8 +564,19 @@ impl ObjectStore {
         Ok(())
     }
 
+    /// Returns true if the object store has been sealed.
+    pub fn is_sealed(&self) -> io::Result<bool> {
+        let stream_name = format!("OBJ_{}", self.name);
+        let stream_info = self.context.stream_info(stream_name)?;
+
+        Ok(stream_info.config.sealed)
+    }
+
     /// Put will place the contents from the given reader into this object-store.
     ///
+    /// The data is split into chunks of `chunk_size` from the given meta and a SHA-256
+    /// digest of the contents is recorded in the object information.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -433,23 +611,212 @@ impl ObjectStore {
             ));
         }
 
+        let chunk_size = object_meta.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+        if chunk_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk size must be greater than zero",
+            ));
+        }
+
+        #[cfg(feature = "compression")]
+        let compression = object_meta.compression;
+        #[cfg(not(feature = "compression"))]
+        let compression = None;
+
+        // Record the upload so it can be resumed if it gets interrupted. It is expected to be
+        // the only one, so a previous upload is only looked up if it never completed, and its
+        // chunks are removed as the new upload replaces it.
+        let upload = Upload {
+            name: object_meta.name,
+            description: object_meta.description,
+            link: object_meta.link,
+            headers: object_meta.headers,
+            compression,
+            nuid: self.context.connection.0.client.next_id(),
+            chunk_size,
+        };
+        if let Err(err) = self.record_upload(&upload, Some(0)) {
+            if !is_wrong_last_sequence(&err) {
+                return Err(err);
+            }
+            let previous = self.pending_upload(&upload.name)?;
+            let stream_name = format!("OBJ_{}", self.name);
+            let chunk_subject = format!("$O.{}.C.{}", self.name, previous.nuid);
+            self.context
+                .purge_stream_subject(&stream_name, &chunk_subject)?;
+            self.record_upload(&upload, None)?;
+        }
+
+        self.upload(upload, data, 0)
+    }
+
+    /// Resumes a `put` of the named object that was interrupted.
+    ///
+    /// The reader has to provide the same data as the interrupted `put` from the beginning,
+    /// the chunks which were already stored are read to compute the digest but not sent again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "resume_put".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let bytes = vec![0, 1, 2, 3, 4];
+    /// if bucket.put("foo", &mut bytes.as_slice()).is_err() {
+    ///     bucket.resume_put("foo", &mut bytes.as_slice())?;
+    /// }
+    ///
+    /// # context.delete_object_store("resume_put")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resume_put(
+        &self,
+        object_name: &str,
+        data: &mut impl io::Read,
+    ) -> io::Result<ObjectInfo> {
+        let upload = self.pending_upload(object_name)?;
+
+        // Count the chunks which made it into the stream, without transferring their data.
+        let chunk_subject = format!("$O.{}.C.{}", self.name, upload.nuid);
+        let subscription = self
+            .context
+            .subscribe_with_options(&chunk_subject, &SubscribeOptions::ordered().headers_only())?;
+
+        let stored_chunks = if subscription.0.num_pending == 0 {
+            0
+        } else {
+            subscription
+                .next()
+                .and_then(|message| {
+                    message
+                        .jetstream_message_info()
+                        .map(|info| info.pending as usize + 1)
+                })
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "missing chunk metadata")
+                })?
+        };
+
+        self.upload(upload, data, stored_chunks)
+    }
+
+    /// Aborts an interrupted `put` of the named object, removing the chunks stored so far.
+    pub fn abort_put(&self, object_name: &str) -> io::Result<()> {
+        let upload = self.pending_upload(object_name)?;
+
+        let stream_name = format!("OBJ_{}", self.name);
+        let chunk_subject = format!("$O.{}.C.{}", self.name, upload.nuid);
+        self.context
+            .purge_stream_subject(&stream_name, &chunk_subject)?;
+
+        self.context
+            .purge_stream_subject(&stream_name, &self.upload_subject(object_name))?;
+
+        Ok(())
+    }
+
+    // Subject of the state of an upload, kept apart from the chunks of the bucket.
+    fn upload_subject(&self, object_name: &str) -> String {
+        format!("$O.{}.U.{}", self.name, encode_object_name(object_name))
+    }
+
+    // Records the state of an upload, optionally expecting the given last sequence of its
+    // subject, where 0 means no upload is recorded.
+    fn record_upload(
+        &self,
+        upload: &Upload,
+        expected_last_subject_sequence: Option<u64>,
+    ) -> io::Result<()> {
+        let state = serde_json::to_vec(upload)?;
+        let subject = self.upload_subject(&upload.name);
+        let options = PublishOptions {
+            expected_last_subject_sequence,
+            ..Default::default()
+        };
+        let err = match self
+            .context
+            .publish_with_options(&subject, &state, &options)
+        {
+            Ok(_) => return Ok(()),
+            Err(err) if is_wrong_last_sequence(&err) => return Err(err),
+            Err(err) => err,
+        };
+
+        // Buckets created by earlier versions don't store the upload subjects yet.
+        let stream_name = format!("OBJ_{}", self.name);
+        let mut config = self.context.stream_info(&stream_name)?.config;
+        let upload_subjects = format!("$O.{}.U.>", self.name);
+        if config.subjects.contains(&upload_subjects) {
+            return Err(err);
+        }
+        config.subjects.push(upload_subjects);
+        self.context.update_stream(&config)?;
+        self.context
+            .publish_with_options(&subject, &state, &options)?;
+
+        Ok(())
+    }
+
+    fn pending_upload(&self, object_name: &str) -> io::Result<Upload> {
+        let stream_name = format!("OBJ_{}", self.name);
+        let subject = self.upload_subject(object_name);
+
+        let message = self
+            .context
+            .get_last_message(stream_name, &subject)
+            .map_err(|err| {
+                let not_found = err
+                    .get_ref()
+                    .and_then(|inner_err| inner_err.downcast_ref::<Error>())
+                    .map_or(false, |error| {
+                        error.error_code() == ErrorCode::NoMessageFound
+                    });
+
+                if not_found {
+                    io::Error::new(io::ErrorKind::NotFound, "no upload in progress")
+                } else {
+                    err
+                }
+            })?;
+
+        let upload = serde_json::from_slice(&message.data)?;
+
+        Ok(upload)
+    }
+
+    // Stores the chunks of an upload, skipping chunks that are already stored, and publishes
+    // the object information once all data has been read.
+    fn upload(
+        &self,
+        upload: Upload,
+        data: &mut impl io::Read,
+        stored_chunks: usize,
+    ) -> io::Result<ObjectInfo> {
         // Fetch any existing object info, if there is any for later use.
-        let maybe_existing_object_info = match self.info(&object_name) {
+        let maybe_existing_object_info = match self.info(&upload.name) {
             Ok(object_info) => Some(object_info),
             Err(_) => None,
         };
 
-        let object_nuid = nuid::next();
-        let chunk_subject = format!("$O.{}.C.{}", &self.name, &object_nuid);
+        let chunk_subject = format!("$O.{}.C.{}", &self.name, &upload.nuid);
 
         let mut object_chunks = 0;
         let mut object_size = 0;
 
         let mut context = ring::digest::Context::new(&SHA256);
-        let mut buffer = [0; DEFAULT_CHUNK_SIZE];
+        let mut buffer = vec![0; upload.chunk_size];
 
         loop {
-            let n = data.read(&mut buffer)?;
+            let n = read_chunk(data, &mut buffer)?;
             if n == 0 {
                 break;
             }
@@ -458,43 +825,63 @@ impl ObjectStore {
             object_size += n;
             object_chunks += 1;
 
-            self.context.publish(&chunk_subject, &buffer[..n])?;
+            if object_chunks <= stored_chunks {
+                continue;
+            }
+
+            match upload.compression {
+                Some(compression) => {
+                    let compressed = compression.compress(&buffer[..n])?;
+                    self.context.publish(&chunk_subject, compressed)?;
+                }
+                None => {
+                    self.context.publish(&chunk_subject, &buffer[..n])?;
+                }
+            }
+        }
+
+        if object_chunks < stored_chunks {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "data is shorter than the chunks already stored",
+            ));
         }
 
         let digest = context.finish();
-        // Create a random subject prefixed with the object stream name.
-        let subject = format!("$O.{}.M.{}", &self.name, &object_name);
         let object_info = ObjectInfo {
-            name: object_meta.name,
-            description: object_meta.description,
-            link: object_meta.link,
+            name: upload.name,
+            description: upload.description,
+            link: upload.link,
             bucket: self.name.clone(),
-            nuid: object_nuid,
+            nuid: upload.nuid,
             chunks: object_chunks,
             size: object_size,
-            digest: format!("SHA-256={}", base64::encode_config(digest, URL_SAFE)),
+            digest: encode_digest(digest),
             modified: OffsetDateTime::now_utc(),
             deleted: false,
+            headers: upload.headers,
+            compression: upload.compression,
         };
 
-        let data = serde_json::to_vec(&object_info)?;
-        let mut headers = HeaderMap::default();
-        headers.insert(NATS_ROLLUP, ROLLUP_SUBJECT.to_string());
-
-        let message = Message::new(&subject, None, data, Some(headers));
-
         // Publish metadata
-        self.context.publish_message(&message)?;
+        self.publish_meta(&object_info)?;
+
+        let stream_name = format!("OBJ_{}", self.name);
 
         // Purge any old chunks.
         if let Some(existing_object_info) = maybe_existing_object_info {
-            let stream_name = format!("OBJ_{}", self.name);
-            let chunk_subject = format!("$O.{}.C.{}", &self.name, &existing_object_info.nuid);
+            if existing_object_info.nuid != object_info.nuid {
+                let chunk_subject = format!("$O.{}.C.{}", &self.name, &existing_object_info.nuid);
 
-            self.context
-                .purge_stream_subject(stream_name, &chunk_subject)?;
+                self.context
+                    .purge_stream_subject(&stream_name, &chunk_subject)?;
+            }
         }
 
+        // The upload is complete and no longer needs to be resumable.
+        self.context
+            .purge_stream_subject(&stream_name, &self.upload_subject(&object_info.name))?;
+
         Ok(object_info)
     }
 
@@ -527,7 +914,19 @@ impl ObjectStore {
     pub fn get(&self, object_name: &str) -> io::Result<Object> {
         let object_info = self.info(object_name)?;
         if let Some(link) = object_info.link {
-            return self.get(&link.name);
+            if link.name.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "object is a link to a bucket",
+                ));
+            }
+
+            return match link.bucket {
+                Some(bucket) if bucket != self.name => {
+                    self.context.object_store(&bucket)?.get(&link.name)
+                }
+                _ => self.get(&link.name),
+            };
         }
 
         let chunk_subject = format!("$O.{}.C.{}", self.name, object_info.nuid);
@@ -538,6 +937,370 @@ impl ObjectStore {
         Ok(Object::new(subscription, object_info))
     }
 
+    /// Get a byte range of an existing object, fetching only the chunks overlapping it.
+    ///
+    /// The range is clamped to the size of the object. Unlike `get`, the digest of the
+    /// object is not verified as only part of the data is read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::io::Read;
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "get_range".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let bytes = vec![0, 1, 2, 3, 4];
+    /// bucket.put("foo", &mut bytes.as_slice())?;
+    ///
+    /// let mut result = Vec::new();
+    /// bucket.get_range("foo", 1, 3)?.read_to_end(&mut result)?;
+    /// assert_eq!(result, vec![1, 2, 3]);
+    ///
+    /// # context.delete_object_store("get_range")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_range(
+        &self,
+        object_name: &str,
+        offset: usize,
+        length: usize,
+    ) -> io::Result<ObjectRange> {
+        let object_info = self.info(object_name)?;
+        if let Some(link) = object_info.link {
+            if link.name.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "object is a link to a bucket",
+                ));
+            }
+
+            return match link.bucket {
+                Some(bucket) if bucket != self.name => self
+                    .context
+                    .object_store(&bucket)?
+                    .get_range(&link.name, offset, length),
+                _ => self.get_range(&link.name, offset, length),
+            };
+        }
+
+        if object_info.deleted {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "object has been deleted",
+            ));
+        }
+
+        if object_info.compression.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "ranged reads are not supported for compressed objects",
+            ));
+        }
+
+        if offset > object_info.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "offset is past the end of the object",
+            ));
+        }
+
+        let end = cmp::min(offset.saturating_add(length), object_info.size);
+        let mut segments = VecDeque::new();
+
+        if offset < end {
+            // Find the sequence and size of the chunks without transferring their data.
+            let chunk_subject = format!("$O.{}.C.{}", self.name, object_info.nuid);
+            let subscription = self.context.subscribe_with_options(
+                &chunk_subject,
+                &SubscribeOptions::ordered().headers_only(),
+            )?;
+
+            let mut position = 0;
+            while position < end && subscription.0.num_pending > 0 {
+                let message = subscription.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "missing object chunks")
+                })?;
+
+                let size = message
+                    .headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(header::NATS_MSG_SIZE))
+                    .and_then(|size| size.parse::<usize>().ok())
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "missing chunk size")
+                    })?;
+
+                let info = message.jetstream_message_info().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "missing chunk metadata")
+                })?;
+
+                if position + size > offset {
+                    let skip = offset.saturating_sub(position);
+                    let take = cmp::min(position + size, end) - position - skip;
+                    segments.push_back((info.stream_seq, skip, take));
+                }
+
+                position += size;
+                if info.pending == 0 {
+                    break;
+                }
+            }
+        }
+
+        Ok(ObjectRange {
+            context: self.context.clone(),
+            stream_name: format!("OBJ_{}", self.name),
+            segments,
+            remaining_bytes: Vec::new(),
+        })
+    }
+
+    /// Adds a link to another object, which can be in another bucket.
+    ///
+    /// Getting the link yields the data of the object it points to.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::io::Read;
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "add_link".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let bytes = vec![0, 1, 2, 3, 4];
+    /// let info = bucket.put("foo", &mut bytes.as_slice())?;
+    /// bucket.add_link("bar", &info)?;
+    ///
+    /// let mut result = Vec::new();
+    /// bucket.get("bar")?.read_to_end(&mut result)?;
+    /// assert_eq!(result, bytes);
+    ///
+    /// # context.delete_object_store("add_link")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_link(&self, name: &str, object: &ObjectInfo) -> io::Result<ObjectInfo> {
+        if object.name.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "link target name is required",
+            ));
+        }
+
+        if object.deleted {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can not link to a deleted object",
+            ));
+        }
+
+        if object.link.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can not link to another link",
+            ));
+        }
+
+        self.put_link(
+            name,
+            ObjectLink {
+                name: object.name.clone(),
+                bucket: Some(object.bucket.clone()),
+            },
+        )
+    }
+
+    /// Adds a link to another object store bucket.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "add_bucket_link".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let info = bucket.add_bucket_link("other", "other_bucket")?;
+    /// assert_eq!(info.link.unwrap().bucket, Some("other_bucket".to_string()));
+    ///
+    /// # context.delete_object_store("add_bucket_link")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_bucket_link(&self, name: &str, bucket: &str) -> io::Result<ObjectInfo> {
+        if !is_valid_bucket_name(bucket) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid bucket name",
+            ));
+        }
+
+        self.put_link(
+            name,
+            ObjectLink {
+                name: String::new(),
+                bucket: Some(bucket.to_string()),
+            },
+        )
+    }
+
+    fn put_link(&self, name: &str, link: ObjectLink) -> io::Result<ObjectInfo> {
+        if !is_valid_object_name(&encode_object_name(name)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid object name",
+            ));
+        }
+
+        // Links may replace other links, but never an object holding data.
+        if let Ok(existing) = self.info(name) {
+            if !existing.deleted && existing.link.is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "an object already exists with that name",
+                ));
+            }
+        }
+
+        let object_info = ObjectInfo {
+            name: name.to_string(),
+            description: None,
+            link: Some(link),
+            bucket: self.name.clone(),
+            nuid: self.context.connection.0.client.next_id(),
+            size: 0,
+            chunks: 0,
+            modified: OffsetDateTime::now_utc(),
+            digest: String::new(),
+            deleted: false,
+            headers: None,
+            compression: None,
+        };
+
+        self.publish_meta(&object_info)?;
+
+        Ok(object_info)
+    }
+
+    /// Updates the name, description and headers of an existing object without
+    /// uploading its data again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nats::object_store::{Config, ObjectMeta};
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "update_metadata".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let bytes = vec![0, 1, 2, 3, 4];
+    /// bucket.put("foo", &mut bytes.as_slice())?;
+    ///
+    /// let info = bucket.update_metadata(
+    ///     "foo",
+    ///     ObjectMeta {
+    ///         name: "bar".to_string(),
+    ///         description: Some("renamed".to_string()),
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    /// assert_eq!(info.name, "bar");
+    ///
+    /// # context.delete_object_store("update_metadata")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_metadata<T>(&self, object_name: &str, meta: T) -> io::Result<ObjectInfo>
+    where
+        ObjectMeta: From<T>,
+    {
+        let object_meta: ObjectMeta = meta.into();
+        if !is_valid_object_name(&encode_object_name(&object_meta.name)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid object name",
+            ));
+        }
+
+        let mut object_info = self.info(object_name)?;
+        if object_info.deleted {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "object has been deleted",
+            ));
+        }
+
+        let renamed = object_meta.name != object_name;
+        if renamed {
+            if let Ok(existing) = self.info(&object_meta.name) {
+                if !existing.deleted {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        "an object already exists with that name",
+                    ));
+                }
+            }
+        }
+
+        object_info.name = object_meta.name;
+        object_info.description = object_meta.description;
+        object_info.headers = object_meta.headers;
+        object_info.modified = OffsetDateTime::now_utc();
+
+        self.publish_meta(&object_info)?;
+
+        // The chunks are shared, only the information stored under the old name is removed.
+        if renamed {
+            let stream_name = format!("OBJ_{}", self.name);
+            let meta_subject = format!("$O.{}.M.{}", self.name, encode_object_name(object_name));
+            self.context
+                .purge_stream_subject(stream_name, &meta_subject)?;
+        }
+
+        Ok(object_info)
+    }
+
+    // Publishes object information, rolling up any previous information for the object.
+    fn publish_meta(&self, object_info: &ObjectInfo) -> io::Result<()> {
+        let data = serde_json::to_vec(object_info)?;
+        let mut headers = HeaderMap::default();
+        headers.insert(NATS_ROLLUP, ROLLUP_SUBJECT.to_string());
+
+        let subject = format!(
+            "$O.{}.M.{}",
+            &self.name,
+            &encode_object_name(&object_info.name)
+        );
+        let message = Message::new(&subject, None, data, Some(headers));
+        self.context.publish_message(&message)?;
+
+        Ok(())
+    }
+
     /// Places a delete marker and purges the data stream associated with the key.
     ///
     /// # Example
@@ -574,15 +1337,7 @@ impl ObjectStore {
         object_info.size = 0;
         object_info.deleted = true;
 
-        let data = serde_json::to_vec(&object_info)?;
-
-        let mut headers = HeaderMap::default();
-        headers.insert(NATS_ROLLUP, ROLLUP_SUBJECT.to_string());
-
-        let subject = format!("$O.{}.M.{}", &self.name, &encode_object_name(object_name));
-        let message = Message::new(&subject, None, data, Some(headers));
-
-        self.context.publish_message(&message)?;
+        self.publish_meta(&object_info)?;
 
         let stream_name = format!("OBJ_{}", self.name);
         let chunk_subject = format!("$O.{}.C.{}", self.name, object_info.nuid);
@@ -595,6 +1350,9 @@ impl ObjectStore {
 
     /// Watch for changes in the underlying store and receive meta information updates.
     ///
+    /// The latest information for each object present in the bucket is yielded first, followed
+    /// by updates as they happen. Deleted objects are yielded with `deleted` set.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -638,6 +1396,108 @@ impl ObjectStore {
 
         Ok(Watch { subscription })
     }
+
+    /// Returns an iterator over the information of all objects currently in the bucket.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// let bucket = context.create_object_store(&Config {
+    ///     bucket: "list".to_string(),
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// let bytes = vec![0, 1, 2, 3, 4];
+    /// bucket.put("foo", &mut bytes.as_slice())?;
+    ///
+    /// for info in bucket.list()? {
+    ///     println!("{}: {} bytes", info.name, info.size);
+    /// }
+    ///
+    /// # context.delete_object_store("list")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list(&self) -> io::Result<List> {
+        let subject = format!("$O.{}.M.>", &self.name);
+        let subscription = self.context.subscribe_with_options(
+            &subject,
+            &SubscribeOptions::ordered().deliver_last_per_subject(),
+        )?;
+
+        Ok(List {
+            subscription,
+            done: false,
+            include_deleted: false,
+        })
+    }
+}
+
+/// Iterator returned by `list`
+pub struct List {
+    subscription: PushSubscription,
+    done: bool,
+    include_deleted: bool,
+}
+
+impl List {
+    /// Sets whether deleted objects are yielded as well, they are skipped by default.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nats::object_store::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # let bucket = context.create_object_store(&Config {
+    /// #     bucket: "list_deleted".to_string(),
+    /// #     ..Default::default()
+    /// # })?;
+    /// #
+    /// for info in bucket.list()?.include_deleted(true) {
+    ///     println!("{} deleted: {}", info.name, info.deleted);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn include_deleted(mut self, include_deleted: bool) -> List {
+        self.include_deleted = include_deleted;
+        self
+    }
+}
+
+impl Iterator for List {
+    type Item = ObjectInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done || self.subscription.0.num_pending == 0 {
+                return None;
+            }
+
+            let message = self.subscription.next()?;
+            if let Some(info) = message.jetstream_message_info() {
+                if info.pending == 0 {
+                    self.done = true;
+                }
+            }
+
+            // Skip meta entries that can not be decoded as well as delete markers, unless asked for.
+            match serde_json::from_slice::<ObjectInfo>(&message.data) {
+                Ok(object_info) if self.include_deleted || !object_info.deleted => {
+                    return Some(object_info)
+                }
+                _ => continue,
+            }
+        }
+    }
 }
 
 /// Iterator returned by `watch`
@@ -649,9 +1509,17 @@ impl Iterator for Watch {
     type Item = ObjectInfo;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.subscription.next() {
-            Some(message) => Some(serde_json::from_slice(&message.data).unwrap()),
-            None => None,
+        loop {
+            let message = self.subscription.next()?;
+
+            // Skip meta entries that can not be decoded rather than ending the watch.
+            match serde_json::from_slice(&message.data) {
+                Ok(object_info) => return Some(object_info),
+                Err(err) => {
+                    crate::logging::warn!("failed to decode object info: {}", err);
+                    continue;
+                }
+            }
         }
     }
 }
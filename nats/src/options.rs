@@ -21,24 +21,45 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::auth_utils;
+use crate::compression::Compression;
+use crate::error::ServerError;
+use crate::rate_limit::RateLimit;
+use crate::rustls::client::ServerCertVerifier;
 use crate::secure_wipe::SecureString;
+use crate::trace_context::TraceContext;
 use crate::Client;
 use crate::Connection;
 use crate::IntoServerList;
+use crate::ProtocolEvent;
+use crate::ServerInfo;
+use crate::{IdGenerator, Metrics, RequestOutcome};
 
 /// Connect options.
 pub struct Options {
     pub(crate) auth: AuthStyle,
     pub(crate) name: Option<String>,
     pub(crate) no_echo: bool,
+    pub(crate) verbose: bool,
+    pub(crate) pedantic: bool,
     pub(crate) retry_on_failed_connect: bool,
     pub(crate) max_reconnects: Option<usize>,
     pub(crate) reconnect_buffer_size: usize,
+    pub(crate) subscription_capacity: Option<usize>,
+    pub(crate) thread_name_prefix: String,
+    pub(crate) dispatch_workers: Option<usize>,
+    pub(crate) publish_rate_limit: Option<RateLimit>,
+    pub(crate) subject_rate_limits: Vec<(String, RateLimit)>,
+    pub(crate) payload_compression: Option<(Compression, usize)>,
+    pub(crate) decompress_payloads: bool,
     pub(crate) tls_required: bool,
     pub(crate) certificates: Vec<PathBuf>,
     pub(crate) client_cert: Option<PathBuf>,
     pub(crate) client_key: Option<PathBuf>,
+    pub(crate) certificate_pins: Vec<String>,
+    pub(crate) public_key_pins: Vec<String>,
+    pub(crate) certificate_verifier: Option<Arc<dyn ServerCertVerifier>>,
     pub(crate) tls_client_config: Option<crate::rustls::ClientConfig>,
+    pub(crate) server_options: Vec<(String, Arc<Options>)>,
 
     pub(crate) error_callback: ErrorCallback,
     pub(crate) disconnect_callback: Callback,
@@ -46,6 +67,14 @@ pub struct Options {
     pub(crate) reconnect_delay_callback: ReconnectDelayCallback,
     pub(crate) close_callback: Callback,
     pub(crate) lame_duck_callback: Callback,
+    pub(crate) metrics: Option<Arc<dyn Metrics>>,
+    pub(crate) trace_context_provider: Option<TraceContextProvider>,
+    pub(crate) protocol_tap: Option<ProtocolTap>,
+    pub(crate) request_complete_callback: Option<RequestCompleteCallback>,
+    pub(crate) server_error_callback: Option<ServerErrorCallback>,
+    pub(crate) server_info_callback: Option<ServerInfoCallback>,
+    pub(crate) id_generator: Option<Arc<dyn IdGenerator>>,
+    pub(crate) record_path: Option<PathBuf>,
 }
 
 impl fmt::Debug for Options {
@@ -54,20 +83,97 @@ impl fmt::Debug for Options {
             .entry(&"auth", &self.auth)
             .entry(&"name", &self.name)
             .entry(&"no_echo", &self.no_echo)
+            .entry(&"verbose", &self.verbose)
+            .entry(&"pedantic", &self.pedantic)
             .entry(&"retry_on_failed_connect", &self.retry_on_failed_connect)
             .entry(&"reconnect_buffer_size", &self.reconnect_buffer_size)
+            .entry(&"subscription_capacity", &self.subscription_capacity)
+            .entry(&"thread_name_prefix", &self.thread_name_prefix)
+            .entry(&"dispatch_workers", &self.dispatch_workers)
+            .entry(&"publish_rate_limit", &self.publish_rate_limit)
+            .entry(&"subject_rate_limits", &self.subject_rate_limits)
+            .entry(&"payload_compression", &self.payload_compression)
+            .entry(&"decompress_payloads", &self.decompress_payloads)
             .entry(&"max_reconnects", &self.max_reconnects)
             .entry(&"tls_required", &self.tls_required)
             .entry(&"certificates", &self.certificates)
             .entry(&"client_cert", &self.client_cert)
             .entry(&"client_key", &self.client_key)
+            .entry(&"certificate_pins", &self.certificate_pins)
+            .entry(&"public_key_pins", &self.public_key_pins)
+            .entry(
+                &"certificate_verifier",
+                if self.certificate_verifier.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
             .entry(&"tls_client_config", &"XXXXXXXX")
+            .entry(&"server_options", &self.server_options)
+            .entry(&"record_path", &self.record_path)
             .entry(&"error_callback", &self.error_callback)
             .entry(&"disconnect_callback", &self.disconnect_callback)
             .entry(&"reconnect_callback", &self.reconnect_callback)
             .entry(&"reconnect_delay_callback", &"set")
             .entry(&"close_callback", &self.close_callback)
             .entry(&"lame_duck_callback", &self.lame_duck_callback)
+            .entry(
+                &"server_error_callback",
+                if self.server_error_callback.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(
+                &"server_info_callback",
+                if self.server_info_callback.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(
+                &"request_complete_callback",
+                if self.request_complete_callback.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(
+                &"protocol_tap",
+                if self.protocol_tap.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(
+                &"trace_context_provider",
+                if self.trace_context_provider.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(
+                &"id_generator",
+                if self.id_generator.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(
+                &"metrics",
+                if self.metrics.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
             .finish()
     }
 }
@@ -78,20 +184,41 @@ impl Default for Options {
             auth: AuthStyle::NoAuth,
             name: None,
             no_echo: false,
+            verbose: false,
+            pedantic: false,
             retry_on_failed_connect: false,
             reconnect_buffer_size: 8 * 1024 * 1024,
+            subscription_capacity: None,
+            thread_name_prefix: "nats".to_string(),
+            dispatch_workers: None,
+            publish_rate_limit: None,
+            subject_rate_limits: Vec::new(),
+            payload_compression: None,
+            decompress_payloads: cfg!(feature = "compression"),
             max_reconnects: Some(60),
             tls_required: false,
             certificates: Vec::new(),
             client_cert: None,
             client_key: None,
+            certificate_pins: Vec::new(),
+            public_key_pins: Vec::new(),
+            certificate_verifier: None,
             error_callback: ErrorCallback(None),
             disconnect_callback: Callback(None),
             reconnect_callback: Callback(None),
             reconnect_delay_callback: ReconnectDelayCallback(Box::new(backoff)),
             close_callback: Callback(None),
             lame_duck_callback: Callback(None),
+            metrics: None,
+            trace_context_provider: None,
+            protocol_tap: None,
+            request_complete_callback: None,
+            server_error_callback: None,
+            server_info_callback: None,
+            id_generator: None,
+            record_path: None,
             tls_client_config: None,
+            server_options: Vec::new(),
         }
     }
 }
@@ -399,6 +526,44 @@ impl Options {
         self
     }
 
+    /// Asks the server to acknowledge every protocol operation with `+OK`.
+    ///
+    /// This only sets the flag sent in `CONNECT`: the client discards the acknowledgements
+    /// and does not match them to operations, so publishing and subscribing neither wait for
+    /// them nor fail on a `-ERR`. Errors the server sends are reported to the
+    /// [`Options::error_callback`] and [`Options::server_error_callback`] as without this
+    /// option. It is meant for debugging together with [`Options::protocol_tap`], which
+    /// shows each acknowledgement or error next to the operations sent before it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new().verbose().connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verbose(mut self) -> Options {
+        self.verbose = true;
+        self
+    }
+
+    /// Asks the server to strictly check protocol operations, e.g. rejecting publishes to
+    /// subjects containing wildcards. Rejected operations are answered with an `-ERR`
+    /// reported to the [`Options::error_callback`] and
+    /// [`Options::server_error_callback`], and the connection stays open.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new().pedantic().connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pedantic(mut self) -> Options {
+        self.pedantic = true;
+        self
+    }
+
     /// Select option to enable reconnect with backoff
     /// on first failed connection attempt.
     /// The reconnect logic with `max_reconnects` and the
@@ -461,6 +626,148 @@ impl Options {
         self
     }
 
+    /// Set the default number of messages buffered by new subscriptions before messages are
+    /// dropped and a slow consumer error is reported, including subscriptions created
+    /// internally, e.g. for `JetStream` consumers. It can be changed per subscription with
+    /// [`crate::Subscription::set_message_limits`].
+    ///
+    /// By default the buffer is unbounded.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .subscription_capacity(64 * 1024)
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscription_capacity(mut self, capacity: usize) -> Options {
+        self.subscription_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the prefix of the names of threads spawned for the connection, such as the
+    /// `{prefix}_client` thread reading from the server, the `{prefix}_flusher` thread and
+    /// the threads running subscription handlers. Defaults to `nats`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .thread_name_prefix("nats_orders")
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn thread_name_prefix(mut self, prefix: impl Into<String>) -> Options {
+        self.thread_name_prefix = prefix.into();
+        self
+    }
+
+    /// Spawn a [`WorkerPool`](crate::dispatch::WorkerPool) of `workers` threads named
+    /// `{prefix}_dispatch_{index}` with the connection, which subscriptions of the
+    /// connection can share with [`Connection::dispatch_pool`](crate::Connection::dispatch_pool).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::dispatch::Dispatch;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .dispatch_workers(4)
+    ///     .connect("demo.nats.io")?;
+    /// let pool = nc.dispatch_pool().expect("configured with dispatch workers");
+    /// nc.subscribe("orders")?
+    ///     .with_handler_on(Dispatch::Pool(pool), |msg| msg.respond("ok"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dispatch_workers(mut self, workers: usize) -> Options {
+        self.dispatch_workers = Some(workers);
+        self
+    }
+
+    /// Limit the rate of messages published on the connection, including requests. Responses
+    /// and `JetStream` acknowledgements and API requests are not limited. See
+    /// [`crate::rate_limit`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::rate_limit::RateLimit;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .publish_rate_limit(RateLimit::per_second(1000).burst(100))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_rate_limit(mut self, limit: RateLimit) -> Options {
+        self.publish_rate_limit = Some(limit);
+        self
+    }
+
+    /// Limit the rate of messages published to subjects matching `subject`, which may
+    /// contain wildcards, instead of the limit of the connection. All matching subjects
+    /// share the limit, and the first matching override applies.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::rate_limit::RateLimit;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .subject_rate_limit("backfill.>", RateLimit::per_second(500))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subject_rate_limit(mut self, subject: impl Into<String>, limit: RateLimit) -> Options {
+        self.subject_rate_limits.push((subject.into(), limit));
+        self
+    }
+
+    /// Compress published payloads of at least `threshold` bytes, if the server supports
+    /// headers to mark their encoding. See [`crate::compression`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::compression::Compression;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .compress_payloads(Compression::Gzip, 1024)
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn compress_payloads(mut self, compression: Compression, threshold: usize) -> Options {
+        self.payload_compression = Some((compression, threshold));
+        self
+    }
+
+    /// Whether to decompress received payloads marked with a known encoding, which is the
+    /// default.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .decompress_payloads(false)
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn decompress_payloads(mut self, decompress: bool) -> Options {
+        self.decompress_payloads = decompress;
+        self
+    }
+
     /// Establish a `Connection` with one or more NATS servers.
     ///
     /// To pass more than one URL check out the the documentation of [`crate::connect()`].
@@ -511,6 +818,60 @@ impl Options {
         self
     }
 
+    /// Set a callback to be executed with the parsed `-ERR` when an async error
+    /// from a server has been received, in addition to the [`Options::error_callback`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::error::ServerError;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .server_error_callback(|err| match err {
+    ///         ServerError::PermissionsViolation { subject, .. } => {
+    ///             println!("not allowed to use {}", subject)
+    ///         }
+    ///         err => println!("server error: {}", err),
+    ///     })
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_error_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(ServerError) + Send + Sync + 'static,
+    {
+        self.server_error_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set a callback to be executed with the parsed `INFO` when the server sends an update
+    /// after the connection has been established, for example when servers join or leave the
+    /// cluster or the server enters lame duck mode.
+    ///
+    /// The advertised servers are added to the known servers before the callback runs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .server_info_callback(|info| {
+    ///         println!("{} advertises {:?}", info.server_name, info.connect_urls)
+    ///     })
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_info_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ServerInfo) + Send + Sync + 'static,
+    {
+        self.server_info_callback = Some(Arc::new(callback));
+        self
+    }
+
     /// Set a callback to be executed when connectivity to
     /// a server has been lost.
     ///
@@ -598,6 +959,150 @@ impl Options {
         self
     }
 
+    /// Record the raw traffic of all connections to a capture file at `path`, which can be
+    /// served back by `nats::test::ReplayServer` to reproduce parsing and dispatch issues
+    /// without a live server. The credentials sent while connecting are redacted, but
+    /// message payloads are recorded as they are. Traffic of TLS connections is recorded
+    /// before encryption.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .record("capture.nats")
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record(mut self, path: impl AsRef<Path>) -> Self {
+        self.record_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the [`IdGenerator`] used for inboxes and the other unique identifiers created by
+    /// the client, making them reproducible across runs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .id_generator(nats::SeededIdGenerator::new(42))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn id_generator<G: IdGenerator>(mut self, generator: G) -> Self {
+        self.id_generator = Some(Arc::new(generator));
+        self
+    }
+
+    /// Set the [`Metrics`] hooks invoked for messages in and out, reconnects, slow consumers
+    /// and the size of the buffer of messages published while disconnected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// struct Reconnects;
+    ///
+    /// impl nats::Metrics for Reconnects {
+    ///     fn reconnected(&self) {
+    ///         println!("reconnected");
+    ///     }
+    /// }
+    ///
+    /// let nc = nats::Options::new()
+    ///     .metrics(Reconnects)
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn metrics<M: Metrics>(mut self, metrics: M) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    /// Set a provider of the current [`TraceContext`], injected into the headers of every
+    /// published message that doesn't carry a `traceparent` header yet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::trace_context::TraceContext;
+    ///
+    /// let nc = nats::Options::new()
+    ///     .trace_context(|| {
+    ///         // Usually taken from the current span of the tracing library.
+    ///         TraceContext::parse("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")
+    ///     })
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn trace_context<F>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> Option<TraceContext> + Send + Sync + 'static,
+    {
+        self.trace_context_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Set a callback invoked with the subject, round trip time and outcome of every
+    /// request, including the requests made by the JetStream, key-value and object store
+    /// APIs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .on_request_complete(|subject, duration, outcome| {
+    ///         println!("request to {} took {:?}: {:?}", subject, duration, outcome)
+    ///     })
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_request_complete<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&str, Duration, RequestOutcome) + Send + Sync + 'static,
+    {
+        self.request_complete_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set a tap receiving every protocol operation sent to and received from the server,
+    /// for debugging. Credentials in `CONNECT` are redacted, and only the first
+    /// [`crate::TAP_PAYLOAD_PREVIEW`] bytes of payloads are included.
+    ///
+    /// The tap is called while the connection is locked and has to return quickly.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .protocol_tap(|event| {
+    ///         println!(
+    ///             "{:?} {} {:?} ({} bytes)",
+    ///             event.direction, event.op, event.subject, event.payload_len
+    ///         )
+    ///     })
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn protocol_tap<F>(mut self, tap: F) -> Self
+    where
+        F: Fn(&ProtocolEvent) + Send + Sync + 'static,
+    {
+        self.protocol_tap = Some(Arc::new(tap));
+        self
+    }
+
     /// Set a callback to be executed for calculating the backoff duration
     /// to wait before a server reconnection attempt.
     ///
@@ -666,6 +1171,109 @@ impl Options {
         self.certificates.push(path.as_ref().to_owned());
         self
     }
+
+    /// Pins a server certificate by the base64 encoded SHA-256 hash of its DER encoding.
+    ///
+    /// Once any certificate or public key is pinned, TLS handshakes fail unless the
+    /// certificate presented by the server matches one of the pins, in addition to being
+    /// verified against the root certificates. Pins are ignored when a custom
+    /// [`Options::tls_client_config()`] is set.
+    ///
+    /// The hash of a certificate can be computed with
+    /// `openssl x509 -in cert.pem -outform der | openssl dgst -sha256 -binary | base64`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .add_root_certificate("my-certs.pem")
+    ///     .pin_certificate("FoAqbvtL8OOIjZk4LySxNWp3Qci5oqC1iKgYFPpIKsY=")
+    ///     .connect("tls://demo.nats.io:4443")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pin_certificate(mut self, sha256: impl Into<String>) -> Options {
+        self.certificate_pins.push(sha256.into());
+        self
+    }
+
+    /// Pins a server public key by the base64 encoded SHA-256 hash of its DER encoded
+    /// `SubjectPublicKeyInfo`.
+    ///
+    /// Unlike [`Options::pin_certificate()`], the pin survives certificates being reissued
+    /// for the same key. See [`Options::pin_certificate()`] for how pins are checked.
+    ///
+    /// The hash of a public key can be computed with
+    /// `openssl x509 -in cert.pem -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .add_root_certificate("my-certs.pem")
+    ///     .pin_public_key("I1vXiO7g8FdH6wgMvOUnzObtikLvcFuZntdfMO31fB4=")
+    ///     .connect("tls://demo.nats.io:4443")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pin_public_key(mut self, sha256: impl Into<String>) -> Options {
+        self.public_key_pins.push(sha256.into());
+        self
+    }
+
+    /// Sets a custom verifier of server certificates, used instead of verifying them
+    /// against the root certificates.
+    ///
+    /// This allows plugging in platform verifiers or identity schemes like SPIFFE, while
+    /// keeping the client certificate and pinning options. Pinned certificates and public
+    /// keys are checked after the verifier accepts a certificate. The verifier is ignored
+    /// when a custom [`Options::tls_client_config()`] is set.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::rustls::client::WebPkiVerifier;
+    ///
+    /// let roots = nats::rustls::RootCertStore::empty();
+    /// let nc = nats::Options::new()
+    ///     .certificate_verifier(WebPkiVerifier::new(roots, None))
+    ///     .connect("tls://demo.nats.io:4443")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn certificate_verifier<V: ServerCertVerifier + 'static>(mut self, verifier: V) -> Options {
+        self.certificate_verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Uses the authentication and TLS settings of `options` when connecting to `server`,
+    /// for instance to reach leafnode clusters requiring different credentials from one
+    /// server pool.
+    ///
+    /// The server is matched by host and port, also when reconnecting to it or when it was
+    /// discovered from the cluster. Authentication embedded in the server URL still takes
+    /// precedence, and the authentication and TLS settings of these options still apply
+    /// where `options` leaves them unset. The other settings of `options` are ignored, and
+    /// its certificates are not reloaded by
+    /// [`Connection::reload_tls`](crate::Connection::reload_tls).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::with_credentials("east.creds")
+    ///     .server_options(
+    ///         "tls://west.example.com:4222",
+    ///         nats::Options::with_credentials("west.creds").add_root_certificate("west.pem"),
+    ///     )
+    ///     .connect("east.example.com:4222,tls://west.example.com:4222")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_options(mut self, server: &str, options: Options) -> Options {
+        self.server_options
+            .push((server.to_string(), Arc::new(options)));
+        self
+    }
 }
 
 #[derive(Clone, Default)]
@@ -734,6 +1342,15 @@ impl fmt::Debug for Callback {
     }
 }
 
+pub(crate) type ServerErrorCallback = Arc<dyn Fn(ServerError) + Send + Sync>;
+pub(crate) type ServerInfoCallback = Arc<dyn Fn(&ServerInfo) + Send + Sync>;
+
+pub(crate) type RequestCompleteCallback = Arc<dyn Fn(&str, Duration, RequestOutcome) + Send + Sync>;
+
+pub(crate) type ProtocolTap = Arc<dyn Fn(&ProtocolEvent) + Send + Sync>;
+
+pub(crate) type TraceContextProvider = Arc<dyn Fn() -> Option<TraceContext> + Send + Sync>;
+
 pub(crate) struct ReconnectDelayCallback(Box<dyn Fn(usize) -> Duration + Send + Sync + 'static>);
 impl ReconnectDelayCallback {
     pub fn call(&self, reconnects: usize) -> Duration {
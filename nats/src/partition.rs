@@ -0,0 +1,214 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ordered per key, parallel consumption of a subject over core NATS.
+//!
+//! A [`Partitioner`] maps keys to one of a fixed number of partitions with a stable hash,
+//! the 32-bit FNV-1a of the key, and publishes messages for partition `i` of `subject` on
+//! `subject.p<i>`. Every partition is consumed by its own queue subscription and handled
+//! by its own thread, so messages with the same key are handled one at a time and in order
+//! by each member of the queue group, while partitions are handled in parallel.
+//!
+//! Publishers and subscribers must agree on the number of partitions.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! use nats::partition::Partitioner;
+//!
+//! let orders = Partitioner::new("orders", 4);
+//! let _handler = orders
+//!     .queue_subscribe(&nc, "billing")?
+//!     .with_handler(|message| {
+//!         println!("received {} on {}", message, message.subject);
+//!         Ok(())
+//!     });
+//!
+//! orders.publish(&nc, "customer-42", "order created")?;
+//! orders.publish(&nc, "customer-42", "order paid")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::{Connection, Handler, Message, Subscription};
+
+const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// Returns the partition of a key, out of the given number of partitions.
+///
+/// The partition only depends on the key and the number of partitions, so it is the same
+/// across processes, platforms and versions.
+///
+/// # Example
+/// ```
+/// use nats::partition::partition;
+///
+/// assert_eq!(partition("customer-42", 4), partition("customer-42", 4));
+/// assert!(partition("customer-42", 4) < 4);
+/// assert_eq!(partition("customer-42", 1), 0);
+/// ```
+pub fn partition(key: impl AsRef<[u8]>, partitions: usize) -> usize {
+    let hash = key.as_ref().iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(FNV_PRIME)
+    });
+    hash as usize % partitions.max(1)
+}
+
+/// Maps keys to the partition subjects of a subject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partitioner {
+    subject: String,
+    partitions: usize,
+}
+
+impl Partitioner {
+    /// Creates a partitioner of a subject into the given number of partitions, at least one.
+    pub fn new(subject: &str, partitions: usize) -> Partitioner {
+        Partitioner {
+            subject: subject.to_string(),
+            partitions: partitions.max(1),
+        }
+    }
+
+    /// Returns the number of partitions.
+    pub fn partitions(&self) -> usize {
+        self.partitions
+    }
+
+    /// Returns the partition of a key.
+    pub fn partition(&self, key: impl AsRef<[u8]>) -> usize {
+        partition(key, self.partitions)
+    }
+
+    /// Returns the subject of a partition.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::partition::Partitioner;
+    ///
+    /// let orders = Partitioner::new("orders", 4);
+    /// assert_eq!(orders.subject(3), "orders.p3");
+    /// ```
+    pub fn subject(&self, partition: usize) -> String {
+        format!("{}.p{}", self.subject, partition)
+    }
+
+    /// Returns the subject of the partition of a key.
+    pub fn subject_for(&self, key: impl AsRef<[u8]>) -> String {
+        self.subject(self.partition(key))
+    }
+
+    /// Publishes a message on the subject of the partition of its key.
+    pub fn publish(
+        &self,
+        connection: &Connection,
+        key: impl AsRef<[u8]>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        connection.publish(self.subject_for(key), msg)
+    }
+
+    /// Subscribes to every partition in a queue group, so instances share the partitions.
+    pub fn queue_subscribe(
+        &self,
+        connection: &Connection,
+        queue_group: &str,
+    ) -> io::Result<PartitionedSubscription> {
+        let subscriptions = (0..self.partitions)
+            .map(|partition| connection.queue_subscribe(self.subject(partition), queue_group))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(PartitionedSubscription { subscriptions })
+    }
+}
+
+/// The subscriptions to every partition of a subject, created by
+/// [`Partitioner::queue_subscribe`].
+#[derive(Debug)]
+pub struct PartitionedSubscription {
+    subscriptions: Vec<Subscription>,
+}
+
+impl PartitionedSubscription {
+    /// Returns the subscriptions, indexed by partition.
+    pub fn partitions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
+    /// Handles the messages of every partition on a thread per partition, so the messages
+    /// of a partition are handled in order while partitions are handled in parallel.
+    ///
+    /// Like with [`Subscription::with_handler`], dropping the returned handler does not
+    /// unsubscribe.
+    pub fn with_handler<F>(self, handler: F) -> PartitionedHandler
+    where
+        F: Fn(Message) -> io::Result<()> + Send + Sync + 'static,
+    {
+        let handler = Arc::new(handler);
+        let handlers = self
+            .subscriptions
+            .into_iter()
+            .map(|subscription| {
+                let handler = handler.clone();
+                subscription.with_handler(move |message| handler(message))
+            })
+            .collect();
+        PartitionedHandler { handlers }
+    }
+
+    /// Unsubscribes from every partition.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        for subscription in self.subscriptions {
+            subscription.unsubscribe()?;
+        }
+        Ok(())
+    }
+
+    /// Unsubscribes from every partition after the server delivered the messages in flight,
+    /// which can still be received.
+    pub fn drain(&self) -> io::Result<()> {
+        for subscription in &self.subscriptions {
+            subscription.drain()?;
+        }
+        Ok(())
+    }
+}
+
+/// Handlers of every partition of a subject, see [`PartitionedSubscription::with_handler`].
+pub struct PartitionedHandler {
+    handlers: Vec<Handler>,
+}
+
+impl fmt::Debug for PartitionedHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionedHandler")
+            .field("partitions", &self.handlers.len())
+            .finish()
+    }
+}
+
+impl PartitionedHandler {
+    /// Unsubscribes from every partition. Messages already being handled are not
+    /// interrupted.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        for handler in self.handlers {
+            handler.unsubscribe()?;
+        }
+        Ok(())
+    }
+}
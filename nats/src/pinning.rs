@@ -0,0 +1,172 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pinning of server certificates.
+
+use std::io::{self, ErrorKind};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use ring::digest::{digest, SHA256};
+
+use crate::rustls::client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use crate::rustls::{
+    Certificate, CertificateError, DigitallySignedStruct, ServerName, SignatureScheme,
+};
+
+/// Verifies server certificates with another verifier, then requires the certificate or
+/// its public key to match one of the pins.
+pub(crate) struct PinnedVerifier {
+    verifier: Arc<dyn ServerCertVerifier>,
+    certificates: Vec<Vec<u8>>,
+    public_keys: Vec<Vec<u8>>,
+}
+
+impl PinnedVerifier {
+    /// Wraps a verifier with base64 encoded SHA-256 pins of certificates and of public keys.
+    pub(crate) fn new(
+        verifier: Arc<dyn ServerCertVerifier>,
+        certificates: &[String],
+        public_keys: &[String],
+    ) -> io::Result<PinnedVerifier> {
+        Ok(PinnedVerifier {
+            verifier,
+            certificates: decode_pins(certificates)?,
+            public_keys: decode_pins(public_keys)?,
+        })
+    }
+
+    fn matches(&self, certificate: &Certificate) -> bool {
+        let hash = digest(&SHA256, &certificate.0);
+        if self.certificates.iter().any(|pin| pin == hash.as_ref()) {
+            return true;
+        }
+
+        match subject_public_key_info(&certificate.0) {
+            Some(spki) => {
+                let hash = digest(&SHA256, spki);
+                self.public_keys.iter().any(|pin| pin == hash.as_ref())
+            }
+            None => false,
+        }
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, crate::rustls::Error> {
+        let verified = self.verifier.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )?;
+
+        if self.matches(end_entity) {
+            Ok(verified)
+        } else {
+            Err(crate::rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, crate::rustls::Error> {
+        self.verifier.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, crate::rustls::Error> {
+        self.verifier.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.verifier.supported_verify_schemes()
+    }
+
+    fn request_scts(&self) -> bool {
+        self.verifier.request_scts()
+    }
+}
+
+fn decode_pins(pins: &[String]) -> io::Result<Vec<Vec<u8>>> {
+    pins.iter()
+        .map(|pin| match base64::decode(pin) {
+            Ok(hash) if hash.len() == SHA256.output_len => Ok(hash),
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid certificate pin {pin}, expected a base64 encoded SHA-256 hash"),
+            )),
+        })
+        .collect()
+}
+
+/// Returns the DER encoded `SubjectPublicKeyInfo` of a DER encoded certificate.
+fn subject_public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = split_element(certificate)?;
+    let (_, tbs_certificate, _) = split_element(certificate)?;
+
+    // Skip the optional version, then the serial number, signature algorithm, issuer,
+    // validity and subject.
+    let mut rest = tbs_certificate;
+    if rest.first() == Some(&0xa0) {
+        rest = split_element(rest)?.2;
+    }
+    for _ in 0..5 {
+        rest = split_element(rest)?.2;
+    }
+
+    let (spki, _, _) = split_element(rest)?;
+    (spki[0] == 0x30).then_some(spki)
+}
+
+/// Splits the first DER element off `input`, returning the whole element, its contents and
+/// the remaining input.
+fn split_element(input: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let first = *input.get(1)?;
+    let (len, header) = if first < 0x80 {
+        (first as usize, 2)
+    } else {
+        let octets = (first & 0x7f) as usize;
+        if octets == 0 || octets > 4 {
+            return None;
+        }
+        let len = input
+            .get(2..2 + octets)?
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (len, 2 + octets)
+    };
+
+    let end = header.checked_add(len)?;
+    let element = input.get(..end)?;
+    Some((element, &element[header..], &input[end..]))
+}
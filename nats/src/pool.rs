@@ -0,0 +1,201 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pools of connections sharing the load of publishing.
+//!
+//! A single connection writes all of its messages to one TCP stream from one flusher
+//! thread, which caps its throughput. A [`ConnectionPool`] spreads publishes and requests
+//! over several connections in round-robin order, to the same or different servers.
+//!
+//! Messages published through a pool may arrive out of order, since consecutive messages
+//! travel over different connections. Publish through one of its connections, see
+//! [`ConnectionPool::get`], when ordering matters.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use nats::pool::ConnectionPool;
+//!
+//! let pool = ConnectionPool::connect(4, "demo.nats.io")?;
+//! for i in 0..1000 {
+//!     pool.publish("events", format!("event {}", i))?;
+//! }
+//! pool.flush()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Connection, HeaderMap, IntoServerList, Message, Options, Subject};
+
+/// A set of connections that publishes and requests are spread over in round-robin order.
+///
+/// Clones share the connections and the round-robin position.
+#[derive(Debug, Clone)]
+pub struct ConnectionPool {
+    connections: Arc<[Connection]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl ConnectionPool {
+    /// Creates a pool of existing connections, which may be connected to different
+    /// servers or with different options.
+    ///
+    /// Fails with `InvalidInput` if there are no connections.
+    pub fn new(connections: Vec<Connection>) -> io::Result<ConnectionPool> {
+        if connections.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a connection pool needs at least one connection",
+            ));
+        }
+
+        Ok(ConnectionPool {
+            connections: connections.into(),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Opens `size` connections to the servers with default options.
+    pub fn connect<I: IntoServerList + Clone>(
+        size: usize,
+        nats_urls: I,
+    ) -> io::Result<ConnectionPool> {
+        ConnectionPool::connect_with_options(size, nats_urls, Options::new)
+    }
+
+    /// Opens `size` connections to the servers, each with the options returned by
+    /// `options`. Connections opened before a failure are closed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::pool::ConnectionPool;
+    ///
+    /// let pool = ConnectionPool::connect_with_options(4, "demo.nats.io", || {
+    ///     nats::Options::new().with_name("producer")
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connect_with_options<I, F>(
+        size: usize,
+        nats_urls: I,
+        mut options: F,
+    ) -> io::Result<ConnectionPool>
+    where
+        I: IntoServerList + Clone,
+        F: FnMut() -> Options,
+    {
+        let mut connections = Vec::with_capacity(size);
+        for _ in 0..size {
+            match options().connect(nats_urls.clone()) {
+                Ok(connection) => connections.push(connection),
+                Err(err) => {
+                    for connection in connections {
+                        connection.close();
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        ConnectionPool::new(connections)
+    }
+
+    /// Returns the next connection in round-robin order.
+    pub fn get(&self) -> &Connection {
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        &self.connections[next % self.connections.len()]
+    }
+
+    /// Returns all connections of the pool.
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    /// Returns the number of connections in the pool.
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Returns true if the pool has no connections, which never happens.
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    /// Publish a message on the next connection.
+    pub fn publish(&self, subject: impl AsRef<str>, msg: impl AsRef<[u8]>) -> io::Result<()> {
+        self.get().publish(subject, msg)
+    }
+
+    /// Publish a message with an optional reply subject and headers on the next connection.
+    pub fn publish_with_reply_or_headers(
+        &self,
+        subject: impl AsRef<str>,
+        reply: Option<&str>,
+        headers: Option<&HeaderMap>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        self.get()
+            .publish_with_reply_or_headers(subject, reply, headers, msg)
+    }
+
+    /// Send a request on the next connection and wait for the response.
+    pub fn request(
+        &self,
+        subject: impl Into<Subject>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<Message> {
+        self.get().request(subject, msg)
+    }
+
+    /// Send a request on the next connection and wait for the response, or fail with
+    /// `TimedOut` after the timeout.
+    pub fn request_timeout(
+        &self,
+        subject: impl Into<Subject>,
+        msg: impl AsRef<[u8]>,
+        timeout: Duration,
+    ) -> io::Result<Message> {
+        self.get().request_timeout(subject, msg, timeout)
+    }
+
+    /// Send a request with headers on the next connection and wait for the response, or
+    /// fail with `TimedOut` after the timeout if one is given.
+    pub fn request_with_headers_or_timeout(
+        &self,
+        subject: impl Into<Subject>,
+        headers: Option<&HeaderMap>,
+        timeout: Option<Duration>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<Message> {
+        self.get()
+            .request_with_headers_or_timeout(subject, headers, timeout, msg)
+    }
+
+    /// Flush all connections, failing with the first error.
+    pub fn flush(&self) -> io::Result<()> {
+        self.connections.iter().try_for_each(Connection::flush)
+    }
+
+    /// Close all connections of the pool, including those held by clones.
+    pub fn close(self) {
+        for connection in self.connections.iter() {
+            connection.clone().close();
+        }
+    }
+}
@@ -49,6 +49,9 @@ pub(crate) enum ServerOp {
     /// `PONG`
     Pong,
 
+    /// `+OK`
+    Ok,
+
     /// `-ERR <error message>`
     Err(String),
 
@@ -124,6 +127,10 @@ pub(crate) fn decode(mut stream: impl BufRead) -> io::Result<Option<ServerOp>> {
         return Ok(Some(ServerOp::Pong));
     }
 
+    if op == "+OK" {
+        return Ok(Some(ServerOp::Ok));
+    }
+
     if op == "INFO" {
         // Parse the JSON-formatted server information.
         let server_info = ServerInfo::parse(&line["INFO".len()..])
@@ -0,0 +1,219 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client-side rate limiting of publishes.
+//!
+//! A [`RateLimit`] is enforced with a token bucket: it allows bursts of up to
+//! [`RateLimit::burst`] messages, refilled at the configured rate. Limits are set with
+//! [`Options::publish_rate_limit`](crate::Options::publish_rate_limit) for the whole
+//! connection, and overridden for subjects with
+//! [`Options::subject_rate_limit`](crate::Options::subject_rate_limit).
+//!
+//! Messages the client publishes on its own behalf are not limited: responses to requests,
+//! `JetStream` acknowledgements and `JetStream` API requests, including those of contexts
+//! with a custom API prefix.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use nats::rate_limit::{RateLimit, RateLimitPolicy};
+//!
+//! let nc = nats::Options::new()
+//!     .publish_rate_limit(RateLimit::per_second(1000))
+//!     .subject_rate_limit(
+//!         "audit.>",
+//!         RateLimit::per_second(10).policy(RateLimitPolicy::Error),
+//!     )
+//!     .connect("demo.nats.io")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::Options;
+
+/// What publishing does when over the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitPolicy {
+    /// Block until the message is allowed.
+    #[default]
+    Block,
+    /// Fail with [`Error::RateLimited`](crate::Error::RateLimited) of kind
+    /// `io::ErrorKind::WouldBlock`.
+    Error,
+}
+
+/// A limit on the rate of published messages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    rate: f64,
+    burst: u32,
+    policy: RateLimitPolicy,
+}
+
+impl RateLimit {
+    /// Allows `messages` messages per second, at least one, in bursts of up to as many
+    /// messages.
+    pub fn per_second(messages: u32) -> RateLimit {
+        let messages = messages.max(1);
+        RateLimit {
+            rate: f64::from(messages),
+            burst: messages,
+            policy: RateLimitPolicy::default(),
+        }
+    }
+
+    /// Sets how many messages, at least one, can be published at once after being idle.
+    pub fn burst(mut self, burst: u32) -> RateLimit {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Sets what publishing does when over the limit, blocking by default.
+    pub fn policy(mut self, policy: RateLimitPolicy) -> RateLimit {
+        self.policy = policy;
+        self
+    }
+}
+
+/// A token bucket enforcing a [`RateLimit`].
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    /// Available tokens, negative when publishers are waiting for reserved tokens, and the
+    /// time they were last refilled.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> TokenBucket {
+        TokenBucket {
+            limit,
+            state: Mutex::new((f64::from(limit.burst), Instant::now())),
+        }
+    }
+
+    /// Takes a token, returning how long to wait before using it, or `None` if the policy
+    /// does not allow waiting.
+    fn reserve(&self, wait: bool) -> Option<Duration> {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        let refill = now.duration_since(state.1).as_secs_f64() * self.limit.rate;
+        state.0 = (state.0 + refill).min(f64::from(self.limit.burst));
+        state.1 = now;
+
+        if state.0 >= 1.0 {
+            state.0 -= 1.0;
+            return Some(Duration::ZERO);
+        }
+        if !wait {
+            return None;
+        }
+        let delay = Duration::from_secs_f64((1.0 - state.0) / self.limit.rate);
+        state.0 -= 1.0;
+        Some(delay)
+    }
+}
+
+/// Prefix of the subjects of `JetStream` acknowledgements and API requests.
+const JETSTREAM_PREFIX: &str = "$JS.";
+
+/// The rate limits of a connection.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    default: Option<TokenBucket>,
+    subjects: Vec<(String, TokenBucket)>,
+    /// Prefixes of subjects the client publishes to internally, which are never limited.
+    exempt: Mutex<Vec<String>>,
+}
+
+impl RateLimiter {
+    /// Creates the rate limiter configured by the options, if any limit is set.
+    pub(crate) fn new(options: &Options) -> Option<RateLimiter> {
+        if options.publish_rate_limit.is_none() && options.subject_rate_limits.is_empty() {
+            return None;
+        }
+
+        Some(RateLimiter {
+            default: options.publish_rate_limit.map(TokenBucket::new),
+            subjects: options
+                .subject_rate_limits
+                .iter()
+                .map(|(subject, limit)| (subject.clone(), TokenBucket::new(*limit)))
+                .collect(),
+            exempt: Mutex::new(vec![JETSTREAM_PREFIX.to_string()]),
+        })
+    }
+
+    /// Exempts subjects starting with `prefix`, e.g. a custom `JetStream` API prefix.
+    pub(crate) fn exempt(&self, prefix: &str) {
+        let mut exempt = self.exempt.lock();
+        if !exempt
+            .iter()
+            .any(|known| prefix.starts_with(known.as_str()))
+        {
+            exempt.push(prefix.to_string());
+        }
+    }
+
+    /// Returns the bucket of the first subject override matching `subject`, falling back
+    /// to the limit of the connection.
+    fn bucket(&self, subject: &str) -> Option<&TokenBucket> {
+        if self
+            .exempt
+            .lock()
+            .iter()
+            .any(|prefix| subject.starts_with(prefix.as_str()))
+        {
+            return None;
+        }
+
+        self.subjects
+            .iter()
+            .find(|(filter, _)| crate::subject::matches(filter, subject))
+            .map(|(_, bucket)| bucket)
+            .or(self.default.as_ref())
+    }
+
+    /// Waits until a message can be published to `subject`, or fails if it is over the
+    /// limit and the policy is [`RateLimitPolicy::Error`].
+    pub(crate) fn acquire(&self, subject: &str) -> io::Result<()> {
+        if let Some(bucket) = self.bucket(subject) {
+            let wait = bucket.limit.policy == RateLimitPolicy::Block;
+            match bucket.reserve(wait) {
+                Some(delay) if !delay.is_zero() => thread::sleep(delay),
+                Some(_) => {}
+                None => return Err(crate::Error::RateLimited.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`RateLimiter::acquire`] but returns `None` instead of blocking.
+    pub(crate) fn try_acquire(&self, subject: &str) -> Option<io::Result<()>> {
+        if let Some(bucket) = self.bucket(subject) {
+            if bucket.reserve(false).is_none() {
+                return match bucket.limit.policy {
+                    RateLimitPolicy::Block => None,
+                    RateLimitPolicy::Error => Some(Err(crate::Error::RateLimited.into())),
+                };
+            }
+        }
+        Some(Ok(()))
+    }
+}
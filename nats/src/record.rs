@@ -0,0 +1,110 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Captures of the raw traffic of a connection, recorded with [`crate::Options::record`].
+//!
+//! A capture is a sequence of records, each a header line `<marker> <length>\r\n` followed by
+//! `<length>` bytes and `\r\n`. The marker is `+` for a new connection, `<` for bytes received
+//! from the server and `>` for bytes sent by the client.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use parking_lot::Mutex;
+
+/// A chunk of recorded traffic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Record {
+    /// Bytes received from the server.
+    Inbound(Vec<u8>),
+    /// Bytes sent by the client.
+    Outbound(Vec<u8>),
+}
+
+/// Appends the traffic of all connections of a client to a capture file.
+pub(crate) struct Recorder {
+    file: Mutex<BufWriter<File>>,
+}
+
+impl Recorder {
+    /// Creates or truncates the capture file at `path`.
+    pub(crate) fn create(path: &Path) -> io::Result<Recorder> {
+        Ok(Recorder {
+            file: Mutex::new(BufWriter::new(File::create(path)?)),
+        })
+    }
+
+    /// Marks the start of a new connection.
+    pub(crate) fn connected(&self) {
+        self.write(b'+', &[]);
+    }
+
+    /// Records bytes received from the server.
+    pub(crate) fn inbound(&self, bytes: &[u8]) {
+        self.write(b'<', bytes);
+    }
+
+    /// Records bytes sent by the client.
+    pub(crate) fn outbound(&self, bytes: &[u8]) {
+        self.write(b'>', bytes);
+    }
+
+    fn write(&self, marker: u8, bytes: &[u8]) {
+        // Recording is best effort and must never fail the connection.
+        let mut file = self.file.lock();
+        let res = write!(file, "{} {}\r\n", marker as char, bytes.len())
+            .and_then(|_| file.write_all(bytes))
+            .and_then(|_| file.write_all(b"\r\n"))
+            .and_then(|_| file.flush());
+        if let Err(err) = res {
+            crate::logging::warn!("failed to record traffic: {}", err);
+        }
+    }
+}
+
+/// Reads a capture, returning the records of every connection in order.
+pub(crate) fn read_capture(path: &Path) -> io::Result<Vec<Vec<Record>>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid capture");
+    let data = fs::read(path)?;
+    let mut rest = &data[..];
+    let mut connections = Vec::new();
+
+    while !rest.is_empty() {
+        let end = memchr::memmem::find(rest, b"\r\n").ok_or_else(invalid)?;
+        let header = std::str::from_utf8(&rest[..end]).map_err(|_| invalid())?;
+        let (marker, len) = header.split_once(' ').ok_or_else(invalid)?;
+        let len: usize = len.parse().map_err(|_| invalid())?;
+        rest = &rest[end + 2..];
+        if rest.len() < len + 2 {
+            return Err(invalid());
+        }
+        let bytes = rest[..len].to_vec();
+        rest = &rest[len + 2..];
+
+        match marker {
+            "+" => connections.push(Vec::new()),
+            "<" => connections
+                .last_mut()
+                .ok_or_else(invalid)?
+                .push(Record::Inbound(bytes)),
+            ">" => connections
+                .last_mut()
+                .ok_or_else(invalid)?
+                .push(Record::Outbound(bytes)),
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(connections)
+}
@@ -0,0 +1,181 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Requests sharing a single response subscription and a single timer.
+//!
+//! Each request gets a reply subject under a wildcard subscription of the connection, whose
+//! handler completes the request on the client thread. Deadlines are kept in a
+//! [`TimerWheel`] expired by one thread, which runs while requests are pending.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel as channel;
+use parking_lot::Mutex;
+
+use crate::client::Client;
+use crate::timer_wheel::TimerWheel;
+use crate::{Error, Message};
+
+/// Resolution of request deadlines.
+const RESOLUTION: Duration = Duration::from_millis(10);
+
+/// Number of slots of the timer wheel, covering about five seconds per revolution.
+const SLOTS: usize = 512;
+
+/// Completes a request with its response or error.
+pub(crate) type Completion = Box<dyn FnOnce(io::Result<Message>) + Send>;
+
+struct State {
+    /// Completions of pending requests, by reply token.
+    pending: HashMap<String, Completion>,
+    timers: TimerWheel<String>,
+    next_token: u64,
+    /// Set while the thread expiring timers runs.
+    expiring: bool,
+}
+
+pub(crate) struct RequestMux {
+    /// Prefix of the reply subjects, ending with a dot.
+    prefix: String,
+    /// Name of the thread expiring timers.
+    thread_name: String,
+    state: Mutex<State>,
+}
+
+impl RequestMux {
+    /// Creates a multiplexer, subscribing to its reply subjects.
+    pub(crate) fn new(client: &Client, inbox: String) -> io::Result<Arc<RequestMux>> {
+        let mux = Arc::new(RequestMux {
+            prefix: format!("{}.", inbox),
+            thread_name: client.thread_name("request_timer"),
+            state: Mutex::new(State {
+                pending: HashMap::new(),
+                timers: TimerWheel::new(RESOLUTION, SLOTS),
+                next_token: 0,
+                expiring: false,
+            }),
+        });
+
+        let (sid, _receiver) = client.subscribe(&format!("{}*", mux.prefix), None)?;
+        let weak = Arc::downgrade(&mux);
+        client.set_handler(
+            sid,
+            Arc::new(move |message: Message| {
+                if let Some(mux) = weak.upgrade() {
+                    mux.respond(message);
+                }
+            }),
+        );
+        Ok(mux)
+    }
+
+    /// Registers a request expiring at the deadline, returning its reply subject.
+    pub(crate) fn register(self: &Arc<Self>, deadline: Instant, completion: Completion) -> String {
+        let mut state = self.state.lock();
+        let token = state.next_token.to_string();
+        state.next_token += 1;
+        state.pending.insert(token.clone(), completion);
+        state.timers.insert(deadline, token.clone());
+
+        if !state.expiring {
+            state.expiring = true;
+            let mux = Arc::downgrade(self);
+            let resolution = state.timers.resolution();
+            thread::Builder::new()
+                .name(self.thread_name.clone())
+                .spawn(move || run_timer(mux, resolution))
+                .expect("threads should be spawnable");
+        }
+
+        format!("{}{}", self.prefix, token)
+    }
+
+    /// Removes a request without completing it, for instance when publishing it failed.
+    pub(crate) fn cancel(&self, reply: &str) {
+        if let Some(token) = reply.strip_prefix(self.prefix.as_str()) {
+            self.state.lock().pending.remove(token);
+        }
+    }
+
+    /// Completes the request a response is for.
+    fn respond(&self, message: Message) {
+        let completion = message
+            .subject
+            .strip_prefix(self.prefix.as_str())
+            .and_then(|token| self.state.lock().pending.remove(token));
+
+        if let Some(completion) = completion {
+            if message.is_no_responders() {
+                completion(Err(Error::NoResponders.into()));
+            } else {
+                completion(Ok(message));
+            }
+        }
+    }
+}
+
+/// Expires the deadlines of requests until none are pending.
+fn run_timer(mux: Weak<RequestMux>, resolution: Duration) {
+    loop {
+        thread::sleep(resolution);
+
+        let mux = match mux.upgrade() {
+            Some(mux) => mux,
+            None => return,
+        };
+
+        let mut state = mux.state.lock();
+        let expired: Vec<Completion> = state
+            .timers
+            .expire(Instant::now())
+            .into_iter()
+            .filter_map(|token| state.pending.remove(&token))
+            .collect();
+        let done = state.timers.is_empty();
+        if done {
+            state.expiring = false;
+        }
+        drop(state);
+
+        for completion in expired {
+            completion(Err(Error::TimedOut.into()));
+        }
+        if done {
+            return;
+        }
+    }
+}
+
+/// A request awaiting its response, see [`Connection::send_request`](crate::Connection::send_request).
+#[derive(Debug)]
+pub struct PendingRequest {
+    pub(crate) response: channel::Receiver<io::Result<Message>>,
+}
+
+impl PendingRequest {
+    /// Blocks until the response arrives or the deadline of the request passes.
+    pub fn wait(self) -> io::Result<Message> {
+        self.response
+            .recv()
+            .unwrap_or_else(|_| Err(io::ErrorKind::ConnectionReset.into()))
+    }
+
+    /// Returns the response or error of the request if it completed.
+    pub fn try_wait(&self) -> Option<io::Result<Message>> {
+        self.response.try_recv().ok()
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dispatch of messages to handlers by subject pattern.
+//!
+//! A [`Router`] subscribes to the pattern of every route and runs the handlers on a pool of
+//! worker threads. The tokens matched by the wildcards of a pattern are passed to the
+//! handler as [`Params`], named by their 1-based position in the pattern. A trailing `>`
+//! captures all remaining tokens as one parameter.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! use nats::router::Router;
+//!
+//! let _router = Router::new()
+//!     .route("orders.*.created", |message, params| {
+//!         message.respond(format!("created order {}", &params["1"]))
+//!     })
+//!     .route("orders.*.items.>", |message, params| {
+//!         message.respond(format!("item {} of order {}", &params["2"], &params["1"]))
+//!     })
+//!     .attach(&nc)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::ops::Index;
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_channel as channel;
+
+use crate::{Connection, Message, Subscription};
+
+/// The tokens matched by the wildcards of a route, named by their 1-based position.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params(HashMap<String, String>);
+
+impl Params {
+    /// Matches a subject against a pattern, returning the captured tokens if it matches.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::router::Params;
+    ///
+    /// let params = Params::capture("orders.*.items.>", "orders.1.items.2.price").unwrap();
+    /// assert_eq!(&params["1"], "1");
+    /// assert_eq!(&params["2"], "2.price");
+    /// assert!(Params::capture("orders.*", "orders.1.items").is_none());
+    /// ```
+    pub fn capture(pattern: &str, subject: &str) -> Option<Params> {
+        let mut params = HashMap::new();
+        let mut tokens = subject.split('.');
+        for filter in pattern.split('.') {
+            let token = tokens.next()?;
+            match filter {
+                "*" => {
+                    params.insert((params.len() + 1).to_string(), token.to_string());
+                }
+                ">" => {
+                    let rest: Vec<&str> = std::iter::once(token).chain(tokens).collect();
+                    params.insert((params.len() + 1).to_string(), rest.join("."));
+                    return Some(Params(params));
+                }
+                filter if filter == token => {}
+                _ => return None,
+            }
+        }
+        if tokens.next().is_some() {
+            return None;
+        }
+        Some(Params(params))
+    }
+
+    /// Returns the parameter with the given name, if captured.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Returns the number of captured parameters.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no parameters were captured.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Index<&str> for Params {
+    type Output = str;
+
+    fn index(&self, name: &str) -> &str {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no parameter named {}", name))
+    }
+}
+
+type RouteHandler = Arc<dyn Fn(Message, Params) -> io::Result<()> + Send + Sync>;
+
+struct Route {
+    pattern: String,
+    handler: RouteHandler,
+}
+
+/// Routes messages to handlers registered per subject pattern.
+pub struct Router {
+    routes: Vec<Route>,
+    workers: usize,
+    queue_group: Option<String>,
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field(
+                "routes",
+                &self
+                    .routes
+                    .iter()
+                    .map(|route| route.pattern.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field("workers", &self.workers)
+            .field("queue_group", &self.queue_group)
+            .finish()
+    }
+}
+
+impl Default for Router {
+    fn default() -> Router {
+        Router::new()
+    }
+}
+
+impl Router {
+    /// Creates a router without routes, running handlers on one worker per available CPU.
+    pub fn new() -> Router {
+        Router {
+            routes: Vec::new(),
+            workers: thread::available_parallelism().map_or(1, |workers| workers.get()),
+            queue_group: None,
+        }
+    }
+
+    /// Registers a handler for messages on subjects matching the pattern.
+    ///
+    /// A message matching several patterns is delivered to each of their handlers.
+    pub fn route<F>(mut self, pattern: &str, handler: F) -> Router
+    where
+        F: Fn(Message, Params) -> io::Result<()> + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            pattern: pattern.to_string(),
+            handler: Arc::new(handler),
+        });
+        self
+    }
+
+    /// Sets the number of worker threads running the handlers, at least one.
+    pub fn workers(mut self, workers: usize) -> Router {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Subscribes to the routes in a queue group, so instances of the router share the load.
+    pub fn queue_group(mut self, queue_group: &str) -> Router {
+        self.queue_group = Some(queue_group.to_string());
+        self
+    }
+
+    /// Subscribes to every route and starts dispatching messages to the handlers.
+    ///
+    /// Errors returned by handlers are logged like errors of
+    /// [`Subscription::with_handler`].
+    pub fn attach(self, connection: &Connection) -> io::Result<RouterHandle> {
+        let mut subscriptions = Vec::with_capacity(self.routes.len());
+        for route in &self.routes {
+            let subscription = match &self.queue_group {
+                Some(queue_group) => connection.queue_subscribe(&route.pattern, queue_group)?,
+                None => connection.subscribe(&route.pattern)?,
+            };
+            subscriptions.push(subscription);
+        }
+
+        let (sender, receiver) = channel::bounded::<(usize, Message)>(self.workers);
+
+        let routes = Arc::new(self.routes);
+        for worker in 0..self.workers {
+            let routes = routes.clone();
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(
+                    connection
+                        .0
+                        .client
+                        .thread_name(format_args!("router_worker_{}", worker)),
+                )
+                .spawn(move || {
+                    for (index, message) in receiver.iter() {
+                        let route = &routes[index];
+                        let params =
+                            Params::capture(&route.pattern, &message.subject).unwrap_or_default();
+                        if let Err(e) = (route.handler)(message, params) {
+                            crate::logging::error!("Error in route {}! {:?}", route.pattern, e);
+                        }
+                    }
+                })
+                .expect("threads should be spawnable");
+        }
+
+        // Forward the messages of every subscription to the workers, which exit once all
+        // subscriptions are closed.
+        for (index, subscription) in subscriptions.iter().enumerate() {
+            let subscription = subscription.clone();
+            let sender = sender.clone();
+            thread::Builder::new()
+                .name(
+                    connection
+                        .0
+                        .client
+                        .thread_name(format_args!("router_{}", routes[index].pattern)),
+                )
+                .spawn(move || {
+                    for message in subscription.iter() {
+                        if sender.send((index, message)).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .expect("threads should be spawnable");
+        }
+
+        Ok(RouterHandle { subscriptions })
+    }
+}
+
+/// A router attached to a connection, which keeps routing after being dropped.
+#[derive(Debug)]
+pub struct RouterHandle {
+    subscriptions: Vec<Subscription>,
+}
+
+impl RouterHandle {
+    /// Unsubscribes every route. Messages already being handled are not interrupted.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        for subscription in self.subscriptions {
+            subscription.unsubscribe()?;
+        }
+        Ok(())
+    }
+
+    /// Unsubscribes every route after the server delivered the messages in flight, which
+    /// are still handled.
+    pub fn drain(&self) -> io::Result<()> {
+        for subscription in &self.subscriptions {
+            subscription.drain()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`tower`] adapters over request/reply, so middleware stacks such as retries, rate limits
+//! and timeouts compose with NATS RPC.
+//!
+//! [`RequestService`] is a client `Service<Message>` sending each message as a request, and
+//! [`crate::Subscription::with_service`] mounts a `Service<Message>` as the responder of a
+//! subscription.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! use nats::rpc::RequestService;
+//! use tower::Service;
+//!
+//! // A responder echoing requests back.
+//! let _handler = nc
+//!     .subscribe("echo")?
+//!     .with_service(tower::service_fn(|message: nats::Message| async move {
+//!         Ok::<_, std::io::Error>(message)
+//!     }));
+//!
+//! let mut service = RequestService::new(nc.clone());
+//! let response = smol::block_on(service.call(nats::Message::new("echo", None, "hi", None)))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Connection, Message};
+
+/// A [`tower::Service`] sending each message as a request on its subject, with its headers,
+/// and resolving to the response.
+///
+/// Requests are performed on the blocking thread pool, so the returned futures can be
+/// awaited on any executor.
+#[derive(Clone, Debug)]
+pub struct RequestService {
+    connection: Connection,
+    timeout: Option<Duration>,
+}
+
+impl RequestService {
+    /// Creates a service sending requests over the connection.
+    pub fn new(connection: Connection) -> RequestService {
+        RequestService {
+            connection,
+            timeout: None,
+        }
+    }
+
+    /// Fails requests with `io::ErrorKind::TimedOut` if no response arrives in time.
+    pub fn timeout(mut self, timeout: Duration) -> RequestService {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl tower::Service<Message> for RequestService {
+    type Response = Message;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Message>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, message: Message) -> Self::Future {
+        let connection = self.connection.clone();
+        let timeout = self.timeout;
+        Box::pin(blocking::unblock(move || {
+            connection.request_with_headers_or_timeout(
+                &message.subject,
+                message.headers.as_ref(),
+                timeout,
+                &message.data,
+            )
+        }))
+    }
+}
+
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Drives `poll` on the current thread until it is ready, parking between wake ups.
+pub(crate) fn block_on<T>(mut poll: impl FnMut(&mut Context<'_>) -> Poll<T>) -> T {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
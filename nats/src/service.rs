@@ -0,0 +1,909 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for services following the NATS micro protocol.
+//!
+//! A [`Service`] groups request/reply endpoints and answers discovery requests on
+//! `$SRV.PING`, `$SRV.INFO` and `$SRV.STATS` on their behalf.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use time::serde::rfc3339;
+use time::OffsetDateTime;
+
+use crate::header::HeaderMap;
+use crate::{Connection, Handler, Message};
+
+/// Prefix of all service discovery subjects.
+pub const API_PREFIX: &str = "$SRV";
+
+/// Queue group used by endpoints, so that instances of a service load-balance requests.
+pub const DEFAULT_QUEUE_GROUP: &str = "q";
+
+/// Header carrying the description of an error returned by an endpoint.
+pub const NATS_SERVICE_ERROR: &str = "Nats-Service-Error";
+
+/// Header carrying the code of an error returned by an endpoint.
+pub const NATS_SERVICE_ERROR_CODE: &str = "Nats-Service-Error-Code";
+
+/// How long discovery requests wait for the first response.
+pub const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long discovery requests wait for further responses after the last one.
+const DISCOVERY_STALL: Duration = Duration::from_millis(100);
+
+lazy_static! {
+    static ref SEMVER: Regex = Regex::new(r#"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-((?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+([0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$"#).unwrap();
+    static ref NAME: Regex = Regex::new(r#"^[A-Za-z0-9\-_]+$"#).unwrap();
+}
+
+/// An error returned by an endpoint handler, sent to the requester in the
+/// `Nats-Service-Error` and `Nats-Service-Error-Code` headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// Error code, usually following HTTP status codes.
+    pub code: usize,
+    /// Description of the error.
+    pub description: String,
+}
+
+impl Error {
+    /// Creates a new endpoint error.
+    pub fn new(code: usize, description: impl Into<String>) -> Error {
+        Error {
+            code,
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.code, self.description)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Response to a `$SRV.PING` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PingResponse {
+    /// Response type.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Service name.
+    pub name: String,
+    /// Unique id of the service instance.
+    pub id: String,
+    /// Service version.
+    pub version: String,
+}
+
+/// Response to a `$SRV.INFO` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Info {
+    /// Response type.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Service name.
+    pub name: String,
+    /// Unique id of the service instance.
+    pub id: String,
+    /// Service version.
+    pub version: String,
+    /// Description of the service.
+    #[serde(default)]
+    pub description: String,
+    /// Additional service metadata.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Endpoints of the service.
+    #[serde(default)]
+    pub endpoints: Vec<EndpointInfo>,
+}
+
+/// Information about a single endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointInfo {
+    /// Endpoint name.
+    pub name: String,
+    /// Subject the endpoint listens on.
+    pub subject: String,
+    /// Queue group the endpoint subscribes with.
+    pub queue_group: String,
+    /// Additional endpoint metadata.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Schema of the requests and responses of the endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Schema>,
+}
+
+/// Schema of the requests and responses of an endpoint, usually a JSON schema or a URL
+/// pointing to one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+    /// Schema of requests.
+    #[serde(default)]
+    pub request: String,
+    /// Schema of responses.
+    #[serde(default)]
+    pub response: String,
+}
+
+/// Response to a `$SRV.STATS` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Response type.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Service name.
+    pub name: String,
+    /// Unique id of the service instance.
+    pub id: String,
+    /// Service version.
+    pub version: String,
+    /// When the service instance was started.
+    #[serde(with = "rfc3339")]
+    pub started: OffsetDateTime,
+    /// Statistics of the endpoints of the service.
+    #[serde(default)]
+    pub endpoints: Vec<EndpointStats>,
+}
+
+/// Statistics of a single endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointStats {
+    /// Endpoint name.
+    pub name: String,
+    /// Subject the endpoint listens on.
+    pub subject: String,
+    /// Queue group the endpoint subscribes with.
+    pub queue_group: String,
+    /// Number of requests handled.
+    #[serde(rename = "num_requests")]
+    pub requests: u64,
+    /// Number of requests the handler returned an error for.
+    #[serde(rename = "num_errors")]
+    pub errors: u64,
+    /// The last error returned by the handler, formatted as `code:description`.
+    #[serde(default)]
+    pub last_error: String,
+    /// Total time spent handling requests.
+    #[serde(with = "serde_nanos")]
+    pub processing_time: Duration,
+    /// Average time spent handling a request.
+    #[serde(with = "serde_nanos")]
+    pub average_processing_time: Duration,
+    /// Custom data added by the handler set with [`ServiceBuilder::stats_handler`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl EndpointStats {
+    fn new(name: String, subject: String, queue_group: String) -> EndpointStats {
+        EndpointStats {
+            name,
+            subject,
+            queue_group,
+            requests: 0,
+            errors: 0,
+            last_error: String::new(),
+            processing_time: Duration::default(),
+            average_processing_time: Duration::default(),
+            data: None,
+        }
+    }
+}
+
+/// Hooks running around the handler of an endpoint, added with
+/// [`EndpointBuilder::middleware`]. Useful for authorization checks, logging or tracing.
+///
+/// The hooks of all middleware of an endpoint run in the order the middleware was added.
+pub trait Middleware: Send + Sync + 'static {
+    /// Runs before the handler. Returning an error skips the remaining `before` hooks and the
+    /// handler, and responds with the error instead.
+    fn before(&self, _request: &Message) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Runs after the handler, and may modify the response.
+    fn after(&self, _request: &Message, _response: &mut Result<Vec<u8>, Error>) {}
+}
+
+type StatsHandler = Box<dyn Fn(&EndpointStats) -> serde_json::Value + Send + Sync>;
+
+struct Endpoint {
+    info: EndpointInfo,
+    stats: Mutex<EndpointStats>,
+    handler: Mutex<Option<Handler>>,
+}
+
+struct Inner {
+    connection: Connection,
+    id: String,
+    name: String,
+    version: String,
+    description: String,
+    metadata: HashMap<String, String>,
+    queue_group: String,
+    started: OffsetDateTime,
+    endpoints: Mutex<Vec<Arc<Endpoint>>>,
+    verbs: Mutex<Vec<Handler>>,
+    stats_handler: Option<StatsHandler>,
+    stopped: AtomicBool,
+}
+
+/// A service answering requests on its endpoints and discovery requests on
+/// `$SRV.PING`, `$SRV.INFO` and `$SRV.STATS`.
+///
+/// The service keeps running until [`Service::stop`] is called or the connection is closed.
+#[derive(Clone)]
+pub struct Service {
+    inner: Arc<Inner>,
+}
+
+impl fmt::Debug for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Service")
+            .field("id", &self.inner.id)
+            .field("name", &self.inner.name)
+            .field("version", &self.inner.version)
+            .finish()
+    }
+}
+
+/// Builder for a [`Service`], created by [`Connection::service_builder`].
+pub struct ServiceBuilder {
+    connection: Connection,
+    description: String,
+    metadata: HashMap<String, String>,
+    queue_group: String,
+    stats_handler: Option<StatsHandler>,
+}
+
+impl fmt::Debug for ServiceBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceBuilder")
+            .field("description", &self.description)
+            .field("metadata", &self.metadata)
+            .field("queue_group", &self.queue_group)
+            .finish()
+    }
+}
+
+impl ServiceBuilder {
+    /// Sets a human readable description of the service.
+    pub fn description(mut self, description: &str) -> ServiceBuilder {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Sets additional service metadata, reported in `$SRV.INFO` responses.
+    pub fn metadata(mut self, metadata: HashMap<String, String>) -> ServiceBuilder {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets the queue group endpoints subscribe with, unless overridden by a [`Group`].
+    /// Defaults to [`DEFAULT_QUEUE_GROUP`].
+    pub fn queue_group(mut self, queue_group: &str) -> ServiceBuilder {
+        self.queue_group = queue_group.to_string();
+        self
+    }
+
+    /// Sets a handler contributing custom data to the statistics of each endpoint in
+    /// `$SRV.STATS` responses, like cache hit rates.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let service = nc
+    ///     .service_builder()
+    ///     .stats_handler(|stats| serde_json::json!({ "endpoint": stats.name }))
+    ///     .start("generator", "1.0.0")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats_handler<F>(mut self, handler: F) -> ServiceBuilder
+    where
+        F: Fn(&EndpointStats) -> serde_json::Value + Send + Sync + 'static,
+    {
+        self.stats_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Starts the service, subscribing to the discovery subjects.
+    ///
+    /// The name may only contain `A-Z`, `a-z`, `0-9`, `-` and `_`, and the version has to
+    /// be a valid semantic version.
+    pub fn start(self, name: &str, version: &str) -> io::Result<Service> {
+        if !NAME.is_match(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "service name is not a valid string (only A-Z, a-z, 0-9, _, - are allowed)",
+            ));
+        }
+        if !SEMVER.is_match(version) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "service version is not a valid semver string",
+            ));
+        }
+
+        let id = self.connection.0.client.next_id();
+        let service = Service {
+            inner: Arc::new(Inner {
+                connection: self.connection,
+                id,
+                name: name.to_string(),
+                version: version.to_string(),
+                description: self.description,
+                metadata: self.metadata,
+                queue_group: self.queue_group,
+                started: OffsetDateTime::now_utc(),
+                endpoints: Mutex::new(Vec::new()),
+                verbs: Mutex::new(Vec::new()),
+                stats_handler: self.stats_handler,
+                stopped: AtomicBool::new(false),
+            }),
+        };
+
+        for verb in &["PING", "INFO", "STATS"] {
+            let subjects = [
+                format!("{}.{}", API_PREFIX, verb),
+                format!("{}.{}.{}", API_PREFIX, verb, name),
+                format!("{}.{}.{}.{}", API_PREFIX, verb, name, service.inner.id),
+            ];
+
+            for subject in &subjects {
+                let handler = service.inner.connection.subscribe(subject)?.with_handler({
+                    let service = service.clone();
+                    let verb = *verb;
+                    move |message| {
+                        let response = match verb {
+                            "PING" => serde_json::to_vec(&service.ping()),
+                            "INFO" => serde_json::to_vec(&service.info()),
+                            _ => serde_json::to_vec(&service.stats()),
+                        }?;
+                        message.respond(response)
+                    }
+                });
+                service.inner.verbs.lock().push(handler);
+            }
+        }
+
+        Ok(service)
+    }
+}
+
+impl Connection {
+    /// Creates a [`ServiceBuilder`] for a new service on this connection.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// let service = nc
+    ///     .service_builder()
+    ///     .description("generates things")
+    ///     .start("generator", "1.0.0")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service_builder(&self) -> ServiceBuilder {
+        ServiceBuilder {
+            connection: self.clone(),
+            description: String::new(),
+            metadata: HashMap::new(),
+            queue_group: DEFAULT_QUEUE_GROUP.to_string(),
+            stats_handler: None,
+        }
+    }
+
+    /// Starts a new service with the given name and version.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// let service = nc.add_service("echo", "1.0.0")?;
+    /// service.add_endpoint("echo", "echo", |message| Ok(message.data.clone()))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_service(&self, name: &str, version: &str) -> io::Result<Service> {
+        self.service_builder().start(name, version)
+    }
+
+    /// Discovers running service instances by sending a `$SRV.PING` request, returning the
+    /// responses that arrive within [`DISCOVERY_TIMEOUT`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// for service in nc.discover_services()? {
+    ///     println!("{} {} ({})", service.name, service.version, service.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn discover_services(&self) -> io::Result<Vec<PingResponse>> {
+        self.discover(&format!("{}.PING", API_PREFIX))
+    }
+
+    /// Returns the [`Info`] of all running instances of the service `name`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// for info in nc.service_info("calculator")? {
+    ///     println!("{}: {:?}", info.id, info.endpoints);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service_info(&self, name: &str) -> io::Result<Vec<Info>> {
+        self.discover(&format!("{}.INFO.{}", API_PREFIX, name))
+    }
+
+    /// Returns the [`Stats`] of all running instances of the service `name`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// for stats in nc.service_stats("calculator")? {
+    ///     println!("{}: {:?}", stats.id, stats.endpoints);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service_stats(&self, name: &str) -> io::Result<Vec<Stats>> {
+        self.discover(&format!("{}.STATS.{}", API_PREFIX, name))
+    }
+
+    fn discover<T: DeserializeOwned>(&self, subject: &str) -> io::Result<Vec<T>> {
+        let responses = self.request_multi(subject, "")?;
+        let mut results = Vec::new();
+        let mut timeout = DISCOVERY_TIMEOUT;
+        while let Ok(message) = responses.next_timeout(timeout) {
+            if message.is_no_responders() {
+                break;
+            }
+            results.push(serde_json::from_slice(&message.data)?);
+            timeout = DISCOVERY_STALL;
+        }
+        Ok(results)
+    }
+}
+
+impl Service {
+    /// Returns the unique id of this service instance.
+    pub fn id(&self) -> &str {
+        &self.inner.id
+    }
+
+    /// Returns the name of the service.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Returns the version of the service.
+    pub fn version(&self) -> &str {
+        &self.inner.version
+    }
+
+    /// Adds an endpoint handling requests on `subject` with `handler`. The data returned by
+    /// the handler is sent as the response; errors are sent as headers and counted in the
+    /// endpoint [`EndpointStats`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let service = nc.add_service("calculator", "1.0.0")?;
+    /// service.add_endpoint("length", "calculator.length", |message| {
+    ///     if message.data.is_empty() {
+    ///         return Err(nats::service::Error::new(400, "empty request"));
+    ///     }
+    ///     Ok(message.data.len().to_string().into_bytes())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_endpoint<F>(&self, name: &str, subject: &str, handler: F) -> io::Result<()>
+    where
+        F: Fn(&Message) -> Result<Vec<u8>, Error> + Send + 'static,
+    {
+        self.endpoint_builder(name, subject).add(handler)
+    }
+
+    /// Creates an [`EndpointBuilder`] for an endpoint handling requests on `subject`, allowing
+    /// to set metadata and a schema reported in `$SRV.INFO` responses.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::service::Schema;
+    ///
+    /// let service = nc.add_service("calculator", "1.0.0")?;
+    /// service
+    ///     .endpoint_builder("length", "calculator.length")
+    ///     .schema(Schema {
+    ///         request: r#"{"type":"string"}"#.to_string(),
+    ///         response: r#"{"type":"integer"}"#.to_string(),
+    ///     })
+    ///     .add(|message| Ok(message.data.len().to_string().into_bytes()))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn endpoint_builder(&self, name: &str, subject: &str) -> EndpointBuilder {
+        EndpointBuilder {
+            service: self.clone(),
+            middleware: Vec::new(),
+            info: EndpointInfo {
+                name: name.to_string(),
+                subject: subject.to_string(),
+                queue_group: self.inner.queue_group.clone(),
+                metadata: HashMap::new(),
+                schema: None,
+            },
+        }
+    }
+
+    /// Creates a [`Group`] of endpoints sharing the subject `prefix`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let service = nc.add_service("calculator", "1.0.0")?;
+    /// let v1 = service.group("calculator.v1").queue_group("calculator-v1");
+    /// // Listens on `calculator.v1.length`.
+    /// v1.add_endpoint("length", "length", |message| {
+    ///     Ok(message.data.len().to_string().into_bytes())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn group(&self, prefix: &str) -> Group {
+        Group {
+            service: self.clone(),
+            prefix: prefix.to_string(),
+            queue_group: self.inner.queue_group.clone(),
+        }
+    }
+
+    fn add_endpoint_with_info<F>(
+        &self,
+        info: EndpointInfo,
+        middleware: Vec<Box<dyn Middleware>>,
+        handler: F,
+    ) -> io::Result<()>
+    where
+        F: Fn(&Message) -> Result<Vec<u8>, Error> + Send + 'static,
+    {
+        if self.inner.stopped.load(Ordering::Acquire) {
+            return Err(io::Error::new(io::ErrorKind::Other, "service is stopped"));
+        }
+        if !NAME.is_match(&info.name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "endpoint name is not a valid string (only A-Z, a-z, 0-9, _, - are allowed)",
+            ));
+        }
+
+        let endpoint = Arc::new(Endpoint {
+            stats: Mutex::new(EndpointStats::new(
+                info.name.clone(),
+                info.subject.clone(),
+                info.queue_group.clone(),
+            )),
+            info,
+            handler: Mutex::new(None),
+        });
+
+        let handler = self
+            .inner
+            .connection
+            .queue_subscribe(&endpoint.info.subject, &endpoint.info.queue_group)?
+            .with_handler({
+                let endpoint = endpoint.clone();
+                let connection = self.inner.connection.clone();
+                move |message| {
+                    let started = Instant::now();
+                    let mut result = middleware
+                        .iter()
+                        .try_for_each(|middleware| middleware.before(&message))
+                        .and_then(|_| handler(&message));
+                    for middleware in &middleware {
+                        middleware.after(&message, &mut result);
+                    }
+                    let elapsed = started.elapsed();
+
+                    {
+                        let mut stats = endpoint.stats.lock();
+                        stats.requests += 1;
+                        stats.processing_time += elapsed;
+                        stats.average_processing_time = stats
+                            .processing_time
+                            .as_nanos()
+                            .checked_div(u128::from(stats.requests))
+                            .map(|nanos| Duration::from_nanos(nanos as u64))
+                            .unwrap_or_default();
+                        if let Err(err) = &result {
+                            stats.errors += 1;
+                            stats.last_error = err.to_string();
+                        }
+                    }
+
+                    let reply = match message.reply.as_deref() {
+                        Some(reply) => reply,
+                        None => return Ok(()),
+                    };
+                    match result {
+                        Ok(response) => connection.publish(reply, response),
+                        Err(err) => {
+                            let mut headers = HeaderMap::new();
+                            headers.insert(NATS_SERVICE_ERROR, err.description);
+                            headers.insert(NATS_SERVICE_ERROR_CODE, err.code.to_string());
+                            connection.publish_with_reply_or_headers(
+                                reply,
+                                None,
+                                Some(&headers),
+                                b"",
+                            )
+                        }
+                    }
+                }
+            });
+        *endpoint.handler.lock() = Some(handler);
+
+        self.inner.endpoints.lock().push(endpoint);
+        Ok(())
+    }
+
+    /// Returns the response to a `$SRV.PING` request for this instance.
+    pub fn ping(&self) -> PingResponse {
+        PingResponse {
+            kind: "io.nats.micro.v1.ping_response".to_string(),
+            name: self.inner.name.clone(),
+            id: self.inner.id.clone(),
+            version: self.inner.version.clone(),
+        }
+    }
+
+    /// Returns the response to a `$SRV.INFO` request for this instance.
+    pub fn info(&self) -> Info {
+        let endpoints = self
+            .inner
+            .endpoints
+            .lock()
+            .iter()
+            .map(|endpoint| endpoint.info.clone())
+            .collect();
+
+        Info {
+            kind: "io.nats.micro.v1.info_response".to_string(),
+            name: self.inner.name.clone(),
+            id: self.inner.id.clone(),
+            version: self.inner.version.clone(),
+            description: self.inner.description.clone(),
+            metadata: self.inner.metadata.clone(),
+            endpoints,
+        }
+    }
+
+    /// Returns the response to a `$SRV.STATS` request for this instance.
+    pub fn stats(&self) -> Stats {
+        let endpoints = self
+            .inner
+            .endpoints
+            .lock()
+            .iter()
+            .map(|endpoint| {
+                let mut stats = endpoint.stats.lock().clone();
+                if let Some(handler) = &self.inner.stats_handler {
+                    stats.data = Some(handler(&stats));
+                }
+                stats
+            })
+            .collect();
+
+        Stats {
+            kind: "io.nats.micro.v1.stats_response".to_string(),
+            name: self.inner.name.clone(),
+            id: self.inner.id.clone(),
+            version: self.inner.version.clone(),
+            started: self.inner.started,
+            endpoints,
+        }
+    }
+
+    /// Resets the statistics of all endpoints.
+    pub fn reset(&self) {
+        for endpoint in self.inner.endpoints.lock().iter() {
+            let mut stats = endpoint.stats.lock();
+            *stats = EndpointStats::new(
+                stats.name.clone(),
+                stats.subject.clone(),
+                stats.queue_group.clone(),
+            );
+        }
+    }
+
+    /// Stops the service, unsubscribing its endpoints and discovery responders.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let service = nc.add_service("echo", "1.0.0")?;
+    /// service.stop()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stop(&self) -> io::Result<()> {
+        if self.inner.stopped.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        for endpoint in self.inner.endpoints.lock().iter() {
+            if let Some(handler) = endpoint.handler.lock().take() {
+                handler.unsubscribe()?;
+            }
+        }
+        for handler in self.inner.verbs.lock().drain(..) {
+            handler.unsubscribe()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A group of endpoints of a [`Service`] sharing a subject prefix and a queue group, created
+/// by [`Service::group`].
+#[derive(Debug, Clone)]
+pub struct Group {
+    service: Service,
+    prefix: String,
+    queue_group: String,
+}
+
+impl Group {
+    /// Sets the queue group endpoints of this group subscribe with, so that only instances
+    /// running the same group load-balance its requests. Nested groups inherit it.
+    pub fn queue_group(mut self, queue_group: &str) -> Group {
+        self.queue_group = queue_group.to_string();
+        self
+    }
+
+    /// Creates a nested group, appending `prefix` to the prefix of this group.
+    pub fn group(&self, prefix: &str) -> Group {
+        Group {
+            service: self.service.clone(),
+            prefix: format!("{}.{}", self.prefix, prefix),
+            queue_group: self.queue_group.clone(),
+        }
+    }
+
+    /// Adds an endpoint handling requests on `subject` prefixed with the group prefix, see
+    /// [`Service::add_endpoint`].
+    pub fn add_endpoint<F>(&self, name: &str, subject: &str, handler: F) -> io::Result<()>
+    where
+        F: Fn(&Message) -> Result<Vec<u8>, Error> + Send + 'static,
+    {
+        self.endpoint_builder(name, subject).add(handler)
+    }
+
+    /// Creates an [`EndpointBuilder`] for an endpoint of this group, see
+    /// [`Service::endpoint_builder`].
+    pub fn endpoint_builder(&self, name: &str, subject: &str) -> EndpointBuilder {
+        self.service
+            .endpoint_builder(name, &format!("{}.{}", self.prefix, subject))
+            .queue_group(&self.queue_group)
+    }
+}
+
+/// Builder for an endpoint of a [`Service`], created by [`Service::endpoint_builder`] or
+/// [`Group::endpoint_builder`].
+pub struct EndpointBuilder {
+    service: Service,
+    info: EndpointInfo,
+    middleware: Vec<Box<dyn Middleware>>,
+}
+
+impl fmt::Debug for EndpointBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EndpointBuilder")
+            .field("service", &self.service)
+            .field("info", &self.info)
+            .finish()
+    }
+}
+
+impl EndpointBuilder {
+    /// Sets the queue group the endpoint subscribes with.
+    pub fn queue_group(mut self, queue_group: &str) -> EndpointBuilder {
+        self.info.queue_group = queue_group.to_string();
+        self
+    }
+
+    /// Sets additional endpoint metadata, reported in `$SRV.INFO` responses.
+    pub fn metadata(mut self, metadata: HashMap<String, String>) -> EndpointBuilder {
+        self.info.metadata = metadata;
+        self
+    }
+
+    /// Sets the schema of requests and responses, reported in `$SRV.INFO` responses.
+    pub fn schema(mut self, schema: Schema) -> EndpointBuilder {
+        self.info.schema = Some(schema);
+        self
+    }
+
+    /// Adds a [`Middleware`] running around the handler of the endpoint.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::service::{Error, Middleware};
+    ///
+    /// struct RequireHeaders;
+    ///
+    /// impl Middleware for RequireHeaders {
+    ///     fn before(&self, request: &nats::Message) -> Result<(), Error> {
+    ///         match request.headers {
+    ///             Some(_) => Ok(()),
+    ///             None => Err(Error::new(401, "missing headers")),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let service = nc.add_service("echo", "1.0.0")?;
+    /// service
+    ///     .endpoint_builder("echo", "echo")
+    ///     .middleware(RequireHeaders)
+    ///     .add(|message| Ok(message.data.clone()))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> EndpointBuilder {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Adds the endpoint to the service, handling requests with `handler`, see
+    /// [`Service::add_endpoint`].
+    pub fn add<F>(self, handler: F) -> io::Result<()>
+    where
+        F: Fn(&Message) -> Result<Vec<u8>, Error> + Send + 'static,
+    {
+        self.service
+            .add_endpoint_with_info(self.info, self.middleware, handler)
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Graceful shutdown of the subscriptions of a service within a deadline.
+//!
+//! A [`Shutdown`] drains subscriptions concurrently, waiting for the messages they already
+//! received to be taken by the application, then acknowledges the JetStream messages
+//! processed meanwhile and flushes the connection. The [`ShutdownReport`] tells what
+//! completed before the deadline and what did not.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! # let nc = nats::connect("demo.nats.io")?;
+//! use std::time::Duration;
+//!
+//! use nats::shutdown::Shutdown;
+//!
+//! let orders = nc.subscribe("orders")?;
+//! let handler = nc.subscribe("payments")?.with_handler(|message| message.respond("ok"));
+//!
+//! let report = Shutdown::new(&nc, Duration::from_secs(10))
+//!     .drain("orders", orders.clone())
+//!     .drain("payments", handler)
+//!     .run();
+//! if !report.is_complete() {
+//!     eprintln!("shutdown incomplete: {:?}", report);
+//! }
+//! nc.close();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel as channel;
+
+#[cfg(feature = "jetstream")]
+use crate::jetstream::BatchAck;
+use crate::Connection;
+
+/// How often draining checks whether received messages were taken by the application.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A subscription, or anything else receiving messages, that a [`Shutdown`] can drain.
+pub trait Drain: Send {
+    /// Stops receiving new messages, letting the messages in flight arrive.
+    fn drain(&self) -> io::Result<()>;
+
+    /// Returns true once the messages received have been taken by the application.
+    fn is_drained(&self) -> bool;
+}
+
+/// Drains subscriptions, acknowledges processed JetStream messages and flushes the
+/// connection, giving up at a deadline.
+///
+/// The connection is left open, so the application can still publish before closing it.
+pub struct Shutdown {
+    connection: Connection,
+    timeout: Duration,
+    targets: Vec<(String, Box<dyn Drain>)>,
+    #[cfg(feature = "jetstream")]
+    acks: Vec<BatchAck>,
+}
+
+impl fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shutdown")
+            .field("timeout", &self.timeout)
+            .field(
+                "targets",
+                &self
+                    .targets
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Shutdown {
+    /// Creates a shutdown of the connection, which must complete within the timeout.
+    pub fn new(connection: &Connection, timeout: Duration) -> Shutdown {
+        Shutdown {
+            connection: connection.clone(),
+            timeout,
+            targets: Vec::new(),
+            #[cfg(feature = "jetstream")]
+            acks: Vec::new(),
+        }
+    }
+
+    /// Adds something to drain, named in the report.
+    pub fn drain(mut self, name: impl Into<String>, target: impl Drain + 'static) -> Shutdown {
+        self.targets.push((name.into(), Box::new(target)));
+        self
+    }
+
+    /// Flushes the acknowledgements of a [`BatchAck`] once draining is over, so messages
+    /// processed while draining are acknowledged.
+    #[cfg(feature = "jetstream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jetstream")))]
+    pub fn batch_ack(mut self, acks: &BatchAck) -> Shutdown {
+        self.acks.push(acks.clone());
+        self
+    }
+
+    /// Runs the shutdown, returning once everything completed or the deadline passed.
+    ///
+    /// Targets still draining at the deadline keep draining in the background.
+    pub fn run(self) -> ShutdownReport {
+        let deadline = Instant::now() + self.timeout;
+        let mut report = ShutdownReport::default();
+
+        let (sender, receiver) = channel::unbounded();
+        let mut pending = Vec::with_capacity(self.targets.len());
+        for (name, target) in self.targets {
+            let label = name.clone();
+            let sender = sender.clone();
+            let spawned = thread::Builder::new()
+                .name(
+                    self.connection
+                        .0
+                        .client
+                        .thread_name(format_args!("shutdown_{}", name)),
+                )
+                .spawn(move || {
+                    let result = drain(target.as_ref(), deadline);
+                    sender.send((name, result)).ok();
+                });
+            match spawned {
+                Ok(_) => pending.push(label),
+                Err(err) => report.failed.push((label, err)),
+            }
+        }
+        drop(sender);
+
+        while !pending.is_empty() {
+            let (name, result) = match receiver.recv_deadline(deadline) {
+                Ok(outcome) => outcome,
+                Err(_) => break,
+            };
+            if let Some(index) = pending.iter().position(|pending| *pending == name) {
+                pending.swap_remove(index);
+            }
+            match result {
+                Ok(true) => report.drained.push(name),
+                Ok(false) => report.timed_out.push(name),
+                Err(err) => report.failed.push((name, err)),
+            }
+        }
+        report.timed_out.extend(pending);
+
+        #[cfg(feature = "jetstream")]
+        for acks in &self.acks {
+            if let Err(err) = acks.flush() {
+                report.ack_errors.push(err);
+            }
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if let Err(err) = self.connection.flush_timeout(remaining) {
+            report.flush_error = Some(err);
+        }
+
+        report
+    }
+}
+
+/// Drains a target, returning whether it drained before the deadline.
+fn drain(target: &dyn Drain, deadline: Instant) -> io::Result<bool> {
+    target.drain()?;
+    while !target.is_drained() {
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        thread::sleep(DRAIN_POLL_INTERVAL);
+    }
+    Ok(true)
+}
+
+/// What a [`Shutdown`] completed before its deadline.
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// Names of the targets drained completely.
+    pub drained: Vec<String>,
+
+    /// Names of the targets still draining at the deadline.
+    pub timed_out: Vec<String>,
+
+    /// Names of the targets that failed to drain, with their errors.
+    pub failed: Vec<(String, io::Error)>,
+
+    /// Errors acknowledging processed JetStream messages.
+    pub ack_errors: Vec<io::Error>,
+
+    /// Error flushing the connection, if it failed or the deadline passed.
+    pub flush_error: Option<io::Error>,
+}
+
+impl ShutdownReport {
+    /// Returns true if every step of the shutdown completed.
+    pub fn is_complete(&self) -> bool {
+        self.timed_out.is_empty()
+            && self.failed.is_empty()
+            && self.ack_errors.is_empty()
+            && self.flush_error.is_none()
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::Error;
+
+/// A subject, cheap to clone.
+///
+/// [`Subject::new`] validates the subject right away. Subscribe and request APIs accept
+/// anything convertible into a `Subject`, such as `&str` and `String`, while publish APIs
+/// accept any `AsRef<str>` and validate it in place, so publishing does not allocate. Both
+/// validate the subject before sending anything to the server. Invalid subjects fail with
+/// an [`Error::InvalidSubject`] of kind `io::ErrorKind::InvalidInput`.
+///
+/// A valid subject is made of non-empty tokens separated by `.` without whitespace. The
+/// wildcards `*` and `>` must be whole tokens, and `>` must be the last one.
+///
+/// # Example
+/// ```
+/// use nats::Subject;
+///
+/// let subject = Subject::new("orders.*.created").unwrap();
+/// assert!(subject.is_wildcard());
+/// assert!(Subject::new("orders..created").is_err());
+/// assert!(Subject::new("orders.>.created").is_err());
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Subject(Arc<str>);
+
+impl Subject {
+    /// Creates a subject, failing if it is invalid.
+    pub fn new(subject: impl Into<Subject>) -> io::Result<Subject> {
+        let subject = subject.into();
+        subject.validate()?;
+        Ok(subject)
+    }
+
+    /// Returns the subject as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the `.` separated tokens of the subject.
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.0.split('.')
+    }
+
+    /// Returns true if the subject contains a wildcard, so it can be subscribed to but not
+    /// published to.
+    pub fn is_wildcard(&self) -> bool {
+        self.tokens().any(|token| token == "*" || token == ">")
+    }
+
+    /// Checks the subject against the token rules.
+    pub fn validate(&self) -> io::Result<()> {
+        validate(self.as_str())
+    }
+
+    /// Returns true if `subject` matches this subject, which may contain wildcards.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::Subject;
+    ///
+    /// let filter = Subject::from("orders.*.>");
+    /// assert!(filter.matches("orders.1.created"));
+    /// assert!(!filter.matches("orders.1"));
+    /// ```
+    pub fn matches(&self, subject: &str) -> bool {
+        matches(self.as_str(), subject)
+    }
+
+    /// Escapes a value so it can be used within a token, percent-encoding `%`, `.`, `*`, `>`
+    /// and whitespace. An empty value is escaped as `%`, so a token never ends up empty.
+    ///
+    /// Used by [`subject!`](crate::subject!) for the values of placeholders.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::Subject;
+    ///
+    /// assert_eq!(Subject::escape_token("eu-west"), "eu-west");
+    /// assert_eq!(Subject::escape_token("a.b *>"), "a%2Eb%20%2A%3E");
+    /// assert_eq!(Subject::escape_token(""), "%");
+    /// ```
+    pub fn escape_token(value: &str) -> String {
+        if value.is_empty() {
+            return "%".to_string();
+        }
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            if matches!(c, '%' | '.' | '*' | '>') || c.is_whitespace() {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    escaped.push_str(&format!("%{:02X}", byte));
+                }
+            } else {
+                escaped.push(c);
+            }
+        }
+        escaped
+    }
+}
+
+/// Builds a [`Subject`] from a template, replacing each `{name}` placeholder with the
+/// escaped value of the argument of the same name, see [`Subject::escape_token`].
+///
+/// The template is checked at compile time: its tokens must follow the rules of
+/// [`Subject`], every placeholder must have an argument and every argument must be used.
+/// Since values are escaped, they cannot add tokens or wildcards to the subject.
+///
+/// # Example
+/// ```
+/// let region = "eu-west";
+/// let subject = nats::subject!("orders.{region}.{id}", region = region, id = 42);
+/// assert_eq!(subject, "orders.eu-west.42");
+///
+/// let subject = nats::subject!("orders.{region}.>", region = "us.east");
+/// assert_eq!(subject, "orders.us%2Eeast.>");
+/// ```
+///
+/// Invalid templates fail to compile:
+/// ```compile_fail
+/// let subject = nats::subject!("orders..{id}", id = 42);
+/// ```
+/// ```compile_fail
+/// let subject = nats::subject!("orders.{region}.{id}", id = 42);
+/// ```
+#[macro_export]
+macro_rules! subject {
+    ($template:literal $(, $name:ident = $value:expr)* $(,)?) => {{
+        const _: () = $crate::__check_subject_template($template, &[$(stringify!($name)),*]);
+        $crate::__render_subject_template(
+            $template,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*],
+        )
+    }};
+}
+
+/// Checks a template of [`subject!`](crate::subject!) with the names of its arguments,
+/// panicking at compile time if it is invalid.
+#[doc(hidden)]
+pub const fn check_template(template: &str, names: &[&str]) {
+    let bytes = template.as_bytes();
+    if bytes.is_empty() {
+        panic!("subject template is empty");
+    }
+
+    let mut n = 0;
+    while n < names.len() {
+        let mut m = n + 1;
+        while m < names.len() {
+            let name = names[n].as_bytes();
+            if name_eq(name, 0, name.len(), names[m].as_bytes()) {
+                panic!("subject template has duplicate arguments");
+            }
+            m += 1;
+        }
+        n += 1;
+    }
+
+    let mut used = 0;
+    let mut token_start = 0;
+    let mut i = 0;
+    while i <= bytes.len() {
+        if i == bytes.len() || bytes[i] == b'.' {
+            if i == token_start {
+                panic!("subject template contains an empty token");
+            }
+            let is_wildcard =
+                i - token_start == 1 && (bytes[token_start] == b'*' || bytes[token_start] == b'>');
+            if is_wildcard && bytes[token_start] == b'>' && i != bytes.len() {
+                panic!("subject template contains `>` before the last token");
+            }
+            if !is_wildcard {
+                let mut j = token_start;
+                while j < i {
+                    if bytes[j] == b'*' || bytes[j] == b'>' {
+                        panic!("subject template contains a wildcard within a token");
+                    }
+                    j += 1;
+                }
+            }
+            token_start = i + 1;
+            i += 1;
+            continue;
+        }
+
+        match bytes[i] {
+            b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c => {
+                panic!("subject template contains whitespace")
+            }
+            b'}' => panic!("subject template contains a closing brace outside a placeholder"),
+            b'{' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && bytes[end] != b'}' {
+                    if bytes[end] == b'{' || bytes[end] == b'.' {
+                        panic!("subject template contains an unclosed placeholder");
+                    }
+                    end += 1;
+                }
+                if end == bytes.len() {
+                    panic!("subject template contains an unclosed placeholder");
+                }
+                if end == start {
+                    panic!("subject template contains a placeholder without a name");
+                }
+                let mut found = false;
+                let mut n = 0;
+                while n < names.len() {
+                    if name_eq(bytes, start, end, names[n].as_bytes()) {
+                        found = true;
+                        // Names used by several placeholders are counted once.
+                        if first_use(bytes, start, end) {
+                            used += 1;
+                        }
+                    }
+                    n += 1;
+                }
+                if !found {
+                    panic!("subject template contains a placeholder without an argument");
+                }
+                i = end + 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if used != names.len() {
+        panic!("subject template does not use every argument");
+    }
+}
+
+/// Returns true if `template[start..end]` equals `name`.
+const fn name_eq(template: &[u8], start: usize, end: usize, name: &[u8]) -> bool {
+    if end - start != name.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < name.len() {
+        if template[start + i] != name[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Returns true if the placeholder named `template[start..end]` is the first one with its
+/// name.
+const fn first_use(template: &[u8], start: usize, end: usize) -> bool {
+    let mut i = 0;
+    while i + 1 < start {
+        if template[i] == b'{' {
+            let mut j = i + 1;
+            while template[j] != b'}' {
+                j += 1;
+            }
+            let mut same = j - (i + 1) == end - start;
+            let mut k = 0;
+            while same && k < end - start {
+                same = template[i + 1 + k] == template[start + k];
+                k += 1;
+            }
+            if same {
+                return false;
+            }
+            i = j;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Renders a template of [`subject!`](crate::subject!) that passed [`check_template`].
+#[doc(hidden)]
+pub fn render_template(template: &str, args: &[(&str, &dyn fmt::Display)]) -> Subject {
+    let mut subject = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        subject.push_str(&rest[..start]);
+        let end = start + rest[start..].find('}').expect("template should be checked");
+        let name = &rest[start + 1..end];
+        let (_, value) = args
+            .iter()
+            .find(|(arg, _)| *arg == name)
+            .expect("template should be checked");
+        subject.push_str(&Subject::escape_token(&value.to_string()));
+        rest = &rest[end + 1..];
+    }
+    subject.push_str(rest);
+    Subject::from(subject)
+}
+
+/// Checks a subject against the token rules without allocating a [`Subject`].
+pub(crate) fn validate(subject: &str) -> io::Result<()> {
+    if subject.is_empty() {
+        return Err(invalid(subject, "is empty"));
+    }
+    if subject.chars().any(char::is_whitespace) {
+        return Err(invalid(subject, "contains whitespace"));
+    }
+    let mut tokens = subject.split('.').peekable();
+    while let Some(token) = tokens.next() {
+        if token.is_empty() {
+            return Err(invalid(subject, "contains an empty token"));
+        }
+        if token.len() > 1 && (token.contains('*') || token.contains('>')) {
+            return Err(invalid(subject, "contains a wildcard within a token"));
+        }
+        if token == ">" && tokens.peek().is_some() {
+            return Err(invalid(subject, "contains `>` before the last token"));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a subject can be published to, which also rules out wildcards.
+pub(crate) fn validate_publish(subject: &str) -> io::Result<()> {
+    validate(subject)?;
+    if subject.split('.').any(|token| token == "*" || token == ">") {
+        return Err(invalid(
+            subject,
+            "cannot be published to as it contains a wildcard",
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(subject: &str, reason: &str) -> io::Error {
+    Error::InvalidSubject(format!("invalid subject {:?}: {}", subject, reason)).into()
+}
+
+/// Returns true if `subject` matches `filter`, which may contain wildcards.
+pub(crate) fn matches(filter: &str, subject: &str) -> bool {
+    let mut filter = filter.split('.');
+    let mut subject = subject.split('.');
+    loop {
+        match (filter.next(), subject.next()) {
+            (Some(">"), Some(_)) => return true,
+            (Some("*"), Some(_)) => {}
+            (Some(f), Some(s)) if f == s => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+impl fmt::Display for Subject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Subject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Deref for Subject {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Subject {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Subject {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Subject {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Subject {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl<'a, T: AsRef<str> + ?Sized> From<&'a T> for Subject {
+    fn from(subject: &'a T) -> Subject {
+        Subject(Arc::from(subject.as_ref()))
+    }
+}
+
+impl From<String> for Subject {
+    fn from(subject: String) -> Subject {
+        Subject(Arc::from(subject))
+    }
+}
+
+impl From<Arc<str>> for Subject {
+    fn from(subject: Arc<str>) -> Subject {
+        Subject(subject)
+    }
+}
+
+impl From<Subject> for String {
+    fn from(subject: Subject) -> String {
+        subject.0.to_string()
+    }
+}
@@ -12,14 +12,17 @@
 // limitations under the License.
 
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
 use crossbeam_channel as channel;
 
-use crate::client::Client;
+use crate::client::{Client, MessageHandler};
+use crate::dispatch::Dispatch;
 use crate::message::Message;
+use crate::shutdown::Drain;
 
 #[derive(Debug)]
 struct Inner {
@@ -142,10 +145,7 @@ impl Subscription {
     pub fn next_timeout(&self, timeout: Duration) -> io::Result<Message> {
         match self.0.messages.recv_timeout(timeout) {
             Ok(msg) => Ok(msg),
-            Err(channel::RecvTimeoutError::Timeout) => Err(io::Error::new(
-                io::ErrorKind::TimedOut,
-                "next_timeout: timed out",
-            )),
+            Err(channel::RecvTimeoutError::Timeout) => Err(crate::Error::TimedOut.into()),
             Err(channel::RecvTimeoutError::Disconnected) => Err(io::Error::new(
                 io::ErrorKind::Other,
                 "next_timeout: unsubscribed",
@@ -244,22 +244,157 @@ impl Subscription {
         // dropped it will not unsubscribe from the server.
         let sub = self.clone();
         thread::Builder::new()
-            .name(format!("nats_subscriber_{}_{}", self.0.sid, self.0.subject))
+            .name(
+                self.0
+                    .client
+                    .thread_name(format_args!("subscriber_{}_{}", self.0.sid, self.0.subject)),
+            )
             .spawn(move || {
                 for m in sub.iter() {
                     if let Err(e) = handler(m) {
                         // TODO(dlc) - Capture for last error?
-                        log::error!("Error in callback! {:?}", e);
+                        crate::logging::error!("Error in callback! {:?}", e);
                     }
                 }
             })
             .expect("threads should be spawnable");
-        Handler { sub: self }
+        Handler {
+            sub: self,
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// Process subscription messages with a handler run where `dispatch` says, see
+    /// [`Dispatch`]. [`Dispatch::Dedicated`] is the same as [`Subscription::with_handler`].
+    ///
+    /// Like with [`Subscription::with_handler`], dropping the returned [`Handler`] does not
+    /// unsubscribe, and messages can no longer be received from the subscription directly.
+    /// Messages queued before are passed to the handler first, so with
+    /// [`Dispatch::Inline`] they are handled on the calling thread.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::dispatch::Dispatch;
+    ///
+    /// nc.subscribe("bar")?.with_handler_on(Dispatch::Inline, move |msg| {
+    ///     println!("Received {}", &msg);
+    ///     Ok(())
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_handler_on<F>(self, dispatch: Dispatch, handler: F) -> Handler
+    where
+        F: Fn(Message) -> io::Result<()> + Send + Sync + 'static,
+    {
+        let pool = match dispatch {
+            Dispatch::Dedicated => return self.with_handler(handler),
+            Dispatch::Inline => None,
+            Dispatch::Pool(pool) => Some(pool),
+        };
+
+        // The handler keeps the subscription alive, like the thread of `with_handler` does.
+        let sub = self.clone();
+        let run = move |m: Message| {
+            if let Err(e) = handler(m) {
+                crate::logging::error!("Error in callback of subscription {}! {:?}", sub.0.sid, e);
+            }
+        };
+
+        // Count messages handed to the handler until it returns, so draining waits for them.
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let deliver: MessageHandler = match pool {
+            None => {
+                let in_flight = in_flight.clone();
+                Arc::new(move |m: Message| {
+                    in_flight.fetch_add(1, Ordering::SeqCst);
+                    run(m);
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            }
+            Some(pool) => {
+                let run = Arc::new(run);
+                let in_flight = in_flight.clone();
+                Arc::new(move |m: Message| {
+                    let run = run.clone();
+                    let in_flight = in_flight.clone();
+                    in_flight.fetch_add(1, Ordering::SeqCst);
+                    pool.execute(move || {
+                        run(m);
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                    });
+                })
+            }
+        };
+
+        self.0
+            .client
+            .set_handler(self.0.sid, deliver, &self.0.messages);
+        Handler {
+            sub: self,
+            in_flight,
+        }
+    }
+
+    /// Mounts a [`tower::Service`] as the responder of this subscription. Each message is
+    /// passed to the service on a dedicated thread, one at a time, and the response is
+    /// published with its headers to the reply subject, if the message has one.
+    ///
+    /// Errors returned by the service are logged like errors of [`Subscription::with_handler`].
+    #[cfg(feature = "tower")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+    pub fn with_service<S>(self, mut service: S) -> Handler
+    where
+        S: tower::Service<Message, Response = Message> + Send + 'static,
+        S::Error: std::fmt::Display,
+    {
+        let sub = self.clone();
+        thread::Builder::new()
+            .name(
+                self.0
+                    .client
+                    .thread_name(format_args!("service_{}_{}", self.0.sid, self.0.subject)),
+            )
+            .spawn(move || {
+                for m in sub.iter() {
+                    if let Err(e) =
+                        crate::rpc::block_on(|cx| tower::Service::poll_ready(&mut service, cx))
+                    {
+                        crate::logging::error!("Service is not ready! {}", e);
+                        break;
+                    }
+                    let reply = m.reply.clone();
+                    let mut future = Box::pin(tower::Service::call(&mut service, m));
+                    match crate::rpc::block_on(|cx| std::future::Future::poll(future.as_mut(), cx))
+                    {
+                        Ok(response) => {
+                            if let Some(reply) = reply {
+                                if let Err(e) = sub.0.client.publish(
+                                    &reply,
+                                    None,
+                                    response.headers.as_ref(),
+                                    &response.data,
+                                ) {
+                                    crate::logging::error!("Error responding! {:?}", e);
+                                }
+                            }
+                        }
+                        Err(e) => crate::logging::error!("Error in service! {}", e),
+                    }
+                }
+            })
+            .expect("threads should be spawnable");
+        Handler {
+            sub: self,
+            in_flight: Arc::default(),
+        }
     }
 
     /// Sets limit of how many messages can wait in internal queue.
     /// If limit will be reached, `error_callback` will be fired with information
-    /// which subscription is affected
+    /// which subscription is affected. Overrides [`crate::Options::subscription_capacity`].
     ///
     /// # Example
     /// ```
@@ -282,8 +417,40 @@ impl Subscription {
             .and_modify(|sub| sub.pending_messages_limit = Some(limit));
     }
 
+    /// Sets limits of how many messages, and how many bytes of payload, can wait in the
+    /// internal queue, `None` meaning unlimited. Messages that would exceed either limit are
+    /// dropped, firing `error_callback` with a slow consumer error for the subscription.
+    /// Overrides [`crate::Options::subscription_capacity`].
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let sub = nc.subscribe("bar")?;
+    /// sub.set_pending_limits(1000, 64 * 1024 * 1024);
+    /// sub.set_pending_limits(None, 1024 * 1024);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_pending_limits<M, B>(&self, max_messages: M, max_bytes: B)
+    where
+        M: Into<Option<usize>>,
+        B: Into<Option<usize>>,
+    {
+        let (max_messages, max_bytes) = (max_messages.into(), max_bytes.into());
+        self.0
+            .client
+            .state
+            .read
+            .lock()
+            .subscriptions
+            .entry(self.0.sid)
+            .and_modify(|sub| sub.set_pending_limits(max_messages, max_bytes));
+    }
+
     /// Returns number of dropped messages for this Subscription.
-    /// Dropped messages occur when `set_message_limits` is set and threshold is reached,
+    /// Dropped messages occur when `set_message_limits` or `set_pending_limits` is set and
+    /// threshold is reached,
     /// triggering `slow consumer` error.
     ///
     /// # Example:
@@ -412,6 +579,8 @@ impl<'a> IntoIterator for &'a Subscription {
 /// A `Handler` may be used to unsubscribe a handler thread.
 pub struct Handler {
     sub: Subscription,
+    /// Messages passed to a handler of [`Subscription::with_handler_on`] that it did not finish.
+    in_flight: Arc<AtomicUsize>,
 }
 
 impl Handler {
@@ -434,6 +603,26 @@ impl Handler {
     }
 }
 
+impl Drain for Subscription {
+    fn drain(&self) -> io::Result<()> {
+        Subscription::drain(self)
+    }
+
+    fn is_drained(&self) -> bool {
+        self.0.messages.is_empty()
+    }
+}
+
+impl Drain for Handler {
+    fn drain(&self) -> io::Result<()> {
+        self.sub.drain()
+    }
+
+    fn is_drained(&self) -> bool {
+        self.sub.0.messages.is_empty() && self.in_flight.load(Ordering::SeqCst) == 0
+    }
+}
+
 /// A non-blocking iterator over messages from a `Subscription`
 pub struct TryIter<'a> {
     subscription: &'a Subscription,
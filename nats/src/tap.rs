@@ -0,0 +1,181 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::proto::{ClientOp, ServerOp};
+use crate::Options;
+
+/// Number of payload bytes included in a [`ProtocolEvent`].
+pub const TAP_PAYLOAD_PREVIEW: usize = 64;
+
+/// Whether a protocol operation was sent or received by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Received from the server.
+    Inbound,
+    /// Sent to the server.
+    Outbound,
+}
+
+/// A protocol operation passed to the tap set with [`Options::protocol_tap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolEvent {
+    /// Whether the operation was sent or received.
+    pub direction: Direction,
+    /// Name of the operation, like `PUB` or `MSG`.
+    pub op: &'static str,
+    /// Subject of the operation, if any.
+    pub subject: Option<String>,
+    /// Reply subject of the operation, if any.
+    pub reply: Option<String>,
+    /// Subscription id of the operation, if any.
+    pub sid: Option<u64>,
+    /// Size of the headers in bytes.
+    pub header_len: usize,
+    /// Size of the payload in bytes.
+    pub payload_len: usize,
+    /// The first [`TAP_PAYLOAD_PREVIEW`] bytes of the payload.
+    pub payload: Vec<u8>,
+    /// The arguments of operations without subjects, like the JSON of `CONNECT` with
+    /// credentials redacted or the message of `-ERR`.
+    pub arguments: Option<String>,
+}
+
+impl ProtocolEvent {
+    fn new(direction: Direction, op: &'static str) -> ProtocolEvent {
+        ProtocolEvent {
+            direction,
+            op,
+            subject: None,
+            reply: None,
+            sid: None,
+            header_len: 0,
+            payload_len: 0,
+            payload: Vec::new(),
+            arguments: None,
+        }
+    }
+
+    fn with_payload(mut self, payload: &[u8]) -> ProtocolEvent {
+        self.payload_len = payload.len();
+        self.payload = payload[..payload.len().min(TAP_PAYLOAD_PREVIEW)].to_vec();
+        self
+    }
+
+    fn outbound(op: &ClientOp<'_>) -> ProtocolEvent {
+        match op {
+            ClientOp::Connect(connect_info) => ProtocolEvent {
+                arguments: connect_info.redacted().dump(),
+                ..ProtocolEvent::new(Direction::Outbound, "CONNECT")
+            },
+            ClientOp::Pub {
+                subject,
+                reply_to,
+                payload,
+            } => ProtocolEvent {
+                subject: Some(subject.to_string()),
+                reply: reply_to.map(str::to_string),
+                ..ProtocolEvent::new(Direction::Outbound, "PUB")
+            }
+            .with_payload(payload),
+            ClientOp::Hpub {
+                subject,
+                reply_to,
+                headers,
+                payload,
+            } => ProtocolEvent {
+                subject: Some(subject.to_string()),
+                reply: reply_to.map(str::to_string),
+                header_len: headers.to_bytes().len(),
+                ..ProtocolEvent::new(Direction::Outbound, "HPUB")
+            }
+            .with_payload(payload),
+            ClientOp::Sub {
+                subject,
+                queue_group,
+                sid,
+            } => ProtocolEvent {
+                subject: Some(subject.to_string()),
+                sid: Some(*sid),
+                arguments: queue_group.map(str::to_string),
+                ..ProtocolEvent::new(Direction::Outbound, "SUB")
+            },
+            ClientOp::Unsub { sid, max_msgs } => ProtocolEvent {
+                sid: Some(*sid),
+                arguments: max_msgs.map(|max_msgs| max_msgs.to_string()),
+                ..ProtocolEvent::new(Direction::Outbound, "UNSUB")
+            },
+            ClientOp::Ping => ProtocolEvent::new(Direction::Outbound, "PING"),
+            ClientOp::Pong => ProtocolEvent::new(Direction::Outbound, "PONG"),
+        }
+    }
+
+    fn inbound(op: &ServerOp) -> ProtocolEvent {
+        match op {
+            ServerOp::Info(server_info) => ProtocolEvent {
+                arguments: Some(server_info.server_id.clone()),
+                ..ProtocolEvent::new(Direction::Inbound, "INFO")
+            },
+            ServerOp::Msg {
+                subject,
+                sid,
+                reply_to,
+                payload,
+            } => ProtocolEvent {
+                subject: Some(subject.clone()),
+                reply: reply_to.clone(),
+                sid: Some(*sid),
+                ..ProtocolEvent::new(Direction::Inbound, "MSG")
+            }
+            .with_payload(payload),
+            ServerOp::Hmsg {
+                subject,
+                headers,
+                sid,
+                reply_to,
+                payload,
+            } => ProtocolEvent {
+                subject: Some(subject.clone()),
+                reply: reply_to.clone(),
+                sid: Some(*sid),
+                header_len: headers.to_bytes().len(),
+                ..ProtocolEvent::new(Direction::Inbound, "HMSG")
+            }
+            .with_payload(payload),
+            ServerOp::Ping => ProtocolEvent::new(Direction::Inbound, "PING"),
+            ServerOp::Pong => ProtocolEvent::new(Direction::Inbound, "PONG"),
+            ServerOp::Ok => ProtocolEvent::new(Direction::Inbound, "+OK"),
+            ServerOp::Err(message) => ProtocolEvent {
+                arguments: Some(message.clone()),
+                ..ProtocolEvent::new(Direction::Inbound, "-ERR")
+            },
+            ServerOp::Unknown(line) => ProtocolEvent {
+                arguments: Some(line.clone()),
+                ..ProtocolEvent::new(Direction::Inbound, "UNKNOWN")
+            },
+        }
+    }
+}
+
+/// Passes an operation sent to the server to the protocol tap, if set.
+pub(crate) fn outbound(options: &Options, op: &ClientOp<'_>) {
+    if let Some(tap) = options.protocol_tap.as_ref() {
+        tap(&ProtocolEvent::outbound(op));
+    }
+}
+
+/// Passes an operation received from the server to the protocol tap, if set.
+pub(crate) fn inbound(options: &Options, op: &ServerOp) {
+    if let Some(tap) = options.protocol_tap.as_ref() {
+        tap(&ProtocolEvent::inbound(op));
+    }
+}
@@ -0,0 +1,656 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities for testing code that uses the client, enabled with the `test_utils` feature.
+//!
+//! [`MockServer`] speaks enough of the protocol to run the client against it without a
+//! `nats-server` binary, and lets tests script the behavior of the server.
+//! [`FaultProxy`] sits between the client and a server and injects latency, partial
+//! writes and disconnects.
+//!
+//! [`ReplayServer`] serves a capture recorded with [`crate::Options::record`] back to the
+//! client.
+//!
+//! # Example
+//! ```
+//! # fn main() -> std::io::Result<()> {
+//! use nats::test::MockServer;
+//!
+//! let server = MockServer::start()?;
+//! let nc = nats::connect(&server.client_url())?;
+//!
+//! let sub = nc.subscribe("greetings")?;
+//! nc.publish("greetings", "hello")?;
+//! assert_eq!(sub.next().unwrap().data, b"hello");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::record::{self, Record};
+
+/// An in-process server speaking the core protocol: `INFO`, `CONNECT`, `PING`, `PONG`,
+/// `SUB`, `UNSUB`, `PUB`, `HPUB`, `MSG` and `HMSG`.
+///
+/// Messages are routed between all connected clients, including wildcard and queue
+/// subscriptions. The server is stopped on drop.
+pub struct MockServer {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    connections: Mutex<Vec<Connection>>,
+    pong_delay: Mutex<Duration>,
+    next_id: AtomicU64,
+}
+
+struct Connection {
+    id: u64,
+    stream: TcpStream,
+    subscriptions: Vec<MockSubscription>,
+}
+
+struct MockSubscription {
+    sid: String,
+    subject: String,
+    queue_group: Option<String>,
+}
+
+impl MockServer {
+    /// Starts a server listening on a random local port.
+    pub fn start() -> io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let shared = Arc::new(Shared::default());
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let shared = shared.clone();
+            let shutdown = shutdown.clone();
+            move || accept(listener, address, shared, shutdown)
+        });
+
+        Ok(MockServer {
+            address,
+            shared,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the URL clients connect to.
+    pub fn client_url(&self) -> String {
+        format!("nats://{}", self.address)
+    }
+
+    /// Returns the number of connected clients.
+    pub fn connections(&self) -> usize {
+        self.shared.connections.lock().len()
+    }
+
+    /// Closes the connections of all clients, which makes them reconnect.
+    pub fn drop_connections(&self) {
+        for connection in self.shared.connections.lock().drain(..) {
+            connection.stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+
+    /// Sends `-ERR '<message>'` to all clients.
+    pub fn send_err(&self, message: &str) {
+        self.write_all(format!("-ERR '{}'\r\n", message).as_bytes());
+    }
+
+    /// Delays every `PONG` sent from now on by `delay`.
+    pub fn delay_pong(&self, delay: Duration) {
+        *self.shared.pong_delay.lock() = delay;
+    }
+
+    /// Delivers a message published by the server itself to the matching subscriptions.
+    pub fn publish(&self, subject: &str, payload: &[u8]) {
+        route(&self.shared, subject, None, None, payload);
+    }
+
+    fn write_all(&self, bytes: &[u8]) {
+        for connection in self.shared.connections.lock().iter() {
+            (&connection.stream).write_all(bytes).ok();
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+        self.drop_connections();
+    }
+}
+
+/// Accepts clients until the server is shut down.
+fn accept(
+    listener: TcpListener,
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(Ordering::Acquire) {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(_) => {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+        };
+
+        let id = shared.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let info = format!(
+            "INFO {{\"server_id\":\"mock\",\"server_name\":\"mock\",\"version\":\"2.9.0\",\
+             \"go\":\"mock\",\"host\":\"{}\",\"port\":{},\"headers\":true,\
+             \"max_payload\":1048576,\"proto\":1,\"client_id\":{}}}\r\n",
+            address.ip(),
+            address.port(),
+            id
+        );
+
+        let registered = stream.set_nonblocking(false).and_then(|_| {
+            (&stream).write_all(info.as_bytes())?;
+            stream.try_clone()
+        });
+        let reader = match registered {
+            Ok(reader) => reader,
+            Err(_) => continue,
+        };
+
+        shared.connections.lock().push(Connection {
+            id,
+            stream,
+            subscriptions: Vec::new(),
+        });
+
+        thread::spawn({
+            let shared = shared.clone();
+            move || {
+                serve(&shared, id, reader).ok();
+                shared
+                    .connections
+                    .lock()
+                    .retain(|connection| connection.id != id);
+            }
+        });
+    }
+}
+
+/// Handles the operations sent by a client until it disconnects.
+fn serve(shared: &Shared, id: u64, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        let mut parts = line.split_whitespace();
+        let op = parts.next().unwrap_or_default().to_ascii_uppercase();
+        let args: Vec<&str> = parts.collect();
+
+        match (op.as_str(), args.as_slice()) {
+            ("CONNECT", _) | ("PONG", _) => {}
+
+            ("PING", _) => {
+                let delay = *shared.pong_delay.lock();
+                thread::sleep(delay);
+                write_to(shared, id, b"PONG\r\n");
+            }
+
+            ("SUB", [subject, sid]) | ("SUB", [subject, _, sid]) => {
+                let queue_group = if args.len() == 3 {
+                    Some(args[1].to_string())
+                } else {
+                    None
+                };
+                let mut connections = shared.connections.lock();
+                if let Some(connection) = connections.iter_mut().find(|c| c.id == id) {
+                    connection.subscriptions.push(MockSubscription {
+                        sid: sid.to_string(),
+                        subject: subject.to_string(),
+                        queue_group,
+                    });
+                }
+            }
+
+            ("UNSUB", [sid, ..]) => {
+                let mut connections = shared.connections.lock();
+                if let Some(connection) = connections.iter_mut().find(|c| c.id == id) {
+                    connection.subscriptions.retain(|sub| sub.sid != *sid);
+                }
+            }
+
+            ("PUB", [subject, .., size]) => {
+                let reply = if args.len() == 3 { Some(args[1]) } else { None };
+                let payload = read_payload(&mut reader, size)?;
+                route(shared, subject, reply, None, &payload);
+            }
+
+            ("HPUB", [subject, .., header_len, size]) => {
+                let reply = if args.len() == 4 { Some(args[1]) } else { None };
+                let header_len = parse_size(header_len)?;
+                let payload = read_payload(&mut reader, size)?;
+                route(shared, subject, reply, Some(header_len), &payload);
+            }
+
+            _ => write_to(shared, id, b"-ERR 'Unknown Protocol Operation'\r\n"),
+        }
+    }
+}
+
+/// Reads a payload of `size` bytes followed by CRLF.
+fn read_payload(reader: &mut impl Read, size: &str) -> io::Result<Vec<u8>> {
+    let mut payload = vec![0; parse_size(size)? + 2];
+    reader.read_exact(&mut payload)?;
+    payload.truncate(payload.len() - 2);
+    Ok(payload)
+}
+
+fn parse_size(size: &str) -> io::Result<usize> {
+    size.parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid size"))
+}
+
+fn write_to(shared: &Shared, id: u64, bytes: &[u8]) {
+    let connections = shared.connections.lock();
+    if let Some(connection) = connections.iter().find(|c| c.id == id) {
+        (&connection.stream).write_all(bytes).ok();
+    }
+}
+
+/// Delivers a message to every matching subscription and to one member of every
+/// matching queue group.
+fn route(
+    shared: &Shared,
+    subject: &str,
+    reply: Option<&str>,
+    header_len: Option<usize>,
+    payload: &[u8],
+) {
+    let connections = shared.connections.lock();
+    let mut queue_groups = HashSet::new();
+
+    for connection in connections.iter() {
+        for sub in &connection.subscriptions {
+            if !crate::subject::matches(&sub.subject, subject) {
+                continue;
+            }
+            if let Some(queue_group) = sub.queue_group.as_ref() {
+                if !queue_groups.insert((sub.subject.as_str(), queue_group.as_str())) {
+                    continue;
+                }
+            }
+
+            let reply = reply.map(|reply| format!("{} ", reply)).unwrap_or_default();
+            let mut message = match header_len {
+                Some(header_len) => format!(
+                    "HMSG {} {} {}{} {}\r\n",
+                    subject,
+                    sub.sid,
+                    reply,
+                    header_len,
+                    payload.len()
+                ),
+                None => format!("MSG {} {} {}{}\r\n", subject, sub.sid, reply, payload.len()),
+            }
+            .into_bytes();
+            message.extend_from_slice(payload);
+            message.extend_from_slice(b"\r\n");
+
+            (&connection.stream).write_all(&message).ok();
+        }
+    }
+}
+
+/// A TCP proxy injecting faults into the traffic between clients and a server, for
+/// testing reconnects and redeliveries deterministically.
+///
+/// Faults apply to both directions and can be changed while clients are connected. The
+/// proxy is stopped on drop.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use nats::test::FaultProxy;
+/// use std::time::Duration;
+///
+/// let proxy = FaultProxy::start("127.0.0.1:4222")?;
+/// let nc = nats::connect(&proxy.client_url())?;
+///
+/// proxy.latency(Duration::from_millis(50));
+/// proxy.chunk_size(Some(3));
+/// proxy.disconnect_after(1024);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FaultProxy {
+    address: SocketAddr,
+    faults: Arc<Faults>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct Faults {
+    latency: Mutex<Duration>,
+    chunk_size: Mutex<Option<usize>>,
+    disconnect_after: Mutex<Option<usize>>,
+    streams: Mutex<Vec<TcpStream>>,
+}
+
+impl Faults {
+    /// Closes all proxied connections.
+    fn disconnect(&self) {
+        for stream in self.streams.lock().drain(..) {
+            stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+}
+
+impl FaultProxy {
+    /// Starts a proxy on a random local port forwarding connections to `upstream`.
+    pub fn start(upstream: impl ToSocketAddrs) -> io::Result<FaultProxy> {
+        let upstream = upstream
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no upstream address"))?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let faults = Arc::new(Faults::default());
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let faults = faults.clone();
+            let shutdown = shutdown.clone();
+            move || proxy(listener, upstream, faults, shutdown)
+        });
+
+        Ok(FaultProxy {
+            address,
+            faults,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the proxy is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the URL clients connect to.
+    pub fn client_url(&self) -> String {
+        format!("nats://{}", self.address)
+    }
+
+    /// Delays every forwarded chunk by `latency`.
+    pub fn latency(&self, latency: Duration) {
+        *self.faults.latency.lock() = latency;
+    }
+
+    /// Splits forwarded data into writes of at most `size` bytes, or forwards it as read
+    /// with `None`.
+    pub fn chunk_size(&self, size: Option<usize>) {
+        *self.faults.chunk_size.lock() = size.map(|size| size.max(1));
+    }
+
+    /// Closes all connections after forwarding `bytes` more bytes, possibly in the middle
+    /// of a protocol operation.
+    pub fn disconnect_after(&self, bytes: usize) {
+        *self.faults.disconnect_after.lock() = Some(bytes);
+    }
+
+    /// Closes all connections now.
+    pub fn disconnect(&self) {
+        self.faults.disconnect();
+    }
+}
+
+impl Drop for FaultProxy {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+        self.faults.disconnect();
+    }
+}
+
+/// Accepts clients and connects each of them to the upstream server until the proxy is
+/// shut down.
+fn proxy(
+    listener: TcpListener,
+    upstream: SocketAddr,
+    faults: Arc<Faults>,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(Ordering::Acquire) {
+        let client = match listener.accept() {
+            Ok((client, _)) => client,
+            Err(_) => {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+        };
+
+        let streams = client.set_nonblocking(false).and_then(|_| {
+            let server = TcpStream::connect(upstream)?;
+            Ok((client.try_clone()?, server.try_clone()?, client, server))
+        });
+        let (client_reader, server_reader, client, server) = match streams {
+            Ok(streams) => streams,
+            Err(_) => continue,
+        };
+
+        {
+            let mut streams = faults.streams.lock();
+            for stream in [&client, &server] {
+                if let Ok(stream) = stream.try_clone() {
+                    streams.push(stream);
+                }
+            }
+        }
+
+        for (reader, writer) in [(client_reader, server), (server_reader, client)] {
+            let faults = faults.clone();
+            thread::spawn(move || forward(&faults, reader, writer).ok());
+        }
+    }
+}
+
+/// Copies data from `reader` to `writer`, injecting the configured faults.
+fn forward(faults: &Faults, mut reader: TcpStream, mut writer: TcpStream) -> io::Result<()> {
+    let mut buf = vec![0; 32 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            writer.shutdown(Shutdown::Write).ok();
+            return Ok(());
+        }
+
+        let chunk_size = faults.chunk_size.lock().unwrap_or(n);
+        for chunk in buf[..n].chunks(chunk_size) {
+            thread::sleep(*faults.latency.lock());
+
+            let mut disconnect_after = faults.disconnect_after.lock();
+            let (chunk, disconnect) = match *disconnect_after {
+                Some(remaining) if remaining <= chunk.len() => (&chunk[..remaining], true),
+                Some(remaining) => {
+                    *disconnect_after = Some(remaining - chunk.len());
+                    (chunk, false)
+                }
+                None => (chunk, false),
+            };
+            if disconnect {
+                *disconnect_after = None;
+            }
+            drop(disconnect_after);
+
+            writer.write_all(chunk)?;
+            writer.flush()?;
+
+            if disconnect {
+                faults.disconnect();
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// How long a replayed connection waits for the client to send what it sent while recording.
+const REPLAY_READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A server replaying a capture recorded with [`crate::Options::record`].
+///
+/// The `n`th accepted connection is served the traffic of the `n`th recorded connection:
+/// recorded server bytes are sent back in order, each after the client sent about as many
+/// bytes as it did while recording. The server is stopped on drop.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use nats::test::ReplayServer;
+///
+/// let server = ReplayServer::start("capture.nats")?;
+/// let nc = nats::connect(&server.client_url())?;
+/// let sub = nc.subscribe("events")?;
+/// for message in sub.iter() {
+///     println!("{}", message);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReplayServer {
+    address: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ReplayServer {
+    /// Starts a server on a random local port replaying the capture at `path`.
+    pub fn start(path: impl AsRef<Path>) -> io::Result<ReplayServer> {
+        let connections = record::read_capture(path.as_ref())?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let shutdown = shutdown.clone();
+            move || {
+                let mut connections = connections.into_iter();
+                while !shutdown.load(Ordering::Acquire) {
+                    let stream = match listener.accept() {
+                        Ok((stream, _)) => stream,
+                        Err(_) => {
+                            thread::sleep(Duration::from_millis(10));
+                            continue;
+                        }
+                    };
+                    // Close connections beyond the recorded ones right away.
+                    if let Some(records) = connections.next() {
+                        thread::spawn(move || replay(stream, records).ok());
+                    }
+                }
+            }
+        });
+
+        Ok(ReplayServer {
+            address,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the URL clients connect to.
+    pub fn client_url(&self) -> String {
+        format!("nats://{}", self.address)
+    }
+}
+
+impl Drop for ReplayServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+/// Replays the records of a connection, then waits for the client to disconnect.
+fn replay(mut stream: TcpStream, records: Vec<Record>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REPLAY_READ_TIMEOUT))?;
+    let mut buf = vec![0; 32 * 1024];
+
+    for record in records {
+        match record {
+            Record::Inbound(bytes) => stream.write_all(&bytes)?,
+            Record::Outbound(bytes) => {
+                let mut remaining = bytes.len();
+                while remaining > 0 {
+                    let len = remaining.min(buf.len());
+                    match stream.read(&mut buf[..len]) {
+                        Ok(0) => return Ok(()),
+                        Ok(n) => remaining -= n,
+                        Err(err)
+                            if err.kind() == io::ErrorKind::WouldBlock
+                                || err.kind() == io::ErrorKind::TimedOut =>
+                        {
+                            break
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+        }
+    }
+
+    stream.set_read_timeout(None)?;
+    while stream.read(&mut buf)? > 0 {}
+    Ok(())
+}
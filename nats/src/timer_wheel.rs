@@ -0,0 +1,142 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A hashed timer wheel, expiring large numbers of deadlines with a single timer.
+//!
+//! Deadlines are rounded up to ticks and hashed into a fixed number of slots by tick, so
+//! inserting is constant time and each tick only visits the timers of one slot. Timers
+//! are not cancelled, their owners ignore timers that expire after they completed.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Timer<T> {
+    tick: u64,
+    value: T,
+}
+
+#[derive(Debug)]
+pub(crate) struct TimerWheel<T> {
+    resolution: Duration,
+    start: Instant,
+    /// The last tick expired, timers of this tick and earlier ones have expired.
+    expired: u64,
+    slots: Vec<Vec<Timer<T>>>,
+    len: usize,
+}
+
+impl<T> TimerWheel<T> {
+    /// Creates a wheel of the given number of slots, expiring deadlines at the resolution.
+    pub(crate) fn new(resolution: Duration, slots: usize) -> TimerWheel<T> {
+        TimerWheel {
+            resolution,
+            start: Instant::now(),
+            expired: 0,
+            slots: (0..slots.max(1)).map(|_| Vec::new()).collect(),
+            len: 0,
+        }
+    }
+
+    /// Returns true if all timers expired.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the resolution of the wheel.
+    pub(crate) fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    /// Adds a timer expiring at the first tick after the deadline, or the next tick if the
+    /// deadline already passed.
+    pub(crate) fn insert(&mut self, deadline: Instant, value: T) {
+        let elapsed = deadline.saturating_duration_since(self.start);
+        let tick = ((elapsed.as_nanos() + self.resolution.as_nanos() - 1)
+            / self.resolution.as_nanos())
+        .max(u128::from(self.expired) + 1) as u64;
+
+        let slot = (tick % self.slots.len() as u64) as usize;
+        self.slots[slot].push(Timer { tick, value });
+        self.len += 1;
+    }
+
+    /// Removes the timers that expired by `now`, in no particular order.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<T> {
+        let now_tick = (now.saturating_duration_since(self.start).as_nanos()
+            / self.resolution.as_nanos()) as u64;
+        if now_tick <= self.expired {
+            return Vec::new();
+        }
+
+        // Visit the slots of the ticks since the last call, each slot at most once.
+        let ticks = (now_tick - self.expired).min(self.slots.len() as u64);
+        let mut expired = Vec::new();
+        for tick in now_tick + 1 - ticks..=now_tick {
+            let index = (tick % self.slots.len() as u64) as usize;
+            let slot = &mut self.slots[index];
+            let mut i = 0;
+            while i < slot.len() {
+                if slot[i].tick <= now_tick {
+                    expired.push(slot.swap_remove(i).value);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        self.expired = now_tick;
+        self.len -= expired.len();
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_after_deadline() {
+        let mut wheel = TimerWheel::new(Duration::from_millis(10), 8);
+        let start = wheel.start;
+        wheel.insert(start + Duration::from_millis(25), "short");
+        // Wraps around the wheel more than once.
+        wheel.insert(start + Duration::from_millis(205), "long");
+        assert!(!wheel.is_empty());
+
+        assert!(wheel.expire(start + Duration::from_millis(20)).is_empty());
+        assert_eq!(
+            wheel.expire(start + Duration::from_millis(30)),
+            vec!["short"]
+        );
+        assert!(wheel.expire(start + Duration::from_millis(200)).is_empty());
+        assert_eq!(
+            wheel.expire(start + Duration::from_millis(210)),
+            vec!["long"]
+        );
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn passed_deadline_expires_next_tick() {
+        let mut wheel = TimerWheel::new(Duration::from_millis(10), 8);
+        let start = wheel.start;
+        wheel.expire(start + Duration::from_millis(50));
+        wheel.insert(start, "late");
+
+        assert!(wheel.expire(start + Duration::from_millis(55)).is_empty());
+        assert_eq!(
+            wheel.expire(start + Duration::from_millis(60)),
+            vec!["late"]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Propagation of [W3C Trace Context](https://www.w3.org/TR/trace-context/) in message
+//! headers, so distributed traces continue across NATS hops.
+//!
+//! Contexts can be injected by hand with [`TraceContext::inject`], or automatically on every
+//! publish by setting a provider with [`crate::Options::trace_context`]. Receivers extract
+//! them with [`crate::Message::trace_context`].
+
+use crate::header::HeaderMap;
+
+/// Header carrying the trace id, parent id and flags.
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Header carrying vendor specific trace state.
+pub const TRACESTATE: &str = "tracestate";
+
+const SAMPLED: u8 = 0x01;
+
+/// A W3C trace context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// The trace id, as 32 lowercase hex characters.
+    pub trace_id: String,
+    /// The id of the parent span, as 16 lowercase hex characters.
+    pub parent_id: String,
+    /// Trace flags.
+    pub flags: u8,
+    /// The vendor specific `tracestate`, if any.
+    pub state: Option<String>,
+}
+
+impl TraceContext {
+    /// Returns true if the trace is sampled.
+    pub fn is_sampled(&self) -> bool {
+        self.flags & SAMPLED != 0
+    }
+
+    /// Parses a `traceparent` header value, returning `None` if it is malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use nats::trace_context::TraceContext;
+    ///
+    /// let context =
+    ///     TraceContext::parse("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01").unwrap();
+    /// assert_eq!(context.trace_id, "0af7651916cd43dd8448eb211c80319c");
+    /// assert!(context.is_sampled());
+    /// ```
+    pub fn parse(traceparent: &str) -> Option<TraceContext> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+
+        // Future versions may append fields, version 00 may not.
+        if !is_hex(version, 2) || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if !is_hex(trace_id, 32) || trace_id.bytes().all(|b| b == b'0') {
+            return None;
+        }
+        if !is_hex(parent_id, 16) || parent_id.bytes().all(|b| b == b'0') {
+            return None;
+        }
+        if !is_hex(flags, 2) {
+            return None;
+        }
+
+        Some(TraceContext {
+            trace_id: trace_id.to_string(),
+            parent_id: parent_id.to_string(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            state: None,
+        })
+    }
+
+    /// Formats the context as a `traceparent` header value.
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.parent_id, self.flags)
+    }
+
+    /// Sets the `traceparent` and, if present, the `tracestate` headers.
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        headers.insert(TRACEPARENT, self.traceparent());
+        if let Some(state) = &self.state {
+            headers.insert(TRACESTATE, state.as_str());
+        }
+    }
+
+    /// Extracts the context from the `traceparent` and `tracestate` headers.
+    pub fn extract(headers: &HeaderMap) -> Option<TraceContext> {
+        let mut context = TraceContext::parse(headers.get(TRACEPARENT)?)?;
+        context.state = headers.get(TRACESTATE).cloned();
+        Some(context)
+    }
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
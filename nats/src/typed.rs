@@ -0,0 +1,373 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed publishing and subscriptions.
+//!
+//! Payloads are encoded and decoded by a [`Codec`], [`Json`] by default. Published messages are
+//! stamped with the `Content-Type` of the codec when the server supports headers, and
+//! received messages carrying a different `Content-Type` fail to decode. Codecs of
+//! self-describing formats like protobuf also stamp the schema of the payload in the
+//! `Nats-Schema` header, which is checked the same way.
+//!
+//! Encoding and decoding failures are reported as `io::ErrorKind::InvalidData` with the
+//! error of the codec as the inner error.
+//!
+//! To migrate a subject from one format to another, subscriptions can decode several
+//! formats, picking the codec by the `Content-Type` of each message with
+//! [`TypedSubscription::with_decoder`], before publishers switch codecs.
+//!
+//! # Example
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Order {
+//!     id: u64,
+//! }
+//!
+//! let nc = nats::connect("demo.nats.io")?;
+//! let orders = nc.subscribe_json::<Order>("orders")?;
+//! nc.publish_json("orders", &Order { id: 1 })?;
+//!
+//! for order in orders.iter() {
+//!     let (order, message) = order?;
+//!     println!("received order {} on {}", order.id, message.subject);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::encryption::Encryption;
+use crate::header::{HeaderMap, CONTENT_TYPE, NATS_SCHEMA};
+use crate::{Message, Subscription};
+
+/// A payload format, implementing [`Encode`] and [`Decode`] for the types it supports.
+pub trait Codec {
+    /// The `Content-Type` of encoded payloads.
+    const CONTENT_TYPE: &'static str;
+}
+
+/// Encodes values of type `T` into payloads.
+pub trait Encode<T: ?Sized>: Codec {
+    /// Encodes a value into a payload.
+    fn encode(value: &T) -> io::Result<Vec<u8>>;
+
+    /// Returns the identifier of the schema of `T`, if the format has one.
+    fn schema() -> Option<String> {
+        None
+    }
+}
+
+/// Decodes payloads into values of type `T`.
+pub trait Decode<T>: Codec {
+    /// Decodes a payload into a value.
+    fn decode(payload: &[u8]) -> io::Result<T>;
+
+    /// Returns the identifier of the schema of `T`, if the format has one.
+    fn schema() -> Option<String> {
+        None
+    }
+}
+
+/// Encodes payloads as JSON with `serde_json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl Codec for Json {
+    const CONTENT_TYPE: &'static str = "application/json";
+}
+
+impl<T: Serialize + ?Sized> Encode<T> for Json {
+    fn encode(value: &T) -> io::Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl<T: DeserializeOwned> Decode<T> for Json {
+    fn decode(payload: &[u8]) -> io::Result<T> {
+        serde_json::from_slice(payload)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Encodes payloads as MessagePack with `rmp-serde`, serializing structs as maps so
+/// fields are matched by name.
+#[cfg(feature = "msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPack;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgPack {
+    const CONTENT_TYPE: &'static str = "application/msgpack";
+}
+
+#[cfg(feature = "msgpack")]
+impl<T: Serialize + ?Sized> Encode<T> for MsgPack {
+    fn encode(value: &T) -> io::Result<Vec<u8>> {
+        rmp_serde::to_vec_named(value)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T: DeserializeOwned> Decode<T> for MsgPack {
+    fn decode(payload: &[u8]) -> io::Result<T> {
+        rmp_serde::from_slice(payload)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Encodes payloads as protobuf with `prost`, identifying the schema by the full name of
+/// the message.
+#[cfg(feature = "prost")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Protobuf;
+
+#[cfg(feature = "prost")]
+impl Codec for Protobuf {
+    const CONTENT_TYPE: &'static str = "application/protobuf";
+}
+
+#[cfg(feature = "prost")]
+impl<T: prost::Message + prost::Name> Encode<T> for Protobuf {
+    fn encode(value: &T) -> io::Result<Vec<u8>> {
+        Ok(value.encode_to_vec())
+    }
+
+    fn schema() -> Option<String> {
+        Some(T::full_name())
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<T: prost::Message + prost::Name + Default> Decode<T> for Protobuf {
+    fn decode(payload: &[u8]) -> io::Result<T> {
+        T::decode(payload).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn schema() -> Option<String> {
+        Some(T::full_name())
+    }
+}
+
+/// Returns the headers stamped on payloads encoded by `C`.
+pub(crate) fn encode_headers<T: ?Sized, C: Encode<T>>() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, C::CONTENT_TYPE);
+    if let Some(schema) = C::schema() {
+        headers.insert(NATS_SCHEMA, schema);
+    }
+    headers
+}
+
+/// Decodes the payload of a message, checking its `Content-Type` and schema if it has them.
+pub(crate) fn decode_message<T, C: Decode<T>>(message: &Message) -> io::Result<T> {
+    if let Some(headers) = message.headers.as_ref() {
+        if let Some(content_type) = headers.get(CONTENT_TYPE) {
+            if content_type != C::CONTENT_TYPE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "expected content type {}, got {}",
+                        C::CONTENT_TYPE,
+                        content_type
+                    ),
+                ));
+            }
+        }
+        if let (Some(schema), Some(expected)) = (headers.get(NATS_SCHEMA), C::schema()) {
+            if *schema != expected {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected schema {}, got {}", expected, schema),
+                ));
+            }
+        }
+    }
+    C::decode(&message.data)
+}
+
+/// A decoder registered for a `Content-Type` with [`TypedSubscription::with_decoder`].
+struct Decoder<T> {
+    content_type: &'static str,
+    decode: fn(&Message) -> io::Result<T>,
+}
+
+impl<T> Clone for Decoder<T> {
+    fn clone(&self) -> Self {
+        Decoder {
+            content_type: self.content_type,
+            decode: self.decode,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Decoder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decoder")
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+/// A subscription yielding decoded payloads along with their messages.
+///
+/// Payloads that fail to decode are yielded as errors without terminating the subscription.
+#[derive(Debug)]
+pub struct TypedSubscription<T, C = Json> {
+    subscription: Subscription,
+    encryption: Option<Encryption>,
+    decoders: Vec<Decoder<T>>,
+    _marker: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T, C> Clone for TypedSubscription<T, C> {
+    fn clone(&self) -> Self {
+        TypedSubscription {
+            subscription: self.subscription.clone(),
+            encryption: self.encryption.clone(),
+            decoders: self.decoders.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, C: Decode<T>> TypedSubscription<T, C> {
+    /// Wraps an existing subscription.
+    pub fn new(subscription: Subscription) -> TypedSubscription<T, C> {
+        TypedSubscription {
+            subscription,
+            encryption: None,
+            decoders: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Decodes payloads with the `Content-Type` of the codec `D` with `D` instead of `C`,
+    /// so publishers can move to another format while subscribers accept both. Payloads
+    /// without a `Content-Type` are still decoded with `C`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[cfg(not(feature = "msgpack"))]
+    /// # fn main() {}
+    /// # #[cfg(feature = "msgpack")]
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::typed::{Json, MsgPack};
+    ///
+    /// let numbers = nc
+    ///     .subscribe_with_codec::<Vec<u32>, Json>("numbers")?
+    ///     .with_decoder::<MsgPack>();
+    /// nc.publish_with_codec::<MsgPack, _>("numbers", &vec![1, 2, 3])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_decoder<D: Decode<T>>(mut self) -> TypedSubscription<T, C> {
+        self.decoders
+            .retain(|decoder| decoder.content_type != D::CONTENT_TYPE);
+        self.decoders.push(Decoder {
+            content_type: D::CONTENT_TYPE,
+            decode: decode_message::<T, D>,
+        });
+        self
+    }
+
+    /// Decrypts payloads published with
+    /// [`Connection::publish_encrypted_with_codec`](crate::Connection::publish_encrypted_with_codec)
+    /// before decoding them. Payloads that are not encrypted are yielded as errors.
+    pub fn with_encryption(mut self, encryption: Encryption) -> TypedSubscription<T, C> {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Returns the underlying untyped subscription.
+    pub fn subscription(&self) -> &Subscription {
+        &self.subscription
+    }
+
+    /// Get the next message and its decoded payload, or `None` if the subscription has been
+    /// unsubscribed or the connection closed.
+    pub fn next(&self) -> Option<io::Result<(T, Message)>> {
+        self.subscription.next().map(|message| self.decode(message))
+    }
+
+    /// Try to get the next message and its decoded payload, or `None` if no messages are
+    /// present or if the subscription has been unsubscribed or the connection closed.
+    pub fn try_next(&self) -> Option<io::Result<(T, Message)>> {
+        self.subscription
+            .try_next()
+            .map(|message| self.decode(message))
+    }
+
+    /// Get the next message and its decoded payload, or a timeout error if no messages are
+    /// available for timeout.
+    pub fn next_timeout(&self, timeout: Duration) -> io::Result<(T, Message)> {
+        self.decode(self.subscription.next_timeout(timeout)?)
+    }
+
+    /// Returns a blocking iterator over decoded payloads and their messages.
+    pub fn iter(&self) -> TypedIter<'_, T, C> {
+        TypedIter { subscription: self }
+    }
+
+    /// Unsubscribe a subscription immediately without draining.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        self.subscription.unsubscribe()
+    }
+
+    fn decode(&self, message: Message) -> io::Result<(T, Message)> {
+        let message = match self.encryption.as_ref() {
+            Some(encryption) => encryption.open(message)?,
+            None => message,
+        };
+        let decoder = message
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(CONTENT_TYPE))
+            .and_then(|content_type| {
+                self.decoders
+                    .iter()
+                    .find(|decoder| decoder.content_type == content_type.as_str())
+            });
+        let value = match decoder {
+            Some(decoder) => (decoder.decode)(&message)?,
+            None => decode_message::<T, C>(&message)?,
+        };
+        Ok((value, message))
+    }
+}
+
+/// A blocking iterator over the decoded payloads of a [`TypedSubscription`].
+pub struct TypedIter<'a, T, C = Json> {
+    subscription: &'a TypedSubscription<T, C>,
+}
+
+impl<'a, T, C: Decode<T>> Iterator for TypedIter<'a, T, C> {
+    type Item = io::Result<(T, Message)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.subscription.next()
+    }
+}
@@ -14,6 +14,7 @@
 use std::time::Duration;
 
 use crossbeam_channel::bounded;
+use nats::error::{PermissionOperation, ServerError};
 
 #[test]
 fn pub_perms() {
@@ -70,3 +71,53 @@ fn sub_perms() {
     let r = drx.recv_timeout(Duration::from_millis(100));
     assert!(r.is_err(), "we got disconnected on perm violation");
 }
+
+#[test]
+fn server_error_callback() {
+    let s = nats_server::run_server("tests/configs/perms.conf");
+
+    let (tx, rx) = bounded(1);
+    let (etx, erx) = bounded(1);
+
+    let nc = nats::Options::with_user_pass("derek", "s3cr3t!")
+        .server_error_callback(move |err| tx.send(err).unwrap())
+        .error_callback(move |err| etx.send(err).unwrap())
+        .connect(s.client_url())
+        .expect("could not connect");
+
+    nc.publish("foo", "NOT ALLOWED").unwrap();
+
+    let err = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        err,
+        ServerError::PermissionsViolation {
+            operation: PermissionOperation::Publish,
+            subject: "foo".to_string(),
+        }
+    );
+
+    let err = erx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    assert!(matches!(
+        nats::Error::from(err),
+        nats::Error::Server(ServerError::PermissionsViolation { .. })
+    ));
+}
+
+#[test]
+fn last_error() {
+    let s = nats_server::run_server("tests/configs/perms.conf");
+
+    let nc = nats::Options::with_user_pass("derek", "s3cr3t!")
+        .connect(s.client_url())
+        .expect("could not connect");
+    assert_eq!(nc.last_error(), None);
+
+    nc.publish("foo", "NOT ALLOWED").unwrap();
+    nc.flush().unwrap();
+
+    assert_eq!(
+        nc.last_error().as_deref(),
+        Some(r#"Permissions Violation for Publish to "foo""#)
+    );
+}
@@ -57,3 +57,161 @@ fn ip_basic_tls() {
         .connect(format!("tls://127.0.0.1:{}", server.client_port()))
         .unwrap();
 }
+
+#[test]
+fn reload_tls() {
+    let server = nats_server::run_server("tests/configs/tls.conf");
+    let certs = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/configs/certs");
+
+    // Work on copies of the client certificate and key, so they can be rotated.
+    let dir = std::env::temp_dir().join(format!("nats-reload-tls-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let cert = dir.join("client-cert.pem");
+    let key = dir.join("client-key.pem");
+    std::fs::copy(certs.join("client-cert.pem"), &cert).unwrap();
+    std::fs::copy(certs.join("client-key.pem"), &key).unwrap();
+
+    let nc = nats::Options::with_user_pass("derek", "porkchop")
+        .add_root_certificate(certs.join("rootCA.pem"))
+        .client_cert(&cert, &key)
+        .connect(server.client_url())
+        .unwrap();
+
+    // A broken rotation is reported and leaves the connection alone.
+    std::fs::write(&key, "not a key").unwrap();
+    assert!(nc.reload_tls().is_err());
+    nc.flush().unwrap();
+
+    std::fs::copy(certs.join("client-key.pem"), &key).unwrap();
+    nc.reload_tls().unwrap();
+    nc.flush().unwrap();
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn certificate_pinning() {
+    let server = nats_server::run_server("tests/configs/tls.conf");
+    let certs = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/configs/certs");
+    let options = || {
+        nats::Options::with_user_pass("derek", "porkchop")
+            .add_root_certificate(certs.join("rootCA.pem"))
+            .client_cert(certs.join("client-cert.pem"), certs.join("client-key.pem"))
+    };
+
+    // SHA-256 hashes of server-cert.pem and of its public key.
+    let certificate = "FoAqbvtL8OOIjZk4LySxNWp3Qci5oqC1iKgYFPpIKsY=";
+    let public_key = "I1vXiO7g8FdH6wgMvOUnzObtikLvcFuZntdfMO31fB4=";
+    let other = base64::encode([0u8; 32]);
+
+    options()
+        .pin_certificate(certificate)
+        .connect(server.client_url())
+        .unwrap();
+    options()
+        .pin_public_key(public_key)
+        .connect(server.client_url())
+        .unwrap();
+    options()
+        .pin_certificate(other.clone())
+        .pin_public_key(public_key)
+        .connect(server.client_url())
+        .unwrap();
+
+    // Should fail when the server matches none of the pins.
+    assert!(options()
+        .pin_certificate(other.clone())
+        .connect(server.client_url())
+        .is_err());
+    assert!(options()
+        .pin_public_key(certificate)
+        .connect(server.client_url())
+        .is_err());
+
+    // Should fail on malformed pins.
+    let err = options()
+        .pin_certificate("not a hash")
+        .connect(server.client_url())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn custom_certificate_verifier() {
+    use nats::rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+    use nats::rustls::{Certificate, CertificateError, RootCertStore, ServerName};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    struct CountingVerifier {
+        verifier: WebPkiVerifier,
+        accept: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl ServerCertVerifier for CountingVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &Certificate,
+            intermediates: &[Certificate],
+            server_name: &ServerName,
+            scts: &mut dyn Iterator<Item = &[u8]>,
+            ocsp_response: &[u8],
+            now: SystemTime,
+        ) -> Result<ServerCertVerified, nats::rustls::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if !self.accept {
+                return Err(nats::rustls::Error::InvalidCertificate(
+                    CertificateError::ApplicationVerificationFailure,
+                ));
+            }
+            self.verifier.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                scts,
+                ocsp_response,
+                now,
+            )
+        }
+    }
+
+    let server = nats_server::run_server("tests/configs/tls.conf");
+    let certs = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/configs/certs");
+
+    let mut roots = RootCertStore::empty();
+    let mut pem = std::io::BufReader::new(std::fs::File::open(certs.join("rootCA.pem")).unwrap());
+    roots.add_parsable_certificates(&rustls_pemfile::certs(&mut pem).unwrap());
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let verifier = |accept| CountingVerifier {
+        verifier: WebPkiVerifier::new(roots.clone(), None),
+        accept,
+        calls: calls.clone(),
+    };
+    let options = || {
+        nats::Options::with_user_pass("derek", "porkchop")
+            .client_cert(certs.join("client-cert.pem"), certs.join("client-key.pem"))
+    };
+
+    // The verifier replaces the root certificates, which are not configured here.
+    options()
+        .certificate_verifier(verifier(true))
+        .connect(server.client_url())
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    assert!(options()
+        .certificate_verifier(verifier(false))
+        .connect(server.client_url())
+        .is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Pins are still checked after the verifier.
+    assert!(options()
+        .certificate_verifier(verifier(true))
+        .pin_certificate(base64::encode([0u8; 32]))
+        .connect(server.client_url())
+        .is_err());
+}
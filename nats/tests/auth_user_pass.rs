@@ -28,3 +28,27 @@ fn basic_user_pass_auth() {
         .connect(s.client_url_with("derek", "s3cr3t"))
         .is_ok());
 }
+
+#[test]
+fn per_server_user_pass_auth() {
+    let user_pass = nats_server::run_server("tests/configs/user_pass.conf");
+    let token = nats_server::run_server("tests/configs/token_auth.conf");
+
+    let options = || {
+        nats::Options::with_token("some-auth-token").server_options(
+            &user_pass.client_url(),
+            nats::Options::with_user_pass("derek", "s3cr3t"),
+        )
+    };
+
+    assert!(options().connect(user_pass.client_url()).is_ok());
+    assert!(options().connect(token.client_url()).is_ok());
+    assert!(nats::Options::with_token("some-auth-token")
+        .connect(user_pass.client_url())
+        .is_err());
+
+    // Check that the URL still takes precedence.
+    assert!(options()
+        .connect(user_pass.client_url_with("derek", "bad-password"))
+        .is_err());
+}
@@ -0,0 +1,44 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use nats::bench::Bench;
+
+#[test]
+fn bench_results() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let bench = Bench::new(&nc)
+        .messages(1000)
+        .payload_size(64)
+        .timeout(Duration::from_secs(5));
+
+    for result in [bench.publish().unwrap(), bench.publish_subscribe().unwrap()] {
+        assert_eq!(result.messages, 1000);
+        assert_eq!(result.bytes, 64_000);
+        assert!(result.messages_per_second() > 0.0);
+        assert!(result.latency.is_none());
+    }
+
+    let result = bench.request().unwrap();
+    let latency = result.latency.unwrap();
+    assert!(latency.min <= latency.p50);
+    assert!(latency.p50 <= latency.p99);
+    assert!(latency.p99 <= latency.max);
+
+    // Publishes, published messages received back, requests and responses.
+    let stats = nc.stats();
+    assert!(stats.out_messages >= 4000);
+    assert!(stats.in_messages >= 3000);
+}
@@ -0,0 +1,92 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::chunking::{NATS_CHUNK_COUNT, NATS_CHUNK_DIGEST, NATS_CHUNK_ID, NATS_CHUNK_SEQUENCE};
+
+#[test]
+fn chunked_round_trip() {
+    let s = nats_server::run_server("tests/configs/max_payload.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    assert_eq!(nc.max_payload(), 2048);
+
+    let sub = nc.subscribe_chunked("reports").unwrap();
+    let raw = nc.subscribe("reports").unwrap();
+
+    let payload: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("key", "value");
+    nc.publish_chunked_with_reply_or_headers("reports", Some("reply"), Some(&headers), &payload)
+        .unwrap();
+
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, payload);
+    assert_eq!(message.reply.as_deref(), Some("reply"));
+    let headers = message.headers.unwrap();
+    assert_eq!(headers.get("key").unwrap(), "value");
+    assert!(!headers.contains_key(NATS_CHUNK_ID));
+
+    // The payload travelled as several messages within the limit of the server.
+    let mut chunks = 0;
+    while let Ok(chunk) = raw.next_timeout(Duration::from_millis(100)) {
+        assert!(chunk.data.len() < 2048);
+        chunks += 1;
+    }
+    assert!(chunks > 1);
+
+    // Small payloads are published as plain messages.
+    nc.publish_chunked("reports", "hello").unwrap();
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, b"hello");
+    assert!(message.headers.is_none());
+}
+
+#[test]
+fn chunked_reassembly_errors() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc
+        .subscribe_chunked("reports")
+        .unwrap()
+        .with_reassembly_timeout(Duration::from_millis(200));
+
+    let chunk = |id: &str, sequence: usize, count: usize, digest: &str| {
+        let mut headers = nats::HeaderMap::new();
+        headers.insert(NATS_CHUNK_ID, id);
+        headers.insert(NATS_CHUNK_SEQUENCE, sequence.to_string());
+        headers.insert(NATS_CHUNK_COUNT, count.to_string());
+        headers.insert(NATS_CHUNK_DIGEST, digest);
+        nc.publish_with_reply_or_headers("reports", None, Some(&headers), "chunk")
+            .unwrap();
+    };
+
+    // A payload missing its second chunk times out.
+    chunk("incomplete", 0, 2, "SHA-256=");
+    let err = sub.next_timeout(Duration::from_secs(1)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert!(err.to_string().contains("incomplete"));
+
+    // A payload not matching its digest is rejected.
+    chunk("corrupt", 0, 1, "SHA-256=");
+    let err = sub.next_timeout(Duration::from_secs(1)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    // Messages keep flowing after errors.
+    nc.publish("reports", "hello").unwrap();
+    assert_eq!(
+        sub.next_timeout(Duration::from_secs(1)).unwrap().data,
+        b"hello"
+    );
+}
@@ -0,0 +1,97 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::cloudevents::CloudEvent;
+use nats::header::{HeaderMap, CONTENT_TYPE};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+fn order_created() -> CloudEvent {
+    let mut event = CloudEvent::new("1", "/orders", "com.example.order.created");
+    event.data_content_type = Some("application/json".to_string());
+    event.subject = Some("orders.1".to_string());
+    event.time = Some(OffsetDateTime::parse("2023-05-01T12:00:00Z", &Rfc3339).unwrap());
+    event
+        .extensions
+        .insert("partitionkey".to_string(), "1".to_string());
+    event.data = br#"{"id":1}"#.to_vec();
+    event
+}
+
+#[test]
+fn cloud_event_headers() {
+    let event = order_created();
+    let headers = event.to_headers().unwrap();
+    assert_eq!(headers.get("ce-specversion").unwrap(), "1.0");
+    assert_eq!(headers.get("ce-type").unwrap(), "com.example.order.created");
+    assert_eq!(headers.get("ce-time").unwrap(), "2023-05-01T12:00:00Z");
+    assert_eq!(headers.get("ce-partitionkey").unwrap(), "1");
+    assert_eq!(headers.get(CONTENT_TYPE).unwrap(), "application/json");
+
+    let parsed = CloudEvent::from_parts(&headers, &event.data).unwrap();
+    assert_eq!(parsed, event);
+
+    // Header names are matched case insensitively.
+    let headers: HeaderMap = [
+        ("CE-ID", "1"),
+        ("Ce-Source", "/orders"),
+        ("ce-SpecVersion", "1.0"),
+        ("ce-type", "created"),
+        ("content-type", "text/plain"),
+    ]
+    .iter()
+    .collect();
+    let parsed = CloudEvent::from_parts(&headers, b"hello").unwrap();
+    assert_eq!(parsed.source, "/orders");
+    assert_eq!(parsed.data_content_type.as_deref(), Some("text/plain"));
+
+    // Required attributes and the spec version are checked.
+    let headers: HeaderMap = [("ce-id", "1"), ("ce-specversion", "1.0")].iter().collect();
+    let err = CloudEvent::from_parts(&headers, b"").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let headers: HeaderMap = [
+        ("ce-id", "1"),
+        ("ce-source", "/"),
+        ("ce-specversion", "0.3"),
+        ("ce-type", "created"),
+    ]
+    .iter()
+    .collect();
+    let err = CloudEvent::from_parts(&headers, b"").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn cloud_event_publish() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let sub = nc.subscribe("orders").unwrap();
+    let event = order_created();
+    nc.publish_cloud_event("orders", &event).unwrap();
+
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, event.data);
+    assert_eq!(message.cloud_event().unwrap(), event);
+
+    nc.publish("orders", "plain").unwrap();
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        message.cloud_event().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
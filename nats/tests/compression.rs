@@ -0,0 +1,71 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "compression")]
+
+use std::time::Duration;
+
+use nats::compression::{Compression, CONTENT_ENCODING};
+
+#[test]
+fn compressed_payloads() {
+    let s = nats_server::run_basic_server();
+    let publisher = nats::Options::new()
+        .compress_payloads(Compression::Zstd, 1024)
+        .connect(s.client_url())
+        .unwrap();
+    let subscriber = nats::connect(s.client_url()).unwrap();
+    let raw = nats::Options::new()
+        .decompress_payloads(false)
+        .connect(s.client_url())
+        .unwrap();
+
+    let sub = subscriber.subscribe("logs").unwrap();
+    let raw_sub = raw.subscribe("logs").unwrap();
+    subscriber.flush().unwrap();
+    raw.flush().unwrap();
+
+    // Large payloads travel compressed and are decompressed on receipt.
+    let payload = b"text heavy log line ".repeat(1000);
+    publisher.publish("logs", &payload).unwrap();
+
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, payload);
+    assert!(!message.headers.unwrap().contains_key(CONTENT_ENCODING));
+
+    let message = raw_sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert!(message.data.len() < payload.len());
+    assert_eq!(
+        message.headers.unwrap().get(CONTENT_ENCODING).unwrap(),
+        "zstd"
+    );
+
+    // Small payloads are published unchanged.
+    publisher.publish("logs", "hello").unwrap();
+    let message = raw_sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, b"hello");
+    assert!(message.headers.is_none());
+    sub.next_timeout(Duration::from_secs(1)).unwrap();
+
+    // Unknown encodings pass through.
+    let mut headers = nats::HeaderMap::new();
+    headers.insert(CONTENT_ENCODING, "br");
+    raw.publish_with_reply_or_headers("logs", None, Some(&headers), "brotli")
+        .unwrap();
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, b"brotli");
+    assert_eq!(
+        message.headers.unwrap().get(CONTENT_ENCODING).unwrap(),
+        "br"
+    );
+}
@@ -0,0 +1,149 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::bounded;
+use nats::dispatch::{Dispatch, WorkerPool};
+
+#[test]
+fn dispatch_pool() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let pool = WorkerPool::new("workers", 2).unwrap();
+    assert_eq!(pool.name(), "workers");
+
+    let (tx, rx) = bounded(4);
+    for subject in ["a", "b"] {
+        let tx = tx.clone();
+        nc.subscribe(subject)
+            .unwrap()
+            .with_handler_on(Dispatch::Pool(pool.clone()), move |msg| {
+                let name = thread::current().name().unwrap().to_string();
+                tx.send((msg.subject, name)).unwrap();
+                Ok(())
+            });
+    }
+
+    nc.publish("a", "").unwrap();
+    nc.publish("b", "").unwrap();
+    let mut received: Vec<_> = (0..2)
+        .map(|_| rx.recv_timeout(Duration::from_secs(1)).unwrap())
+        .collect();
+    received.sort();
+    assert_eq!(received[0].0, "a");
+    assert_eq!(received[1].0, "b");
+    for (_, name) in received {
+        assert!(name.starts_with("workers_"), "ran on {}", name);
+    }
+}
+
+#[test]
+fn dispatch_inline() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    // Messages queued before the handler is set are handed over to it.
+    let sub = nc.subscribe("inline").unwrap();
+    nc.publish("inline", "1").unwrap();
+    nc.flush().unwrap();
+
+    let (tx, rx) = bounded(2);
+    let handler = sub.with_handler_on(Dispatch::Inline, move |msg| {
+        // The handler can use the connection while running on the read loop.
+        msg.respond("ack")?;
+        tx.send(msg.data).unwrap();
+        Ok(())
+    });
+    nc.publish("inline", "2").unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), b"1");
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), b"2");
+
+    let response = nc
+        .request_timeout("inline", "3", Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(response.data, b"ack");
+
+    handler.unsubscribe().unwrap();
+    nc.close();
+}
+
+#[test]
+fn dispatch_dedicated() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    // A blocked handler on its own thread does not stall inline handlers.
+    let (blocked_tx, blocked_rx) = bounded::<()>(0);
+    nc.subscribe("slow")
+        .unwrap()
+        .with_handler_on(Dispatch::Dedicated, move |_| {
+            blocked_rx.recv().ok();
+            Ok(())
+        });
+    let (tx, rx) = bounded(1);
+    nc.subscribe("fast")
+        .unwrap()
+        .with_handler_on(Dispatch::Inline, move |msg| {
+            tx.send(msg.data).unwrap();
+            Ok(())
+        });
+
+    nc.publish("slow", "").unwrap();
+    nc.publish("fast", "done").unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), b"done");
+    drop(blocked_tx);
+}
+
+#[test]
+fn dispatch_thread_names() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .thread_name_prefix("orders")
+        .dispatch_workers(2)
+        .connect(s.client_url())
+        .unwrap();
+
+    let (tx, rx) = bounded(2);
+    {
+        let tx = tx.clone();
+        nc.subscribe("dedicated").unwrap().with_handler(move |_| {
+            tx.send(thread::current().name().unwrap().to_string())
+                .unwrap();
+            Ok(())
+        });
+    }
+    let pool = nc.dispatch_pool().unwrap();
+    assert_eq!(pool.name(), "orders_dispatch");
+    nc.subscribe("pooled")
+        .unwrap()
+        .with_handler_on(Dispatch::Pool(pool), move |_| {
+            tx.send(thread::current().name().unwrap().to_string())
+                .unwrap();
+            Ok(())
+        });
+
+    nc.publish("dedicated", "").unwrap();
+    let name = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(name.starts_with("orders_subscriber_"), "ran on {}", name);
+
+    nc.publish("pooled", "").unwrap();
+    let name = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(name.starts_with("orders_dispatch_"), "ran on {}", name);
+
+    // Without dispatch workers, the connection has no pool.
+    let nc = nats::connect(s.client_url()).unwrap();
+    assert!(nc.dispatch_pool().is_none());
+}
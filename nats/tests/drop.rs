@@ -11,6 +11,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "jetstream")]
 use nats::{jetstream::JetStream, Connection, JetStreamOptions};
 use smol::future::FutureExt;
 use std::{
@@ -114,6 +115,7 @@ fn drop_responsiveness_regression_check() {
     assert!(now.elapsed().le(&Duration::from_secs(5)));
 }
 
+#[cfg(feature = "jetstream")]
 #[test]
 fn close_responsiveness_regression_jetstream() {
     let (_s, nc, js) = run_basic_jetstream();
@@ -134,6 +136,7 @@ fn close_responsiveness_regression_jetstream() {
     nc.close();
 }
 
+#[cfg(feature = "jetstream")]
 #[test]
 fn close_responsiveness_regression_jetstream_complex() {
     let (_s, conn, jetstream) = run_basic_jetstream();
@@ -191,6 +194,7 @@ fn close_responsiveness_regression_jetstream_complex() {
 }
 
 // Helper function to return server and client.
+#[cfg(feature = "jetstream")]
 pub fn run_basic_jetstream() -> (nats_server::Server, Connection, JetStream) {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
     let nc = nats::connect(s.client_url()).unwrap();
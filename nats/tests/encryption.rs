@@ -0,0 +1,121 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::encryption::{Algorithm, Encryption, StaticKeys, NATS_ENCRYPTION_KEY};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Secret {
+    password: String,
+}
+
+#[test]
+fn encrypted_messages() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("secrets").unwrap();
+
+    let old = Encryption::new(StaticKeys::new("old", [1; 32])).algorithm(Algorithm::Aes256Gcm);
+    let rotated = Encryption::new(StaticKeys::new("new", [2; 32]).with_key("old", [1; 32]));
+
+    nc.publish_encrypted("secrets", &old, "hunter2").unwrap();
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        message
+            .headers
+            .as_ref()
+            .unwrap()
+            .get(NATS_ENCRYPTION_KEY)
+            .unwrap(),
+        "old"
+    );
+    assert_ne!(message.data, b"hunter2");
+
+    // Payloads encrypted with a rotated out key still decrypt.
+    let message = rotated.open(message).unwrap();
+    assert_eq!(message.data, b"hunter2");
+    assert!(!message.headers.unwrap().contains_key(NATS_ENCRYPTION_KEY));
+
+    // Payloads encrypted with an unknown key do not.
+    nc.publish_encrypted("secrets", &rotated, "hunter3")
+        .unwrap();
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    let err = old.open(message).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    // Tampered payloads fail authentication.
+    nc.publish_encrypted("secrets", &rotated, "hunter4")
+        .unwrap();
+    let mut message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    let last = message.data.len() - 1;
+    message.data[last] ^= 1;
+    let err = rotated.open(message).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn encrypted_typed_messages() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let encryption = Encryption::new(StaticKeys::new("key", [3; 32]));
+
+    let secrets = nc
+        .subscribe_json::<Secret>("secrets")
+        .unwrap()
+        .with_encryption(encryption.clone());
+    let secret = Secret {
+        password: "hunter2".to_string(),
+    };
+    nc.publish_encrypted_with_codec::<nats::typed::Json, _>("secrets", &secret, &encryption)
+        .unwrap();
+
+    let (received, _) = secrets.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(received, secret);
+
+    // Plain payloads are rejected.
+    nc.publish_json("secrets", &secret).unwrap();
+    let err = secrets.next_timeout(Duration::from_secs(1)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "kv")]
+#[test]
+fn encrypted_key_value() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let kv = context
+        .create_key_value(&nats::kv::Config {
+            bucket: "SECRETS".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let secrets = kv
+        .typed::<Secret>()
+        .with_encryption(Encryption::new(StaticKeys::new("key", [4; 32])));
+
+    let secret = Secret {
+        password: "hunter2".to_string(),
+    };
+    secrets.put("admin", &secret).unwrap();
+    assert_eq!(secrets.get("admin").unwrap(), Some(secret));
+
+    // The stored value is not readable without the key.
+    let stored = kv.get("admin").unwrap().unwrap();
+    assert!(serde_json::from_slice::<Secret>(&stored).is_err());
+    assert!(!String::from_utf8_lossy(&stored).contains("hunter2"));
+}
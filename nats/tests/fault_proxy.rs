@@ -0,0 +1,59 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "test_utils")]
+
+use std::time::Duration;
+
+use crossbeam_channel::bounded;
+use nats::test::FaultProxy;
+
+#[test]
+fn fault_proxy_partial_writes_and_latency() {
+    let s = nats_server::run_basic_server();
+    let proxy = FaultProxy::start(("127.0.0.1", s.client_port())).unwrap();
+    let nc = nats::connect(&proxy.client_url()).unwrap();
+
+    proxy.chunk_size(Some(1));
+    proxy.latency(Duration::from_millis(1));
+
+    let sub = nc.subscribe("data").unwrap();
+    nc.publish("data", "fragmented").unwrap();
+    let message = sub.next_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(message.data, b"fragmented");
+}
+
+#[test]
+fn fault_proxy_disconnect_mid_stream() {
+    let s = nats_server::run_basic_server();
+    let proxy = FaultProxy::start(("127.0.0.1", s.client_port())).unwrap();
+    let (tx, rx) = bounded(1);
+    let nc = nats::Options::new()
+        .reconnect_callback(move || {
+            tx.try_send(()).ok();
+        })
+        .connect(&proxy.client_url())
+        .unwrap();
+
+    let sub = nc.subscribe("data").unwrap();
+    nc.flush().unwrap();
+
+    // Cut the connection in the middle of the next PUB.
+    proxy.disconnect_after(10);
+    nc.publish("data", "lost in transit").unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    nc.publish("data", "after reconnect").unwrap();
+    let message = sub.next_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(message.data, b"after reconnect");
+}
@@ -0,0 +1,65 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use nats::header::NATS_ORIGINAL_SUBJECT;
+
+#[test]
+fn forward() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let audit = nc.subscribe("audit").unwrap();
+    let bridged = nc.subscribe("bridged").unwrap();
+    let tap = nc.clone();
+    let _tap = nc
+        .subscribe("orders.*")
+        .unwrap()
+        .with_handler(move |message| {
+            tap.forward(&message, "bridged")?;
+            tap.forward_with_original_subject(&message, "audit")
+        });
+    nc.flush().unwrap();
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("order", "42");
+    nc.publish_with_reply_or_headers("orders.created", Some("reply"), Some(&headers), "data")
+        .unwrap();
+
+    let message = bridged.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, b"data");
+    assert_eq!(message.reply, None);
+    let headers = message.headers.unwrap();
+    assert_eq!(headers.get("order").unwrap(), "42");
+    assert!(!headers.contains_key(NATS_ORIGINAL_SUBJECT));
+
+    let message = audit.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.data, b"data");
+    let headers = message.headers.as_ref().unwrap();
+    assert_eq!(headers.get("order").unwrap(), "42");
+    assert_eq!(
+        headers.get(NATS_ORIGINAL_SUBJECT).unwrap(),
+        "orders.created"
+    );
+
+    // Forwarding again keeps the original subject.
+    let audit_copy = nc.subscribe("audit.copy").unwrap();
+    nc.forward_with_original_subject(&message, "audit.copy")
+        .unwrap();
+    let message = audit_copy.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        message.headers.unwrap().get(NATS_ORIGINAL_SUBJECT).unwrap(),
+        "orders.created"
+    );
+}
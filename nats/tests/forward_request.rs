@@ -0,0 +1,79 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use crossbeam_channel as channel;
+
+#[test]
+fn forward_request() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _validate = nc
+        .subscribe("orders.validate")
+        .unwrap()
+        .with_handler(|request| request.forward_request("orders.price", b"validated"));
+    let _price = nc
+        .subscribe("orders.price")
+        .unwrap()
+        .with_handler(|request| {
+            assert_eq!(request.data, b"validated");
+            assert_eq!(request.hop_count(), 1);
+            let headers = request.headers.as_ref().unwrap();
+            assert_eq!(headers.get("order").unwrap(), "42");
+            request.respond(format!("priced after {} hops", request.hop_count()))
+        });
+    nc.flush().unwrap();
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("order", "42");
+    let inbox = nc.new_inbox();
+    let responses = nc.subscribe(&inbox).unwrap();
+    nc.publish_with_reply_or_headers("orders.validate", Some(&inbox), Some(&headers), "order")
+        .unwrap();
+    let response = responses.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(response.data, b"priced after 1 hops");
+}
+
+#[test]
+fn forwarding_loop() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let (errors, errors_received) = channel::unbounded();
+    let _first = nc
+        .subscribe("first")
+        .unwrap()
+        .with_handler(|request| request.forward_request("second", &request.data));
+    let _second = nc
+        .subscribe("second")
+        .unwrap()
+        .with_handler(move |request| {
+            let err = request.forward_request("first", &request.data).unwrap_err();
+            errors.send(err).unwrap();
+            request.respond("loop")
+        });
+    nc.flush().unwrap();
+
+    let response = nc.request("first", "data").unwrap();
+    assert_eq!(response.data, b"loop");
+
+    let err = errors_received
+        .recv_timeout(Duration::from_secs(1))
+        .unwrap();
+    assert!(matches!(
+        nats::Error::from(err),
+        nats::Error::ForwardingLoop { subject, hops: 1 } if subject == "first"
+    ));
+}
@@ -0,0 +1,79 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "http")]
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::gateway::{self, HTTP_METHOD, HTTP_PATH};
+
+#[test]
+fn subject_for_path() {
+    assert_eq!(
+        gateway::subject_for_path("api", "/orders/1/").unwrap(),
+        "api.orders.1"
+    );
+    assert_eq!(gateway::subject_for_path("", "/orders").unwrap(), "orders");
+    assert_eq!(
+        gateway::subject_for_path("api", "/orders/*")
+            .unwrap_err()
+            .kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn http_request_reply() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    nc.subscribe("api.orders.>")
+        .unwrap()
+        .with_handler(|message| {
+            let headers = message.headers.as_ref().unwrap();
+            assert_eq!(headers.get(HTTP_METHOD).unwrap(), "PUT");
+            assert_eq!(headers.get(HTTP_PATH).unwrap(), "/orders/1?notify=true");
+
+            let request = gateway::decode_request(&message)?;
+            let response = http::Response::builder()
+                .status(http::StatusCode::CREATED)
+                .header("x-order", request.headers()["x-order"].clone())
+                .body(request.into_body())
+                .unwrap();
+            message.respond_http(&response)
+        });
+
+    let request = http::Request::put("/orders/1?notify=true")
+        .header("x-order", "1")
+        .body(b"book".to_vec())
+        .unwrap();
+    let subject = gateway::subject_for_path("api", request.uri().path()).unwrap();
+    let response = nc
+        .request_http(&subject, &request, Some(Duration::from_secs(1)))
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::CREATED);
+    assert_eq!(response.headers()["x-order"], "1");
+    assert_eq!(response.body(), b"book");
+
+    // Plain responders are treated as `200 OK`.
+    nc.subscribe("plain")
+        .unwrap()
+        .with_handler(|message| message.respond("hello"));
+    let response = nc
+        .request_http("plain", &request, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.body(), b"hello");
+}
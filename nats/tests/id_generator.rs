@@ -0,0 +1,59 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use nats::IdGenerator;
+
+#[test]
+fn seeded_inboxes_are_reproducible() {
+    let s = nats_server::run_basic_server();
+    let connect = || {
+        nats::Options::new()
+            .id_generator(nats::SeededIdGenerator::new(7))
+            .connect(s.client_url())
+            .unwrap()
+    };
+
+    let first: Vec<String> = (0..3).map(|_| connect()).map(|nc| nc.new_inbox()).collect();
+    let second: Vec<String> = (0..3).map(|_| connect()).map(|nc| nc.new_inbox()).collect();
+    assert_eq!(first, second);
+
+    let nc = connect();
+    assert_ne!(nc.new_inbox(), nc.new_inbox());
+}
+
+#[test]
+fn custom_id_generator() {
+    struct Sequential(AtomicUsize);
+
+    impl IdGenerator for Sequential {
+        fn next_id(&self) -> String {
+            self.0.fetch_add(1, Ordering::SeqCst).to_string()
+        }
+    }
+
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .id_generator(Sequential(AtomicUsize::new(1)))
+        .connect(s.client_url())
+        .unwrap();
+
+    assert_eq!(nc.new_inbox(), "_INBOX.1");
+    assert_eq!(nc.new_inbox(), "_INBOX.2");
+
+    nc.subscribe("echo")
+        .unwrap()
+        .with_handler(|msg| msg.respond(msg.data.clone()));
+    assert_eq!(nc.request("echo", "hello").unwrap().data, b"hello");
+}
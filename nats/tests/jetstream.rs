@@ -11,6 +11,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#![cfg(feature = "jetstream")]
+
 use std::{io, time::Duration};
 
 use nats::jetstream::*;
@@ -634,6 +636,113 @@ fn jetstream_pull_subscribe_fetch() {
     assert_eq!(i, 20);
 }
 
+#[test]
+fn jetstream_pull_subscribe_messages_rate_limit() {
+    use nats::jetstream::pull_subscription::PullRateLimit;
+
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    for _ in 0..20 {
+        js.publish("foo", b"lorem").unwrap();
+    }
+
+    let consumer = js.pull_subscribe("foo").unwrap();
+
+    // Four batches of five messages at 50 messages per second, so at least three pauses
+    // of 100ms between the pull requests.
+    let start = std::time::Instant::now();
+    let messages = consumer
+        .messages(5)
+        .rate_limit(PullRateLimit::Messages(50))
+        .take(20)
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(messages.len(), 20);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+#[test]
+fn jetstream_pull_subscribe_consume() {
+    use nats::jetstream::pull_subscription::ConsumeOptions;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc.clone());
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    for _ in 0..20 {
+        js.publish("foo", b"lorem").unwrap();
+    }
+
+    let consumer = js
+        .pull_subscribe_with_options(
+            "foo",
+            &PullSubscribeOptions::new().durable_name("CONSUMER".to_string()),
+        )
+        .unwrap();
+
+    let handled = Arc::new(AtomicUsize::new(0));
+    let active = Arc::new(AtomicUsize::new(0));
+    let max_active = Arc::new(AtomicUsize::new(0));
+    let handler = consumer
+        .consume(
+            {
+                let handled = handled.clone();
+                let active = active.clone();
+                let max_active = max_active.clone();
+                move |message| {
+                    let now_active = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(now_active, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    active.fetch_sub(1, Ordering::SeqCst);
+
+                    // Fail the first delivery of one message, which is redelivered.
+                    let info = message.jetstream_message_info().unwrap();
+                    if info.stream_seq == 5 && info.delivered == 1 {
+                        return Err(io::Error::new(io::ErrorKind::Other, "failed"));
+                    }
+                    handled.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            },
+            ConsumeOptions {
+                max_concurrent: 4,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while handled.load(Ordering::SeqCst) < 20 {
+        assert!(std::time::Instant::now() < deadline, "messages not handled");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    handler.stop().unwrap();
+
+    assert!(max_active.load(Ordering::SeqCst) <= 4);
+    nc.flush().unwrap();
+    let info = js.consumer_info("TEST", "CONSUMER").unwrap();
+    assert_eq!(info.num_ack_pending, 0);
+    assert_eq!(info.num_pending, 0);
+}
+
 #[test]
 fn jetstream_pull_subscribe_timeout_fetch() {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
@@ -784,6 +893,65 @@ fn jetstream_pull_subscribe_bad_stream() {
 }
 
 // Helper function to return server and client.
+#[test]
+fn jetstream_ack_metrics() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorded {
+        acks: Vec<(String, String)>,
+        redeliveries: Vec<i64>,
+    }
+
+    struct Recorder(Arc<Mutex<Recorded>>);
+
+    impl nats::Metrics for Recorder {
+        fn message_acked(&self, stream: &str, consumer: &str, _kind: AckKind, _latency: Duration) {
+            self.0
+                .lock()
+                .unwrap()
+                .acks
+                .push((stream.to_string(), consumer.to_string()));
+        }
+
+        fn message_redelivered(&self, _stream: &str, _consumer: &str, delivered: i64) {
+            self.0.lock().unwrap().redeliveries.push(delivered);
+        }
+    }
+
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let recorded = Arc::new(Mutex::new(Recorded::default()));
+    let nc = nats::Options::new()
+        .metrics(Recorder(recorded.clone()))
+        .connect(s.client_url())
+        .unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    js.publish("foo", b"data").unwrap();
+
+    let sub = js
+        .subscribe_with_options(
+            "foo",
+            &SubscribeOptions::new().durable_name("CONSUMER".to_string()),
+        )
+        .unwrap();
+    sub.next().unwrap().ack_kind(AckKind::Nak).unwrap();
+    sub.next().unwrap().ack().unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(
+        recorded.acks,
+        vec![("TEST".to_string(), "CONSUMER".to_string()); 2]
+    );
+    assert_eq!(recorded.redeliveries, vec![2]);
+}
+
 pub fn run_basic_jetstream() -> (nats_server::Server, Connection, JetStream) {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
     let nc = nats::connect(s.client_url()).unwrap();
@@ -791,3 +959,410 @@ pub fn run_basic_jetstream() -> (nats_server::Server, Connection, JetStream) {
 
     (s, nc, js)
 }
+
+#[test]
+fn jetstream_batch_ack() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc.clone());
+
+    js.add_stream(&StreamConfig {
+        name: "BATCH".to_string(),
+        subjects: vec!["batch".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    for _ in 0..10 {
+        js.publish("batch", b"data").unwrap();
+    }
+
+    let sub = js
+        .subscribe_with_options(
+            "batch",
+            &SubscribeOptions::new()
+                .durable_name("batch".to_string())
+                .ack_all()
+                .deliver_all(),
+        )
+        .unwrap();
+    let acks = sub
+        .batch_ack()
+        .unwrap()
+        .max_pending(100)
+        .interval(Duration::from_secs(60));
+
+    let messages = (0..10)
+        .map(|_| {
+            let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+            acks.track(&message).unwrap();
+            message
+        })
+        .collect::<Vec<_>>();
+
+    // Nothing is acknowledged while the first message is still being processed.
+    for message in &messages[1..] {
+        acks.done(message).unwrap();
+    }
+    acks.flush().unwrap();
+    nc.flush().unwrap();
+    assert_eq!(sub.consumer_info().unwrap().num_ack_pending, 10);
+
+    acks.done(&messages[0]).unwrap();
+    acks.flush().unwrap();
+    nc.flush().unwrap();
+    assert_eq!(sub.consumer_info().unwrap().num_ack_pending, 0);
+
+    // Batch acknowledgements require `AckPolicy::All`.
+    let sub = js.subscribe("batch").unwrap();
+    let err = sub.batch_ack().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn jetstream_push_sequence_gap() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(StreamConfig {
+        name: "GAPS".to_string(),
+        subjects: vec!["gaps".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    for _ in 0..3 {
+        js.publish("gaps", b"data").unwrap();
+    }
+
+    let consumer_config = ConsumerConfig {
+        durable_name: Some("durable".to_string()),
+        deliver_policy: DeliverPolicy::All,
+        ack_policy: AckPolicy::None,
+        deliver_subject: Some("gaps.deliver".to_string()),
+        ..Default::default()
+    };
+    js.add_consumer("GAPS", &consumer_config).unwrap();
+
+    let gaps = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sub = js
+        .subscribe_with_options(
+            "gaps",
+            &SubscribeOptions::bind("GAPS".to_string(), "durable".to_string()).on_sequence_gap({
+                let gaps = gaps.clone();
+                move |gap| gaps.lock().unwrap().push(gap.clone())
+            }),
+        )
+        .unwrap();
+    for _ in 0..3 {
+        sub.next_timeout(Duration::from_secs(1)).unwrap();
+    }
+    assert!(gaps.lock().unwrap().is_empty());
+
+    // Recreating the consumer restarts its sequences, which is reported.
+    js.delete_consumer("GAPS", "durable").unwrap();
+    js.add_consumer("GAPS", &consumer_config).unwrap();
+    let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(message.jetstream_message_info().unwrap().consumer_seq, 1);
+    assert_eq!(
+        gaps.lock().unwrap().as_slice(),
+        &[SequenceGap {
+            stream: "GAPS".to_string(),
+            expected: 4,
+            received: 1,
+            last_stream_seq: 3,
+        }]
+    );
+
+    // Queue subscriptions cannot detect gaps.
+    let err = js
+        .queue_subscribe_with_options(
+            "gaps",
+            "queue",
+            &SubscribeOptions::new().on_sequence_gap(|_| {}),
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn jetstream_push_resume_on_reconnect() {
+    let mut s = nats_server::run_server_with_port("tests/configs/jetstream.conf", Some("4508"));
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(StreamConfig {
+        name: "RESUME".to_string(),
+        subjects: vec!["resume".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    for _ in 0..3 {
+        js.publish("resume", b"data").unwrap();
+    }
+
+    let sub = js
+        .subscribe_with_options(
+            "resume",
+            &SubscribeOptions::new()
+                .ack_none()
+                .deliver_all()
+                .resume_on_reconnect(),
+        )
+        .unwrap();
+    for _ in 0..3 {
+        sub.next_timeout(Duration::from_secs(1)).unwrap();
+    }
+
+    // The consumer is gone after the restart, and recreated after the last message received.
+    js.delete_consumer("RESUME", sub.consumer_info().unwrap().name)
+        .unwrap();
+    s.restart();
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while js.publish("resume", b"data").is_err() {
+        assert!(std::time::Instant::now() < deadline, "failed to reconnect");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let message = sub.next_timeout(Duration::from_secs(5)).unwrap();
+    let info = message.jetstream_message_info().unwrap();
+    assert_eq!(info.stream_seq, 4);
+    assert_eq!(info.consumer_seq, 1);
+
+    // Durable consumers are not recreated.
+    let err = js
+        .subscribe_with_options(
+            "resume",
+            &SubscribeOptions::new()
+                .durable_name("durable".to_string())
+                .resume_on_reconnect(),
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn jetstream_publish_iter() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "BACKFILL".to_string(),
+        subjects: vec!["backfill.>".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let records: Vec<String> = (0..100)
+        .map(|i| format!("{},record {}", i % 2, i))
+        .collect();
+    let subject = |record: &&String| {
+        if record.starts_with('0') {
+            "backfill.even"
+        } else {
+            "backfill.odd"
+        }
+    };
+    let options = PublishIterOptions {
+        max_in_flight: 16,
+        id_prefix: Some("backfill-".to_string()),
+        ..Default::default()
+    };
+
+    let summary = js.publish_iter_with_options(subject, &records, &options);
+    assert_eq!(summary.published, 100);
+    assert_eq!(summary.duplicates, 0);
+    assert!(summary.failures.is_empty());
+    assert_eq!(js.stream_info("BACKFILL").unwrap().state.messages, 100);
+
+    // Resuming the backfill is deduplicated, and messages outside the stream fail.
+    let summary = js.publish_iter_with_options(subject, &records, &options);
+    assert_eq!(summary.duplicates, 100);
+    let summary = js.publish_iter(|_: &&str| "elsewhere", vec!["a", "b"]);
+    assert_eq!(summary.published, 0);
+    let failed: Vec<usize> = summary.failures.iter().map(|(index, _)| *index).collect();
+    assert_eq!(failed, vec![0, 1]);
+    assert_eq!(js.stream_info("BACKFILL").unwrap().state.messages, 100);
+}
+
+#[test]
+fn jetstream_consumer_lag() {
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<ConsumerLag>>>);
+
+    impl nats::Metrics for Recorder {
+        fn consumer_lag(&self, lag: &ConsumerLag) {
+            self.0.lock().unwrap().push(lag.clone());
+        }
+    }
+
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let nc = nats::Options::new()
+        .metrics(Recorder(recorded.clone()))
+        .connect(s.client_url())
+        .unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "LAG".to_string(),
+        subjects: vec!["lag".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    js.add_consumer(
+        "LAG",
+        ConsumerConfig {
+            durable_name: Some("CONSUMER".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    for _ in 0..5 {
+        js.publish("lag", b"data").unwrap();
+    }
+
+    // Two messages delivered but not acknowledged.
+    let consumer = js
+        .pull_subscribe_with_options(
+            "lag",
+            &PullSubscribeOptions::new().durable_name("CONSUMER".to_string()),
+        )
+        .unwrap();
+    assert_eq!(consumer.fetch(2).unwrap().count(), 2);
+
+    let lag = js.consumer_lag("LAG", "CONSUMER").unwrap();
+    assert_eq!(lag.num_pending, 3);
+    assert_eq!(lag.num_ack_pending, 2);
+    assert_eq!(lag.lag(), 5);
+    assert_eq!(lag.stream_last_seq, 5);
+    assert_eq!(lag.ack_floor_stream_seq, 0);
+
+    let reporter = js
+        .report_consumer_lag("LAG", "CONSUMER", Duration::from_millis(50))
+        .unwrap();
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while recorded.lock().unwrap().is_empty() {
+        assert!(std::time::Instant::now() < deadline, "lag was not reported");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    drop(reporter);
+    assert_eq!(recorded.lock().unwrap()[0], lag);
+
+    // Reporting requires metrics hooks.
+    let (_s, _nc, js) = run_basic_jetstream();
+    let err = js
+        .report_consumer_lag("LAG", "CONSUMER", Duration::from_secs(1))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn jetstream_fetch_managed() {
+    use nats::jetstream::pull_subscription::DropAckPolicy;
+
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "MANAGED".to_string(),
+        subjects: vec!["managed".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    js.add_consumer(
+        "MANAGED",
+        ConsumerConfig {
+            durable_name: Some("MANAGED".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    for i in 1..=3 {
+        js.publish("managed", i.to_string()).unwrap();
+    }
+    let consumer = js
+        .pull_subscribe_with_options(
+            "managed",
+            &PullSubscribeOptions::new().durable_name("MANAGED".to_string()),
+        )
+        .unwrap();
+
+    // The first message is acknowledged, the second dropped and the third released.
+    let mut messages = consumer.fetch_managed(3).unwrap();
+    messages.next().unwrap().ack().unwrap();
+    drop(messages.next().unwrap());
+    let released = messages.next().unwrap().into_inner();
+    released.ack().unwrap();
+
+    // Dropped messages are redelivered right away.
+    let message = consumer
+        .timeout_fetch(1, Duration::from_secs(5))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.data, b"2");
+    assert_eq!(message.jetstream_message_info().unwrap().delivered, 2);
+    message.ack().unwrap();
+
+    // Unless they are terminated.
+    js.publish("managed", "4").unwrap();
+    let message = consumer
+        .fetch_managed(1)
+        .unwrap()
+        .on_drop(DropAckPolicy::Term)
+        .next()
+        .unwrap();
+    assert_eq!(message.data, b"4");
+    drop(message);
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let info = js.consumer_info("MANAGED", "MANAGED").unwrap();
+        if info.num_ack_pending == 0 && info.num_pending == 0 {
+            break;
+        }
+        assert!(std::time::Instant::now() < deadline, "{:?}", info);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn jetstream_info_times() {
+    use std::time::SystemTime;
+
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    let before = SystemTime::now() - Duration::from_secs(1);
+    js.add_stream(&StreamConfig {
+        name: "TIMES".to_string(),
+        subjects: vec!["times".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    js.publish("times", b"data").unwrap();
+    let consumer = js
+        .add_consumer(
+            "TIMES",
+            ConsumerConfig {
+                durable_name: Some("TIMES".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let after = SystemTime::now() + Duration::from_secs(1);
+
+    let info = js.stream_info("TIMES").unwrap();
+    let created: SystemTime = info.created_at();
+    assert!(before <= created && created <= after);
+    assert_eq!(info.created_at::<DateTime>(), info.created);
+    assert!(before <= info.state.first_at::<SystemTime>());
+    assert!(info.state.last_at::<SystemTime>() <= after);
+
+    let created: SystemTime = consumer.created_at();
+    assert!(before <= created && created <= after);
+    assert_eq!(
+        consumer.delivered.last_active_at::<SystemTime>(),
+        consumer.delivered.last_active.map(SystemTime::from)
+    );
+}
@@ -11,7 +11,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#![cfg(feature = "unstable")]
+#![cfg(all(feature = "kv", feature = "unstable"))]
 
 use std::assert_eq;
 
@@ -390,4 +390,273 @@ fn key_value_domain() {
 
     // Try a delete too for good measure
     kv.delete("bar").expect("should be able to delete");
+
+    // Purges are published through the domain as well, removing the history of the key
+    kv.purge("foo").expect("should be able to purge");
+    assert_eq!(kv.get("foo").unwrap(), None);
+    assert_eq!(kv.history("foo").unwrap().count(), 1);
+}
+
+#[test]
+fn key_value_typed() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let kv = context
+        .create_key_value(&Config {
+            bucket: "TYPED".to_string(),
+            history: 5,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let points = kv.typed::<Point>();
+    assert_eq!(points.get("origin").unwrap(), None);
+
+    let revision = points.put("origin", &Point { x: 0, y: 0 }).unwrap();
+    assert_eq!(points.get("origin").unwrap(), Some(Point { x: 0, y: 0 }));
+
+    points
+        .update("origin", &Point { x: 1, y: 1 }, revision)
+        .unwrap();
+    let entry = points.entry("origin").unwrap().unwrap();
+    assert_eq!(entry.value, Some(Point { x: 1, y: 1 }));
+
+    // Values which do not decode surface as invalid data.
+    kv.put("garbage", b"not json").unwrap();
+    let err = points.get("garbage").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    points.delete("origin").unwrap();
+    let entry = points.entry("origin").unwrap().unwrap();
+    assert_eq!(entry.operation, Operation::Delete);
+    assert_eq!(entry.value, None);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn key_value_typed_msgpack() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let kv = context
+        .create_key_value(&Config {
+            bucket: "MSGPACK".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let points = kv.typed_with_codec::<Point, nats::typed::MsgPack>();
+    points.put("origin", &Point { x: 1, y: 2 }).unwrap();
+    assert_eq!(points.get("origin").unwrap(), Some(Point { x: 1, y: 2 }));
+
+    // The raw value is MessagePack, not JSON.
+    let raw = kv.get("origin").unwrap().unwrap();
+    assert_eq!(raw, rmp_serde::to_vec_named(&Point { x: 1, y: 2 }).unwrap());
+}
+
+#[test]
+fn key_value_mirror() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let origin = context
+        .create_key_value(&Config {
+            bucket: "ORIGIN".to_string(),
+            history: 5,
+            ..Default::default()
+        })
+        .unwrap();
+
+    origin.put("foo", b"bar").unwrap();
+
+    let mirror = context
+        .create_key_value(&Config {
+            bucket: "MIRROR".to_string(),
+            history: 5,
+            mirror: Some(nats::jetstream::Source::new("ORIGIN")),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let info = context.stream_info("KV_MIRROR").unwrap();
+    assert_eq!(info.config.mirror.unwrap().name, "KV_ORIGIN".to_string());
+    assert!(info.config.subjects.is_empty());
+
+    // Wait for the mirror to catch up with the origin.
+    for _ in 0..50 {
+        if mirror.get("foo").unwrap().is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(mirror.get("foo").unwrap(), Some(b"bar".to_vec()));
+
+    // Writes through the mirror end up in the origin bucket.
+    mirror.put("baz", b"qux").unwrap();
+    assert_eq!(origin.get("baz").unwrap(), Some(b"qux".to_vec()));
+
+    let mirror = context.key_value("MIRROR").unwrap();
+    assert_eq!(mirror.bucket(), "MIRROR");
+    mirror.delete("foo").unwrap();
+    assert_eq!(origin.get("foo").unwrap(), None);
+}
+
+#[test]
+fn key_value_ttl() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    if !client.is_server_compatible_version(2, 11, 0) {
+        return;
+    }
+    let context = nats::jetstream::new(client);
+
+    let kv = context
+        .create_key_value(&Config {
+            bucket: "TTL".to_string(),
+            limit_markers: Some(std::time::Duration::from_secs(1)),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let err = kv
+        .put_with_ttl("session", b"token", std::time::Duration::from_millis(10))
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    kv.put_with_ttl("session", b"token", std::time::Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(kv.get("session").unwrap(), Some(b"token".to_vec()));
+
+    std::thread::sleep(std::time::Duration::from_secs(3));
+    assert_eq!(kv.get("session").unwrap(), None);
+}
+
+#[test]
+fn key_value_lock() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let kv = context
+        .create_key_value(&Config {
+            bucket: "LOCKS".to_string(),
+            history: 5,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut watch = kv.watch_lock("leader").unwrap();
+
+    let mut lock = kv
+        .acquire("leader", std::time::Duration::from_secs(1))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        watch.next(),
+        Some(LockEvent::Acquired(lock.owner().to_string()))
+    );
+
+    // Held locks can not be acquired until their lease expires.
+    assert!(kv
+        .acquire("leader", std::time::Duration::from_secs(1))
+        .unwrap()
+        .is_none());
+    lock.renew().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    let other = kv
+        .acquire("leader", std::time::Duration::from_secs(10))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        watch.next(),
+        Some(LockEvent::Acquired(other.owner().to_string()))
+    );
+
+    // The previous owner has lost the lock.
+    assert!(lock.renew().is_err());
+
+    other.release().unwrap();
+    assert_eq!(watch.next(), Some(LockEvent::Released));
+
+    assert!(kv
+        .acquire("leader", std::time::Duration::from_secs(1))
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn key_value_cached() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let kv = context
+        .create_key_value(&Config {
+            bucket: "CACHED".to_string(),
+            history: 5,
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Empty buckets are loaded right away.
+    let empty = kv.cached().unwrap();
+    assert_eq!(empty.revision(), 0);
+    assert!(empty.keys().is_empty());
+    drop(empty);
+
+    kv.put("a", b"1").unwrap();
+    kv.put("b", b"2").unwrap();
+    kv.put("a", b"3").unwrap();
+    kv.delete("b").unwrap();
+
+    let cached = kv.cached().unwrap();
+    assert_eq!(cached.get("a"), Some(b"3".to_vec()));
+    assert_eq!(cached.get("b"), None);
+    assert_eq!(cached.keys(), vec!["a".to_string()]);
+    assert_eq!(cached.revision(), 4);
+    assert!(!cached.is_stale());
+
+    // Changes are applied as they happen.
+    let revision = kv.put("c", b"4").unwrap();
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while cached.revision() < revision {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "change was not applied"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let entry = cached.entry("c").unwrap();
+    assert_eq!(entry.value, b"4");
+    assert_eq!(entry.revision, revision);
+
+    // Losing the connection makes the cache stale, still serving the last values.
+    let (stale, staleness) = crossbeam_channel::unbounded();
+    cached.on_staleness_change(move |is_stale| {
+        stale.send(is_stale).ok();
+    });
+    drop(server);
+    assert_eq!(
+        staleness.recv_timeout(std::time::Duration::from_secs(5)),
+        Ok(true)
+    );
+    assert!(cached.is_stale());
+    assert_eq!(cached.get("a"), Some(b"3".to_vec()));
 }
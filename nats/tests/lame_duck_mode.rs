@@ -30,3 +30,28 @@ fn lame_duck_mode() {
     let r = lrx.recv_timeout(Duration::from_millis(500));
     assert!(r.is_ok(), "expected lame duck response, got nothing");
 }
+
+#[test]
+#[cfg_attr(target_os = "windows", ignore)]
+fn server_info_callback() {
+    let (tx, rx) = bounded(1);
+
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .server_info_callback(move |info| {
+            tx.try_send(info.clone()).ok();
+        })
+        .connect(s.client_url().as_str())
+        .expect("could not connect to the server");
+
+    // The INFO of the handshake is not reported.
+    nc.flush().unwrap();
+    assert!(rx.try_recv().is_err());
+
+    nats_server::set_lame_duck_mode(&s);
+    let info = rx
+        .recv_timeout(Duration::from_millis(500))
+        .expect("expected an INFO update, got nothing");
+    assert!(info.lame_duck_mode);
+    assert!(!info.server_id.is_empty());
+}
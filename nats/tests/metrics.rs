@@ -0,0 +1,118 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Default)]
+struct Counters {
+    sent: AtomicUsize,
+    received: AtomicUsize,
+    bytes_in: AtomicUsize,
+}
+
+struct Exporter(Arc<Counters>);
+
+impl nats::Metrics for Exporter {
+    fn message_sent(&self, _subject: &str, _bytes: usize) {
+        self.0.sent.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn message_received(&self, _subject: &str, bytes: usize) {
+        self.0.received.fetch_add(1, Ordering::SeqCst);
+        self.0.bytes_in.fetch_add(bytes, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn metrics_hooks() {
+    let s = nats_server::run_basic_server();
+    let counters = Arc::new(Counters::default());
+    let nc = nats::Options::new()
+        .metrics(Exporter(counters.clone()))
+        .connect(s.client_url())
+        .unwrap();
+
+    let sub = nc.subscribe("data").unwrap();
+    for _ in 0..10 {
+        nc.publish("data", "hello").unwrap();
+    }
+    for _ in 0..10 {
+        sub.next().unwrap();
+    }
+
+    assert_eq!(counters.sent.load(Ordering::SeqCst), 10);
+    assert_eq!(counters.received.load(Ordering::SeqCst), 10);
+    assert_eq!(counters.bytes_in.load(Ordering::SeqCst), 50);
+}
+
+#[test]
+fn request_complete_callback() {
+    use nats::RequestOutcome;
+    use std::sync::Mutex;
+
+    let s = nats_server::run_basic_server();
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let nc = nats::Options::new()
+        .on_request_complete({
+            let outcomes = outcomes.clone();
+            move |subject, _duration, outcome| {
+                outcomes
+                    .lock()
+                    .unwrap()
+                    .push((subject.to_string(), outcome))
+            }
+        })
+        .connect(s.client_url())
+        .unwrap();
+
+    nc.subscribe("echo")
+        .unwrap()
+        .with_handler(|message| message.respond(&message.data));
+    nc.request("echo", "data").unwrap();
+    nc.request("missing", "data").unwrap_err();
+
+    assert_eq!(
+        *outcomes.lock().unwrap(),
+        vec![
+            ("echo".to_string(), RequestOutcome::Success),
+            ("missing".to_string(), RequestOutcome::NoResponders),
+        ]
+    );
+}
+
+#[test]
+fn connection_stats() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let sub = nc.subscribe("data").unwrap();
+    for _ in 0..10 {
+        nc.publish("data", "hello").unwrap();
+    }
+    for _ in 0..10 {
+        sub.next().unwrap();
+    }
+
+    let stats = nc.stats();
+    assert_eq!(stats.out_messages, 10);
+    assert_eq!(stats.out_bytes, 50);
+    assert_eq!(stats.in_messages, 10);
+    assert_eq!(stats.in_bytes, 50);
+    assert_eq!(stats.reconnects, 0);
+
+    nc.stats_reset();
+    let stats = nc.stats();
+    assert_eq!(stats.out_messages, 0);
+    assert_eq!(stats.in_bytes, 0);
+}
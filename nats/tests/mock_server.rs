@@ -0,0 +1,135 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "test_utils")]
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use crossbeam_channel::bounded;
+use nats::test::MockServer;
+
+#[test]
+fn mock_server_routing() {
+    let server = MockServer::start().unwrap();
+    let nc = nats::connect(&server.client_url()).unwrap();
+
+    let sub = nc.subscribe("orders.*").unwrap();
+    let queue_a = nc.queue_subscribe("orders.new", "workers").unwrap();
+    let queue_b = nc.queue_subscribe("orders.new", "workers").unwrap();
+
+    nc.publish("orders.new", "order").unwrap();
+    assert_eq!(sub.next().unwrap().data, b"order");
+    let queued = queue_a
+        .next_timeout(Duration::from_millis(200))
+        .ok()
+        .or_else(|| queue_b.next_timeout(Duration::from_millis(200)).ok());
+    assert!(queued.is_some());
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("key", "value");
+    nc.publish_with_reply_or_headers("orders.new", None, Some(&headers), "order")
+        .unwrap();
+    let message = sub.next().unwrap();
+    assert_eq!(message.headers.unwrap().get("key").unwrap(), "value");
+
+    server.publish("orders.old", b"server");
+    assert_eq!(sub.next().unwrap().data, b"server");
+
+    nc.subscribe("echo")
+        .unwrap()
+        .with_handler(|msg| msg.respond(msg.data.clone()));
+    assert_eq!(nc.request("echo", "ping").unwrap().data, b"ping");
+}
+
+#[test]
+fn mock_server_scripted_behavior() {
+    let server = MockServer::start().unwrap();
+    let (etx, erx) = bounded(1);
+    let (rtx, rrx) = bounded(1);
+    let nc = nats::Options::new()
+        .error_callback(move |err| {
+            etx.try_send(err).ok();
+        })
+        .reconnect_callback(move || {
+            rtx.try_send(()).ok();
+        })
+        .connect(&server.client_url())
+        .unwrap();
+
+    server.send_err("Maximum Payload Violation");
+    let err = erx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(err.to_string(), "Maximum Payload Violation");
+
+    server.delay_pong(Duration::from_millis(500));
+    let err = nc.flush_timeout(Duration::from_millis(100)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    server.delay_pong(Duration::ZERO);
+
+    server.drop_connections();
+    rrx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(server.connections(), 1);
+    nc.flush().unwrap();
+}
+
+#[test]
+fn mock_server_stale_ping() {
+    let server = MockServer::start().unwrap();
+    let (rtx, rrx) = bounded(1);
+    let nc = nats::Options::new()
+        .reconnect_callback(move || {
+            rtx.try_send(()).ok();
+        })
+        .connect(&server.client_url())
+        .unwrap();
+    nc.ping(Duration::from_secs(1)).unwrap();
+
+    // A late PONG marks the connection as stale and triggers a reconnect.
+    server.delay_pong(Duration::from_millis(500));
+    let err = nc.ping(Duration::from_millis(100)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    server.delay_pong(Duration::ZERO);
+
+    rrx.recv_timeout(Duration::from_secs(5)).unwrap();
+    nc.ping(Duration::from_secs(1)).unwrap();
+}
+
+#[test]
+fn mock_server_idempotent_request() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let server = MockServer::start().unwrap();
+    let nc = nats::connect(&server.client_url()).unwrap();
+
+    // Leave the first request unanswered, as if the connection dropped before the response.
+    let (tx, rx) = bounded(1);
+    let answered = AtomicBool::new(false);
+    nc.subscribe("inventory").unwrap().with_handler(move |msg| {
+        if answered.swap(true, Ordering::SeqCst) {
+            msg.respond("book")
+        } else {
+            tx.send(()).unwrap();
+            Ok(())
+        }
+    });
+
+    let requester = std::thread::spawn({
+        let nc = nc.clone();
+        move || nc.request_idempotent("inventory", None, Some(Duration::from_secs(10)), "get")
+    });
+    rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    server.drop_connections();
+
+    let response = requester.join().unwrap().unwrap();
+    assert_eq!(response.data, b"book");
+}
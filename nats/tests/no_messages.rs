@@ -11,6 +11,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#![cfg(feature = "jetstream")]
+
 use nats::jetstream::*;
 pub use nats_server::*;
 
@@ -20,3 +20,26 @@ fn no_responders() {
     let nc = nats::connect(s.client_url()).expect("could not connect");
     nc.request("nobody-home", "hello").unwrap();
 }
+
+#[test]
+fn no_responders_error_kind() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).expect("could not connect");
+
+    let err = nc.request("nobody-home", "hello").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(matches!(nats::Error::from(err), nats::Error::NoResponders));
+
+    let _sub = nc.subscribe("slow").unwrap();
+    let err = nc
+        .request_timeout("slow", "hello", std::time::Duration::from_millis(100))
+        .unwrap_err();
+    assert!(matches!(nats::Error::from(err), nats::Error::TimedOut));
+
+    nc.clone().close();
+    let err = nc.publish("foo", "hello").unwrap_err();
+    assert!(matches!(
+        nats::Error::from_io(&err),
+        Some(nats::Error::Closed)
+    ));
+}
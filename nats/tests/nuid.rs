@@ -0,0 +1,35 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use nats::nuid::{self, Nuid};
+
+#[test]
+fn nuid_unique() {
+    let mut generator = Nuid::new();
+    let ids: HashSet<String> = (0..10_000)
+        .map(|i| {
+            if i % 2 == 0 {
+                nuid::next()
+            } else {
+                generator.next()
+            }
+        })
+        .collect();
+    assert_eq!(ids.len(), 10_000);
+    for id in ids {
+        assert_eq!(id.len(), nuid::LEN);
+        assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
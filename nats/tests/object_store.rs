@@ -11,7 +11,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#![cfg(feature = "unstable")]
+#![cfg(all(feature = "object_store", feature = "unstable"))]
 
 use rand::prelude::*;
 use std::io::Read;
@@ -114,10 +114,23 @@ fn object_sealed() {
         })
         .unwrap();
 
+    let bytes = vec![1, 2, 3];
+    bucket.put("foo", &mut bytes.as_slice()).unwrap();
+    assert!(!bucket.is_sealed().unwrap());
+
     bucket.seal().unwrap();
 
     let stream_info = context.stream_info("OBJ_OBJECTS").unwrap();
     assert!(stream_info.config.sealed);
+    assert!(bucket.is_sealed().unwrap());
+
+    // Sealed buckets are read-only.
+    bucket.put("bar", &mut bytes.as_slice()).unwrap_err();
+    bucket.delete("foo").unwrap_err();
+
+    let mut result = Vec::new();
+    bucket.get("foo").unwrap().read_to_end(&mut result).unwrap();
+    assert_eq!(result, bytes);
 }
 
 #[test]
@@ -257,6 +270,21 @@ fn object_watch() {
     let info = watch.next().unwrap();
     assert_eq!(info.name, "bar");
     assert_eq!(info.size, bytes.len());
+
+    bucket.delete("foo").unwrap();
+
+    let info = watch.next().unwrap();
+    assert_eq!(info.name, "foo");
+    assert!(info.deleted);
+
+    // A new watch starts with the latest state of every object.
+    let mut watch = bucket.watch().unwrap();
+    let mut infos = vec![watch.next().unwrap(), watch.next().unwrap()];
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(infos[0].name, "bar");
+    assert!(!infos[0].deleted);
+    assert_eq!(infos[1].name, "foo");
+    assert!(infos[1].deleted);
 }
 
 #[test]
@@ -306,3 +334,386 @@ fn object_digest() {
         assert_eq!(result, file);
     }
 }
+
+#[test]
+fn object_list() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "LIST".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(bucket.list().unwrap().count(), 0);
+
+    let bytes = vec![1, 2, 3];
+    bucket.put("foo", &mut bytes.as_slice()).unwrap();
+    bucket.put("bar", &mut bytes.as_slice()).unwrap();
+    bucket.put("baz", &mut bytes.as_slice()).unwrap();
+    bucket.delete("bar").unwrap();
+
+    let mut names: Vec<String> = bucket.list().unwrap().map(|info| info.name).collect();
+    names.sort();
+    assert_eq!(names, vec!["baz".to_string(), "foo".to_string()]);
+
+    let mut infos: Vec<nats::object_store::ObjectInfo> =
+        bucket.list().unwrap().include_deleted(true).collect();
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(infos.len(), 3);
+    assert_eq!(infos[0].name, "bar");
+    assert!(infos[0].deleted);
+    assert_eq!(infos[1].size, bytes.len());
+    assert_eq!(infos[1].chunks, 1);
+    assert!(!infos[1].digest.is_empty());
+
+    // Replacing an object purges the chunks of the previous version.
+    let before = context.stream_info("OBJ_LIST").unwrap();
+    bucket.put("foo", &mut bytes.as_slice()).unwrap();
+    let after = context.stream_info("OBJ_LIST").unwrap();
+    assert_eq!(before.state.messages, after.state.messages);
+}
+
+#[test]
+fn object_chunk_size_and_integrity() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "CHUNKS".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let bytes = vec![7; 100];
+    let info = bucket
+        .put(
+            nats::object_store::ObjectMeta {
+                name: "foo".to_string(),
+                chunk_size: Some(10),
+                ..Default::default()
+            },
+            &mut bytes.as_slice(),
+        )
+        .unwrap();
+    assert_eq!(info.chunks, 10);
+
+    let mut result = Vec::new();
+    bucket.get("foo").unwrap().read_to_end(&mut result).unwrap();
+    assert_eq!(result, bytes);
+
+    // Tamper with the stored chunks.
+    context
+        .publish(&format!("$O.CHUNKS.C.{}", info.nuid), b"garbage")
+        .unwrap();
+
+    let mut result = Vec::new();
+    let err = bucket
+        .get("foo")
+        .unwrap()
+        .read_to_end(&mut result)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let integrity = err
+        .get_ref()
+        .and_then(|err| err.downcast_ref::<nats::object_store::IntegrityError>())
+        .unwrap();
+    assert_eq!(integrity.expected(), info.digest);
+}
+
+#[test]
+fn object_links() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "LINKS".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let other = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "OTHER".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let bytes = vec![1, 2, 3, 4, 5];
+    let info = other.put("foo", &mut bytes.as_slice()).unwrap();
+
+    let link = bucket.add_link("bar", &info).unwrap();
+    assert_eq!(
+        link.link,
+        Some(nats::object_store::ObjectLink {
+            name: "foo".to_string(),
+            bucket: Some("OTHER".to_string()),
+        })
+    );
+
+    // Links resolve across buckets.
+    let mut result = Vec::new();
+    bucket.get("bar").unwrap().read_to_end(&mut result).unwrap();
+    assert_eq!(result, bytes);
+
+    // Links to links are not allowed, and links never replace objects holding data.
+    bucket.add_link("baz", &link).unwrap_err();
+    let data = bucket.put("data", &mut bytes.as_slice()).unwrap();
+    bucket.add_link("data", &data).unwrap_err();
+
+    let bucket_link = bucket.add_bucket_link("other", "OTHER").unwrap();
+    assert_eq!(bucket_link.link.unwrap().bucket, Some("OTHER".to_string()));
+    assert!(bucket.get("other").is_err());
+}
+
+#[test]
+fn object_update_metadata() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "META".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let bytes = vec![1, 2, 3, 4, 5];
+    let original = bucket.put("foo", &mut bytes.as_slice()).unwrap();
+    bucket.put("taken", &mut bytes.as_slice()).unwrap();
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("X-Version", "2");
+
+    let info = bucket
+        .update_metadata(
+            "foo",
+            nats::object_store::ObjectMeta {
+                name: "bar".to_string(),
+                description: Some("renamed".to_string()),
+                headers: Some(headers.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(info.nuid, original.nuid);
+
+    let info = bucket.info("bar").unwrap();
+    assert_eq!(info.description, Some("renamed".to_string()));
+    assert_eq!(info.headers, Some(headers));
+    bucket.info("foo").unwrap_err();
+
+    let mut result = Vec::new();
+    bucket.get("bar").unwrap().read_to_end(&mut result).unwrap();
+    assert_eq!(result, bytes);
+
+    // Renaming onto an existing object is rejected.
+    bucket.update_metadata("bar", "taken").unwrap_err();
+}
+
+#[test]
+fn object_get_range() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "RANGE".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let bytes: Vec<u8> = (0..100).collect();
+    bucket
+        .put(
+            nats::object_store::ObjectMeta {
+                name: "foo".to_string(),
+                chunk_size: Some(8),
+                ..Default::default()
+            },
+            &mut bytes.as_slice(),
+        )
+        .unwrap();
+
+    for (offset, length) in [(0, 100), (5, 10), (8, 8), (95, 50), (99, 1), (100, 10)] {
+        let mut result = Vec::new();
+        bucket
+            .get_range("foo", offset, length)
+            .unwrap()
+            .read_to_end(&mut result)
+            .unwrap();
+
+        let end = std::cmp::min(offset + length, bytes.len());
+        assert_eq!(result, bytes[offset..end].to_vec());
+    }
+
+    bucket.get_range("foo", 101, 1).unwrap_err();
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn object_compression() {
+    use nats::object_store::{Compression, ObjectMeta};
+
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "COMPRESSED".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let bytes = b"text heavy artifact ".repeat(10_000);
+
+    for (name, compression) in [("gzip", Compression::Gzip), ("zstd", Compression::Zstd)] {
+        let info = bucket
+            .put(
+                ObjectMeta {
+                    name: name.to_string(),
+                    chunk_size: Some(64 * 1024),
+                    compression: Some(compression),
+                    ..Default::default()
+                },
+                &mut bytes.as_slice(),
+            )
+            .unwrap();
+        assert_eq!(info.size, bytes.len());
+        assert_eq!(info.compression, Some(compression));
+        assert_eq!(bucket.info(name).unwrap().compression, Some(compression));
+
+        let mut result = Vec::new();
+        bucket.get(name).unwrap().read_to_end(&mut result).unwrap();
+        assert_eq!(result, bytes);
+    }
+
+    // The stored chunks are smaller than the original data.
+    let stream_info = context.stream_info("OBJ_COMPRESSED").unwrap();
+    assert!(stream_info.state.bytes < bytes.len() as u64);
+}
+
+#[test]
+fn object_resume_put() {
+    // A reader which fails after yielding a number of bytes, simulating an interrupted upload.
+    struct Interrupted<'a> {
+        data: &'a [u8],
+        remaining: usize,
+    }
+
+    impl<'a> Read for Interrupted<'a> {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "interrupted",
+                ));
+            }
+
+            let len = std::cmp::min(std::cmp::min(buffer.len(), self.remaining), self.data.len());
+            buffer[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            self.remaining -= len;
+            Ok(len)
+        }
+    }
+
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    let bucket = context
+        .create_object_store(&nats::object_store::Config {
+            bucket: "RESUME".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut rng = rand::thread_rng();
+    let mut bytes = vec![0; 1000];
+    rng.try_fill_bytes(&mut bytes).unwrap();
+
+    let meta = nats::object_store::ObjectMeta {
+        name: "foo".to_string(),
+        chunk_size: Some(100),
+        ..Default::default()
+    };
+
+    bucket
+        .put(
+            meta.clone(),
+            &mut Interrupted {
+                data: &bytes,
+                remaining: 450,
+            },
+        )
+        .unwrap_err();
+    bucket.info("foo").unwrap_err();
+
+    let info = bucket.resume_put("foo", &mut bytes.as_slice()).unwrap();
+    assert_eq!(info.size, bytes.len());
+    assert_eq!(info.chunks, 10);
+
+    let mut result = Vec::new();
+    bucket.get("foo").unwrap().read_to_end(&mut result).unwrap();
+    assert_eq!(result, bytes);
+
+    // Completed uploads can not be resumed.
+    bucket.resume_put("foo", &mut bytes.as_slice()).unwrap_err();
+
+    // A new upload removes the chunks of the interrupted one it replaces, and aborting
+    // removes the chunks of the interrupted upload.
+    let before = context.stream_info("OBJ_RESUME").unwrap();
+    for remaining in [450, 250] {
+        bucket
+            .put(
+                meta.clone(),
+                &mut Interrupted {
+                    data: &bytes,
+                    remaining,
+                },
+            )
+            .unwrap_err();
+    }
+    bucket.abort_put("foo").unwrap();
+    let after = context.stream_info("OBJ_RESUME").unwrap();
+    assert_eq!(before.state.messages, after.state.messages);
+}
+
+#[test]
+fn object_put_existing_bucket() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    // A bucket created without the subjects recording the state of uploads.
+    context
+        .add_stream(&nats::jetstream::StreamConfig {
+            name: "OBJ_EXISTING".to_string(),
+            subjects: vec!["$O.EXISTING.C.>".to_string(), "$O.EXISTING.M.>".to_string()],
+            discard: nats::jetstream::DiscardPolicy::New,
+            allow_rollup: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let bucket = context.object_store("EXISTING").unwrap();
+    let bytes = vec![0, 1, 2, 3, 4];
+    let info = bucket.put("foo", &mut bytes.as_slice()).unwrap();
+    assert_eq!(info.size, bytes.len());
+
+    let mut result = Vec::new();
+    bucket.get("foo").unwrap().read_to_end(&mut result).unwrap();
+    assert_eq!(result, bytes);
+}
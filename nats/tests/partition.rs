@@ -0,0 +1,91 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use nats::partition::{partition, Partitioner};
+
+#[test]
+fn partition_is_stable() {
+    // The 32-bit FNV-1a of the keys, which must not change across versions.
+    assert_eq!(partition("", 8), 5);
+    assert_eq!(partition("a", 8), 4);
+    assert_eq!(partition("customer-1", 8), 7);
+    assert_eq!(partition("customer-2", 8), 2);
+    assert_eq!(partition("customer-1", 0), 0);
+
+    let orders = Partitioner::new("orders", 8);
+    assert_eq!(orders.partitions(), 8);
+    assert_eq!(orders.subject_for("customer-1"), "orders.p7");
+}
+
+#[test]
+fn partitioned_handler_keeps_key_order() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let orders = Partitioner::new("orders", 4);
+    let received: Arc<Mutex<HashMap<String, Vec<u32>>>> = Arc::default();
+    let handler = {
+        let received = received.clone();
+        orders
+            .queue_subscribe(&nc, "billing")
+            .unwrap()
+            .with_handler(move |message| {
+                let data = String::from_utf8(message.data).unwrap();
+                let (key, sequence) = data.split_once(':').unwrap();
+                assert_eq!(
+                    message.subject,
+                    Partitioner::new("orders", 4).subject_for(key)
+                );
+                received
+                    .lock()
+                    .unwrap()
+                    .entry(key.to_string())
+                    .or_default()
+                    .push(sequence.parse().unwrap());
+                Ok(())
+            })
+    };
+
+    let keys = ["customer-1", "customer-2", "customer-3"];
+    for sequence in 0..100 {
+        for key in keys {
+            orders
+                .publish(&nc, key, format!("{}:{}", key, sequence))
+                .unwrap();
+        }
+    }
+    nc.flush().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while received
+        .lock()
+        .unwrap()
+        .values()
+        .map(Vec::len)
+        .sum::<usize>()
+        < 300
+    {
+        assert!(Instant::now() < deadline, "timed out waiting for messages");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    for key in keys {
+        let sequences = &received.lock().unwrap()[key];
+        assert_eq!(*sequences, (0..100).collect::<Vec<u32>>());
+    }
+
+    handler.unsubscribe().unwrap();
+}
@@ -0,0 +1,72 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::pool::ConnectionPool;
+
+#[test]
+fn pool_round_robin() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("events").unwrap();
+    nc.subscribe("echo")
+        .unwrap()
+        .with_handler(|msg| msg.respond(msg.data.clone()));
+    nc.flush().unwrap();
+
+    let pool = ConnectionPool::connect(3, s.client_url()).unwrap();
+    assert_eq!(pool.len(), 3);
+
+    for i in 0..6 {
+        pool.publish("events", i.to_string()).unwrap();
+    }
+    pool.flush().unwrap();
+    let mut received: Vec<String> = (0..6)
+        .map(|_| {
+            let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+            String::from_utf8(msg.data).unwrap()
+        })
+        .collect();
+    received.sort();
+    assert_eq!(received, ["0", "1", "2", "3", "4", "5"]);
+
+    // Each connection published two of the messages.
+    for connection in pool.connections() {
+        assert_eq!(connection.stats().out_messages, 2);
+    }
+
+    let response = pool
+        .request_timeout("echo", "ping", Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(response.data, b"ping");
+
+    pool.close();
+}
+
+#[test]
+fn pool_of_connections() {
+    let s = nats_server::run_basic_server();
+    let connections = vec![
+        nats::connect(s.client_url()).unwrap(),
+        nats::connect(s.client_url()).unwrap(),
+    ];
+    let pool = ConnectionPool::new(connections).unwrap();
+    let first = pool.get().client_id();
+    assert_ne!(pool.get().client_id(), first);
+    assert_eq!(pool.get().client_id(), first);
+
+    let err = ConnectionPool::new(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
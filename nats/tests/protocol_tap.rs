@@ -0,0 +1,49 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use nats::{Direction, ProtocolEvent};
+
+#[test]
+fn protocol_tap() {
+    let s = nats_server::run_server("tests/configs/user_pass.conf");
+    let events: Arc<Mutex<Vec<ProtocolEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let nc = nats::Options::with_user_pass("derek", "s3cr3t")
+        .protocol_tap({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event.clone())
+        })
+        .connect(s.client_url())
+        .unwrap();
+
+    let sub = nc.subscribe("tapped").unwrap();
+    nc.publish("tapped", vec![0; 1024]).unwrap();
+    sub.next().unwrap();
+
+    let events = events.lock().unwrap();
+    let connect = events.iter().find(|event| event.op == "CONNECT").unwrap();
+    let arguments = connect.arguments.as_ref().unwrap();
+    assert!(arguments.contains("derek"));
+    assert!(!arguments.contains("s3cr3t"));
+
+    let publish = events.iter().find(|event| event.op == "PUB").unwrap();
+    assert_eq!(publish.direction, Direction::Outbound);
+    assert_eq!(publish.subject.as_deref(), Some("tapped"));
+    assert_eq!(publish.payload_len, 1024);
+    assert_eq!(publish.payload.len(), nats::TAP_PAYLOAD_PREVIEW);
+
+    let message = events.iter().find(|event| event.op == "MSG").unwrap();
+    assert_eq!(message.direction, Direction::Inbound);
+    assert_eq!(message.payload_len, 1024);
+}
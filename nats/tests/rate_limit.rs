@@ -0,0 +1,91 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+
+use nats::rate_limit::{RateLimit, RateLimitPolicy};
+
+#[test]
+fn publish_rate_limit_blocks() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .publish_rate_limit(RateLimit::per_second(20).burst(5))
+        .connect(s.client_url())
+        .unwrap();
+
+    // The burst goes out right away, the rest at the configured rate.
+    let start = Instant::now();
+    for _ in 0..5 {
+        nc.publish("events", "").unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(100));
+    for _ in 0..10 {
+        nc.publish("events", "").unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(450));
+}
+
+#[test]
+fn subject_rate_limit_errors() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .subject_rate_limit(
+            "audit.>",
+            RateLimit::per_second(1)
+                .burst(2)
+                .policy(RateLimitPolicy::Error),
+        )
+        .connect(s.client_url())
+        .unwrap();
+
+    nc.publish("audit.login", "").unwrap();
+    nc.publish("audit.logout", "").unwrap();
+    let err = nc.publish("audit.login", "").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert!(matches!(nats::Error::from(err), nats::Error::RateLimited));
+
+    // Other subjects are not limited.
+    for _ in 0..100 {
+        nc.publish("events", "").unwrap();
+    }
+}
+
+#[test]
+fn internal_publishes_are_not_limited() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::Options::new()
+        .publish_rate_limit(
+            RateLimit::per_second(1)
+                .burst(1)
+                .policy(RateLimitPolicy::Error),
+        )
+        .connect(s.client_url())
+        .unwrap();
+
+    nc.publish("events", "").unwrap();
+    nc.publish("events", "").unwrap_err();
+
+    // Responses are not limited.
+    let requester = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("service").unwrap();
+    for _ in 0..5 {
+        requester.publish_request("service", "reply", "").unwrap();
+        sub.next().unwrap().respond("ok").unwrap();
+    }
+
+    // Neither are JetStream API requests.
+    let context = nats::jetstream::new(nc.clone());
+    context.add_stream("limited").unwrap();
+    context.stream_info("limited").unwrap();
+}
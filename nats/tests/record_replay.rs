@@ -0,0 +1,50 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "test_utils")]
+
+use std::time::Duration;
+
+use nats::test::ReplayServer;
+
+#[test]
+fn record_and_replay() {
+    let capture = std::env::temp_dir().join(format!("nats-capture-{}.nats", std::process::id()));
+
+    {
+        let s = nats_server::run_server("tests/configs/user_pass.conf");
+        let nc = nats::Options::with_user_pass("derek", "s3cr3t")
+            .record(&capture)
+            .connect(s.client_url())
+            .unwrap();
+        let sub = nc.subscribe("data").unwrap();
+        nc.publish("data", "recorded").unwrap();
+        sub.next_timeout(Duration::from_secs(1)).unwrap();
+        nc.flush().unwrap();
+        nc.close();
+    }
+
+    let recorded = std::fs::read(&capture).unwrap();
+    let recorded = String::from_utf8_lossy(&recorded);
+    assert!(recorded.contains("[REDACTED]"));
+    assert!(!recorded.contains("s3cr3t"));
+
+    let server = ReplayServer::start(&capture).unwrap();
+    let nc = nats::connect(&server.client_url()).unwrap();
+    let sub = nc.subscribe("data").unwrap();
+    nc.publish("data", "recorded").unwrap();
+    let message = sub.next_timeout(Duration::from_secs(2)).unwrap();
+    assert_eq!(message.data, b"recorded");
+
+    std::fs::remove_file(&capture).ok();
+}
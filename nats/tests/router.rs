@@ -0,0 +1,88 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Barrier};
+use std::time::Duration;
+
+use nats::router::{Params, Router};
+
+#[test]
+fn params_capture() {
+    let params = Params::capture("orders.*.created", "orders.42.created").unwrap();
+    assert_eq!(params.len(), 1);
+    assert_eq!(&params["1"], "42");
+
+    let params = Params::capture("*.items.>", "orders.items.1.price").unwrap();
+    assert_eq!(&params["1"], "orders");
+    assert_eq!(&params["2"], "1.price");
+
+    assert!(Params::capture("orders", "orders").unwrap().is_empty());
+    assert!(Params::capture("orders.*", "orders").is_none());
+    assert!(Params::capture("orders.*", "orders.1.created").is_none());
+    assert!(Params::capture("orders.>", "orders").is_none());
+    assert!(Params::capture("orders.*.created", "orders.1.deleted").is_none());
+}
+
+#[test]
+fn router_dispatch() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let router = Router::new()
+        .route("orders.*.created", |message, params| {
+            message.respond(format!("created {}", &params["1"]))
+        })
+        .route("orders.*.items.>", |message, params| {
+            message.respond(format!("item {} of {}", &params["2"], &params["1"]))
+        })
+        .attach(&nc)
+        .unwrap();
+
+    let response = nc.request("orders.1.created", "").unwrap();
+    assert_eq!(response.data, b"created 1");
+    let response = nc.request("orders.1.items.2.price", "").unwrap();
+    assert_eq!(response.data, b"item 2.price of 1");
+
+    router.unsubscribe().unwrap();
+    nc.flush().unwrap();
+    let err = nc.request("orders.1.created", "").unwrap_err();
+    assert!(matches!(nats::Error::from(err), nats::Error::NoResponders));
+}
+
+#[test]
+fn router_workers() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    // Handlers only return once all workers are busy, so this deadlocks unless they run
+    // concurrently.
+    let barrier = Arc::new(Barrier::new(4));
+    let _router = Router::new()
+        .workers(4)
+        .route("work", move |message, _| {
+            barrier.wait();
+            message.respond(std::thread::current().name().unwrap_or_default())
+        })
+        .attach(&nc)
+        .unwrap();
+
+    let sub = nc.subscribe("done").unwrap();
+    for _ in 0..4 {
+        nc.publish_request("work", "done", "").unwrap();
+    }
+    let workers: HashSet<Vec<u8>> = (0..4)
+        .map(|_| sub.next_timeout(Duration::from_secs(5)).unwrap().data)
+        .collect();
+    assert_eq!(workers.len(), 4);
+}
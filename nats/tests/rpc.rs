@@ -0,0 +1,75 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "tower")]
+
+use std::io;
+use std::time::Duration;
+
+use nats::rpc::RequestService;
+use nats::{HeaderMap, Message};
+use tower::{service_fn, ServiceBuilder, ServiceExt};
+
+#[test]
+fn tower_request_reply() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _handler =
+        nc.subscribe("upper")
+            .unwrap()
+            .with_service(service_fn(|message: Message| async move {
+                let mut headers = HeaderMap::new();
+                headers.insert("Handled-By", "tower");
+                let data = String::from_utf8_lossy(&message.data).to_uppercase();
+                Ok::<_, io::Error>(Message::new("", None, data, Some(headers)))
+            }));
+
+    // Middleware composes with the request service.
+    let service = ServiceBuilder::new()
+        .map_request(|mut message: Message| {
+            message.data.extend_from_slice(b" world");
+            message
+        })
+        .service(RequestService::new(nc.clone()));
+    let response =
+        smol::block_on(service.oneshot(Message::new("upper", None, "hello", None))).unwrap();
+
+    assert_eq!(response.data, b"HELLO WORLD");
+    assert_eq!(
+        response.headers.unwrap().get("Handled-By").unwrap(),
+        "tower"
+    );
+}
+
+#[test]
+fn tower_request_errors() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _handler =
+        nc.subscribe("fail")
+            .unwrap()
+            .with_service(service_fn(|_: Message| async move {
+                Err::<Message, _>(io::Error::new(io::ErrorKind::Other, "boom"))
+            }));
+
+    // Failed calls publish no response, so the request times out.
+    let service = RequestService::new(nc.clone()).timeout(Duration::from_millis(200));
+    let err = smol::block_on(service.oneshot(Message::new("fail", None, "", None))).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    let service = RequestService::new(nc);
+    let err = smol::block_on(service.oneshot(Message::new("nobody", None, "", None))).unwrap_err();
+    assert!(matches!(nats::Error::from(err), nats::Error::NoResponders));
+}
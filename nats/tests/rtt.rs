@@ -0,0 +1,60 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn rtt() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let rtt = nc.rtt_timeout(Duration::from_secs(1)).unwrap();
+    assert!(rtt < Duration::from_secs(1));
+    nc.flush_timeout(Duration::from_secs(1)).unwrap();
+}
+
+#[test]
+fn rtt_unresponsive_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    // A server that completes the handshake and then stops answering.
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .write_all(b"INFO {\"server_id\":\"silent\",\"max_payload\":1048576}\r\n")
+            .unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            if line.starts_with("PING") {
+                stream.write_all(b"PONG\r\n").unwrap();
+                break;
+            }
+            line.clear();
+        }
+        // Keep the socket open without responding.
+        thread::sleep(Duration::from_secs(5));
+    });
+
+    let nc = nats::connect(&format!("nats://127.0.0.1:{}", port)).unwrap();
+
+    let err = nc.rtt_timeout(Duration::from_millis(200)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+    let err = nc.flush_timeout(Duration::from_millis(200)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+}
@@ -0,0 +1,78 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+#[test]
+fn send_request() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    nc.subscribe("echo")
+        .unwrap()
+        .with_handler(|message| message.respond(&message.data));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let pending = (0..1000)
+        .map(|i| nc.send_request("echo", i.to_string(), deadline).unwrap())
+        .collect::<Vec<_>>();
+    for (i, request) in pending.into_iter().enumerate() {
+        assert_eq!(request.wait().unwrap().data, i.to_string().as_bytes());
+    }
+}
+
+#[test]
+fn send_request_deadline() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let _silent = nc.subscribe("silent").unwrap();
+    nc.flush().unwrap();
+
+    let start = Instant::now();
+    let request = nc
+        .send_request("silent", "data", start + Duration::from_millis(200))
+        .unwrap();
+    let err = request.wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    let err = nc
+        .send_request("nobody", "data", Instant::now() + Duration::from_secs(5))
+        .unwrap()
+        .wait()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn request_with_callback() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    nc.subscribe("echo")
+        .unwrap()
+        .with_handler(|message| message.respond(&message.data));
+
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    nc.request_with_callback(
+        "echo",
+        "hello",
+        Instant::now() + Duration::from_secs(5),
+        move |result| {
+            sender.send(result.map(|message| message.data)).unwrap();
+        },
+    )
+    .unwrap();
+    let response = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(response.unwrap(), b"hello");
+}
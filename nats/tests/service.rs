@@ -0,0 +1,279 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use nats::service::*;
+
+#[test]
+fn service_validation() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    assert!(nc.add_service("invalid name", "1.0.0").is_err());
+    assert!(nc.add_service("name", "1.0").is_err());
+    assert!(nc.add_service("name", "1.0.0").is_ok());
+}
+
+#[test]
+fn service_endpoint() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let service = nc.add_service("calculator", "1.0.0").unwrap();
+    service
+        .add_endpoint("length", "calculator.length", |message| {
+            if message.data.is_empty() {
+                return Err(Error::new(400, "empty request"));
+            }
+            Ok(message.data.len().to_string().into_bytes())
+        })
+        .unwrap();
+
+    let response = nc.request("calculator.length", "data").unwrap();
+    assert_eq!(response.data, b"4");
+
+    let response = nc.request("calculator.length", "").unwrap();
+    let headers = response.headers.unwrap();
+    assert_eq!(
+        headers.get(NATS_SERVICE_ERROR),
+        Some(&"empty request".to_string())
+    );
+    assert_eq!(
+        headers.get(NATS_SERVICE_ERROR_CODE),
+        Some(&"400".to_string())
+    );
+
+    let stats = service.stats();
+    assert_eq!(stats.endpoints[0].requests, 2);
+    assert_eq!(stats.endpoints[0].errors, 1);
+    assert_eq!(stats.endpoints[0].last_error, "400:empty request");
+
+    service.stop().unwrap();
+    assert!(nc
+        .request_timeout(
+            "calculator.length",
+            "data",
+            std::time::Duration::from_millis(500)
+        )
+        .is_err());
+}
+
+#[test]
+fn service_discovery_responders() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let service = nc
+        .service_builder()
+        .description("echoes requests")
+        .start("echo", "1.0.0")
+        .unwrap();
+    service
+        .add_endpoint("echo", "echo", |message| Ok(message.data.clone()))
+        .unwrap();
+
+    let ping: PingResponse =
+        serde_json::from_slice(&nc.request("$SRV.PING.echo", "").unwrap().data).unwrap();
+    assert_eq!(ping.id, service.id());
+    assert_eq!(ping.kind, "io.nats.micro.v1.ping_response");
+
+    let info: Info = serde_json::from_slice(
+        &nc.request(&format!("$SRV.INFO.echo.{}", service.id()), "")
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    assert_eq!(info.description, "echoes requests");
+    assert_eq!(info.endpoints[0].subject, "echo");
+    assert_eq!(info.endpoints[0].queue_group, DEFAULT_QUEUE_GROUP);
+
+    nc.request("echo", "data").unwrap();
+    let stats: Stats = serde_json::from_slice(&nc.request("$SRV.STATS", "").unwrap().data).unwrap();
+    assert_eq!(stats.endpoints[0].requests, 1);
+}
+
+#[test]
+fn service_groups() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let service = nc.add_service("calculator", "1.0.0").unwrap();
+    let v1 = service.group("calculator").group("v1");
+    v1.add_endpoint("length", "length", |message| {
+        Ok(message.data.len().to_string().into_bytes())
+    })
+    .unwrap();
+    service
+        .group("calculator.v2")
+        .queue_group("v2")
+        .add_endpoint("length", "length", |message| {
+            Ok((message.data.len() * 2).to_string().into_bytes())
+        })
+        .unwrap();
+
+    assert_eq!(
+        nc.request("calculator.v1.length", "data").unwrap().data,
+        b"4"
+    );
+    assert_eq!(
+        nc.request("calculator.v2.length", "data").unwrap().data,
+        b"8"
+    );
+
+    let info = service.info();
+    assert_eq!(info.endpoints[0].subject, "calculator.v1.length");
+    assert_eq!(info.endpoints[0].queue_group, DEFAULT_QUEUE_GROUP);
+    assert_eq!(info.endpoints[1].queue_group, "v2");
+}
+
+#[test]
+fn service_metadata_and_schema() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert("team".to_string(), "platform".to_string());
+    let service = nc
+        .service_builder()
+        .metadata(metadata.clone())
+        .start("calculator", "1.0.0")
+        .unwrap();
+    let schema = Schema {
+        request: r#"{"type":"string"}"#.to_string(),
+        response: r#"{"type":"integer"}"#.to_string(),
+    };
+    service
+        .group("calculator")
+        .endpoint_builder("length", "length")
+        .metadata(metadata.clone())
+        .schema(schema.clone())
+        .add(|message| Ok(message.data.len().to_string().into_bytes()))
+        .unwrap();
+
+    let info: Info =
+        serde_json::from_slice(&nc.request("$SRV.INFO.calculator", "").unwrap().data).unwrap();
+    assert_eq!(info.metadata, metadata);
+    assert_eq!(info.endpoints[0].subject, "calculator.length");
+    assert_eq!(info.endpoints[0].metadata, metadata);
+    assert_eq!(info.endpoints[0].schema, Some(schema));
+}
+
+#[test]
+fn service_middleware() {
+    use std::sync::{Arc, Mutex};
+
+    struct Record(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+    impl Middleware for Record {
+        fn before(&self, request: &nats::Message) -> Result<(), Error> {
+            self.1.lock().unwrap().push(self.0);
+            if request.data == b"forbidden" {
+                return Err(Error::new(403, "forbidden"));
+            }
+            Ok(())
+        }
+
+        fn after(&self, _request: &nats::Message, response: &mut Result<Vec<u8>, Error>) {
+            self.1.lock().unwrap().push(self.0);
+            if let Ok(data) = response {
+                data.push(b'!');
+            }
+        }
+    }
+
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let service = nc.add_service("echo", "1.0.0").unwrap();
+    service
+        .endpoint_builder("echo", "echo")
+        .middleware(Record("first", calls.clone()))
+        .middleware(Record("second", calls.clone()))
+        .add(|message| Ok(message.data.clone()))
+        .unwrap();
+
+    assert_eq!(nc.request("echo", "data").unwrap().data, b"data!!");
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["first", "second", "first", "second"]
+    );
+
+    let response = nc.request("echo", "forbidden").unwrap();
+    assert_eq!(
+        response.headers.unwrap().get(NATS_SERVICE_ERROR_CODE),
+        Some(&"403".to_string())
+    );
+    assert_eq!(service.stats().endpoints[0].errors, 1);
+}
+
+#[test]
+fn service_stats_handler() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let service = nc
+        .service_builder()
+        .stats_handler(|stats| serde_json::json!({ "hits": stats.requests * 2 }))
+        .start("echo", "1.0.0")
+        .unwrap();
+    service
+        .add_endpoint("echo", "echo", |message| Ok(message.data.clone()))
+        .unwrap();
+
+    nc.request("echo", "data").unwrap();
+    let stats: Stats =
+        serde_json::from_slice(&nc.request("$SRV.STATS.echo", "").unwrap().data).unwrap();
+    assert_eq!(
+        stats.endpoints[0].data,
+        Some(serde_json::json!({ "hits": 2 }))
+    );
+}
+
+#[test]
+fn service_discovery_client() {
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+
+    let first = nc.add_service("echo", "1.0.0").unwrap();
+    let second = nc.add_service("echo", "1.0.0").unwrap();
+    nc.add_service("other", "2.0.0").unwrap();
+    for service in &[&first, &second] {
+        service
+            .add_endpoint("echo", "echo", |message| Ok(message.data.clone()))
+            .unwrap();
+    }
+
+    assert_eq!(nc.discover_services().unwrap().len(), 3);
+
+    let mut ids: Vec<String> = nc
+        .service_info("echo")
+        .unwrap()
+        .into_iter()
+        .map(|info| info.id)
+        .collect();
+    ids.sort();
+    let mut expected = vec![first.id().to_string(), second.id().to_string()];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    nc.request("echo", "data").unwrap();
+    let requests: u64 = nc
+        .service_stats("echo")
+        .unwrap()
+        .iter()
+        .map(|stats| stats.endpoints[0].requests)
+        .sum();
+    assert_eq!(requests, 1);
+
+    assert!(nc.service_info("missing").unwrap().is_empty());
+}
@@ -0,0 +1,59 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nats::shutdown::Shutdown;
+
+#[test]
+fn shutdown_drains_subscriptions() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let handled = Arc::new(AtomicUsize::new(0));
+    let handler = {
+        let handled = handled.clone();
+        nc.subscribe("orders").unwrap().with_handler(move |_| {
+            std::thread::sleep(Duration::from_millis(1));
+            handled.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+    };
+    // Nobody takes the messages of this subscription.
+    let stuck = nc.subscribe("payments").unwrap();
+
+    for _ in 0..100 {
+        nc.publish("orders", "order").unwrap();
+        nc.publish("payments", "payment").unwrap();
+    }
+
+    let report = Shutdown::new(&nc, Duration::from_secs(5))
+        .drain("orders", handler)
+        .run();
+    assert_eq!(report.drained, ["orders"]);
+    assert!(report.is_complete(), "{:?}", report);
+    assert!(handled.load(Ordering::SeqCst) >= 99);
+
+    let report = Shutdown::new(&nc, Duration::from_millis(200))
+        .drain("payments", stuck.clone())
+        .run();
+    assert!(report.drained.is_empty());
+    assert_eq!(report.timed_out, ["payments"]);
+    assert!(report.failed.is_empty());
+    assert!(!report.is_complete());
+
+    // Messages received before draining can still be taken.
+    assert_eq!(stuck.try_iter().count(), 100);
+}
@@ -62,3 +62,61 @@ fn slow_consumers() {
     // check if expected number of messages were dropped
     assert_eq!(sub.dropped_messages().unwrap(), 40);
 }
+
+#[test]
+fn subscription_capacity() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .subscription_capacity(2)
+        .connect(s.client_url())
+        .unwrap();
+
+    let limited = nc.subscribe("data").unwrap();
+    let overridden = nc.subscribe("data").unwrap();
+    overridden.set_message_limits(10);
+    nc.flush().unwrap();
+
+    for _ in 0..5 {
+        nc.publish("data", "payload").unwrap();
+    }
+    nc.flush().unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(limited.dropped_messages().unwrap(), 3);
+    assert_eq!(limited.try_iter().count(), 2);
+    assert_eq!(overridden.dropped_messages().unwrap(), 0);
+    assert_eq!(overridden.try_iter().count(), 5);
+}
+
+#[test]
+fn pending_limits() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let by_bytes = nc.subscribe("data").unwrap();
+    by_bytes.set_pending_limits(None, 50);
+    let by_messages = nc.subscribe("data").unwrap();
+    by_messages.set_pending_limits(6, None);
+    nc.flush().unwrap();
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("key", "value");
+    for i in 0..8 {
+        let headers = if i % 2 == 0 { Some(&headers) } else { None };
+        nc.publish_with_reply_or_headers("data", None, headers, "0123456789")
+            .unwrap();
+    }
+    nc.flush().unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(by_bytes.dropped_messages().unwrap(), 3);
+    assert_eq!(by_messages.dropped_messages().unwrap(), 2);
+
+    // Taking messages from the queue frees their bytes.
+    assert_eq!(by_bytes.try_iter().count(), 5);
+    nc.publish("data", "0123456789").unwrap();
+    nc.flush().unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(by_bytes.try_iter().count(), 1);
+    assert_eq!(by_bytes.dropped_messages().unwrap(), 3);
+}
@@ -0,0 +1,102 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::Subject;
+
+#[test]
+fn subject_validation() {
+    for valid in [
+        "foo",
+        "foo.bar",
+        "foo.*.baz",
+        "foo.>",
+        ">",
+        "*",
+        "$JS.API.INFO",
+    ] {
+        assert!(Subject::new(valid).is_ok(), "{} should be valid", valid);
+    }
+    for invalid in [
+        "",
+        "foo bar",
+        "foo\tbar",
+        ".foo",
+        "foo.",
+        "foo..bar",
+        "foo*",
+        "foo.b>",
+        "foo.>.bar",
+    ] {
+        let err = Subject::new(invalid).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(matches!(
+            nats::Error::from(err),
+            nats::Error::InvalidSubject(_)
+        ));
+    }
+
+    let subject = Subject::new("orders.*").unwrap();
+    assert!(subject.is_wildcard());
+    assert_eq!(subject.tokens().collect::<Vec<_>>(), ["orders", "*"]);
+    assert_eq!(subject.clone(), "orders.*");
+}
+
+#[test]
+fn subject_apis() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let subject = Subject::new("orders.created").unwrap();
+    let sub = nc.subscribe(subject.clone()).unwrap();
+    nc.publish(subject, "order").unwrap();
+    nc.publish(String::from("orders.created"), "order").unwrap();
+    for _ in 0..2 {
+        assert_eq!(
+            sub.next_timeout(Duration::from_secs(1)).unwrap().data,
+            b"order"
+        );
+    }
+
+    // Malformed subjects fail before reaching the server.
+    let err = nc.subscribe("orders created").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = nc.publish("orders.*", "order").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = nc.request("orders..created", "order").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn subject_macro() {
+    let region = "eu-west";
+    let id = 42;
+    let subject = nats::subject!("orders.{region}.{id}", region = region, id = id);
+    assert_eq!(subject, "orders.eu-west.42");
+    assert!(subject.validate().is_ok());
+
+    // Values cannot add tokens or wildcards.
+    let subject = nats::subject!("orders.{region}.>", region = "us.east *");
+    assert_eq!(subject, "orders.us%2Eeast%20%2A.>");
+    assert_eq!(subject.tokens().count(), 3);
+    let subject = nats::subject!("orders.p{partition}.{id}", partition = 3, id = "");
+    assert_eq!(subject, "orders.p3.%");
+
+    // A placeholder used twice needs a single argument.
+    let subject = nats::subject!("{kind}.{kind}", kind = "a>");
+    assert_eq!(subject, "a%3E.a%3E");
+
+    assert_eq!(nats::subject!("orders.created"), "orders.created");
+}
@@ -0,0 +1,62 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use nats::header::HeaderMap;
+use nats::trace_context::{TraceContext, TRACEPARENT};
+
+const TRACEPARENT_VALUE: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+#[test]
+fn trace_context_parse() {
+    let context = TraceContext::parse(TRACEPARENT_VALUE).unwrap();
+    assert_eq!(context.parent_id, "b7ad6b7169203331");
+    assert_eq!(context.traceparent(), TRACEPARENT_VALUE);
+
+    assert!(
+        TraceContext::parse("00-00000000000000000000000000000000-b7ad6b7169203331-01").is_none()
+    );
+    assert!(TraceContext::parse("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331").is_none());
+    assert!(
+        TraceContext::parse("00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01").is_none()
+    );
+}
+
+#[test]
+fn trace_context_propagation() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .trace_context(|| TraceContext::parse(TRACEPARENT_VALUE))
+        .connect(s.client_url())
+        .unwrap();
+
+    let sub = nc.subscribe("traced").unwrap();
+    nc.publish("traced", "data").unwrap();
+    let context = sub.next().unwrap().trace_context().unwrap();
+    assert_eq!(context.trace_id, "0af7651916cd43dd8448eb211c80319c");
+    assert_eq!(context.state, None);
+
+    // An explicitly propagated context is left alone.
+    let mut headers = HeaderMap::new();
+    let mut explicit = TraceContext::parse(TRACEPARENT_VALUE).unwrap();
+    explicit.parent_id = "00f067aa0ba902b7".to_string();
+    explicit.state = Some("vendor=value".to_string());
+    explicit.inject(&mut headers);
+    nc.publish_with_reply_or_headers("traced", None, Some(&headers), "data")
+        .unwrap();
+    let message = sub.next().unwrap();
+    assert_eq!(
+        message.headers.as_ref().unwrap().get(TRACEPARENT),
+        Some(&explicit.traceparent())
+    );
+    assert_eq!(message.trace_context(), Some(explicit));
+}
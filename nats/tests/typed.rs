@@ -0,0 +1,202 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use nats::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Order {
+    id: u64,
+    item: String,
+}
+
+#[test]
+fn json_publish_subscribe() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let orders = nc.subscribe_json::<Order>("orders").unwrap();
+    let order = Order {
+        id: 1,
+        item: "book".to_string(),
+    };
+    nc.publish_json("orders", &order).unwrap();
+
+    let (received, message) = orders.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(received, order);
+    assert_eq!(
+        message.headers.unwrap().get(CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+
+    // Invalid payloads are reported without ending the subscription.
+    nc.publish("orders", "not json").unwrap();
+    nc.publish_json("orders", &order).unwrap();
+    let err = orders.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(orders.next().unwrap().unwrap().0, order);
+
+    // So are payloads of another content type.
+    let mut headers = nats::HeaderMap::new();
+    headers.insert(CONTENT_TYPE, "text/plain");
+    nc.publish_with_reply_or_headers("orders", None, Some(&headers), "{}")
+        .unwrap();
+    let err = orders.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_publish_subscribe() {
+    use nats::typed::MsgPack;
+
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let orders = nc.subscribe_with_codec::<Order, MsgPack>("orders").unwrap();
+    let order = Order {
+        id: 1,
+        item: "book".to_string(),
+    };
+    nc.publish_with_codec::<MsgPack, _>("orders", &order)
+        .unwrap();
+
+    let (received, message) = orders.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(received, order);
+    assert_eq!(
+        message.headers.unwrap().get(CONTENT_TYPE).unwrap(),
+        "application/msgpack"
+    );
+
+    // JSON payloads are rejected by their content type.
+    nc.publish_json("orders", &order).unwrap();
+    let err = orders.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "prost")]
+mod protobuf {
+    use super::*;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Shipment {
+        #[prost(uint64, tag = "1")]
+        order: u64,
+        #[prost(string, tag = "2")]
+        carrier: String,
+    }
+
+    impl prost::Name for Shipment {
+        const NAME: &'static str = "Shipment";
+        const PACKAGE: &'static str = "shop.v1";
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Refund {
+        #[prost(uint64, tag = "1")]
+        order: u64,
+    }
+
+    impl prost::Name for Refund {
+        const NAME: &'static str = "Refund";
+        const PACKAGE: &'static str = "shop.v1";
+    }
+
+    #[test]
+    fn protobuf_publish_subscribe() {
+        let s = nats_server::run_basic_server();
+        let nc = nats::connect(s.client_url()).unwrap();
+
+        let shipments = nc.subscribe_protobuf::<Shipment>("shipments").unwrap();
+        let shipment = Shipment {
+            order: 1,
+            carrier: "post".to_string(),
+        };
+        nc.publish_protobuf("shipments", &shipment).unwrap();
+
+        let (received, message) = shipments.next_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(received, shipment);
+        let headers = message.headers.unwrap();
+        assert_eq!(headers.get(CONTENT_TYPE).unwrap(), "application/protobuf");
+        assert_eq!(
+            headers.get(nats::header::NATS_SCHEMA).unwrap(),
+            "shop.v1.Shipment"
+        );
+
+        // Messages of another schema are rejected.
+        nc.publish_protobuf("shipments", &Refund { order: 1 })
+            .unwrap();
+        let err = shipments.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
+
+/// Encodes orders as `id,item` lines.
+struct Csv;
+
+impl nats::typed::Codec for Csv {
+    const CONTENT_TYPE: &'static str = "text/csv";
+}
+
+impl nats::typed::Encode<Order> for Csv {
+    fn encode(order: &Order) -> std::io::Result<Vec<u8>> {
+        Ok(format!("{},{}", order.id, order.item).into_bytes())
+    }
+}
+
+impl nats::typed::Decode<Order> for Csv {
+    fn decode(payload: &[u8]) -> std::io::Result<Order> {
+        let invalid = || std::io::Error::from(ErrorKind::InvalidData);
+        let payload = std::str::from_utf8(payload).map_err(|_| invalid())?;
+        let (id, item) = payload.split_once(',').ok_or_else(invalid)?;
+        Ok(Order {
+            id: id.parse().map_err(|_| invalid())?,
+            item: item.to_string(),
+        })
+    }
+}
+
+#[test]
+fn content_type_decoders() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let orders = nc
+        .subscribe_json::<Order>("orders")
+        .unwrap()
+        .with_decoder::<Csv>();
+    let order = Order {
+        id: 1,
+        item: "book".to_string(),
+    };
+
+    nc.publish_json("orders", &order).unwrap();
+    nc.publish_with_codec::<Csv, _>("orders", &order).unwrap();
+    nc.publish_with_content_type("orders", "text/csv", "1,book")
+        .unwrap();
+    // Payloads without a content type are decoded with the codec of the subscription.
+    nc.publish("orders", r#"{"id":1,"item":"book"}"#).unwrap();
+    for _ in 0..4 {
+        let (received, _) = orders.next_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(received, order);
+    }
+
+    // Content types without a decoder are still rejected.
+    nc.publish_with_content_type("orders", "text/plain", "1,book")
+        .unwrap();
+    let err = orders.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
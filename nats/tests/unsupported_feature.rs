@@ -0,0 +1,47 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+#[test]
+fn headers_unsupported() {
+    let s = nats_server::run_server("tests/configs/no_headers.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap();
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("key", "value");
+    let err = nc
+        .publish_with_reply_or_headers("foo", None, Some(&headers), "hello")
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+    assert!(matches!(
+        nats::Error::from(err),
+        nats::Error::UnsupportedFeature("headers")
+    ));
+
+    // Empty headers are published as a plain message.
+    nc.publish_with_reply_or_headers("foo", None, Some(&nats::HeaderMap::new()), "hello")
+        .unwrap();
+    assert_eq!(
+        sub.next_timeout(Duration::from_secs(1)).unwrap().data,
+        b"hello"
+    );
+
+    // Without no responders, requests to nobody time out instead.
+    let err = nc
+        .request_timeout("nobody-home", "hello", Duration::from_millis(100))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+}
@@ -0,0 +1,63 @@
+// Copyright 2020-2023 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use nats::{Direction, ProtocolEvent};
+
+#[test]
+fn verbose_pedantic() {
+    let s = nats_server::run_basic_server();
+    let events: Arc<Mutex<Vec<ProtocolEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let nc = nats::Options::new()
+        .verbose()
+        .pedantic()
+        .protocol_tap({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event.clone())
+        })
+        .error_callback({
+            let errors = errors.clone();
+            move |err| errors.lock().unwrap().push(err.to_string())
+        })
+        .connect(s.client_url())
+        .unwrap();
+
+    let sub = nc.subscribe("verbose").unwrap();
+    nc.publish("verbose", "hello").unwrap();
+    nc.flush().unwrap();
+    assert_eq!(
+        sub.next_timeout(Duration::from_secs(1)).unwrap().data,
+        b"hello"
+    );
+
+    let events = events.lock().unwrap();
+    let connect = events.iter().find(|event| event.op == "CONNECT").unwrap();
+    let arguments = connect.arguments.as_ref().unwrap();
+    assert!(arguments.contains(r#""verbose":true"#));
+    assert!(arguments.contains(r#""pedantic":true"#));
+
+    // The CONNECT, SUB and PUB were acknowledged without surfacing as errors.
+    let acknowledgements = events
+        .iter()
+        .filter(|event| event.direction == Direction::Inbound && event.op == "+OK")
+        .count();
+    assert!(
+        acknowledgements >= 3,
+        "{} acknowledgements",
+        acknowledgements
+    );
+    assert!(errors.lock().unwrap().is_empty());
+}